@@ -0,0 +1,75 @@
+//! Drives the playground from an event loop owned by the host app, instead of `run`.
+//! Run it from the repository root, so the default shader is found
+
+use std::{thread, time::Duration};
+
+use te_shader_play::{
+    handle_message, handle_window_event, Gpu, GpuOptions, Message, ScalarUniformValue,
+    StartupOptions, State, UniformValue,
+};
+use winit::{
+    dpi::PhysicalSize,
+    event::{Event, WindowEvent},
+    event_loop::{ControlFlow, EventLoop},
+    window::Window,
+};
+
+fn main() {
+    let event_loop = EventLoop::new().expect("Couldn't create event loop");
+    let size = PhysicalSize::new(1024, 768);
+    let window = event_loop
+        .create_window(
+            Window::default_attributes()
+                .with_title("Host app")
+                .with_inner_size(size),
+        )
+        .expect("Couldn't create window");
+
+    // The playground's own settings, so the UI looks like it does in the app
+    let options = StartupOptions::from_settings();
+    let gpu = Gpu::new(&window, size, GpuOptions::default()).expect("Couldn't set up the GPU");
+    let mut state = State::new(gpu, &window, &options).expect("Couldn't start the playground");
+
+    // The host sets up its own parameters, they need a new pipeline
+    let value = UniformValue::Scalar(ScalarUniformValue::F32(0.5));
+    state
+        .im_state
+        .ui
+        .inputs
+        .push_value(&state.gpu.device, 0, value)
+        .expect("Couldn't add the uniform");
+    state.handle_message(Message::ReloadPipeline);
+
+    // Other threads drive it through a handle. It's binding 4, after the builtins of group 0
+    let handle = state.uniforms_handle();
+    thread::spawn(move || {
+        for step in 0.. {
+            let value = (step as f32 * 0.05).sin() * 0.5 + 0.5;
+            if handle.set_f32(0, 4, value).is_err() {
+                break;
+            }
+            thread::sleep(Duration::from_millis(16))
+        }
+    });
+
+    event_loop
+        .run(|event, window_target| {
+            window_target.set_control_flow(ControlFlow::Poll);
+            // imgui sees every event, the playground only the ones of its window
+            state.im_state.handle_event(&event, &window);
+            match event {
+                Event::WindowEvent { window_id, event } if window_id == window.id() => {
+                    handle_window_event(event, window_target, &mut state, &window)
+                }
+                Event::AboutToWait => {
+                    state.apply_uniform_requests();
+                    state.poll_mesh_generation();
+                    let message = state.poll_osc();
+                    handle_message(&mut state, message, &window);
+                    window.request_redraw()
+                }
+                _ => (),
+            }
+        })
+        .unwrap()
+}
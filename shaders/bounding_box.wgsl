@@ -0,0 +1,22 @@
+// Outline of the custom shader's mesh, drawn with the same camera as the grid
+
+struct Camera {
+    @location(0) pos: vec3<f32>,
+    @location(1) projection: mat4x4<f32>,
+    @location(5) view: mat4x4<f32>,
+    @location(9) inverse_view: mat4x4<f32>,
+    @location(13) inverse_proj: mat4x4<f32>,
+}
+
+@group(1) @binding(0)
+var<uniform> camera: Camera;
+
+@vertex
+fn vs_main(@location(0) pos: vec3<f32>) -> @builtin(position) vec4<f32> {
+    return camera.projection * camera.view * vec4<f32>(pos, 1.0);
+}
+
+@fragment
+fn fs_main() -> @location(0) vec4<f32> {
+    return vec4<f32>(1.0, 0.8, 0.0, 1.0);
+}
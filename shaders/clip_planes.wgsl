@@ -0,0 +1,44 @@
+// Tints what is close to being clipped by the camera's near (red) and far (blue) planes
+
+struct VertexInput {
+    @location(0) pos: vec3<f32>
+}
+
+struct ClipParams {
+    // Depth buffer to view distance: distance = b / (depth + a)
+    a: f32,
+    b: f32,
+    // Distances of the planes
+    near: f32,
+    far: f32,
+}
+
+@group(0) @binding(0)
+var depth: texture_depth_2d;
+@group(0) @binding(1)
+var<uniform> params: ClipParams;
+
+@vertex
+fn vs_main(inp: VertexInput) -> @builtin(position) vec4<f32> {
+    return vec4<f32>(inp.pos, 1.0);
+}
+
+@fragment
+fn fs_main(@builtin(position) frag_coord: vec4<f32>) -> @location(0) vec4<f32> {
+    let d = textureLoad(depth, vec2<i32>(frag_coord.xy), 0);
+    // Nothing was drawn here
+    if d >= 1.0 {
+        discard;
+    }
+
+    let distance = params.b / (d + params.a);
+    let near_fade = 1.0 - smoothstep(params.near, params.near * 4.0, distance);
+    let far_fade = smoothstep(params.far * 0.75, params.far, distance);
+    let strength = max(near_fade, far_fade);
+    if strength <= 0.0 {
+        discard;
+    }
+
+    let color = (vec3(1.0, 0.1, 0.1) * near_fade + vec3(0.1, 0.3, 1.0) * far_fade) / (near_fade + far_fade);
+    return vec4(color, strength * 0.6);
+}
@@ -0,0 +1,75 @@
+// Debug views of the mesh, drawn with the same camera as the grid instead of the custom shader.
+// Every view has its own fragment entry point
+
+struct Camera {
+    pos: vec3<f32>,
+    projection: mat4x4<f32>,
+    view: mat4x4<f32>,
+    inverse_view: mat4x4<f32>,
+    inverse_proj: mat4x4<f32>,
+}
+
+@group(1) @binding(0)
+var<uniform> camera: Camera;
+
+// Cells of the checker per unit of the mesh
+const CHECKER_CELLS: f32 = 4.0;
+
+struct VertexOutput {
+    @builtin(position) position: vec4<f32>,
+    @location(0) normal: vec3<f32>,
+    // Before the instance offset, so every instance gets the same checker
+    @location(1) mesh_pos: vec3<f32>,
+    // Distance in front of the camera
+    @location(2) view_depth: f32,
+}
+
+@vertex
+fn vs_main(
+    @location(0) pos: vec3<f32>,
+    @location(1) offset: vec3<f32>,
+    @location(2) normal: vec3<f32>,
+) -> VertexOutput {
+    let view_pos = camera.view * vec4<f32>(pos + offset, 1.0);
+    var out: VertexOutput;
+    out.position = camera.projection * view_pos;
+    out.normal = normal;
+    out.mesh_pos = pos;
+    out.view_depth = -view_pos.z;
+    return out;
+}
+
+// The normal, remapped from [-1, 1] to [0, 1]
+@fragment
+fn fs_normals(in: VertexOutput) -> @location(0) vec4<f32> {
+    return vec4<f32>(normalize(in.normal) * 0.5 + 0.5, 1.0);
+}
+
+// The meshes have no UVs, so they are projected from the position along the normal's main axis
+@fragment
+fn fs_uv_checker(in: VertexOutput) -> @location(0) vec4<f32> {
+    let normal = abs(in.normal);
+    var uv: vec2<f32>;
+    if normal.x >= normal.y && normal.x >= normal.z {
+        uv = in.mesh_pos.zy;
+    } else if normal.y >= normal.z {
+        uv = in.mesh_pos.xz;
+    } else {
+        uv = in.mesh_pos.xy;
+    }
+    let cell = floor(uv * CHECKER_CELLS);
+    let checker = fract((cell.x + cell.y) * 0.5) * 2.0;
+    // Tinted by the position in the cell, so stretching and flips show
+    let tint = vec3<f32>(fract(uv * CHECKER_CELLS), 1.0);
+    return vec4<f32>(mix(vec3<f32>(0.15), tint, 0.25 + checker * 0.75), 1.0);
+}
+
+// Near is white and far is black, logarithmic so the depth close to the camera can be told apart
+@fragment
+fn fs_depth(in: VertexOutput) -> @location(0) vec4<f32> {
+    // The planes of a perspective projection
+    let near = camera.projection[3].z / (camera.projection[2].z - 1.0);
+    let far = camera.projection[3].z / (camera.projection[2].z + 1.0);
+    let depth = log(max(in.view_depth, near) / near) / log(far / near);
+    return vec4<f32>(vec3<f32>(1.0 - clamp(depth, 0.0, 1.0)), 1.0);
+}
@@ -0,0 +1,6 @@
+// Wireframe drawn over the filled mesh, its vertex stage is the custom shader's
+
+@fragment
+fn fs_main() -> @location(0) vec4<f32> {
+    return vec4<f32>(0.0, 1.0, 0.5, 1.0);
+}
@@ -1,16 +1,26 @@
+use std::path::PathBuf;
+
+use rfd::{MessageButtons, MessageDialog, MessageDialogResult, MessageLevel};
+use wgpu::SurfaceError;
 use winit::{
     event::{ElementState, Event, WindowEvent},
     event_loop::{ActiveEventLoop, ControlFlow},
+    keyboard::PhysicalKey,
     window::Window,
 };
 
-use crate::{rendering::render, State};
+use crate::{
+    imgui_state::{Message, Severity},
+    rendering::{handle_message, handle_render_message, render},
+    State,
+};
 
-pub fn run_event_loop(
+/// Handles an event of the event loop that owns `window`
+pub fn run_event_loop<'window>(
     event: Event<()>,
     window_target: &ActiveEventLoop,
-    window: &Window,
-    state: &mut State,
+    window: &'window Window,
+    state: &mut State<'window>,
 ) {
     window_target.set_control_flow(ControlFlow::Poll);
     state.im_state.handle_event(&event, window);
@@ -20,43 +30,190 @@ pub fn run_event_loop(
             event,
         } => handle_window_event(event, window_target, state, window),
         Event::Suspended => window_target.set_control_flow(ControlFlow::Wait),
-        Event::AboutToWait => window.request_redraw(),
+        Event::AboutToWait => {
+            state.apply_uniform_requests();
+            state.poll_mesh_generation();
+            let message = state.poll_osc();
+            handle_message(state, message, window);
+            window.request_redraw()
+        }
         _ => (),
     };
 }
 
-fn handle_window_event(
+/// Handles an event of `window`, imgui must have seen it already with `ImState::handle_event`
+pub fn handle_window_event<'window>(
     event: WindowEvent,
     window_target: &ActiveEventLoop,
-    state: &mut State,
-    window: &Window,
+    state: &mut State<'window>,
+    window: &'window Window,
 ) {
     match event {
-        WindowEvent::CloseRequested => window_target.exit(),
+        WindowEvent::CloseRequested => {
+            if state.im_state.ui.has_unsaved_changes() && !confirm_exit(state, window) {
+                return;
+            }
+            state.save_settings();
+            state.gpu.save_pipeline_cache();
+            window_target.exit()
+        }
         WindowEvent::RedrawRequested => {
-            let _dt = state
-                .time
-                .update_time(&state.gpu.queue, &mut state.im_state.ui.inputs);
-            if let Ok(output) = state.gpu.surface.get_current_texture() {
-                render(output, state, window);
+            if state.gpu.is_lost() {
+                if let Err(err) = state.recover_device(window) {
+                    let error = format!("The GPU device was lost and couldn't be recreated. {err}");
+                    fatal_error(&error, window_target, state);
+                    return;
+                }
+            }
+            let _dt = state.time.update_time(&mut state.im_state.ui.inputs);
+            match state.gpu.surface.get_current_texture() {
+                Ok(output) => {
+                    state.gpu.surface_recovered();
+                    render(output, state, window);
+                }
+                Err(SurfaceError::Lost | SurfaceError::Outdated) => {
+                    if !state.gpu.reconfigure_surface() {
+                        let error = "The window surface couldn't be recovered";
+                        fatal_error(error, window_target, state);
+                        return;
+                    }
+                }
+                Err(SurfaceError::Timeout) => {
+                    state.im_state.log(
+                        Severity::Warning,
+                        "Timed out getting the surface texture, skipping frame".into(),
+                    )
+                }
+                Err(SurfaceError::OutOfMemory) => {
+                    fatal_error("Out of memory", window_target, state);
+                    return;
+                }
             }
+            state.report_gpu_errors();
+            if let Some(message) = state.update_title() {
+                handle_render_message(message, window)
+            }
+        }
+        WindowEvent::KeyboardInput { event, .. } => {
+            let message = handle_keyboard(event, state);
+            handle_message(state, message, window)
+        }
+        WindowEvent::Resized(size) => state.resize(size, window.fullscreen().is_some()),
+        WindowEvent::Moved(position) => state.moved(position, window.fullscreen().is_some()),
+        WindowEvent::ScaleFactorChanged { .. } => state.im_state.scale_factor_changed(&state.gpu),
+        WindowEvent::DroppedFile(path) => {
+            let message = handle_dropped_file(path, state);
+            handle_message(state, message, window)
         }
-        WindowEvent::KeyboardInput { event, .. } => handle_keyboard(event, state),
-        WindowEvent::Resized(size) => state.resize(size),
         _ => (),
     }
 }
 
-fn handle_keyboard(event: winit::event::KeyEvent, state: &mut State) {
+/// Asks what to do with the unsaved parameters. Returns false if the program shouldn't exit
+fn confirm_exit(state: &mut State, window: &Window) -> bool {
+    let result = MessageDialog::new()
+        .set_level(MessageLevel::Warning)
+        .set_title("te-shader-play")
+        .set_description(format!(
+            "The parameters of {} have unsaved changes",
+            state.current_shader_path
+        ))
+        .set_buttons(MessageButtons::YesNoCancelCustom(
+            "Save".into(),
+            "Discard".into(),
+            "Cancel".into(),
+        ))
+        .set_parent(window)
+        .show();
+
+    match result {
+        MessageDialogResult::Custom(button) if button == "Save" => {
+            handle_message(state, Some(Message::SaveParameters), window);
+            // If saving failed the error is in the log, and the changes are kept
+            !state.im_state.ui.has_unsaved_changes()
+        }
+        MessageDialogResult::Custom(button) => button == "Discard",
+        MessageDialogResult::Yes => {
+            handle_message(state, Some(Message::SaveParameters), window);
+            !state.im_state.ui.has_unsaved_changes()
+        }
+        MessageDialogResult::No => true,
+        MessageDialogResult::Ok | MessageDialogResult::Cancel => false,
+    }
+}
+
+fn fatal_error(error: &str, window_target: &ActiveEventLoop, state: &mut State) {
+    eprintln!("Fatal error: {error}");
+    MessageDialog::new()
+        .set_level(MessageLevel::Error)
+        .set_title("te-shader-play")
+        .set_description(format!("Fatal error: {error}"))
+        .set_buttons(MessageButtons::Ok)
+        .show();
+    state.save_settings();
+    window_target.exit()
+}
+
+fn handle_keyboard(event: winit::event::KeyEvent, state: &mut State) -> Option<Message> {
     if let ElementState::Released = event.state {
-        return;
+        return None;
+    }
+
+    let key = match event.physical_key {
+        PhysicalKey::Code(key) => key,
+        PhysicalKey::Unidentified(_) => return None,
+    };
+    let modifiers = state.im_state.modifiers();
+    let wants_keyboard = state.im_state.wants_keyboard();
+    let keybindings = &mut state.im_state.ui.keybindings;
+
+    if keybindings.is_capturing() {
+        return keybindings
+            .capture(key, modifiers)
+            .then_some(Message::SaveSettings);
+    }
+
+    // Typing in a text field shouldn't trigger shortcuts
+    if event.repeat || wants_keyboard {
+        return None;
     }
 
-    match event.physical_key {
-        winit::keyboard::PhysicalKey::Code(c) => match c {
-            winit::keyboard::KeyCode::KeyQ => state.refresh_shader(),
-            _ => (),
-        },
-        winit::keyboard::PhysicalKey::Unidentified(_) => (),
+    keybindings
+        .action_for(key, modifiers)
+        .map(|action| action.message())
+}
+
+fn handle_dropped_file(path: PathBuf, state: &mut State) -> Option<Message> {
+    let extension = path
+        .extension()
+        .and_then(|extension| extension.to_str())
+        .map(|extension| extension.to_lowercase());
+
+    match extension.as_deref() {
+        Some("wgsl") => {
+            let shader_name = path.to_string_lossy().into_owned();
+            state.im_state.ui.set_shader_name(&shader_name);
+            Some(Message::LoadShader(shader_name))
+        }
+        Some("png" | "jpg" | "jpeg" | "bmp" | "tga" | "hdr") => {
+            state.im_state.log(
+                Severity::Warning,
+                format!(
+                    "Can't use {}: texture bindings aren't supported yet",
+                    path.display()
+                ),
+            );
+            None
+        }
+        _ => {
+            state.im_state.log(
+                Severity::Warning,
+                format!(
+                    "Can't load {}: only .wgsl shaders are supported",
+                    path.display()
+                ),
+            );
+            None
+        }
     }
 }
@@ -0,0 +1,47 @@
+#[cfg(test)]
+use std::cell::RefCell;
+use std::fmt::Display;
+#[cfg(not(test))]
+use std::sync::Mutex;
+
+/// Validation errors of GPU operations, waiting to be shown in the log.
+/// GPU operations happen deep inside the uniforms, which don't have access to it
+#[cfg(not(test))]
+static GPU_ERRORS: Mutex<Vec<String>> = Mutex::new(Vec::new());
+
+#[cfg(not(test))]
+fn with_errors<R>(f: impl FnOnce(&mut Vec<String>) -> R) -> Option<R> {
+    GPU_ERRORS.lock().ok().map(|mut errors| f(&mut errors))
+}
+
+/// Tests run in parallel, each on its own thread with its own device, so each one only sees the
+/// errors of its device
+#[cfg(test)]
+fn with_errors<R>(f: impl FnOnce(&mut Vec<String>) -> R) -> Option<R> {
+    thread_local! {
+        static TEST_ERRORS: RefCell<Vec<String>> = const { RefCell::new(Vec::new()) };
+    }
+    Some(TEST_ERRORS.with_borrow_mut(f))
+}
+
+pub(crate) fn report(error: impl Display) {
+    let error = error.to_string();
+    log::error!("GPU error: {error}");
+    with_errors(|errors| errors.push(error));
+}
+
+/// Errors reported since the last call
+pub(crate) fn take() -> Vec<String> {
+    with_errors(std::mem::take).unwrap_or_default()
+}
+
+/// Reports the error instead of panicking, for operations the UI can recover from
+pub(crate) trait OrReport<T> {
+    fn or_report(self) -> Option<T>;
+}
+
+impl<T, E: Display> OrReport<T> for Result<T, E> {
+    fn or_report(self) -> Option<T> {
+        self.map_err(report).ok()
+    }
+}
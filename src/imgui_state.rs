@@ -1,31 +1,133 @@
-use std::{array::IntoIter, iter::Chain, path::Path};
+use std::{
+    array::IntoIter,
+    borrow::Cow,
+    collections::{HashMap, HashSet, VecDeque},
+    iter::Chain,
+    num::NonZeroU64,
+    path::{Path, PathBuf},
+    sync::atomic::{AtomicU64, Ordering},
+    time::{Duration, Instant},
+};
 
-use cgmath::{Deg, Matrix4, Point3, Rad, Vector4};
-use imgui::{ConfigFlags, Context, Image, StyleVar, TextureId, TreeNodeFlags, Ui};
+use cgmath::{Deg, Matrix4, Point3, Rad, Vector3, Vector4};
+use imgui::{
+    Condition, ConfigFlags, Context, FontSource, Image, ItemHoveredFlags, MouseButton, Style,
+    StyleVar, TextureId, TreeNodeFlags, Ui,
+};
 use imgui_wgpu::{Renderer, RendererConfig, Texture as ImTexture, TextureConfig};
 use imgui_winit_support::{HiDpiMode, WinitPlatform};
 use serde_json::{Map, Value as JsonValue};
 use wgpu::{
-    core::pipeline::CreateShaderModuleError, util::{BufferInitDescriptor, DeviceExt}, BindGroup, BindGroupDescriptor, BindGroupEntry, BindGroupLayout, BindGroupLayoutDescriptor, BindGroupLayoutEntry, BindingType, Buffer, BufferBindingType, BufferUsages, CommandEncoder, Device, Queue, ShaderStages, TextureView
+    core::pipeline::CreateShaderModuleError, util::{BufferInitDescriptor, DeviceExt}, AdapterInfo, BindGroup, BlendComponent, BlendFactor, BlendOperation, BlendState, BindGroupDescriptor, BindGroupEntry, BindGroupLayout, BindGroupLayoutDescriptor, BindGroupLayoutEntry, BindingResource, BindingType, Buffer, BufferBinding, BufferBindingType, BufferUsages, CommandEncoder, Device, Features, PresentMode, Queue, ShaderStages, TextureView
 };
 use winit::{
     event::Event,
     window::{Window as WinitWindow, WindowLevel},
 };
 
-use crate::{imgui_state::uniform_types::VecType, state::Gpu};
+use crate::{
+    gpu_errors::OrReport,
+    keybindings::{Keybindings, Modifiers},
+    reflection,
+    rust_export::{ElementType, RustBinding, RustValues},
+    settings::{
+        present_mode_label, Appearance, OscSettings, OutputEncoding, UiTheme, UiWindow,
+        IMGUI_INI_FILE, MAX_FONT_SIZE, MAX_UI_SCALE, MIN_FONT_SIZE, MIN_UI_SCALE, PRESENT_MODES,
+    },
+    state::{
+        debug_texture::{DebugPattern, DebugTextureOptions, MAX_CHECKER_CELLS},
+        mesh_generation::MAX_SEGMENTS,
+        Gpu, Paths, StartupOptions, OPTIONAL_FEATURES,
+    },
+    web_export::{WebAnimation, WebBinding},
+};
 
 use uniform_types::UniformType;
 
-use self::uniform_types::{BuiltinValue, MatrixType, ScalarType, ScalarUniformValue, UniformValue};
+pub use self::uniform_types::{
+    BuiltinValue, Column2, Column3, Column4, MatrixUniformValue, RandomType, ScalarUniformValue,
+    TimeUnit, TransformUniformValue, UniformValue, Vec2UniformValue, Vec3UniformValue,
+    Vec4UniformValue, VectorUniformValue,
+};
+pub use self::uniforms_handle::{BindingInfo, UniformsError, UniformsHandle};
+pub(crate) use self::{
+    log::Severity, uniform_types::camera_projection, uniforms_handle::UniformRequests,
+};
+use self::{
+    camera_bookmarks::CameraBookmarks,
+    camera_path::{CameraKeyframe, CameraPath, CameraTransition},
+    gizmo::{GizmoAxis, TranslationGizmo},
+    log::Log,
+    osc::OscServer,
+    parameter_source::ParameterSource,
+    randomize::Perturbation,
+    script::Script,
+    uniform_types::{
+        ColorOptions, MatrixType, MatrixView, Rng, ScalarType, VecDisplay, LIGHT_STRUCT,
+    },
+    uniform_writes::{UniformWrites, WriteStats, WriteTarget},
+};
 
+mod byte_view;
+mod camera_bookmarks;
+mod camera_path;
+mod gizmo;
+mod log;
+mod osc;
+mod parameter_source;
+mod randomize;
+mod script;
 mod uniform_types;
+mod uniform_writes;
+mod uniforms_handle;
 
 pub const IMAGE_HEIGHT: f32 = 512.0;
 pub const IMAGE_WIDTH: f32 = 512.0;
+/// The fitted Render image doesn't shrink below this, so it stays usable in a tiny window
+const MIN_PREVIEW_SCALE: f32 = 0.1;
 
 const DEFAULT_U32_UNIFORM: u32 = 0;
+
+const DEFAULT_PLANE_RESOLUTION_CAP: u32 = 256;
+const PLANE_RESOLUTION_LIMIT: u32 = 4096;
+/// Plane resolutions above this many triangles need to be confirmed before generating them
+const PLANE_TRIANGLE_WARNING: u64 = 500_000;
 const DEFAULT_UNIFORM: UniformValue = UniformValue::Scalar(ScalarUniformValue::F32(0.0));
+const DEFAULT_PAN_ZOOM: BuiltinValue = BuiltinValue::PanZoom {
+    offset: [0.0, 0.0],
+    zoom: 1.0,
+    enabled: true,
+};
+const DEFAULT_RANDOM: BuiltinValue = BuiltinValue::Random {
+    kind: RandomType::F32,
+    frozen: false,
+    bits: 0,
+};
+/// Above and in front of the origin, where the default camera looks
+const DEFAULT_LIGHT: BuiltinValue = BuiltinValue::Light {
+    position: [2.0, 4.0, 3.0],
+    color: [1.0, 1.0, 1.0],
+    intensity: 1.0,
+    ambient: 0.1,
+};
+pub(crate) const DEFAULT_INSTANCE_SPACING: f32 = 1.5;
+const MAX_INSTANCES: u32 = 1 << 16;
+/// Zoom multiplier of each mouse wheel step
+const ZOOM_STEP: f32 = 1.1;
+/// Zoom of the 2D view, it's never 0 so the offset can be divided by it
+const MIN_ZOOM: f32 = 0.01;
+const MAX_ZOOM: f32 = 10_000.0;
+/// Seconds the timeline spans by default, and the shortest and longest it can be
+const DEFAULT_TIMELINE_LENGTH: f32 = 10.0;
+const MIN_TIMELINE_LENGTH: f32 = 0.1;
+const MAX_TIMELINE_LENGTH: f32 = 24.0 * 60.0 * 60.0;
+/// Seconds a frame advances the time in deterministic mode by default, and the shortest and
+/// longest step
+const DEFAULT_FIXED_STEP: f32 = 1.0 / 60.0;
+const MIN_FIXED_STEP: f32 = 0.0001;
+const MAX_FIXED_STEP: f32 = 60.0;
+/// The random builtin starts from it in deterministic mode
+const DETERMINISTIC_SEED: u64 = 0;
 
 trait ImguiScalar {
     fn increase(&mut self);
@@ -50,31 +152,110 @@ trait ImguiUniformSelectable {
         val_name: &mut String,
     ) -> Option<UniformEditEvent>;
     fn to_le_bytes(&self) -> Vec<u8>;
+    /// Appends the same bytes as `to_le_bytes` to `out`, without allocating a new Vec
+    fn write_le_bytes(&self, out: &mut Vec<u8>);
 }
 
 pub enum Message {
     ReloadShader,
     LoadShader(String),
+    /// None removes the post-process pass
+    LoadPostShader(Option<String>),
+    /// None removes the debug texture
+    SetDebugTexture(Option<DebugTextureOptions>),
     ReloadPipeline,
+    /// Creates the pipeline of the selected debug view if it isn't cached, the custom shader
+    /// isn't recompiled
+    RefreshDebugView,
     ReloadMeshBuffers,
+    ReloadInstances,
+    /// Only the normals changed, unlike ReloadMeshBuffers it keeps the camera as it is
+    ReloadMeshNormals,
     ChangeWindowLevel(WindowLevel),
+    /// Reconfigures the surface and saves it in settings.json
+    SetPresentMode(PresentMode),
+    /// Reconfigures the surface with a format of that encoding, recreating what draws to it,
+    /// and saves it in settings.json
+    SetOutputEncoding(OutputEncoding),
+    /// Saved in settings.json, shaders are loaded from it from the next start
+    SetShadersDir(String),
+    /// Saves the preferences of the UI that are kept in settings.json, like the hidden windows
+    SaveSettings,
+    /// Applies the theme, scale and font, and saves them in settings.json
+    SetAppearance(Appearance),
+    /// Borderless fullscreen on the window's monitor, or back to a normal window
+    SetFullscreen(bool),
     SaveParameters,
+    ToggleGrid,
+    ToggleMesh,
+    ToggleAlwaysOnTop,
+    ToggleFullscreen,
+    TogglePause,
+    ResetTime,
+    /// Holds the time at a point of the timeline while it's dragged
+    ScrubTime(Duration),
+    /// Time goes on from this point, after the timeline is released
+    SeekTime(Duration),
+    /// Time wraps around after this long, or never
+    SetTimeLoop(Option<Duration>),
+    /// Every frame advances the time by this step, or by the time it took with None
+    SetFixedStep(Option<Duration>),
+    /// Puts the Render window image on the clipboard
+    CopyImage,
+    /// Writes the Render window image to a PNG the user picks, with the parameters in it
+    SaveScreenshot,
+    /// Restores the shader, parameters and time embedded in a screenshot
+    ImportScreenshotParameters,
+    /// Writes an HTML page that runs the shader in a browser
+    ExportWeb,
+    /// Writes the custom shader's mesh to an OBJ file the user picks
+    ExportMesh,
+    /// Writes Rust code that sets up the bindings and pipeline with wgpu
+    ExportRust,
 }
 
 enum UniformEditEvent {
     UpdateBuffer(usize, usize),
     AddUniform(usize),
+    AddLight(usize),
     AddBindGroup,
     ChangeType(UniformType, usize, usize),
     Increase(usize, usize),
     Decrease(usize, usize),
     ChangeInnerType(ScalarType, usize, usize),
     ChangeMatrixSize(MatrixType, usize, usize),
+    SetGroupEnabled(usize, bool),
+    SetGroupPacked(usize, bool),
+    /// Only marks the parameters as edited, the name is already in the group
+    RenameGroup(usize),
+    Perturb(Perturbation, usize, usize),
+    /// Randomizes every unlocked binding of the group that can be randomized
+    RandomizeGroup(usize),
 }
 struct UniformBinding {
-    pub buffer: Buffer,
+    /// None while the group is packed, the binding is a range of the group's buffer then
+    buffer: Option<Buffer>,
     value: UniformValue,
+    /// Bytes last written to the buffer, they differ from `value` for builtins like the time.
+    /// Buffers are created again from them
+    contents: Vec<u8>,
     name: String,
+    /// Ignores edits from the UI, scripts, OSC and `UniformsHandle::set`
+    locked: bool,
+    /// Value at the last save or load, which "Reset" goes back to
+    baseline: Option<UniformValue>,
+    /// Editors shown for an f32 vec3 or vec4
+    display: VecDisplay,
+    /// How the color picker of an f32 vec3 or vec4 shows and uploads the color
+    color: ColorOptions,
+    /// Layout of the matrix editor
+    matrix_view: MatrixView,
+    /// A transform's translation gizmo is drawn over the Render image, it isn't saved
+    gizmo: bool,
+    /// The last edit had NaN or infinity, which were replaced
+    sanitized: bool,
+    /// Set by the parameter source file the last time it changed
+    linked: bool,
 }
 impl UniformBinding {
     fn bgl_entry(&self, index: u32) -> BindGroupLayoutEntry {
@@ -90,257 +271,645 @@ impl UniformBinding {
         }
     }
 
-    fn bg_entry(&self, index: u32) -> BindGroupEntry<'_> {
-        BindGroupEntry {
-            binding: index,
-            resource: self.buffer.as_entire_binding(),
-        }
-    }
-
-    fn new(device: &Device, value: UniformValue) -> UniformBinding {
+    fn new(device: &Device, value: UniformValue, name: String, label: &str) -> UniformBinding {
         let contents = value.to_le_bytes();
 
+        // Without a buffer the group keeps its previous bind group
         let buffer = device
             .create_buffer_init(&BufferInitDescriptor {
-                label: Some("new uniform buffer"),
+                label: Some(label),
                 contents: &contents,
                 usage: BufferUsages::UNIFORM | BufferUsages::COPY_DST,
             })
-            .unwrap();
+            .or_report();
 
         UniformBinding {
             buffer,
             value,
-            name: "unnamed".to_string(),
+            contents,
+            name,
+            locked: false,
+            baseline: None,
+            display: VecDisplay::Both,
+            color: ColorOptions::default(),
+            matrix_view: MatrixView::default(),
+            gizmo: false,
+            sanitized: false,
+            linked: false,
+        }
+    }
+
+    fn create_buffer(&self, device: &Device, label: &str) -> Option<Buffer> {
+        device
+            .create_buffer_init(&BufferInitDescriptor {
+                label: Some(label),
+                contents: &self.contents,
+                usage: BufferUsages::UNIFORM | BufferUsages::COPY_DST,
+            })
+            .or_report()
+    }
+
+    fn recreate_buffer(&mut self, device: &Device, label: &str) {
+        if let Some(buffer) = self.create_buffer(device, label) {
+            self.buffer = Some(buffer)
         }
     }
 
-    fn change_type(&mut self, new_type: UniformType, queue: &Queue, device: &Device) {
-        let old_value = self.value;
-        let old_size = old_value.to_le_bytes().len();
-        let new_value = old_value.cast_to(new_type);
+    /// What the shader gets for `value`, colors picked in sRGB are converted to linear
+    fn encode(&self, value: UniformValue) -> UniformValue {
+        if self.display.shows_color() {
+            self.color.encode(value)
+        } else {
+            value
+        }
+    }
 
-        self.value = new_value;
-        let new_bytes = self.value.to_le_bytes();
-        if new_bytes.len() != old_size {
-            self.buffer = device
+    /// Returns true if the new value has the same size, so it still has to be written.
+    /// Otherwise the binding gets a new buffer, or its packed group has to be laid out again
+    fn change_value(&mut self, new_value: UniformValue, device: &Device, label: &str) -> bool {
+        let new_bytes = self.encode(new_value).to_le_bytes();
+        if new_bytes.len() == self.contents.len() {
+            self.value = new_value;
+            return true;
+        }
+
+        if self.buffer.is_some() {
+            let buffer = device
                 .create_buffer_init(&BufferInitDescriptor {
-                    label: Some("Resized buffer"),
+                    label: Some(label),
                     contents: &new_bytes,
                     usage: BufferUsages::COPY_DST | BufferUsages::UNIFORM,
                 })
-                .unwrap();
-        } else {
-            queue.write_buffer(&self.buffer, 0, &new_bytes).unwrap();
+                .or_report();
+            // The value must keep matching the size of the buffer
+            let Some(buffer) = buffer else {
+                return false;
+            };
+            self.buffer = Some(buffer);
         }
+        self.value = new_value;
+        self.contents = new_bytes;
+        false
     }
 
+    /// `lock_linked` ignores the edits of a binding set by the parameter source
     fn show_editor(
         &mut self,
         ui: &Ui,
         group_index: usize,
         binding_index: usize,
+        lock_linked: bool,
     ) -> Option<UniformEditEvent> {
-        self.value
-            .show_editor(ui, group_index, binding_index, &mut self.name)
-    }
-
-    fn decrease(&mut self, queue: &Queue) {
-        self.value.decrease();
-
-        let new_value = self.value.to_le_bytes();
-        queue.write_buffer(&self.buffer, 0, &new_value).unwrap();
-    }
-
-    fn increase(&mut self, queue: &Queue) {
-        self.value.increase();
-
-        let new_value = self.value.to_le_bytes();
-        queue.write_buffer(&self.buffer, 0, &new_value).unwrap();
-    }
-
-    fn change_inner_type(&mut self, inner_type: ScalarType, queue: &Queue) {
-        self.value.change_inner_type(inner_type);
-        let new_value = self.value.to_le_bytes();
-        queue.write_buffer(&self.buffer, 0, &new_value).unwrap();
-    }
-
-    fn change_binding_size(&mut self, new_size: u64, device: &Device, queue: &Queue) {
-        /*
-        Matrix sizes:
-        2x2: 4*f32 = 4*4 = 16
-        2x3: 6*f32 = 6*4 = 24
-        3x2: 6*f32 = 6*4 = 24
-        2x4: 8*f32 = 8*4 = 32
-        4x2: 8*f32 = 8*4 = 32
-        3x3: 9*f32 = 9*4 = 36
-        3x4: 12*f32 = 12*4 = 48
-        4x3: 12*f32 = 12*4 = 48
-        4x4: 16*f32 = 16*4 = 64
-        */
-        #[rustfmt::skip]
-        const DEFAULT_SIZEN_TYPE: &[Option<UniformType>] = &[
-            None,None,None, None, // sizes 0..=3 don't have any default value
-            Some(UniformType::Scalar(ScalarType::F32)), // Size 4
-            None,None,None, // sizes 5..=7 don't have any default value
-            Some(UniformType::Vec(VecType::Vec2(ScalarType::F32))), // Size 8
-            None,None,None, // sizes 9..=11 don't have any default value
-            Some(UniformType::Vec(VecType::Vec3(ScalarType::F32))), // Size 12
-            None,None,None, // sizes 13..=15 don't have any default value
-            Some(UniformType::Vec(VecType::Vec4(ScalarType::F32))), // Size 16
-            None,None,None,None,None,None,None, // sizes 17..=23 don't have any default value
-            Some(UniformType::Matrix(MatrixType::M2x3)), // Size 24
-            None,None,None,None,None,None,None, // sizes 25..=31 don't have any default value
-            Some(UniformType::Matrix(MatrixType::M2x4)), // Size 32
-            None,None,None, // sizes 33..=35 don't have any default value
-            Some(UniformType::Matrix(MatrixType::M3x3)), // Size 36
-            None,None,None,None,None,None,None,None,None,None,None, // sizes 37..=37 don't have any default value
-            Some(UniformType::Matrix(MatrixType::M3x4)), // Size 48
-            None,None,None,None,None,None,None,None,None,None,None,None,None,None,None, // sizes 49..=63 don't have any default value
-            Some(UniformType::Matrix(MatrixType::M4x4)), // Size 64
-            // Sizes 65..infinity don't have any default value
-        ];
-        // Make sure that I've coutned correctly
-        // TODO: Make this into a test
-        assert_eq!(
-            DEFAULT_SIZEN_TYPE[4],
-            Some(UniformType::Scalar(ScalarType::F32))
-        );
-        assert_eq!(
-            DEFAULT_SIZEN_TYPE[8],
-            Some(UniformType::Vec(VecType::Vec2(ScalarType::F32)))
-        );
-        assert_eq!(
-            DEFAULT_SIZEN_TYPE[12],
-            Some(UniformType::Vec(VecType::Vec3(ScalarType::F32)))
-        );
-        assert_eq!(
-            DEFAULT_SIZEN_TYPE[16],
-            Some(UniformType::Vec(VecType::Vec4(ScalarType::F32)))
-        );
-        assert_eq!(
-            DEFAULT_SIZEN_TYPE[24],
-            Some(UniformType::Matrix(MatrixType::M2x3))
-        );
-        assert_eq!(
-            DEFAULT_SIZEN_TYPE[32],
-            Some(UniformType::Matrix(MatrixType::M2x4))
-        );
-        assert_eq!(
-            DEFAULT_SIZEN_TYPE[36],
-            Some(UniformType::Matrix(MatrixType::M3x3))
-        );
-        assert_eq!(
-            DEFAULT_SIZEN_TYPE[48],
-            Some(UniformType::Matrix(MatrixType::M3x4))
+        let locked = self.locked || (self.linked && lock_linked);
+        if self.linked {
+            ui.text_colored([0.4, 0.7, 1.0, 1.0], "(linked)");
+            if ui.is_item_hovered() {
+                ui.tooltip_text("Set by the parameter source file")
+            }
+            ui.same_line();
+        }
+        ui.checkbox(
+            format!("Lock##lock_{group_index}_{binding_index}"),
+            &mut self.locked,
         );
-        assert_eq!(
-            DEFAULT_SIZEN_TYPE[64],
-            Some(UniformType::Matrix(MatrixType::M4x4))
+        ui.same_line();
+        let disabled = ui.begin_disabled(locked);
+        let mut reset = None;
+        if !matches!(self.value, UniformValue::BuiltIn(_)) {
+            // A type change can't be undone by writing the buffer
+            let baseline = self
+                .baseline
+                .filter(|baseline| baseline.same_type(&self.value));
+            let unchanged = baseline.map_or(true, |baseline| baseline == self.value);
+            ui.disabled(unchanged, || {
+                if ui.small_button(format!("Reset##reset_{group_index}_{binding_index}")) {
+                    reset = baseline
+                }
+            });
+            if ui.is_item_hovered_with_flags(ItemHoveredFlags::ALLOW_WHEN_DISABLED) {
+                ui.tooltip_text(match (self.baseline, baseline) {
+                    (None, _) => "The parameters haven't been saved or loaded yet",
+                    (Some(_), None) => "The saved value has another type",
+                    (Some(_), Some(_)) => "Go back to the value of the last save or load",
+                })
+            }
+            ui.same_line();
+        }
+        let mut perturbation = None;
+        if randomize::can_perturb(&self.value) {
+            if ui.small_button(format!("Random##random_{group_index}_{binding_index}")) {
+                perturbation = Some(Perturbation::Randomize)
+            }
+            if ui.is_item_hovered() {
+                ui.tooltip_text("A random value between 0 and 1 for every component")
+            }
+            ui.same_line();
+            if ui.small_button(format!("Nudge##nudge_{group_index}_{binding_index}")) {
+                perturbation = Some(Perturbation::Nudge)
+            }
+            if ui.is_item_hovered() {
+                ui.tooltip_text("Moves every component a little, at random")
+            }
+            ui.same_line();
+        }
+        let mut event = self.value.show_editor_with_display(
+            ui,
+            group_index,
+            binding_index,
+            &mut self.name,
+            &mut self.display,
+            &mut self.color,
+            &mut self.matrix_view,
         );
-        assert_eq!(DEFAULT_SIZEN_TYPE.len(), 65);
-        self.change_type(
-            DEFAULT_SIZEN_TYPE.get(new_size as usize).unwrap().unwrap(),
-            queue,
-            device,
-        )
-    }
+        disabled.end();
+        if let UniformValue::Transform(_) | UniformValue::BuiltIn(BuiltinValue::Light { .. }) =
+            self.value
+        {
+            ui.checkbox(
+                format!("Gizmo##gizmo_{group_index}_{binding_index}"),
+                &mut self.gizmo,
+            );
+            if ui.is_item_hovered() {
+                ui.tooltip_text("Drag the arrows drawn over the Render image to move it,\nwith the camera of group 1 binding 0")
+            }
+        }
+        if let Some(baseline) = reset {
+            self.value = baseline;
+            event = Some(UniformEditEvent::UpdateBuffer(group_index, binding_index))
+        }
+        if let Some(perturbation) = perturbation {
+            // The value changes once the event reaches `Uniforms`, which has the random state
+            event = Some(UniformEditEvent::Perturb(
+                perturbation,
+                group_index,
+                binding_index,
+            ))
+        }
+        if event.is_some() {
+            self.sanitized = self.value.sanitize()
+        }
+        if self.sanitized {
+            ui.same_line();
+            ui.text_colored([1.0, 1.0, 0.0, 1.0], "(!)");
+            if ui.is_item_hovered() {
+                ui.tooltip_text("NaN was replaced with 0 and infinity with the largest finite value")
+            }
+        }
 
-    fn change_matrix_size(&mut self, matrix_size: MatrixType, queue: &Queue) {
-        self.value.change_matrix_size(matrix_size);
-        let new_value = self.value.to_le_bytes();
-        queue.write_buffer(&self.buffer, 0, &new_value).unwrap();
+        // Locked bindings keep uploading their value, but edits are ignored
+        if locked {
+            None
+        } else {
+            event
+        }
     }
 
     fn to_json(&self) -> serde_json::Value {
         let mut val = serde_json::Map::new();
         val.insert("name".into(), self.name.clone().into());
         val.insert("value".into(), self.value.to_json());
+        val.insert("locked".into(), self.locked.into());
+        // Saving makes the current value the baseline
+        val.insert("baseline".into(), self.value.to_json());
+        val.insert("display".into(), self.display.to_json());
+        val.insert("color".into(), self.color.to_json());
+        val.insert("matrix_view".into(), self.matrix_view.to_json());
         serde_json::Value::Object(val)
     }
 }
 
+/// Buffer shared by every binding of a packed group
+struct PackedBuffer {
+    buffer: Buffer,
+    /// Offset and size of every binding, offsets are aligned to the device's
+    /// `min_uniform_buffer_offset_alignment`
+    ranges: Vec<(u64, u64)>,
+}
+
+/// Uniform buffer objects and the memory they take
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct BufferStats {
+    pub(crate) buffers: u32,
+    pub(crate) bytes: u64,
+}
+
+impl std::ops::AddAssign for BufferStats {
+    fn add_assign(&mut self, other: BufferStats) {
+        self.buffers += other.buffers;
+        self.bytes += other.bytes;
+    }
+}
+
 pub struct UniformGroup {
     bindings: Vec<UniformBinding>,
     pub bind_group: BindGroup,
+    /// Every binding has the same layout entry, so it only changes with the number of bindings
+    layout: BindGroupLayout,
+    /// Number of bindings `layout` was created for
+    layout_len: usize,
+    /// Disabled groups aren't part of the pipeline layout and aren't bound
+    enabled: bool,
+    /// All bindings share one buffer instead of having one each. The layout doesn't change
+    packed: Option<PackedBuffer>,
+    /// `@group(index)` of the group, groups are only added at the end so it doesn't change
+    index: usize,
+    /// Shown in the UI and used in the labels of its GPU objects, empty if it has none
+    name: String,
+}
+
+/// Case-insensitive substring match, `filter` must already be lowercase
+fn name_matches(name: &str, filter: &str) -> bool {
+    filter.is_empty() || name.to_lowercase().contains(filter)
+}
+
+/// Label of a binding's buffer, e.g. "group 1 'lighting' binding 2 'sun_dir'"
+fn binding_label(group_label: &str, b_index: usize, name: &str) -> String {
+    format!("{group_label} binding {b_index} '{name}'")
+}
+
+/// Keywords and predeclared types of WGSL, which can't name a declaration
+const WGSL_KEYWORDS: &[&str] = &[
+    "alias",
+    "array",
+    "atomic",
+    "bitcast",
+    "bool",
+    "break",
+    "case",
+    "const",
+    "const_assert",
+    "continue",
+    "continuing",
+    "default",
+    "diagnostic",
+    "discard",
+    "else",
+    "enable",
+    "f16",
+    "f32",
+    "false",
+    "fn",
+    "for",
+    "i32",
+    "if",
+    "let",
+    "loop",
+    "mat2x2",
+    "mat2x3",
+    "mat2x4",
+    "mat3x2",
+    "mat3x3",
+    "mat3x4",
+    "mat4x2",
+    "mat4x3",
+    "mat4x4",
+    "override",
+    "ptr",
+    "requires",
+    "return",
+    "sampler",
+    "sampler_comparison",
+    "struct",
+    "switch",
+    "true",
+    "u32",
+    "var",
+    "vec2",
+    "vec3",
+    "vec4",
+    "while",
+    "Camera",
+    "Light",
+];
+
+/// Names of the WGSL declarations, bindings without a usable name are named after their location
+/// and keywords get a suffix
+fn wgsl_identifier(name: &str, g_index: usize, b_index: usize) -> String {
+    let identifier: String = name
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '_' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    if identifier.is_empty()
+        || identifier == "unnamed"
+        || identifier.starts_with(|c: char| c.is_ascii_digit())
+        || identifier.chars().all(|c| c == '_')
+        // Reserved for the compiler
+        || identifier.starts_with("__")
+    {
+        format!("binding_{g_index}_{b_index}")
+    } else if WGSL_KEYWORDS.contains(&identifier.as_str()) {
+        format!("{identifier}_")
+    } else {
+        identifier
+    }
+}
+
+/// `wgsl_identifier` of every binding of the group, with a suffix on repeated names
+fn wgsl_identifiers<'a>(names: impl Iterator<Item = &'a str>, g_index: usize) -> Vec<String> {
+    let mut identifiers: Vec<String> = Vec::new();
+    for (b_index, name) in names.enumerate() {
+        let name = wgsl_identifier(name, g_index, b_index);
+        let mut identifier = name.clone();
+        let mut suffix = b_index;
+        while identifiers.contains(&identifier) {
+            identifier = format!("{name}_{suffix}");
+            suffix += 1
+        }
+        identifiers.push(identifier)
+    }
+
+    identifiers
 }
 
 impl UniformGroup {
-    fn new(device: &Device) -> UniformGroup {
+    fn new(device: &Device, index: usize) -> Option<UniformGroup> {
+        let label = format!("group {index}");
+        let layout = device
+            .create_bind_group_layout(&BindGroupLayoutDescriptor {
+                label: Some(&label),
+                entries: &[],
+            })
+            .or_report()?;
         let bg = device
             .create_bind_group(&BindGroupDescriptor {
-                label: None,
-                layout: &device
-                    .create_bind_group_layout(&BindGroupLayoutDescriptor {
-                        label: Some("Automaticall created layout in new"),
-                        entries: &[],
-                    })
-                    .unwrap(),
+                label: Some(&label),
+                layout: &layout,
                 entries: &[],
             })
-            .unwrap();
+            .or_report()?;
 
-        UniformGroup {
+        Some(UniformGroup {
             bindings: Vec::new(),
             bind_group: bg,
+            layout,
+            layout_len: 0,
+            enabled: true,
+            packed: None,
+            index,
+            name: String::new(),
+        })
+    }
+
+    /// Label of the group's GPU objects, e.g. "group 1 'lighting'"
+    fn label(&self) -> String {
+        if self.name.is_empty() {
+            format!("group {}", self.index)
+        } else {
+            format!("group {} '{}'", self.index, self.name)
         }
     }
 
-    pub fn bg_layout(&self, device: &Device) -> BindGroupLayout {
+    fn binding_label(&self, b_index: usize) -> String {
+        binding_label(&self.label(), b_index, &self.bindings[b_index].name)
+    }
+
+    pub fn bg_layout(&self) -> &BindGroupLayout {
+        &self.layout
+    }
+
+    /// Number of bindings of the layout, pipeline layouts must be recreated when it changes
+    pub(crate) fn layout_len(&self) -> usize {
+        self.layout_len
+    }
+
+    /// Returns false if the layout couldn't be created, the previous one is kept
+    fn recreate_layout(&mut self, device: &Device) -> bool {
         let mut entries = Vec::new();
         for (index, binding) in self.bindings.iter().enumerate() {
             entries.push(binding.bgl_entry(index as u32))
         }
 
-        device
+        let layout = device
             .create_bind_group_layout(&BindGroupLayoutDescriptor {
-                label: Some("Automatically created layout in bg_layout"),
+                label: Some(&self.label()),
                 entries: &entries,
             })
-            .unwrap()
+            .or_report();
+        match layout {
+            Some(layout) => {
+                self.layout = layout;
+                self.layout_len = self.bindings.len();
+                true
+            }
+            None => false,
+        }
     }
 
     fn add_f32(&mut self, device: &Device) {
-        self.bindings
-            .push(UniformBinding::new(device, DEFAULT_UNIFORM));
-        self.refresh_bind_group(device)
+        self.add_custom(device, DEFAULT_UNIFORM)
     }
 
     fn add_custom(&mut self, device: &Device, uniform: UniformValue) {
-        self.bindings.push(UniformBinding::new(device, uniform));
-        self.refresh_bind_group(device)
+        self.add_named(device, uniform, "unnamed".to_string())
     }
 
-    fn update_buffer(&mut self, b_index: usize, queue: &Queue) {
-        let binding = &mut self.bindings[b_index];
-        queue
-            .write_buffer(&binding.buffer, 0, &binding.value.to_le_bytes())
-            .unwrap();
+    /// The name is set before the buffer is created, so its label has it
+    fn add_named(&mut self, device: &Device, uniform: UniformValue, name: String) {
+        let label = binding_label(&self.label(), self.bindings.len(), &name);
+        self.bindings
+            .push(UniformBinding::new(device, uniform, name, &label));
+        self.refresh_bind_group(device)
     }
 
     fn refresh_bind_group(&mut self, device: &Device) {
-        let mut layout_entries = Vec::new();
+        // The previous bind group is kept if the new one is invalid
+        if self.layout_len != self.bindings.len() && !self.recreate_layout(device) {
+            return;
+        }
+        // Added or resized bindings need a new range
+        if self.packed.is_some() && !self.pack(device) {
+            return;
+        }
+
         let mut bindgroup_entries = Vec::new();
         for (index, binding) in self.bindings.iter().enumerate() {
-            layout_entries.push(binding.bgl_entry(index as u32));
-            bindgroup_entries.push(binding.bg_entry(index as u32));
+            let resource = match (&self.packed, &binding.buffer) {
+                (Some(packed), _) => {
+                    let (offset, size) = packed.ranges[index];
+                    BindingResource::Buffer(BufferBinding {
+                        buffer: &packed.buffer,
+                        offset,
+                        size: NonZeroU64::new(size),
+                    })
+                }
+                (None, Some(buffer)) => buffer.as_entire_binding(),
+                (None, None) => return,
+            };
+            bindgroup_entries.push(BindGroupEntry {
+                binding: index as u32,
+                resource,
+            });
         }
-
-        let layout = device
-            .create_bind_group_layout(&BindGroupLayoutDescriptor {
-                label: Some("Autogenerated bind group layout in refresh_bind_group"),
-                entries: &layout_entries,
-            })
-            .unwrap();
         let bg = device
             .create_bind_group(&BindGroupDescriptor {
-                label: Some("Autogenerated bind group in refresh_bind_group"),
-                layout: &layout,
+                label: Some(&self.label()),
+                layout: &self.layout,
                 entries: &bindgroup_entries,
             })
-            .unwrap();
+            .or_report();
+
+        if let Some(bg) = bg {
+            self.bind_group = bg
+        }
+    }
+
+    /// Lays out every binding in a single buffer, the current one is kept if no binding was
+    /// added or resized. Returns false if the buffer couldn't be created
+    fn pack(&mut self, device: &Device) -> bool {
+        let alignment = device.limits().min_uniform_buffer_offset_alignment as u64;
+        let mut ranges = Vec::new();
+        let mut end: u64 = 0;
+        for binding in self.bindings.iter() {
+            let offset = end.next_multiple_of(alignment);
+            let size = binding.contents.len() as u64;
+            ranges.push((offset, size));
+            end = offset + size;
+        }
+        if self
+            .packed
+            .as_ref()
+            .is_some_and(|packed| packed.ranges == ranges)
+        {
+            return true;
+        }
+
+        let mut contents = vec![0; end.max(4) as usize];
+        for (binding, (offset, size)) in self.bindings.iter().zip(ranges.iter()) {
+            contents[*offset as usize..(offset + size) as usize].copy_from_slice(&binding.contents)
+        }
+        let buffer = device
+            .create_buffer_init(&BufferInitDescriptor {
+                label: Some(&format!("{} packed", self.label())),
+                contents: &contents,
+                usage: BufferUsages::UNIFORM | BufferUsages::COPY_DST,
+            })
+            .or_report();
+        let Some(buffer) = buffer else {
+            return false;
+        };
+
+        self.packed = Some(PackedBuffer { buffer, ranges });
+        for binding in self.bindings.iter_mut() {
+            binding.buffer = None
+        }
+        true
+    }
+
+    /// Gives every binding its own buffer again. Returns false if any couldn't be created
+    fn unpack(&mut self, device: &Device) -> bool {
+        let buffers = (0..self.bindings.len())
+            .map(|b_index| {
+                self.bindings[b_index].create_buffer(device, &self.binding_label(b_index))
+            })
+            .collect::<Option<Vec<_>>>();
+        let Some(buffers) = buffers else {
+            return false;
+        };
+
+        for (binding, buffer) in self.bindings.iter_mut().zip(buffers) {
+            binding.buffer = Some(buffer)
+        }
+        self.packed = None;
+        true
+    }
+
+    fn set_packed(&mut self, packed: bool, device: &Device) {
+        let changed = if packed {
+            self.pack(device)
+        } else {
+            self.unpack(device)
+        };
+        if changed {
+            self.refresh_bind_group(device)
+        }
+    }
+
+    /// Creates the buffers again from their contents, e.g. after the device is lost or the
+    /// group is renamed
+    fn recreate_buffers(&mut self, device: &Device) {
+        if self.packed.take().is_some() {
+            self.pack(device);
+        } else {
+            for b_index in 0..self.bindings.len() {
+                let label = self.binding_label(b_index);
+                self.bindings[b_index].recreate_buffer(device, &label)
+            }
+        }
+        // The old layout belongs to the old device
+        self.recreate_layout(device);
+        self.refresh_bind_group(device)
+    }
+
+    /// Buffers of the group, and the buffers it would have without packing
+    fn buffer_stats(&self) -> (BufferStats, BufferStats) {
+        let unpacked = BufferStats {
+            buffers: self.bindings.len() as u32,
+            bytes: self
+                .bindings
+                .iter()
+                .map(|binding| binding.contents.len() as u64)
+                .sum(),
+        };
+        let current = match &self.packed {
+            Some(packed) => BufferStats {
+                buffers: 1,
+                bytes: packed.buffer.size(),
+            },
+            None => unpacked,
+        };
+
+        (current, unpacked)
+    }
+
+    /// Writes the bytes to the binding's buffer, or to its range of the packed buffer.
+    /// Returns false if they couldn't be written
+    fn write(&mut self, b_index: usize, bytes: &[u8], queue: &Queue) -> bool {
+        let Some(binding) = self.bindings.get_mut(b_index) else {
+            return false;
+        };
+        // Other sizes would spill into the next binding of a packed buffer
+        if bytes.len() != binding.contents.len() {
+            return false;
+        }
+
+        binding.contents.copy_from_slice(bytes);
+        let written = match (&self.packed, &binding.buffer) {
+            (Some(packed), _) => match packed.ranges.get(b_index) {
+                Some((offset, size)) if *size == bytes.len() as u64 => {
+                    queue.write_buffer(&packed.buffer, *offset, bytes)
+                }
+                _ => return false,
+            },
+            (None, Some(buffer)) => queue.write_buffer(buffer, 0, bytes),
+            (None, None) => return false,
+        };
+        written.or_report().is_some()
+    }
+
+    fn write_value(&mut self, b_index: usize, queue: &Queue) {
+        let binding = &self.bindings[b_index];
+        let bytes = binding.encode(binding.value).to_le_bytes();
+        self.write(b_index, &bytes, queue);
+    }
 
-        self.bind_group = bg;
+    fn change_value(
+        &mut self,
+        b_index: usize,
+        new_value: UniformValue,
+        device: &Device,
+        queue: &Queue,
+    ) {
+        let label = self.binding_label(b_index);
+        if self.bindings[b_index].change_value(new_value, device, &label) {
+            self.write_value(b_index, queue)
+        }
+        self.refresh_bind_group(device);
     }
 
     fn define_binding(&mut self, binding: u32, device: &Device) {
@@ -356,16 +925,8 @@ impl UniformGroup {
         queue: &Queue,
         device: &Device,
     ) {
-        self.bindings[b_index].change_type(unitype, queue, device);
-        self.refresh_bind_group(device);
-    }
-
-    fn increase(&mut self, b_index: usize, queue: &Queue) {
-        self.bindings[b_index].increase(queue)
-    }
-
-    fn decrease(&mut self, b_index: usize, queue: &Queue) {
-        self.bindings[b_index].decrease(queue)
+        let new_value = self.bindings[b_index].value.cast_to(unitype);
+        self.change_value(b_index, new_value, device, queue)
     }
 
     fn change_inner_type(
@@ -375,8 +936,9 @@ impl UniformGroup {
         device: &Device,
         queue: &Queue,
     ) {
-        self.bindings[b_index].change_inner_type(inner_type, queue);
-        self.refresh_bind_group(device);
+        let mut new_value = self.bindings[b_index].value;
+        new_value.change_inner_type(inner_type);
+        self.change_value(b_index, new_value, device, queue)
     }
 
     fn change_binding_size(
@@ -386,8 +948,12 @@ impl UniformGroup {
         device: &Device,
         queue: &Queue,
     ) {
-        self.bindings[b_index].change_binding_size(new_size, device, queue);
-        self.refresh_bind_group(device);
+        let new_value = match UniformType::from_size(new_size) {
+            Some(new_type) => self.bindings[b_index].value.cast_to(new_type),
+            // Structs and other types that can't be edited yet
+            None => UniformValue::Raw(new_size.max(4)),
+        };
+        self.change_value(b_index, new_value, device, queue)
     }
 
     fn change_matrix_size(
@@ -397,8 +963,9 @@ impl UniformGroup {
         device: &Device,
         queue: &Queue,
     ) {
-        self.bindings[b_index].change_matrix_size(matrix_size, queue);
-        self.refresh_bind_group(device);
+        let mut new_value = self.bindings[b_index].value;
+        new_value.change_matrix_size(matrix_size);
+        self.change_value(b_index, new_value, device, queue)
     }
 
     fn to_json(&self) -> serde_json::Value {
@@ -410,8 +977,67 @@ impl UniformGroup {
         serde_json::Value::Array(bindings)
     }
 
-    fn set_name(&mut self, b_index: usize, name: String) {
-        self.bindings[b_index].name = name
+    /// Declarations of every binding for the shader, after a comment with the group's label
+    fn wgsl_declarations(&self) -> String {
+        let g_index = self.index;
+        let mut wgsl = format!("// {}\n", self.label());
+        if self.bindings.iter().any(|binding| {
+            matches!(
+                binding.value,
+                UniformValue::BuiltIn(BuiltinValue::Light { .. })
+            )
+        }) {
+            wgsl.push_str(LIGHT_STRUCT)
+        }
+        let names = wgsl_identifiers(
+            self.bindings.iter().map(|binding| binding.name.as_str()),
+            g_index,
+        );
+        for (b_index, (binding, name)) in self.bindings.iter().zip(names).enumerate() {
+            match (binding.value.wgsl_type(), binding.value) {
+                (Some(wgsl_type), _) => wgsl.push_str(&format!(
+                    "@group({g_index}) @binding({b_index}) var<uniform> {name}: {wgsl_type};\n"
+                )),
+                (None, UniformValue::BuiltIn(BuiltinValue::Camera { .. })) => {
+                    wgsl.push_str(&CameraUniform::wgsl_declaration(g_index, b_index))
+                }
+                (None, UniformValue::BuiltIn(BuiltinValue::Light { .. })) => wgsl.push_str(
+                    &format!("@group({g_index}) @binding({b_index}) var<uniform> {name}: Light;\n"),
+                ),
+                (None, _) => wgsl.push_str(&format!(
+                    "// @group({g_index}) @binding({b_index}) {name}: a struct of {} bytes\n",
+                    binding.contents.len()
+                )),
+            }
+        }
+
+        wgsl
+    }
+
+    fn has_builtin(&self) -> bool {
+        self.bindings
+            .iter()
+            .any(|binding| matches!(binding.value, UniformValue::BuiltIn(_)))
+    }
+
+    fn set_locked(&mut self, b_index: usize, locked: bool) {
+        self.bindings[b_index].locked = locked
+    }
+
+    fn set_baseline(&mut self, b_index: usize, baseline: UniformValue) {
+        self.bindings[b_index].baseline = Some(baseline)
+    }
+
+    fn set_display(&mut self, b_index: usize, display: VecDisplay) {
+        self.bindings[b_index].display = display
+    }
+
+    fn set_color(&mut self, b_index: usize, color: ColorOptions) {
+        self.bindings[b_index].color = color
+    }
+
+    fn set_matrix_view(&mut self, b_index: usize, matrix_view: MatrixView) {
+        self.bindings[b_index].matrix_view = matrix_view
     }
 }
 
@@ -443,7 +1069,32 @@ fn get_matrix4_bytes(mat4: Matrix4<f32>) -> M4Iter {
 }
 
 impl CameraUniform {
+    /// WGSL declaration of the camera. Must match the layout of `to_le_bytes`
+    pub(crate) fn wgsl_declaration(group_index: usize, binding_index: usize) -> String {
+        format!(
+            "struct Camera {{
+    pos: vec3<f32>,
+    projection: mat4x4<f32>,
+    view: mat4x4<f32>,
+    inverse_view: mat4x4<f32>,
+    inverse_proj: mat4x4<f32>,
+}}
+
+@group({group_index}) @binding({binding_index})
+var<uniform> camera: Camera;
+"
+        )
+    }
+
     pub(crate) fn to_le_bytes(&self) -> Vec<u8> {
+        self.le_bytes().collect()
+    }
+
+    pub(crate) fn write_le_bytes(&self, out: &mut Vec<u8>) {
+        out.extend(self.le_bytes())
+    }
+
+    fn le_bytes(&self) -> impl Iterator<Item = u8> {
         let position = self
             .position
             .x
@@ -463,68 +1114,633 @@ impl CameraUniform {
             .chain(view)
             .chain(inverse_view)
             .chain(inverse_proj)
-            .collect()
     }
 }
 
 pub struct Uniforms {
     pub groups: Vec<UniformGroup>,
     time_uniform_location: (usize, usize),
+    window_size_uniform_location: (usize, usize),
+    pan_zoom_uniform_location: (usize, usize),
+    random_uniform_location: (usize, usize),
     camera_uniform_location: (usize, usize),
+    camera_path: CameraPath,
+    camera_bookmarks: CameraBookmarks,
+    /// Smooth move of the manual camera that's in progress
+    camera_transition: Option<CameraTransition>,
+    /// What the time builtin got the last frame
+    time: Duration,
+    rng: Rng,
+    writes: UniformWrites,
+    script: Script,
+    parameter_source: ParameterSource,
+    /// Changes with every edit of what's saved, so the unsaved changes are only compared after one
+    edit: u64,
 }
 
-impl Uniforms {
-    fn new(device: &Device) -> Uniforms {
-        let mut group0 = UniformGroup::new(device);
-        group0.add_custom(device, UniformValue::BuiltIn(BuiltinValue::Time));
-        let time_uniform_location = (0, 0);
-        let mut group1 = UniformGroup::new(device);
-        let yaw: Rad<f32> = Deg(-45.0).into();
-        let pitch: Rad<f32> = Deg(-45.0).into();
-        group1.add_custom(
-            device,
-            UniformValue::BuiltIn(BuiltinValue::Camera {
-                position: Point3 {
-                    x: -1.5,
-                    y: 1.2,
-                    z: 0.5,
-                },
-                yaw: yaw.0,
-                pitch: pitch.0,
-                enabled: false,
-            }),
-        );
-        let camera_uniform_location = (1, 0);
-        Uniforms {
-            groups: vec![group0, group1],
-            time_uniform_location,
-            camera_uniform_location,
-        }
-    }
+/// Source of `Uniforms::edit`, shared so uniforms that replace others never reuse a value
+static EDITS: AtomicU64 = AtomicU64::new(0);
 
-    fn add_f32(&mut self, g_index: usize, device: &Device) {
-        self.groups[g_index].add_f32(device)
-    }
+fn next_edit() -> u64 {
+    EDITS.fetch_add(1, Ordering::Relaxed) + 1
+}
 
-    fn update_buffer(&mut self, g_index: usize, b_index: usize, queue: &Queue) {
-        self.groups[g_index].update_buffer(b_index, queue)
+fn scalar_element_type(scalar: ScalarUniformValue) -> ElementType {
+    match scalar {
+        ScalarUniformValue::U32(_) => ElementType::U32,
+        ScalarUniformValue::I32(_) => ElementType::I32,
+        ScalarUniformValue::F32(_) => ElementType::F32,
     }
+}
+
+/// Where the camera starts, and where "Reset view" takes it back to
+fn default_camera_view() -> CameraKeyframe {
+    let yaw: Rad<f32> = Deg(-45.0).into();
+    let pitch: Rad<f32> = Deg(-45.0).into();
+    CameraKeyframe {
+        time: 0.0,
+        position: Point3 {
+            x: -1.5,
+            y: 1.2,
+            z: 0.5,
+        },
+        yaw: yaw.0,
+        pitch: pitch.0,
+    }
+}
+
+/// Flushes uniform writes to the buffers of the groups
+struct GroupWrites<'a> {
+    groups: &'a mut [UniformGroup],
+    queue: &'a Queue,
+}
+
+impl WriteTarget for GroupWrites<'_> {
+    fn value(&self, (g_index, b_index): (usize, usize), out: &mut Vec<u8>) -> bool {
+        match self
+            .groups
+            .get(g_index)
+            .and_then(|group| group.bindings.get(b_index))
+        {
+            Some(binding) => {
+                binding.encode(binding.value).write_le_bytes(out);
+                true
+            }
+            None => false,
+        }
+    }
+
+    fn write(&mut self, (g_index, b_index): (usize, usize), bytes: &[u8]) -> bool {
+        self.groups
+            .get_mut(g_index)
+            .is_some_and(|group| group.write(b_index, bytes, self.queue))
+    }
+}
+
+impl Uniforms {
+    fn new(device: &Device) -> Option<Uniforms> {
+        let mut group0 = UniformGroup::new(device, 0)?;
+        group0.add_custom(
+            device,
+            UniformValue::BuiltIn(BuiltinValue::Time(TimeUnit::Milliseconds)),
+        );
+        let time_uniform_location = (0, 0);
+        group0.add_custom(device, UniformValue::BuiltIn(BuiltinValue::WindowSize));
+        let window_size_uniform_location = (0, 1);
+        group0.add_custom(device, UniformValue::BuiltIn(DEFAULT_PAN_ZOOM));
+        let pan_zoom_uniform_location = (0, 2);
+        group0.add_custom(device, UniformValue::BuiltIn(DEFAULT_RANDOM));
+        let random_uniform_location = (0, 3);
+        let mut group1 = UniformGroup::new(device, 1)?;
+        let view = default_camera_view();
+        group1.add_custom(
+            device,
+            UniformValue::BuiltIn(BuiltinValue::Camera {
+                position: view.position,
+                yaw: view.yaw,
+                pitch: view.pitch,
+                enabled: false,
+            }),
+        );
+        let camera_uniform_location = (1, 0);
+        Some(Uniforms {
+            groups: vec![group0, group1],
+            time_uniform_location,
+            window_size_uniform_location,
+            pan_zoom_uniform_location,
+            random_uniform_location,
+            camera_uniform_location,
+            camera_path: CameraPath::default(),
+            camera_bookmarks: CameraBookmarks::default(),
+            camera_transition: None,
+            time: Duration::ZERO,
+            rng: Rng::from_time(),
+            writes: UniformWrites::default(),
+            script: Script::default(),
+            parameter_source: ParameterSource::default(),
+            edit: next_edit(),
+        })
+    }
+
+    fn add_f32(&mut self, g_index: usize, device: &Device) {
+        self.groups[g_index].add_f32(device)
+    }
+
+    fn add_light(&mut self, g_index: usize, device: &Device) {
+        let group = &mut self.groups[g_index];
+        let name = format!("light_{}", group.bindings.len());
+        group.add_named(device, UniformValue::BuiltIn(DEFAULT_LIGHT), name)
+    }
+
+    /// The binding's value is written before the next submit, once however many times it changes
+    fn update_buffer(&mut self, g_index: usize, b_index: usize) {
+        self.mark_edited();
+        self.writes.mark_dirty((g_index, b_index))
+    }
+
+    /// Something that's saved changed
+    fn mark_edited(&mut self) {
+        self.edit = next_edit()
+    }
+
+    /// Writes bytes other than the binding's value before the next submit, instead of the value
+    fn override_buffer(&mut self, location: (usize, usize), write: impl FnOnce(&mut Vec<u8>)) {
+        self.writes.override_with(location, write)
+    }
+
+    /// Issues the writes of this frame, it must be called right before submitting
+    pub(crate) fn flush_writes(&mut self, queue: &Queue) {
+        self.writes.flush(&mut GroupWrites {
+            groups: &mut self.groups,
+            queue,
+        })
+    }
+
+    /// Current contents of every enabled binding. Builtins that change every frame are
+    /// animated by the page instead
+    pub(crate) fn web_groups(&self) -> Vec<Vec<WebBinding>> {
+        self.enabled_groups()
+            .map(|group| {
+                group
+                    .bindings
+                    .iter()
+                    .map(|binding| {
+                        let bytes = binding.contents.clone();
+                        let animation = match binding.value {
+                            UniformValue::BuiltIn(BuiltinValue::Time(TimeUnit::Milliseconds)) => {
+                                Some(WebAnimation::Milliseconds)
+                            }
+                            UniformValue::BuiltIn(BuiltinValue::Time(TimeUnit::Seconds)) => {
+                                Some(WebAnimation::Seconds)
+                            }
+                            UniformValue::BuiltIn(BuiltinValue::WindowSize) => {
+                                Some(WebAnimation::WindowSize)
+                            }
+                            UniformValue::BuiltIn(BuiltinValue::Random {
+                                kind,
+                                frozen: false,
+                                ..
+                            }) => Some(match kind {
+                                RandomType::F32 => WebAnimation::RandomF32,
+                                RandomType::U32 => WebAnimation::RandomU32,
+                            }),
+                            _ => None,
+                        };
+                        WebBinding { bytes, animation }
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+
+    /// The bindings of every enabled group with the contents of their buffers
+    pub(crate) fn rust_groups(&self) -> Vec<Vec<RustBinding>> {
+        self.enabled_groups()
+            .map(|group| {
+                group
+                    .bindings
+                    .iter()
+                    .map(|binding| {
+                        let element_type = match binding.value {
+                            UniformValue::Scalar(scalar) => scalar_element_type(scalar),
+                            UniformValue::Vector(vector) => {
+                                scalar_element_type(script::components(&vector)[0])
+                            }
+                            UniformValue::BuiltIn(BuiltinValue::Time(TimeUnit::Milliseconds))
+                            | UniformValue::BuiltIn(BuiltinValue::Random {
+                                kind: RandomType::U32,
+                                ..
+                            }) => ElementType::U32,
+                            UniformValue::Raw(_) => ElementType::Byte,
+                            _ => ElementType::F32,
+                        };
+                        let builtin = match binding.value {
+                            UniformValue::BuiltIn(BuiltinValue::Time(TimeUnit::Milliseconds)) => {
+                                Some("the milliseconds since it started")
+                            }
+                            UniformValue::BuiltIn(BuiltinValue::Time(TimeUnit::Seconds)) => {
+                                Some("the seconds since it started")
+                            }
+                            UniformValue::BuiltIn(BuiltinValue::WindowSize) => {
+                                Some("the window size")
+                            }
+                            UniformValue::BuiltIn(BuiltinValue::PanZoom { .. }) => {
+                                Some("the 2D view")
+                            }
+                            UniformValue::BuiltIn(BuiltinValue::Camera { .. }) => {
+                                Some("the camera")
+                            }
+                            UniformValue::BuiltIn(BuiltinValue::Random {
+                                frozen: false, ..
+                            }) => Some("a random value every frame"),
+                            _ => None,
+                        };
+                        RustBinding {
+                            name: binding.name.clone(),
+                            builtin,
+                            values: RustValues::from_le_bytes(element_type, &binding.contents),
+                        }
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+
+    pub(crate) fn write_stats(&self) -> WriteStats {
+        self.writes.last_frame()
+    }
+
+    /// Uniform buffers of every group, and the buffers there would be without packing
+    pub(crate) fn buffer_stats(&self) -> (BufferStats, BufferStats) {
+        let mut current = BufferStats::default();
+        let mut unpacked = BufferStats::default();
+        for group in self.groups.iter() {
+            let (group_current, group_unpacked) = group.buffer_stats();
+            current += group_current;
+            unpacked += group_unpacked;
+        }
 
-    fn add_bind_group(&mut self, device: &Device) {
-        self.groups.push(UniformGroup::new(device))
+        (current, unpacked)
     }
 
-    pub(crate) fn update_time(&self, elapsed_time: u32, queue: &Queue) {
+    fn binding_size(&self, g_index: usize, b_index: usize) -> usize {
+        self.groups[g_index].bindings[b_index]
+            .value
+            .to_le_bytes()
+            .len()
+    }
+
+    /// Creates every buffer again from its contents, e.g. after the device is lost
+    pub(crate) fn recreate_buffers(&mut self, device: &Device) {
+        for group in self.groups.iter_mut() {
+            group.recreate_buffers(device)
+        }
+    }
+
+    /// Returns false if the group couldn't be created
+    fn add_bind_group(&mut self, device: &Device) -> bool {
+        let Some(group) = UniformGroup::new(device, self.groups.len()) else {
+            return false;
+        };
+        self.groups.push(group);
+        true
+    }
+
+    /// Appends a binding with `value` to the group, adding groups until it exists.
+    /// The pipeline must be reloaded afterwards, with `Message::ReloadPipeline`.
+    /// Builtins can't be added, since their locations are tracked when loading parameters
+    pub fn push_value(
+        &mut self,
+        device: &Device,
+        g_index: usize,
+        value: UniformValue,
+    ) -> Result<(), String> {
+        if let UniformValue::BuiltIn(_) = value {
+            return Err("Builtin uniforms can't be added programmatically".into());
+        }
+
+        while self.groups.len() <= g_index {
+            if !self.add_bind_group(device) {
+                return Err(format!("Couldn't create group {}", self.groups.len()));
+            }
+        }
+        self.groups[g_index].add_custom(device, value);
+        self.mark_edited();
+        Ok(())
+    }
+
+    /// Groups that are part of the pipeline layout.
+    /// Only trailing groups can be disabled, so these keep the indices the shader expects
+    pub(crate) fn enabled_groups(&self) -> impl Iterator<Item = &UniformGroup> {
+        self.groups.iter().take_while(|group| group.enabled)
+    }
+
+    /// Whether toggling the group would keep every enabled group at its original index.
+    /// Groups with builtin uniforms are always enabled, since the grid shader needs them.
+    /// Groups past the device's limit can't be enabled
+    fn can_toggle_group(&self, g_index: usize, max_groups: usize) -> bool {
+        let group = &self.groups[g_index];
+        if group.enabled {
+            let next_disabled = self
+                .groups
+                .get(g_index + 1)
+                .map_or(true, |next| !next.enabled);
+            !group.has_builtin() && next_disabled
+        } else {
+            g_index < max_groups && (g_index == 0 || self.groups[g_index - 1].enabled)
+        }
+    }
+
+    fn set_group_enabled(&mut self, g_index: usize, enabled: bool) {
+        self.groups[g_index].enabled = enabled
+    }
+
+    pub(crate) fn is_group_disabled(&self, g_index: usize) -> bool {
+        self.groups
+            .get(g_index)
+            .map_or(false, |group| !group.enabled)
+    }
+
+    /// Enables the group and all the groups before it
+    pub(crate) fn enable_group(&mut self, g_index: usize) {
+        for group in self.groups.iter_mut().take(g_index + 1) {
+            group.enabled = true
+        }
+    }
+
+    pub(crate) fn update_time(&mut self, elapsed: Duration) {
+        self.time = elapsed;
         let (g_index, b_index) = self.time_uniform_location;
         let time_binding = &self.groups[g_index].bindings[b_index];
-        assert!(time_binding.value == UniformValue::BuiltIn(BuiltinValue::Time));
+        let UniformValue::BuiltIn(BuiltinValue::Time(unit)) = time_binding.value else {
+            unreachable!()
+        };
 
-        queue
-            .write_buffer(&time_binding.buffer, 0, &elapsed_time.to_le_bytes())
-            .unwrap();
+        self.override_buffer((g_index, b_index), |out| {
+            out.extend(unit.to_le_bytes(elapsed))
+        })
+    }
+
+    /// Runs the script, which changes the uniforms like a `UniformsHandle` would
+    pub(crate) fn run_script(&mut self, time: Duration, dt: Duration) {
+        if !self.script.is_runnable() {
+            return;
+        }
+
+        // When names repeat, the script sees the first binding
+        let mut values = rhai::Map::new();
+        let mut locations = HashMap::new();
+        for (g_index, group) in self.groups.iter().enumerate() {
+            for (b_index, binding) in group.bindings.iter().enumerate() {
+                if locations.contains_key(&binding.name) {
+                    continue;
+                }
+                if let Some(value) = script::to_dynamic(&binding.value) {
+                    values.insert(binding.name.as_str().into(), value);
+                    locations.insert(binding.name.clone(), (g_index, b_index));
+                }
+            }
+        }
+
+        let Some(values) = self.script.run(time, dt, values) else {
+            return;
+        };
+        for (name, value) in values {
+            let Some(&location) = locations.get(name.as_str()) else {
+                continue;
+            };
+            let current = self.groups[location.0].bindings[location.1].value;
+            let result = match script::from_dynamic(&current, &value) {
+                Some(new_value) if new_value == current => Ok(()),
+                Some(new_value) => UniformRequests::set(self, location, new_value),
+                None => Err(UniformsError::TypeMismatch(current)),
+            };
+            if let Err(err) = result {
+                self.script.set_error(format!("Couldn't set u.{name}: {err}"))
+            }
+        }
+    }
+
+    /// Mirrors the parameter source file into the bindings with the same names, if it changed.
+    /// Values that don't fit their binding are reported
+    pub(crate) fn apply_parameter_source(&mut self, log: &mut Log) {
+        let Some(values) = self.parameter_source.poll(log) else {
+            return;
+        };
+
+        self.unlink_parameters();
+        for (name, value) in values {
+            // When names repeat, the file sets the first binding
+            let location = self.groups.iter().enumerate().find_map(|(g_index, group)| {
+                let b_index = group
+                    .bindings
+                    .iter()
+                    .position(|binding| binding.name == name)?;
+                Some((g_index, b_index))
+            });
+            let Some(location) = location else {
+                log.push(
+                    Severity::Warning,
+                    format!(
+                        "{}: no parameter is named {name}",
+                        self.parameter_source.path()
+                    ),
+                );
+                continue;
+            };
+            let current = self.groups[location.0].bindings[location.1].value;
+            let result = match parameter_source::uniform_value(&current, &value) {
+                Some(new_value) if new_value == current => Ok(()),
+                Some(new_value) => UniformRequests::set(self, location, new_value),
+                None => Err(UniformsError::TypeMismatch(current)),
+            };
+            match result {
+                Ok(()) => self.groups[location.0].bindings[location.1].linked = true,
+                Err(err) => log.push(
+                    Severity::Warning,
+                    format!(
+                        "{}: couldn't set {name}: {err}",
+                        self.parameter_source.path()
+                    ),
+                ),
+            }
+        }
+    }
+
+    fn unlink_parameters(&mut self) {
+        for binding in self
+            .groups
+            .iter_mut()
+            .flat_map(|group| group.bindings.iter_mut())
+        {
+            binding.linked = false
+        }
+    }
+
+    /// The random builtin gives the same values again, for deterministic runs
+    pub(crate) fn reseed_random(&mut self) {
+        self.rng = Rng::from_seed(DETERMINISTIC_SEED)
+    }
+
+    /// Writes a new random value, unless it's frozen
+    pub(crate) fn update_random(&mut self) {
+        let (g_index, b_index) = self.random_uniform_location;
+        match &mut self.groups[g_index].bindings[b_index].value {
+            UniformValue::BuiltIn(BuiltinValue::Random {
+                frozen: false,
+                bits,
+                ..
+            }) => *bits = self.rng.next_u32(),
+            _ => return,
+        }
+
+        // Unfrozen values aren't saved, so this isn't an edit
+        self.writes.mark_dirty((g_index, b_index))
+    }
+
+    pub(crate) fn update_window_size(&mut self, width: u32, height: u32) {
+        let (g_index, b_index) = self.window_size_uniform_location;
+        let window_size_binding = &self.groups[g_index].bindings[b_index];
+        assert!(window_size_binding.value == UniformValue::BuiltIn(BuiltinValue::WindowSize));
+
+        self.override_buffer((g_index, b_index), |out| {
+            out.extend([width as f32, height as f32].iter().flat_map(|v| v.to_le_bytes()))
+        })
+    }
+
+    /// Moves the 2D view by `delta`, in normalized device coordinates of the render
+    fn pan_2d(&mut self, delta: [f32; 2]) {
+        let (g_index, b_index) = self.pan_zoom_uniform_location;
+        if let UniformValue::BuiltIn(BuiltinValue::PanZoom { offset, zoom, .. }) =
+            &mut self.groups[g_index].bindings[b_index].value
+        {
+            offset[0] -= delta[0] / *zoom;
+            offset[1] -= delta[1] / *zoom;
+        }
+
+        self.update_buffer(g_index, b_index)
+    }
+
+    /// Zooms the 2D view by `steps` mouse wheel steps, keeping the point under `cursor` in place
+    fn zoom_2d(&mut self, steps: f32, cursor: [f32; 2]) {
+        let (g_index, b_index) = self.pan_zoom_uniform_location;
+        if let UniformValue::BuiltIn(BuiltinValue::PanZoom { offset, zoom, .. }) =
+            &mut self.groups[g_index].bindings[b_index].value
+        {
+            let new_zoom = (*zoom * ZOOM_STEP.powf(steps)).clamp(MIN_ZOOM, MAX_ZOOM);
+            offset[0] += cursor[0] * (1.0 / *zoom - 1.0 / new_zoom);
+            offset[1] += cursor[1] * (1.0 / *zoom - 1.0 / new_zoom);
+            *zoom = new_zoom;
+        }
+
+        self.update_buffer(g_index, b_index)
+    }
+
+    pub(crate) fn enable_pan_zoom(&mut self, enable: bool) {
+        let (g_index, b_index) = self.pan_zoom_uniform_location;
+        match &mut self.groups[g_index].bindings[b_index].value {
+            UniformValue::BuiltIn(BuiltinValue::PanZoom { enabled, .. }) => *enabled = enable,
+            _ => unreachable!(),
+        };
+
+        self.update_buffer(g_index, b_index)
+    }
+
+    /// Overrides the camera buffer with the camera path while it is being followed.
+    /// The manual camera value is left untouched
+    pub(crate) fn update_camera_path(&mut self, elapsed: Duration) {
+        if !self.camera_path.enabled {
+            return;
+        }
+
+        let (g_index, b_index) = self.camera_uniform_location;
+        let camera_binding = &self.groups[g_index].bindings[b_index];
+        let seconds = elapsed.as_secs_f32();
+        if let (UniformValue::BuiltIn(BuiltinValue::Camera { enabled, .. }), Some(keyframe)) =
+            (camera_binding.value, self.camera_path.sample(seconds))
+        {
+            let camera = UniformValue::BuiltIn(BuiltinValue::Camera {
+                position: keyframe.position,
+                yaw: keyframe.yaw,
+                pitch: keyframe.pitch,
+                enabled,
+            });
+            self.override_buffer((g_index, b_index), |out| camera.write_le_bytes(out));
+        }
+    }
+
+    fn current_camera(&self) -> Option<CameraKeyframe> {
+        let (g_index, b_index) = self.camera_uniform_location;
+        match self.groups[g_index].bindings[b_index].value {
+            UniformValue::BuiltIn(BuiltinValue::Camera {
+                position,
+                yaw,
+                pitch,
+                ..
+            }) => Some(CameraKeyframe {
+                time: 0.0,
+                position,
+                yaw,
+                pitch,
+            }),
+            _ => None,
+        }
+    }
+
+    /// Moves the manual camera to the view, over the bookmarks' transition duration
+    fn move_camera_to(&mut self, view: CameraKeyframe) {
+        let duration = self.camera_bookmarks.transition_seconds();
+        match self.current_camera() {
+            Some(current) if duration > 0.0 => {
+                self.camera_transition = Some(CameraTransition::new(current, view, duration))
+            }
+            _ => {
+                self.camera_transition = None;
+                self.set_camera(view)
+            }
+        }
+    }
+
+    /// Advances the camera transition by `dt`, if there's one
+    pub(crate) fn update_camera_transition(&mut self, dt: Duration) {
+        let Some(transition) = &mut self.camera_transition else {
+            return;
+        };
+
+        let (view, finished) = transition.advance(dt.as_secs_f32());
+        if finished {
+            self.camera_transition = None
+        }
+        self.set_camera(view);
+        let (g_index, b_index) = self.camera_uniform_location;
+        self.update_buffer(g_index, b_index)
+    }
+
+    /// Moves the manual camera to the view, it still has to be written to its buffer
+    fn set_camera(&mut self, view: CameraKeyframe) {
+        let (g_index, b_index) = self.camera_uniform_location;
+        if let UniformValue::BuiltIn(BuiltinValue::Camera {
+            position,
+            yaw,
+            pitch,
+            ..
+        }) = &mut self.groups[g_index].bindings[b_index].value
+        {
+            *position = view.position;
+            *yaw = view.yaw;
+            *pitch = view.pitch;
+        }
+    }
+
+    pub(crate) fn camera_enabled(&self) -> bool {
+        let (g_index, b_index) = self.camera_uniform_location;
+        matches!(
+            self.groups[g_index].bindings[b_index].value,
+            UniformValue::BuiltIn(BuiltinValue::Camera { enabled: true, .. })
+        )
     }
 
-    pub(crate) fn enable_camera(&mut self, enable: bool, queue: &Queue) {
+    pub(crate) fn enable_camera(&mut self, enable: bool) {
         let (g_index, b_index) = self.camera_uniform_location;
         let camera_binding = &mut self.groups[g_index].bindings[b_index];
 
@@ -533,12 +1749,14 @@ impl Uniforms {
             _ => unreachable!(),
         };
 
-        self.update_buffer(g_index, b_index, queue)
+        self.update_buffer(g_index, b_index)
     }
 
     pub(crate) fn define_binding(&mut self, group: u32, binding: u32, device: &Device) {
         while group >= self.groups.len() as u32 {
-            self.add_bind_group(device)
+            if !self.add_bind_group(device) {
+                return;
+            }
         }
 
         self.groups[group as usize].define_binding(binding, device);
@@ -555,12 +1773,84 @@ impl Uniforms {
         self.groups[g_index].change_type(unitype, b_index, queue, device)
     }
 
-    fn increase(&mut self, g_index: usize, b_index: usize, queue: &Queue) {
-        self.groups[g_index].increase(b_index, queue)
+    /// Like the other value edits, it's written once per frame however many times it's repeated
+    fn increase(&mut self, g_index: usize, b_index: usize) {
+        self.groups[g_index].bindings[b_index].value.increase();
+        self.update_buffer(g_index, b_index)
+    }
+
+    fn decrease(&mut self, g_index: usize, b_index: usize) {
+        self.groups[g_index].bindings[b_index].value.decrease();
+        self.update_buffer(g_index, b_index)
+    }
+
+    /// Locked bindings, and linked ones while the parameter source locks edits, don't change
+    fn perturb(
+        &mut self,
+        g_index: usize,
+        b_index: usize,
+        perturbation: Perturbation,
+        rng: &mut Rng,
+    ) {
+        let lock_linked = self.parameter_source.is_active() && self.parameter_source.lock_edits;
+        let binding = &mut self.groups[g_index].bindings[b_index];
+        if binding.locked || (binding.linked && lock_linked) {
+            return;
+        }
+        if let Some(value) = randomize::perturb(&binding.value, perturbation, rng) {
+            binding.value = value;
+            binding.sanitized = binding.value.sanitize();
+            self.update_buffer(g_index, b_index)
+        }
+    }
+
+    /// View and projection of the camera, the identity when it's disabled
+    fn camera_view_projection(&self) -> Option<Matrix4<f32>> {
+        let (g_index, b_index) = self.camera_uniform_location;
+        match self.groups.get(g_index)?.bindings.get(b_index)?.value {
+            UniformValue::BuiltIn(camera) => camera.view_projection(),
+            _ => None,
+        }
+    }
+
+    /// Translation of every transform, or position of every light, that shows its gizmo and can
+    /// be edited
+    fn gizmo_translations(&self) -> Vec<((usize, usize), Vector3<f32>)> {
+        let lock_linked = self.parameter_source.is_active() && self.parameter_source.lock_edits;
+        let mut translations = Vec::new();
+        for (g_index, group) in self.groups.iter().enumerate() {
+            for (b_index, binding) in group.bindings.iter().enumerate() {
+                let translation = match binding.value {
+                    UniformValue::Transform(transform) => transform.translation(),
+                    UniformValue::BuiltIn(BuiltinValue::Light { position, .. }) => position.into(),
+                    _ => continue,
+                };
+                if binding.gizmo && !binding.locked && !(binding.linked && lock_linked) {
+                    translations.push(((g_index, b_index), translation))
+                }
+            }
+        }
+
+        translations
+    }
+
+    fn translate_binding(&mut self, (g_index, b_index): (usize, usize), offset: Vector3<f32>) {
+        let binding = &mut self.groups[g_index].bindings[b_index];
+        match &mut binding.value {
+            UniformValue::Transform(transform) => transform.translate(offset),
+            UniformValue::BuiltIn(BuiltinValue::Light { position, .. }) => {
+                *position = (Vector3::from(*position) + offset).into()
+            }
+            _ => return,
+        }
+        binding.sanitized = binding.value.sanitize();
+        self.update_buffer(g_index, b_index)
     }
 
-    fn decrease(&mut self, g_index: usize, b_index: usize, queue: &Queue) {
-        self.groups[g_index].decrease(b_index, queue)
+    fn randomize_group(&mut self, g_index: usize, rng: &mut Rng) {
+        for b_index in 0..self.groups[g_index].bindings.len() {
+            self.perturb(g_index, b_index, Perturbation::Randomize, rng)
+        }
     }
 
     fn change_inner_type(
@@ -596,25 +1886,68 @@ impl Uniforms {
         self.groups[g_index].change_matrix_size(matrix_size, b_index, device, queue)
     }
 
-    pub(crate) fn save(&self, shader_name: &str) {
-        let config = std::fs::read_to_string("save.json").unwrap_or(String::from("{}"));
-        let config = serde_json::from_str(&config).unwrap_or(JsonValue::Object(Map::new()));
-
-        let mut config = if let JsonValue::Object(config) = config {
-            config
-        } else {
-            serde_json::Map::new()
+    /// Once saved, the values are the bindings' new baselines
+    pub(crate) fn save(&mut self, shader_name: &str, save_file: &Path) -> Result<(), String> {
+        let mut config = match std::fs::read_to_string(save_file) {
+            Ok(config) => match serde_json::from_str(&config) {
+                Ok(JsonValue::Object(config)) => config,
+                // Saving would wipe the parameters of every other shader
+                _ => {
+                    return Err(format!(
+                        "Not saving because {} isn't valid, fix or delete it first",
+                        save_file.display()
+                    ))
+                }
+            },
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => Map::new(),
+            Err(err) => return Err(format!("Couldn't read {}: {err}", save_file.display())),
         };
 
-        config.get(shader_name);
+        config.insert(shader_name.into(), self.to_json());
+        // Serialize before touching the file, so a failure leaves the previous save intact
+        let contents = serde_json::to_string(&config)
+            .map_err(|err| format!("Couldn't serialize the parameters: {err}"))?;
+
+        // The save only replaces the original once it's been fully written
+        let mut temp_file = save_file.as_os_str().to_owned();
+        temp_file.push(".tmp");
+        let temp_file = PathBuf::from(temp_file);
+        if let Err(err) = std::fs::write(&temp_file, contents) {
+            let _ = std::fs::remove_file(&temp_file);
+            return Err(format!("Couldn't write {}: {err}", temp_file.display()));
+        }
+        std::fs::rename(&temp_file, save_file).map_err(|err| {
+            let _ = std::fs::remove_file(&temp_file);
+            format!("Couldn't replace {}: {err}", save_file.display())
+        })?;
+
+        for binding in self
+            .groups
+            .iter_mut()
+            .flat_map(|group| group.bindings.iter_mut())
+        {
+            binding.baseline = Some(binding.value)
+        }
+        Ok(())
+    }
 
+    pub(crate) fn to_json(&self) -> JsonValue {
         let tul = self.time_uniform_location;
         let time_uniform_location = JsonValue::Array(vec![JsonValue::Number(serde_json::Number::from(tul.0)), JsonValue::Number(serde_json::Number::from(tul.1))]);
+        let wsul = self.window_size_uniform_location;
+        let window_size_uniform_location = JsonValue::Array(vec![JsonValue::Number(serde_json::Number::from(wsul.0)), JsonValue::Number(serde_json::Number::from(wsul.1))]);
         let cul = self.camera_uniform_location;
         let camera_uniform_location = JsonValue::Array(vec![JsonValue::Number(serde_json::Number::from(cul.0)), JsonValue::Number(serde_json::Number::from(cul.1))]);
 
         let mut shader_conf = Map::new();
         shader_conf.insert("time_uniform_location".into(), time_uniform_location);
+        shader_conf.insert("window_size_uniform_location".into(), window_size_uniform_location);
+        let pzul = self.pan_zoom_uniform_location;
+        let pan_zoom_uniform_location = JsonValue::Array(vec![JsonValue::Number(serde_json::Number::from(pzul.0)), JsonValue::Number(serde_json::Number::from(pzul.1))]);
+        shader_conf.insert("pan_zoom_uniform_location".into(), pan_zoom_uniform_location);
+        let rul = self.random_uniform_location;
+        let random_uniform_location = JsonValue::Array(vec![JsonValue::Number(serde_json::Number::from(rul.0)), JsonValue::Number(serde_json::Number::from(rul.1))]);
+        shader_conf.insert("random_uniform_location".into(), random_uniform_location);
         shader_conf.insert("camera_uniform_location".into(), camera_uniform_location);
 
         let mut json_groups = Vec::new();
@@ -623,21 +1956,153 @@ impl Uniforms {
             json_groups.push(group.to_json());
         }
 
+        // The camera path and bookmarks are stored alongside the camera they drive
+        if let Some(camera) = json_groups
+            .get_mut(cul.0)
+            .and_then(|group| group.get_mut(cul.1))
+            .and_then(|binding| binding.get_mut("value"))
+            .and_then(JsonValue::as_object_mut)
+        {
+            camera.insert("path".into(), self.camera_path.to_json());
+            camera.insert("bookmarks".into(), self.camera_bookmarks.to_json());
+        }
+
         let json_groups = JsonValue::Array(json_groups);
         shader_conf.insert("groups".into(), json_groups);
+        // Kept apart from the groups so older versions can still read them, and left out
+        // while no group has a name so older saves don't look changed
+        if self.groups.iter().any(|group| !group.name.is_empty()) {
+            let group_names = self
+                .groups
+                .iter()
+                .map(|group| JsonValue::from(group.name.clone()))
+                .collect();
+            shader_conf.insert("group_names".into(), JsonValue::Array(group_names));
+        }
+        shader_conf.insert("script".into(), self.script.to_json());
+        shader_conf.insert(
+            "parameter_source".into(),
+            self.parameter_source.to_json(),
+        );
+
+        JsonValue::Object(shader_conf)
+    }
+
+    /// The parameters of `shader_name` as they are currently stored in the save file
+    pub(crate) fn read_saved(shader_name: &str, save_file: &Path) -> Option<JsonValue> {
+        let config = std::fs::read_to_string(save_file).ok()?;
+        let mut config: JsonValue = serde_json::from_str(&config).ok()?;
+
+        Some(config.as_object_mut()?.remove(shader_name)?)
+    }
+
+    /// Lists the bindings that differ from the saved parameters
+    fn unsaved_changes(&self, saved: Option<&JsonValue>) -> Vec<String> {
+        let Some(saved) = saved else {
+            return vec!["Parameters have never been saved".into()];
+        };
+
+        let current = self.to_json();
+        if current == *saved {
+            return Vec::new();
+        }
+
+        let no_groups = Vec::new();
+        let current_groups = current
+            .get("groups")
+            .and_then(JsonValue::as_array)
+            .unwrap_or(&no_groups);
+        let saved_groups = saved
+            .get("groups")
+            .and_then(JsonValue::as_array)
+            .unwrap_or(&no_groups);
+
+        let mut changes = Vec::new();
+        for g_index in 0..current_groups.len().max(saved_groups.len()) {
+            let current_group = current_groups
+                .get(g_index)
+                .and_then(JsonValue::as_array)
+                .unwrap_or(&no_groups);
+            let saved_group = saved_groups
+                .get(g_index)
+                .and_then(JsonValue::as_array)
+                .unwrap_or(&no_groups);
+            for b_index in 0..current_group.len().max(saved_group.len()) {
+                let current_binding = current_group.get(b_index);
+                let saved_binding = saved_group.get(b_index);
+                if current_binding != saved_binding {
+                    let name = current_binding
+                        .or(saved_binding)
+                        .and_then(|binding| binding.get("name"))
+                        .and_then(JsonValue::as_str)
+                        .unwrap_or("unnamed");
+                    changes.push(format!("Group {g_index}, binding {b_index} ({name})"))
+                }
+            }
+        }
 
-        config.insert(shader_name.into(), JsonValue::Object(shader_conf));
-        let file = std::fs::OpenOptions::new().create(true).write(true).open("save.json").unwrap();
-        serde_json::to_writer(file, &config).unwrap();
+        if current.get("group_names") != saved.get("group_names") {
+            changes.push("Group names".into())
+        }
+        if current.get("script") != saved.get("script") {
+            changes.push("Script".into())
+        }
+        if current.get("parameter_source") != saved.get("parameter_source") {
+            changes.push("Parameter source".into())
+        }
+
+        if changes.is_empty() {
+            changes.push("Builtin uniform locations".into())
+        }
+
+        changes
+    }
+
+    /// Older saves don't have every builtin. They are added at the end of the time's group,
+    /// so existing bindings keep their indices. If it's full they go in the first enabled group
+    /// with room, or in a new group
+    fn add_missing_builtin(
+        groups: &mut Vec<UniformGroup>,
+        time_group: usize,
+        builtin: BuiltinValue,
+        device: &Device,
+    ) -> Option<(usize, usize)> {
+        let max_bindings = device.limits().max_bindings_per_bind_group as usize;
+        let has_room = |group: &UniformGroup| group.enabled && group.bindings.len() < max_bindings;
+        let g_index = match groups.get(time_group) {
+            Some(group) if has_room(group) => time_group,
+            _ => match groups.iter().position(has_room) {
+                Some(g_index) => g_index,
+                None => {
+                    groups.push(UniformGroup::new(device, groups.len())?);
+                    groups.len() - 1
+                }
+            },
+        };
+        let group = &mut groups[g_index];
+        group.add_custom(device, UniformValue::BuiltIn(builtin));
+        Some((g_index, group.bindings.len() - 1))
     }
 
-    pub(crate) fn load(device: &Device, shader_name: &str) -> Option<Uniforms> {
-        let config = std::fs::read_to_string("save.json").ok()?;
+    pub(crate) fn load(
+        device: &Device,
+        shader_name: &str,
+        save_file: &Path,
+        log: &mut Log,
+    ) -> Option<Uniforms> {
+        let config = std::fs::read_to_string(save_file).ok()?;
         let config: JsonValue = serde_json::from_str(&config).ok()?;
 
-        let config = config.as_object()?
-            .get(shader_name)?
-            .as_object()?;
+        Self::from_json(device, config.as_object()?.get(shader_name)?, log)
+    }
+
+    /// The parameters of a shader, as they are stored in the save file
+    pub(crate) fn from_json(
+        device: &Device,
+        config: &JsonValue,
+        log: &mut Log,
+    ) -> Option<Uniforms> {
+        let config = config.as_object()?;
 
         let time_uniform_location = config.get("time_uniform_location")?.as_array()?;
         let camera_uniform_location = config.get("camera_uniform_location")?.as_array()?;
@@ -651,37 +2116,106 @@ impl Uniforms {
             ((tul_0 as usize, tul_1 as usize), (cul_0 as usize, cul_1 as usize))
         } else {
             if let None = cul_0.as_u64() {
-                println!("cul_0 is not a number")
+                log.push(Severity::Warning, "cul_0 is not a number".into())
             }
             if let None = cul_1.as_u64() {
-                println!("cul_1 is not a number")
+                log.push(Severity::Warning, "cul_1 is not a number".into())
             }
             if let None = tul_0.as_u64() {
-                println!("tul_0 is not a number")
+                log.push(Severity::Warning, "tul_0 is not a number".into())
             }
             if let None = tul_1.as_u64() {
-                println!("tul_1 is not a number")
+                log.push(Severity::Warning, "tul_1 is not a number".into())
             }
-            println!("Couldn't load saved data because uniform locations items aren't numbers");
+            log.push(Severity::Warning, "Couldn't load saved data because uniform locations items aren't numbers".into());
             return None
         };
 
         let json_groups = config.get("groups")?.as_array()?;
+        // Older saves don't have group names
+        let no_names = Vec::new();
+        let group_names = config
+            .get("group_names")
+            .and_then(JsonValue::as_array)
+            .unwrap_or(&no_names);
 
         let mut groups = Vec::new();
         let mut time_count = 0;
+        let mut window_size_location = None;
+        let mut pan_zoom_location = None;
+        let mut random_location = None;
         let mut camera_count = 0;
-        for group in json_groups {
-            let mut uniform_group = UniformGroup::new(device);
+        for (g, group) in json_groups.iter().enumerate() {
+            let mut uniform_group = UniformGroup::new(device, g)?;
+            // Named before the bindings are added, so their labels have it
+            uniform_group.name = group_names
+                .get(g)
+                .and_then(JsonValue::as_str)
+                .unwrap_or_default()
+                .to_string();
             let group = group.as_array()?;
             for (i, uniform) in group.iter().enumerate() {
                 let name = uniform.get("name")?.as_str()?.into();
+                // Older saves don't have a lock flag
+                let locked = uniform
+                    .get("locked")
+                    .and_then(JsonValue::as_bool)
+                    .unwrap_or(false);
+                // Older saves show both editors
+                let display = uniform
+                    .get("display")
+                    .and_then(VecDisplay::from_json)
+                    .unwrap_or(VecDisplay::Both);
+                // Older saves upload the color as it was picked
+                let color = uniform
+                    .get("color")
+                    .and_then(ColorOptions::from_json)
+                    .unwrap_or_default();
+                // Older saves show the rows of matrices
+                let matrix_view = uniform
+                    .get("matrix_view")
+                    .and_then(MatrixView::from_json)
+                    .unwrap_or_default();
+                // Older saves don't have a baseline, the value was the baseline when it was saved
+                let baseline = uniform
+                    .get("baseline")
+                    .and_then(JsonValue::as_object)
+                    .and_then(|baseline| UniformValue::from_json(baseline, log))
+                    .map(|mut baseline| {
+                        baseline.sanitize();
+                        baseline
+                    });
                 let uniform = uniform.get("value")?.as_object()?;
-                let uniform = UniformValue::from_json(uniform)?;
-                uniform_group.add_custom(device, uniform);
-                uniform_group.set_name(i, name);
+                let mut uniform = UniformValue::from_json(uniform, log)?;
+                // Hand edited saves can have values no field can produce
+                let sanitized = uniform.sanitize();
+                uniform_group.add_named(device, uniform, name);
+                uniform_group.bindings[i].sanitized = sanitized;
+                uniform_group.set_locked(i, locked);
+                uniform_group.set_baseline(i, baseline.unwrap_or(uniform));
+                uniform_group.set_display(i, display);
+                uniform_group.set_color(i, color);
+                uniform_group.set_matrix_view(i, matrix_view);
                 match uniform {
-                    UniformValue::BuiltIn(BuiltinValue::Time) => time_count += 1,
+                    UniformValue::BuiltIn(BuiltinValue::Time(_)) => time_count += 1,
+                    UniformValue::BuiltIn(BuiltinValue::WindowSize) => {
+                        if window_size_location.replace((g, i)).is_some() {
+                            log.push(Severity::Warning, "Couldn't load saved data because there is more than 1 window size".into());
+                            return None
+                        }
+                    }
+                    UniformValue::BuiltIn(BuiltinValue::PanZoom { .. }) => {
+                        if pan_zoom_location.replace((g, i)).is_some() {
+                            log.push(Severity::Warning, "Couldn't load saved data because there is more than 1 2D view".into());
+                            return None
+                        }
+                    }
+                    UniformValue::BuiltIn(BuiltinValue::Random { .. }) => {
+                        if random_location.replace((g, i)).is_some() {
+                            log.push(Severity::Warning, "Couldn't load saved data because there is more than 1 random value".into());
+                            return None
+                        }
+                    }
                     UniformValue::BuiltIn(BuiltinValue::Camera { .. }) => camera_count += 1,
                     _ => ()
                 }
@@ -689,21 +2223,115 @@ impl Uniforms {
             groups.push(uniform_group)
         }
 
+        // Saved on a device with higher limits, the excess groups stay disabled
+        let max_groups = device.limits().max_bind_groups as usize;
+        if groups.len() > max_groups {
+            for group in groups.iter_mut().skip(max_groups) {
+                group.enabled = false
+            }
+            log.push(
+                Severity::Warning,
+                format!(
+                    "The saved parameters have {} groups, but this device only supports {max_groups}. The rest have been disabled",
+                    groups.len()
+                ),
+            )
+        }
+
         if time_count != 1 || camera_count != 1 {
-            println!("Couldn't load saved data because there is not exactl 1 time and 1 camera");
+            log.push(Severity::Warning, "Couldn't load saved data because there is not exactly 1 time and 1 camera".into());
             return None
         }
 
         // TODO: Check that time and camera are in correct positions
 
+        let window_size_uniform_location = match window_size_location {
+            Some(location) => location,
+            None => Self::add_missing_builtin(&mut groups, tul.0, BuiltinValue::WindowSize, device)?,
+        };
+        let pan_zoom_uniform_location = match pan_zoom_location {
+            Some(location) => location,
+            None => Self::add_missing_builtin(&mut groups, tul.0, DEFAULT_PAN_ZOOM, device)?,
+        };
+        let random_uniform_location = match random_location {
+            Some(location) => location,
+            None => Self::add_missing_builtin(&mut groups, tul.0, DEFAULT_RANDOM, device)?,
+        };
+
+        let max_bindings = device.limits().max_bindings_per_bind_group as usize;
+        for (g_index, group) in groups.iter().enumerate() {
+            if group.bindings.len() > max_bindings {
+                log.push(
+                    Severity::Warning,
+                    format!(
+                        "Binding group {g_index} has {} bindings, but this device only supports {max_bindings} per group",
+                        group.bindings.len()
+                    ),
+                )
+            }
+        }
+
+        let camera = json_groups
+            .get(cul.0)
+            .and_then(|group| group.get(cul.1))
+            .and_then(|binding| binding.get("value"));
+        let camera_path = camera
+            .and_then(|camera| camera.get("path"))
+            .and_then(CameraPath::from_json)
+            .unwrap_or_default();
+        let camera_bookmarks = camera
+            .and_then(|camera| camera.get("bookmarks"))
+            .and_then(CameraBookmarks::from_json)
+            .unwrap_or_default();
+        // Older saves don't have a script
+        let script = config
+            .get("script")
+            .and_then(Script::from_json)
+            .unwrap_or_default();
+        let parameter_source = config
+            .get("parameter_source")
+            .and_then(ParameterSource::from_json)
+            .unwrap_or_default();
+
+        // The buffers were created with the colors as they were picked
+        let mut writes = UniformWrites::default();
+        for (g_index, group) in groups.iter().enumerate() {
+            for (b_index, binding) in group.bindings.iter().enumerate() {
+                if binding.encode(binding.value) != binding.value {
+                    writes.mark_dirty((g_index, b_index))
+                }
+            }
+        }
+
         Some(Uniforms {
             groups,
             time_uniform_location: tul,
-            camera_uniform_location: cul
+            window_size_uniform_location,
+            pan_zoom_uniform_location,
+            random_uniform_location,
+            camera_uniform_location: cul,
+            camera_path,
+            camera_bookmarks,
+            camera_transition: None,
+            time: Duration::ZERO,
+            rng: Rng::from_time(),
+            writes,
+            script,
+            parameter_source,
+            edit: next_edit(),
         })
     }
 }
 
+/// How big the Render image is drawn, it's always rendered at `IMAGE_WIDTH`x`IMAGE_HEIGHT`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum PreviewSize {
+    /// As big as the window allows, keeping the aspect ratio
+    Fit,
+    /// One pixel per rendered pixel
+    Actual,
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 enum MeshType {
     Screen2D,
@@ -715,14 +2343,201 @@ enum MeshType {
     Torus,
 }
 
+/// How the custom shader's output is combined with what's behind it
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum BlendMode {
+    Alpha,
+    Additive,
+    Opaque,
+    Multiply,
+}
+
+impl BlendMode {
+    const ALL: &'static [BlendMode] = &[
+        BlendMode::Alpha,
+        BlendMode::Additive,
+        BlendMode::Opaque,
+        BlendMode::Multiply,
+    ];
+
+    fn label(&self) -> &'static str {
+        match self {
+            BlendMode::Alpha => "Alpha",
+            BlendMode::Additive => "Additive",
+            BlendMode::Opaque => "Opaque",
+            BlendMode::Multiply => "Multiply",
+        }
+    }
+
+    pub(crate) fn blend_state(&self) -> BlendState {
+        match self {
+            BlendMode::Alpha => BlendState::ALPHA_BLENDING,
+            BlendMode::Additive => BlendState {
+                color: BlendComponent {
+                    src_factor: BlendFactor::SrcAlpha,
+                    dst_factor: BlendFactor::One,
+                    operation: BlendOperation::Add,
+                },
+                alpha: BlendComponent {
+                    src_factor: BlendFactor::One,
+                    dst_factor: BlendFactor::One,
+                    operation: BlendOperation::Add,
+                },
+            },
+            BlendMode::Opaque => BlendState::REPLACE,
+            BlendMode::Multiply => BlendState {
+                color: BlendComponent {
+                    src_factor: BlendFactor::Dst,
+                    dst_factor: BlendFactor::Zero,
+                    operation: BlendOperation::Add,
+                },
+                alpha: BlendComponent::OVER,
+            },
+        }
+    }
+}
+
+/// Built-in shader the mesh is drawn with instead of the custom one
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub(crate) enum DebugView {
+    Off,
+    Normals,
+    UvChecker,
+    Depth,
+}
+
+impl DebugView {
+    const ALL: &'static [DebugView] = &[
+        DebugView::Off,
+        DebugView::Normals,
+        DebugView::UvChecker,
+        DebugView::Depth,
+    ];
+
+    pub(crate) fn label(&self) -> &'static str {
+        match self {
+            DebugView::Off => "Off",
+            DebugView::Normals => "Normals",
+            DebugView::UvChecker => "UV checker",
+            DebugView::Depth => "Depth",
+        }
+    }
+}
+
+/// How the normals of the generated meshes are computed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum NormalMode {
+    /// Per face, the vertices aren't shared between triangles
+    Flat,
+    /// Averaged per vertex
+    Smooth,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub enum MeshConfig {
     Screen2D,
     Plane((f32, f32), (u32, u32)),
-    Sphere,
-    Cube,
-    Cylinder,
-    Cone,
-    Torus,
+    /// Radius, and segments around and rings from pole to pole
+    Sphere(f32, (u32, u32)),
+    /// Side length, and squares per side along each edge
+    Cube(f32, u32),
+    /// Radius and height, and segments around
+    Cylinder((f32, f32), u32),
+    /// Radius of the base and height, and segments around
+    Cone((f32, f32), u32),
+    /// Radius of the ring and of its tube, and segments around each
+    Torus((f32, f32), (u32, u32)),
+}
+
+/// Settings of the shared output, and how it's doing
+pub(crate) struct SharedOutputStatus {
+    pub(crate) enabled: bool,
+    pub(crate) name: String,
+    /// Where the frames are written while it's enabled
+    pub(crate) path: Option<PathBuf>,
+    /// How long sharing the last frame took
+    pub(crate) cost: Option<Duration>,
+}
+
+impl Default for SharedOutputStatus {
+    fn default() -> Self {
+        SharedOutputStatus {
+            enabled: false,
+            name: "te-shader-play".to_string(),
+            path: None,
+            cost: None,
+        }
+    }
+}
+
+/// Settings of the debug texture, it only exists while it's enabled
+#[derive(Default)]
+pub(crate) struct DebugTextureStatus {
+    pub(crate) enabled: bool,
+    options: DebugTextureOptions,
+}
+
+impl DebugTextureStatus {
+    /// `group` is the one it's bound to, after the enabled uniform groups
+    fn show_editor(&mut self, ui: &Ui, group: usize) -> Option<Message> {
+        let mut changed = false;
+        let choices: Vec<Option<DebugPattern>> = std::iter::once(None)
+            .chain(DebugPattern::ALL.iter().copied().map(Some))
+            .collect();
+        let current = self.enabled.then_some(self.options.pattern);
+        let mut choice_index = choices
+            .iter()
+            .position(|choice| *choice == current)
+            .unwrap_or(0);
+        if ui.combo(
+            "Texture##debug_texture",
+            &mut choice_index,
+            &choices,
+            |choice| Cow::Borrowed(choice.map_or("None", |pattern| pattern.label())),
+        ) {
+            self.enabled = choices[choice_index].is_some();
+            if let Some(pattern) = choices[choice_index] {
+                self.options.pattern = pattern
+            }
+            changed = true
+        }
+        if self.enabled && self.options.pattern == DebugPattern::Checkerboard {
+            changed |= ui.slider(
+                "Squares per side",
+                1,
+                MAX_CHECKER_CELLS,
+                &mut self.options.cells,
+            );
+        }
+        let declaration = format!("@group({group}) @binding(0) var debug_texture: texture_2d<f32>;\n@group({group}) @binding(1) var debug_sampler: sampler;");
+        ui.text(&declaration);
+        if ui.small_button("Copy##copy_debug_texture") {
+            ui.set_clipboard_text(declaration)
+        }
+        ui.text_disabled("The meshes have no UVs, pass the shader's own coordinates\nto textureSample(debug_texture, debug_sampler, uv)");
+
+        changed.then(|| Message::SetDebugTexture(self.enabled.then_some(self.options)))
+    }
+}
+
+impl SharedOutputStatus {
+    fn show_editor(&mut self, ui: &Ui) {
+        if !cfg!(feature = "shared-output") {
+            ui.text_disabled(
+                "Build with --features shared-output to share the render\nwith other applications",
+            );
+            return;
+        }
+
+        ui.checkbox("Enabled##shared_output", &mut self.enabled);
+        ui.input_text("Sender name", &mut self.name).build();
+        if let Some(path) = &self.path {
+            ui.text(format!("Writing to {}", path.display()));
+            if ui.is_item_hovered() {
+                ui.tooltip_text("Every frame is copied to this file, see the README for its layout")
+            }
+        }
+    }
 }
 
 pub struct UiState {
@@ -730,227 +2545,1498 @@ pub struct UiState {
     shader_name: String,
     shader_exists: bool,
     pub inputs: Uniforms,
-    errors: Vec<String>,
-    show_errors: bool,
+    log: Log,
     mesh_type: MeshType,
     pub mesh_config: MeshConfig,
     pub show_mesh: bool,
+    preview_size: PreviewSize,
+    pub(crate) normal_mode: NormalMode,
+    /// A mesh is being generated, the previous one is drawn until it's done
+    pub(crate) generating_mesh: bool,
+    /// Times the custom shader's mesh is drawn
+    pub(crate) instances: u32,
+    /// Distance between the instances' offsets
+    pub(crate) instance_spacing: f32,
+    /// Tint the geometry close to the camera's near and far planes
+    pub(crate) show_clip_planes: bool,
+    /// Outline the box that contains the custom shader's mesh
+    pub(crate) show_bounding_box: bool,
+    /// The device supports PolygonMode::Line, which "Show mesh" needs
+    pub(crate) wireframe_supported: bool,
+    /// "Show mesh" draws the wireframe in a second pass over the filled mesh
+    pub(crate) mesh_overlay: bool,
+    /// Draw only the vertices of the mesh, as points one pixel wide
+    pub(crate) show_points: bool,
+    /// Built-in shader that draws the mesh instead of the custom one, picked in "Debug view"
+    pub(crate) debug_view: DebugView,
     always_on_top: bool,
+    fullscreen: bool,
     pub background_color: [f32; 4],
     pub draw_grid: bool,
+    pub(crate) blend_mode: BlendMode,
+    pub(crate) depth_test: bool,
+    pub(crate) depth_write: bool,
+    shader_source: String,
+    saved_parameters: Option<JsonValue>,
+    /// What `Uniforms::unsaved_changes` gave, with the edit it was compared at
+    unsaved_changes: Option<(u64, Vec<String>)>,
+    plane_resolution_cap: u32,
+    applied_plane_resolution: (u32, u32),
+    confirm_plane_resolution: bool,
+    /// Saving replaces the parameters that were saved before
+    confirm_save: bool,
+    pub(crate) keybindings: Keybindings,
+    osc: OscServer,
+    pub(crate) shared_output: SharedOutputStatus,
+    pub(crate) debug_texture: DebugTextureStatus,
+    paths: Paths,
+    show_builtins: bool,
+    /// "Random" and "Nudge" draw from `randomizer`, typing a seed restarts it
+    random_seed: i32,
+    randomizer: Rng,
+    /// Only the bindings whose name contains it are shown, it isn't saved
+    parameter_filter: String,
+    post_shader_name: String,
+    post_shader_exists: bool,
+    /// Bindings declared by the shader, and by the post shader if there is one
+    used_bindings: HashSet<(u32, u32)>,
+    post_used_bindings: HashSet<(u32, u32)>,
+    /// Size of the uniforms declared by the shader and the post shader, when their type is known
+    declared_sizes: HashMap<(u32, u32), u64>,
+    post_declared_sizes: HashMap<(u32, u32), u64>,
+    adapter: AdapterInfo,
+    adapters: Vec<AdapterInfo>,
+    /// Why the device didn't get the limits it asked for, if it didn't
+    limits_fallback: Option<String>,
+    pub(crate) present_mode: PresentMode,
+    present_modes: Vec<PresentMode>,
+    output_encoding: OutputEncoding,
+    /// Encodings the surface has a format for
+    output_encodings: Vec<OutputEncoding>,
+    /// The shaders directory saved in settings.json, while it's edited
+    saved_shaders_dir: String,
+    /// Windows that aren't drawn, toggled from the Windows menu
+    pub(crate) hidden_windows: Vec<UiWindow>,
+    /// Parameter edits are uploaded once the field is released, saved in settings.json
+    pub(crate) defer_edits: bool,
+    /// Bindings edited while a field is still active, uploaded when it's released
+    pending_writes: HashSet<(usize, usize)>,
+    /// Seconds the timeline of the Control window spans
+    timeline_length: f32,
+    /// Time wraps around at the end of the timeline
+    loop_time: bool,
+    /// Time advances by `fixed_step` seconds every frame
+    deterministic: bool,
+    fixed_step: f32,
+    /// Transform and axis of the gizmo handle being dragged
+    gizmo_drag: Option<((usize, usize), GizmoAxis)>,
+    /// Edited in the Settings window, sent with SetAppearance when a change is done
+    pub(crate) appearance: Appearance,
+    /// The font path, while it's edited
+    font_path: String,
+    /// Scale factor of the window's monitor, imgui already scales the UI by it
+    scale_factor: f64,
+    /// When the pipelines were recreated during the last minute
+    pipeline_rebuilds: VecDeque<Instant>,
+    /// How long creating the pipelines took the last time
+    last_pipeline_build: Option<Duration>,
+    /// Pipelines are cached between runs
+    pipeline_cache: bool,
+}
+
+/// Name, backend and device type, enough to identify the GPU in a bug report
+fn adapter_label(adapter: &AdapterInfo) -> String {
+    format!(
+        "{} ({:?}, {:?})",
+        adapter.name, adapter.backend, adapter.device_type
+    )
+}
+
+fn output_encodings(gpu: &Gpu) -> Vec<OutputEncoding> {
+    OutputEncoding::ALL
+        .iter()
+        .copied()
+        .filter(|encoding| encoding.format(&gpu.formats).is_some())
+        .collect()
 }
 
 impl UiState {
-    fn new(texture_id: TextureId, device: &Device) -> UiState {
-        UiState {
+    fn new(texture_id: TextureId, gpu: &Gpu, options: &StartupOptions) -> Option<UiState> {
+        let device = &gpu.device;
+        let mut log = Log::new();
+        let settings = &options.settings;
+        let osc = OscServer::new(settings.osc, &mut log);
+        Some(UiState {
             texture_id,
-            shader_name: "shader.wgsl".to_string(),
+            shader_name: options.shader.clone(),
             shader_exists: true,
-            inputs: Uniforms::new(device),
-            errors: vec![],
-            show_errors: false,
+            inputs: Uniforms::new(device)?,
+            log,
             mesh_type: MeshType::Screen2D,
             mesh_config: MeshConfig::Screen2D,
             show_mesh: false,
-            always_on_top: false,
+            preview_size: PreviewSize::Fit,
+            show_clip_planes: false,
+            show_bounding_box: false,
+            normal_mode: NormalMode::Smooth,
+            generating_mesh: false,
+            instances: 1,
+            instance_spacing: DEFAULT_INSTANCE_SPACING,
+            wireframe_supported: device.features().contains(Features::POLYGON_MODE_LINE),
+            mesh_overlay: false,
+            show_points: false,
+            debug_view: DebugView::Off,
+            always_on_top: options.starts_on_top(),
+            fullscreen: false,
             background_color: [1.0, 0.5, 0.5, 1.0],
             draw_grid: true,
+            blend_mode: BlendMode::Alpha,
+            depth_test: true,
+            depth_write: true,
+            shader_source: String::new(),
+            saved_parameters: Uniforms::read_saved(&options.shader, &options.paths.save_file),
+            unsaved_changes: None,
+            plane_resolution_cap: DEFAULT_PLANE_RESOLUTION_CAP,
+            applied_plane_resolution: (1, 1),
+            confirm_plane_resolution: false,
+            confirm_save: false,
+            keybindings: Keybindings::new(settings.keybindings.clone()),
+            osc,
+            shared_output: SharedOutputStatus::default(),
+            debug_texture: DebugTextureStatus::default(),
+            paths: options.paths.clone(),
+            show_builtins: true,
+            random_seed: 0,
+            randomizer: Rng::from_seed(0),
+            parameter_filter: String::new(),
+            post_shader_name: String::new(),
+            post_shader_exists: false,
+            used_bindings: HashSet::new(),
+            post_used_bindings: HashSet::new(),
+            declared_sizes: HashMap::new(),
+            post_declared_sizes: HashMap::new(),
+            adapter: gpu.adapter.clone(),
+            adapters: gpu.adapters.clone(),
+            limits_fallback: gpu.limits_fallback.clone(),
+            present_mode: gpu.config.present_mode,
+            present_modes: gpu.present_modes.clone(),
+            output_encoding: OutputEncoding::of(gpu.config.format),
+            output_encodings: output_encodings(gpu),
+            saved_shaders_dir: settings.shaders_dir.to_string_lossy().into_owned(),
+            hidden_windows: settings.hidden_windows.clone(),
+            defer_edits: settings.defer_edits,
+            pending_writes: HashSet::new(),
+            timeline_length: DEFAULT_TIMELINE_LENGTH,
+            loop_time: false,
+            deterministic: false,
+            fixed_step: DEFAULT_FIXED_STEP,
+            gizmo_drag: None,
+            font_path: settings
+                .appearance
+                .font_path
+                .as_ref()
+                .map(|path| path.to_string_lossy().into_owned())
+                .unwrap_or_default(),
+            appearance: settings.appearance.clone(),
+            scale_factor: 1.0,
+            pipeline_rebuilds: VecDeque::new(),
+            last_pipeline_build: None,
+            pipeline_cache: gpu.pipeline_cache.is_some(),
+        })
+    }
+
+    /// Applies the edit, returns whether the pipelines have to be recreated.
+    /// Value edits only write the buffer, so they never need it
+    fn apply_edit_event(&mut self, event: UniformEditEvent, device: &Device, queue: &Queue) -> bool {
+        self.inputs.mark_edited();
+        match event {
+            UniformEditEvent::UpdateBuffer(g_index, b_index) => {
+                self.inputs.update_buffer(g_index, b_index);
+                false
+            }
+            UniformEditEvent::Increase(g_index, b_index) => {
+                self.inputs.increase(g_index, b_index);
+                false
+            }
+            UniformEditEvent::Decrease(g_index, b_index) => {
+                self.inputs.decrease(g_index, b_index);
+                false
+            }
+            UniformEditEvent::AddUniform(g_index) => {
+                self.inputs.add_f32(g_index, device);
+                true
+            }
+            UniformEditEvent::AddLight(g_index) => {
+                self.inputs.add_light(g_index, device);
+                true
+            }
+            UniformEditEvent::AddBindGroup => {
+                self.inputs.add_bind_group(device);
+                true
+            }
+            UniformEditEvent::SetGroupEnabled(g_index, enabled) => {
+                self.inputs.set_group_enabled(g_index, enabled);
+                true
+            }
+            UniformEditEvent::SetGroupPacked(g_index, packed) => {
+                self.inputs.groups[g_index].set_packed(packed, device);
+                false
+            }
+            // The name is only shown and saved. The GPU objects keep their label until the group
+            // is rebuilt for something else
+            UniformEditEvent::RenameGroup(_) => false,
+            UniformEditEvent::Perturb(perturbation, g_index, b_index) => {
+                self.inputs
+                    .perturb(g_index, b_index, perturbation, &mut self.randomizer);
+                false
+            }
+            UniformEditEvent::RandomizeGroup(g_index) => {
+                self.inputs.randomize_group(g_index, &mut self.randomizer);
+                false
+            }
+            UniformEditEvent::ChangeType(unitype, g_index, b_index) => {
+                let old_size = self.inputs.binding_size(g_index, b_index);
+                self.inputs
+                    .change_type(unitype, g_index, b_index, queue, device);
+                self.inputs.binding_size(g_index, b_index) != old_size
+            }
+            UniformEditEvent::ChangeInnerType(inner_type, g_index, b_index) => {
+                let old_size = self.inputs.binding_size(g_index, b_index);
+                self.inputs
+                    .change_inner_type(inner_type, g_index, b_index, device, queue);
+                self.inputs.binding_size(g_index, b_index) != old_size
+            }
+            UniformEditEvent::ChangeMatrixSize(matrix_size, g_index, b_index) => {
+                let old_size = self.inputs.binding_size(g_index, b_index);
+                self.inputs
+                    .change_matrix_size(matrix_size, g_index, b_index, device, queue);
+                self.inputs.binding_size(g_index, b_index) != old_size
+            }
+        }
+    }
+
+    /// Holds back the buffer writes of a field that is still being edited when edits are deferred.
+    /// Enter and clicking elsewhere release the field, then the last value is uploaded
+    fn defer_buffer_update(&mut self, ui: &Ui, event: &UniformEditEvent) -> bool {
+        match event {
+            UniformEditEvent::UpdateBuffer(g_index, b_index)
+                if self.defer_edits && ui.is_any_item_active() =>
+            {
+                self.pending_writes.insert((*g_index, *b_index));
+                true
+            }
+            _ => false,
+        }
+    }
+
+    fn apply_pending_writes(&mut self, ui: &Ui) {
+        if ui.is_any_item_active() {
+            return;
+        }
+        for (g_index, b_index) in self.pending_writes.drain() {
+            self.inputs.update_buffer(g_index, b_index)
+        }
+    }
+
+    /// Called every time the pipelines are recreated, shown as a debug counter
+    pub(crate) fn count_pipeline_rebuild(&mut self, duration: Duration) {
+        self.pipeline_rebuilds.push_back(Instant::now());
+        self.last_pipeline_build = Some(duration)
+    }
+
+    fn is_shown(&self, window: UiWindow) -> bool {
+        !self.hidden_windows.contains(&window)
+    }
+
+    /// Returns true if a window was shown or hidden
+    fn show_windows_menu(&mut self, ui: &Ui) -> bool {
+        let mut changed = false;
+        ui.menu("Windows", || {
+            for window in UiWindow::ALL {
+                let mut shown = self.is_shown(*window);
+                if ui
+                    .menu_item_config(window.title())
+                    .build_with_ref(&mut shown)
+                {
+                    if shown {
+                        self.hidden_windows.retain(|hidden| hidden != window)
+                    } else {
+                        self.hidden_windows.push(*window)
+                    }
+                    changed = true
+                }
+            }
+        });
+
+        changed
+    }
+
+    /// Theme, scale and font of the Settings window. Returns the appearance once a change is done,
+    /// sliders only count when they are released so the UI doesn't resize under the mouse
+    fn show_appearance_editor(&mut self, ui: &Ui) -> Option<Appearance> {
+        let mut changed = false;
+        ui.separator();
+        let mut theme_index = UiTheme::ALL
+            .iter()
+            .position(|theme| *theme == self.appearance.theme)
+            .unwrap_or(0);
+        if ui.combo("Theme", &mut theme_index, UiTheme::ALL, |theme| {
+            Cow::Borrowed(theme.label())
+        }) {
+            self.appearance.theme = UiTheme::ALL[theme_index];
+            changed = true
+        }
+        ui.slider_config("UI scale", MIN_UI_SCALE, MAX_UI_SCALE)
+            .display_format("%.2f")
+            .build(&mut self.appearance.ui_scale);
+        changed |= ui.is_item_deactivated_after_edit();
+        if ui.is_item_hovered() {
+            ui.tooltip_text(format!(
+                "Multiplies the scale factor of the monitor ({:.0}%), which is already applied",
+                self.scale_factor * 100.0
+            ))
+        }
+        if ui
+            .input_text("Font", &mut self.font_path)
+            .hint("imgui's default font")
+            .enter_returns_true(true)
+            .build()
+        {
+            let path = self.font_path.trim();
+            self.appearance.font_path = (!path.is_empty()).then(|| PathBuf::from(path));
+            changed = true
+        }
+        if ui.is_item_hovered() {
+            ui.tooltip_text("Path of a TTF or OTF font. Press enter to load it,\nleave it empty to go back to the default font")
+        }
+        if self.appearance.font_path.is_some() {
+            ui.slider_config("Font size", MIN_FONT_SIZE, MAX_FONT_SIZE)
+                .display_format("%.0f px")
+                .build(&mut self.appearance.font_size);
+            changed |= ui.is_item_deactivated_after_edit();
+        }
+        if ui.checkbox(
+            "High contrast errors",
+            &mut self.appearance.high_contrast_errors,
+        ) {
+            changed = true
+        }
+        if ui.is_item_hovered() {
+            ui.tooltip_text("Stronger colors for the errors and warnings of the Log window")
+        }
+
+        changed.then(|| self.appearance.clone())
+    }
+
+    fn create_ui(&mut self, ui: &Ui, device: &Device, queue: &Queue) -> Option<Message> {
+        let mut message = None;
+        // Before the dockspace, so the windows are docked below it
+        ui.main_menu_bar(|| {
+            if self.show_windows_menu(ui) {
+                message = Some(Message::SaveSettings)
+            }
+        });
+        ui.dockspace_over_main_viewport();
+        if self.is_shown(UiWindow::Render) {
+            ui.window("Render").build(|| {
+                let image_size = self.preview_image_size(ui);
+                let a = ui.push_style_var(StyleVar::FrameBorderSize(50.0));
+                let image_pos = ui.cursor_pos();
+                Image::new(self.texture_id, mint::Vector2{ x: image_size[0], y: image_size[1] }).border_col([1.0;4]).build(ui);
+                a.pop();
+                // Catches the mouse over the image, so dragging it doesn't move the window
+                let after_image = ui.cursor_pos();
+                ui.set_cursor_pos(image_pos);
+                ui.invisible_button("##render_image", image_size);
+                if !self.transform_gizmos(ui, image_size) {
+                    self.pan_zoom_input(ui, image_size);
+                }
+                ui.set_cursor_pos(after_image);
+                if ui.button("Copy image") {
+                    message = Some(Message::CopyImage)
+                }
+                ui.same_line();
+                if ui.button("Save screenshot") {
+                    message = Some(Message::SaveScreenshot)
+                }
+                if ui.is_item_hovered() {
+                    ui.tooltip_text("Writes a PNG with the shader, its parameters and the time embedded,\nso \"Import from image\" can render it again")
+                }
+                ui.same_line();
+                if ui.button("Import from image") {
+                    message = Some(Message::ImportScreenshotParameters)
+                }
+                ui.same_line();
+                if ui.button("Export web") {
+                    message = Some(Message::ExportWeb)
+                }
+                if ui.is_item_hovered() {
+                    ui.tooltip_text("Writes a page that runs the shader in a browser with WebGPU.\nOnly the 2D whole screen mesh is exported")
+                }
+                ui.same_line();
+                if ui.button("Export Rust") {
+                    message = Some(Message::ExportRust)
+                }
+                if ui.is_item_hovered() {
+                    ui.tooltip_text("Writes the bind group layouts, uniform values and pipeline settings\nas Rust code for wgpu")
+                }
+                ui.same_line();
+                ui.radio_button("Fit", &mut self.preview_size, PreviewSize::Fit);
+                ui.same_line();
+                ui.radio_button("1:1", &mut self.preview_size, PreviewSize::Actual);
+                if self.show_mesh || self.show_points {
+                    ui.text_colored([1.0, 0.0, 0.0, 1.0], "Mesh rendering is enabled, turn it off\nin the \"Mesh configuration\" window to see\nthe expected output")
+                }
+            });
+        }
+
+        if self.is_shown(UiWindow::Control) {
+            ui.window("Control").build(|| {
+                if ui.button("Reload shader") {
+                    message = Some(Message::ReloadShader)
+                };
+                self.log.show_error_badge(ui, &self.appearance);
+                ui.separator();
+                if ui.input_text("Shader file", &mut self.shader_name).build() {
+                    self.check_shader_exists()
+                };
+                ui.disabled(!self.shader_exists, || {
+                    if ui.button("Load") {
+                        message = Some(Message::LoadShader(self.shader_name.clone()))
+                    };
+                });
+                if !self.shader_exists {
+                    ui.text(format!(
+                        "{} doesn't exist",
+                        self.paths.shader_path(&self.shader_name).display()
+                    ));
+                }
+                ui.separator();
+                if ui.input_text("Post shader file", &mut self.post_shader_name).build() {
+                    self.post_shader_exists = self.paths.shader_path(&self.post_shader_name).is_file()
+                };
+                ui.disabled(!self.post_shader_exists, || {
+                    if ui.button("Load post shader") {
+                        message = Some(Message::LoadPostShader(Some(self.post_shader_name.clone())))
+                    };
+                });
+                ui.same_line();
+                if ui.button("Remove post shader") {
+                    message = Some(Message::LoadPostShader(None))
+                }
+                let input_group =
+                    self.inputs.enabled_groups().count() + self.debug_texture.enabled as usize;
+                ui.text(format!(
+                    "The post shader reads the image from\n@group({input_group}) @binding(0) var t: texture_2d<f32>;\n@group({input_group}) @binding(1) var s: sampler;"
+                ));
+                ui.separator();
+                if ui.checkbox("Show always on top", &mut self.always_on_top) {
+                    if self.always_on_top {
+                        message = Some(Message::ChangeWindowLevel(WindowLevel::AlwaysOnTop))
+                    } else {
+                        message = Some(Message::ChangeWindowLevel(WindowLevel::Normal))
+                    }
+                }
+                if ui.checkbox("Borderless fullscreen", &mut self.fullscreen) {
+                    message = Some(Message::SetFullscreen(self.fullscreen))
+                }
+                ui.separator();
+                if ui.button("Pause/resume time") {
+                    message = Some(Message::TogglePause)
+                }
+                ui.same_line();
+                if ui.button("Reset time") {
+                    message = Some(Message::ResetTime)
+                }
+                if let Some(time_message) = self.show_timeline(ui) {
+                    message = Some(time_message)
+                }
+                if ui.collapsing_header("OSC", TreeNodeFlags::empty())
+                    && self.osc.show_status(ui, &mut self.log)
+                {
+                    message = Some(Message::SaveSettings)
+                }
+                if ui.collapsing_header("Shared output", TreeNodeFlags::empty()) {
+                    self.shared_output.show_editor(ui)
+                }
+                if ui.collapsing_header("GPU", TreeNodeFlags::empty()) {
+                    ui.text(format!("Adapter: {}", adapter_label(&self.adapter)));
+                    if ui.small_button("Copy##copy_adapter") {
+                        ui.set_clipboard_text(adapter_label(&self.adapter))
+                    }
+                    if !self.adapter.driver.is_empty() {
+                        ui.text(format!(
+                            "Driver: {} {}",
+                            self.adapter.driver, self.adapter.driver_info
+                        ));
+                    }
+                    if let Some(fallback) = &self.limits_fallback {
+                        ui.text_colored([1.0, 1.0, 0.0, 1.0], fallback);
+                    }
+                    if let Some(_node) = ui.tree_node("Limits") {
+                        let limits = device.limits();
+                        for (name, limit) in [
+                            ("Bind groups", limits.max_bind_groups),
+                            ("Bindings per group", limits.max_bindings_per_bind_group),
+                            ("Uniform binding size", limits.max_uniform_buffer_binding_size),
+                            ("Uniform buffers per stage", limits.max_uniform_buffers_per_shader_stage),
+                            ("Texture size", limits.max_texture_dimension_2d),
+                            ("Vertex buffers", limits.max_vertex_buffers),
+                            ("Vertex attributes", limits.max_vertex_attributes),
+                        ] {
+                            ui.text(format!("{name}: {limit}"));
+                        }
+                        ui.text(format!("Buffer size: {}", limits.max_buffer_size));
+                    }
+                    if let Some(_node) = ui.tree_node("Optional features") {
+                        let features = device.features();
+                        for (feature, usage) in OPTIONAL_FEATURES {
+                            let name = feature.iter_names().next().map_or("", |(name, _)| name);
+                            if features.contains(*feature) {
+                                ui.text(format!("{name}: enabled"));
+                            } else {
+                                ui.text_disabled(format!("{name}: not supported"));
+                            }
+                            if ui.is_item_hovered() {
+                                ui.tooltip_text(format!("Used for: {usage}"));
+                            }
+                        }
+                    }
+                    ui.text("Available adapters:");
+                    for adapter in self.adapters.iter() {
+                        ui.bullet_text(adapter_label(adapter));
+                    }
+                    ui.text_disabled("Start with --adapter <name>, --backend <backend> or\n--power <low|high> to use another one");
+                    while self
+                        .pipeline_rebuilds
+                        .front()
+                        .is_some_and(|rebuild| rebuild.elapsed() > Duration::from_secs(60))
+                    {
+                        self.pipeline_rebuilds.pop_front();
+                    }
+                    ui.text(format!(
+                        "Pipeline rebuilds in the last minute: {}",
+                        self.pipeline_rebuilds.len()
+                    ));
+                    if let Some(duration) = self.last_pipeline_build {
+                        let cache = if self.pipeline_cache {
+                            "cached"
+                        } else {
+                            "not cached"
+                        };
+                        ui.text(format!(
+                            "Last pipeline build: {:.1} ms ({cache})",
+                            duration.as_secs_f64() * 1000.0
+                        ));
+                        if !self.pipeline_cache && ui.is_item_hovered() {
+                            ui.tooltip_text("This adapter doesn't support pipeline caches");
+                        }
+                    }
+                    if let Some(cost) = self.shared_output.cost {
+                        ui.text(format!(
+                            "Shared output: {:.2} ms per frame",
+                            cost.as_secs_f64() * 1000.0
+                        ));
+                        if ui.is_item_hovered() {
+                            ui.tooltip_text("Reading the frame back waits for the GPU to finish it")
+                        }
+                    }
+                    let writes = self.inputs.write_stats();
+                    ui.text(format!(
+                        "Uniform writes last frame: {} ({} requested)",
+                        writes.issued, writes.requested
+                    ));
+                    if ui.is_item_hovered() {
+                        ui.tooltip_text(
+                            "Each binding is written once per frame, however many times it changes",
+                        );
+                    }
+                    let (buffers, unpacked) = self.inputs.buffer_stats();
+                    ui.text(format!(
+                        "Uniform buffers: {} ({} bytes)",
+                        buffers.buffers, buffers.bytes
+                    ));
+                    if buffers != unpacked {
+                        ui.same_line();
+                        ui.text_disabled(format!(
+                            "{} ({} bytes) without packing",
+                            unpacked.buffers, unpacked.bytes
+                        ));
+                    }
+                }
+            });
+        }
+
+        if self.is_shown(UiWindow::Keybindings) {
+            let keybindings_changed = ui
+                .window("Keybindings")
+                .collapsed(true, Condition::FirstUseEver)
+                .build(|| self.keybindings.show_editor(ui));
+            if keybindings_changed == Some(true) {
+                message = Some(Message::SaveSettings)
+            }
+        }
+
+        if self.is_shown(UiWindow::Settings) {
+            ui.window("Settings").collapsed(true, Condition::FirstUseEver).build(|| {
+                let present_modes: Vec<PresentMode> = PRESENT_MODES
+                    .into_iter()
+                    .filter(|mode| self.present_modes.contains(mode))
+                    .collect();
+                let mut present_index = present_modes
+                    .iter()
+                    .position(|mode| *mode == self.present_mode)
+                    .unwrap_or(0);
+                if ui.combo("Present mode", &mut present_index, &present_modes, |mode| {
+                    Cow::Borrowed(present_mode_label(*mode))
+                }) {
+                    message = Some(Message::SetPresentMode(present_modes[present_index]))
+                }
+                if ui.is_item_hovered() {
+                    ui.tooltip_text("Fifo waits for vsync, the others can show frames as soon as they are ready.\nOnly the modes this GPU supports are listed")
+                }
+                let mut encoding_index = self
+                    .output_encodings
+                    .iter()
+                    .position(|encoding| *encoding == self.output_encoding)
+                    .unwrap_or(0);
+                if ui.combo("Output encoding", &mut encoding_index, &self.output_encodings, |encoding| {
+                    Cow::Borrowed(encoding.label())
+                }) {
+                    message = Some(Message::SetOutputEncoding(self.output_encodings[encoding_index]))
+                }
+                if ui.is_item_hovered() {
+                    ui.tooltip_text("sRGB encodes the colors the shaders output, which are treated as linear.\nLinear writes them as they are, so they look darker.\nOnly the encodings this GPU supports are listed")
+                }
+                if ui
+                    .input_text("Shaders directory", &mut self.saved_shaders_dir)
+                    .enter_returns_true(true)
+                    .build()
+                {
+                    message = Some(Message::SetShadersDir(self.saved_shaders_dir.clone()))
+                }
+                if ui.is_item_hovered() {
+                    ui.tooltip_text("Used from the next start, --shaders-dir overrides it.\nPress enter to save it")
+                }
+                if ui.checkbox("Always on top", &mut self.always_on_top) {
+                    message = Some(Message::ChangeWindowLevel(if self.always_on_top {
+                        WindowLevel::AlwaysOnTop
+                    } else {
+                        WindowLevel::Normal
+                    }))
+                }
+                ui.text_disabled(
+                    "The window size, position and always on top are saved when the app closes",
+                );
+                if ui.checkbox("Defer parameter edits", &mut self.defer_edits) {
+                    message = Some(Message::SaveSettings)
+                }
+                if ui.is_item_hovered() {
+                    ui.tooltip_text("Parameters are uploaded when their field is released or enter is pressed,\ninstead of on every change. Helps with shaders that are slow to render")
+                }
+                if let Some(appearance) = self.show_appearance_editor(ui) {
+                    message = Some(Message::SetAppearance(appearance))
+                }
+            });
+        }
+
+        if self.is_shown(UiWindow::ShaderParameters) {
+            ui.window("Shader parameters").build(|| {
+                ui.input_text("Filter##parameter_filter", &mut self.parameter_filter)
+                    .hint("Binding name")
+                    .build();
+                if !self.parameter_filter.is_empty() {
+                    ui.same_line();
+                    if ui.small_button("Clear##parameter_filter") {
+                        self.parameter_filter.clear()
+                    }
+                }
+                let filter = self.parameter_filter.trim().to_lowercase();
+                ui.color_edit4("Background color", &mut self.background_color);
+                ui.checkbox("Show builtins", &mut self.show_builtins);
+                if ui
+                    .input_int("Random seed", &mut self.random_seed)
+                    .enter_returns_true(true)
+                    .build()
+                {
+                    self.randomizer = Rng::from_seed(self.random_seed as u64)
+                }
+                if ui.is_item_hovered() {
+                    ui.tooltip_text("Press enter to restart \"Random\" and \"Nudge\" from this seed, the same clicks give the same values")
+                }
+                if ui.collapsing_header("Parameter source", TreeNodeFlags::empty())
+                    && self.inputs.parameter_source.show_editor(ui)
+                {
+                    self.inputs.unlink_parameters()
+                }
+                let lock_linked = self.inputs.parameter_source.is_active()
+                    && self.inputs.parameter_source.lock_edits;
+                let mut edit_event = None;
+                let limits = device.limits();
+                let max_groups = limits.max_bind_groups as usize;
+                let max_bindings = limits.max_bindings_per_bind_group as usize;
+                let max_uniform_size = limits.max_uniform_buffer_binding_size as usize;
+                let toggleable_groups: Vec<bool> = (0..self.inputs.groups.len())
+                    .map(|g_index| self.inputs.can_toggle_group(g_index, max_groups))
+                    .collect();
+                let mut any_match = false;
+                for (group_index, group) in self.inputs.groups.iter_mut().enumerate() {
+                    if !filter.is_empty()
+                        && !group
+                            .bindings
+                            .iter()
+                            .any(|binding| name_matches(&binding.name, &filter))
+                    {
+                        continue;
+                    }
+                    any_match = true;
+                    // Groups with matches are open while filtering, without changing their state
+                    let header_flags = if filter.is_empty() {
+                        TreeNodeFlags::empty()
+                    } else {
+                        TreeNodeFlags::LEAF
+                    };
+                    let disabled_suffix = if group.enabled { "" } else { " (disabled)" };
+                    let name_suffix = if group.name.is_empty() {
+                        String::new()
+                    } else {
+                        format!(" '{}'", group.name)
+                    };
+                    let group_number = group_index + 1;
+                    if ui.collapsing_header(
+                        format!("Binding group {group_index}{name_suffix} ({group_number} of {max_groups}){disabled_suffix}###binding_group_{group_index}"),
+                        header_flags,
+                    ) {
+                        if ui
+                            .input_text(format!("Name##group_name_{group_index}"), &mut group.name)
+                            .enter_returns_true(true)
+                            .build()
+                        {
+                            edit_event = Some(UniformEditEvent::RenameGroup(group_index))
+                        }
+                        if ui.is_item_hovered() {
+                            ui.tooltip_text("Used in the labels of the group's GPU objects, which show up in\nvalidation errors and graphics debuggers. They get it the next time\nthe group is rebuilt, like when a binding is added. Press enter to apply it")
+                        }
+                        ui.same_line();
+                        if ui.button(format!("Copy WGSL##group_wgsl_{group_index}")) {
+                            ui.set_clipboard_text(group.wgsl_declarations())
+                        }
+                        if ui.is_item_hovered() {
+                            ui.tooltip_text(format!("Copies the declarations of every binding of @group({group_index})"))
+                        }
+                        let mut enabled = group.enabled;
+                        ui.disabled(!toggleable_groups[group_index], || {
+                            if ui.checkbox(
+                                format!("Enabled##group_enabled_{group_index}"),
+                                &mut enabled,
+                            ) {
+                                edit_event =
+                                    Some(UniformEditEvent::SetGroupEnabled(group_index, enabled))
+                            }
+                        });
+                        if !toggleable_groups[group_index]
+                            && ui.is_item_hovered_with_flags(ItemHoveredFlags::ALLOW_WHEN_DISABLED)
+                        {
+                            ui.tooltip_text(format!("Only the last enabled group can be disabled, and only the first\ndisabled group can be enabled, so @group(n) indices still match.\nGroups with builtin uniforms can't be disabled.\nThis device supports up to {max_groups} enabled groups."));
+                        }
+                        ui.same_line();
+                        let mut packed = group.packed.is_some();
+                        if ui.checkbox(format!("Packed##group_packed_{group_index}"), &mut packed) {
+                            edit_event = Some(UniformEditEvent::SetGroupPacked(group_index, packed))
+                        }
+                        if ui.is_item_hovered() {
+                            ui.tooltip_text("Keep every binding of the group in a single buffer,\ninstead of a buffer each. The shader sees the same bindings");
+                        }
+                        for (binding_index, uniform) in group.bindings.iter_mut().enumerate() {
+                            // Hidden builtins still upload their buffers
+                            if !self.show_builtins && matches!(uniform.value, UniformValue::BuiltIn(builtin) if builtin.is_automatic()) {
+                                continue;
+                            }
+                            if !name_matches(&uniform.name, &filter) {
+                                continue;
+                            }
+                            let location = (group_index as u32, binding_index as u32);
+                            if !matches!(uniform.value, UniformValue::BuiltIn(builtin) if builtin.is_automatic())
+                                && !self.used_bindings.contains(&location)
+                                && !self.post_used_bindings.contains(&location)
+                            {
+                                ui.text_disabled("(unused)");
+                                if ui.is_item_hovered() {
+                                    ui.tooltip_text(format!("The shader doesn't declare @group({group_index}) @binding({binding_index})"));
+                                }
+                                ui.same_line();
+                            }
+                            if let Some(event) =
+                                uniform.show_editor(ui, group_index, binding_index, lock_linked)
+                            {
+                                edit_event = Some(event);
+                            }
+                            let size = uniform.value.to_le_bytes().len();
+                            if size > max_uniform_size {
+                                ui.text_colored(
+                                    [1.0, 1.0, 0.0, 1.0],
+                                    format!("{size} bytes is over this device's limit of {max_uniform_size} bytes per uniform"),
+                                );
+                            }
+                            let declared: Vec<(&str, u64)> = [
+                                ("shader", self.declared_sizes.get(&location)),
+                                ("post shader", self.post_declared_sizes.get(&location)),
+                            ]
+                            .into_iter()
+                            .filter_map(|(shader, size)| Some((shader, *size?)))
+                            .collect();
+                            byte_view::show_bytes(ui, group_index, binding_index, &uniform.contents, &declared);
+                            ui.separator();
+                        }
+                        let group_full = group.bindings.len() >= max_bindings;
+                        ui.disabled(group_full, || {
+                            if ui.button(format!("Add parameter to this group##add_f32{group_index}")) {
+                                edit_event = Some(UniformEditEvent::AddUniform(group_index))
+                            };
+                            ui.same_line();
+                            if ui.button(format!("Add light##add_light{group_index}")) {
+                                edit_event = Some(UniformEditEvent::AddLight(group_index))
+                            };
+                        });
+                        if group_full && ui.is_item_hovered_with_flags(ItemHoveredFlags::ALLOW_WHEN_DISABLED) {
+                            ui.tooltip_text(format!("This device supports up to {max_bindings} bindings per group"));
+                        }
+                        ui.same_line();
+                        if ui.button(format!("Randomize unlocked##randomize_group{group_index}")) {
+                            edit_event = Some(UniformEditEvent::RandomizeGroup(group_index))
+                        }
+                        if ui.is_item_hovered() {
+                            ui.tooltip_text("Gives every unlocked f32 scalar and vector a random value between 0 and 1");
+                        }
+                    }
+                }
+                // The texture is bound like one more group, after the enabled ones
+                let texture_group = self.inputs.enabled_groups().count();
+                if filter.is_empty()
+                    && ui.collapsing_header(
+                        format!("Binding group {texture_group} (texture)###texture_group"),
+                        TreeNodeFlags::empty(),
+                    )
+                {
+                    if let Some(debug_message) = self.debug_texture.show_editor(ui, texture_group) {
+                        message = Some(debug_message)
+                    }
+                }
+                if !any_match && !filter.is_empty() {
+                    ui.text_disabled(format!("No binding name contains '{}'", self.parameter_filter.trim()));
+                }
+
+                let current_camera = self.inputs.current_camera();
+                let camera_location = self.inputs.camera_uniform_location;
+                if let Some(event) =
+                    self.inputs
+                        .camera_path
+                        .show_editor(ui, current_camera, camera_location)
+                {
+                    edit_event = Some(event)
+                }
+                if let Some(view) = self.inputs.camera_bookmarks.show_editor(ui, current_camera) {
+                    self.inputs.move_camera_to(view);
+                    edit_event = Some(UniformEditEvent::UpdateBuffer(
+                        camera_location.0,
+                        camera_location.1,
+                    ))
+                }
+
+                ui.separator();
+                let groups_full = self.inputs.groups.len() >= max_groups;
+                ui.disabled(groups_full, || {
+                    if ui.button("Add Bind Group") {
+                        edit_event = Some(UniformEditEvent::AddBindGroup)
+                    }
+                });
+                if groups_full && ui.is_item_hovered_with_flags(ItemHoveredFlags::ALLOW_WHEN_DISABLED) {
+                    ui.tooltip_text(format!("This device supports up to {max_groups} bind groups"));
+                }
+
+                let unsaved_changes = self.unsaved_changes().to_vec();
+                let save_label = if unsaved_changes.is_empty() {
+                    "Save parameters###save_parameters"
+                } else {
+                    "Save parameters*###save_parameters"
+                };
+                if ui.button(save_label) {
+                    if self.saved_parameters.is_some() && !unsaved_changes.is_empty() {
+                        self.confirm_save = true
+                    } else {
+                        message = Some(Message::SaveParameters)
+                    }
+                }
+                if self.confirm_save {
+                    ui.text_colored(
+                        [1.0, 1.0, 0.0, 1.0],
+                        "This will overwrite the parameters saved for this shader",
+                    );
+                    if ui.button("Overwrite") {
+                        self.confirm_save = false;
+                        message = Some(Message::SaveParameters)
+                    }
+                    ui.same_line();
+                    if ui.button("Cancel##cancel_save") {
+                        self.confirm_save = false
+                    }
+                }
+                if !unsaved_changes.is_empty() {
+                    if let Some(_node) = ui.tree_node("Unsaved changes") {
+                        for change in unsaved_changes.iter() {
+                            ui.bullet_text(change)
+                        }
+                    }
+                }
+
+                if let Some(event) = edit_event {
+                    if !self.defer_buffer_update(ui, &event)
+                        && self.apply_edit_event(event, device, queue)
+                    {
+                        message = Some(Message::ReloadPipeline);
+                    }
+                }
+            });
+        }
+        // Outside the window, so the writes still happen if it's collapsed while a field is active
+        self.apply_pending_writes(ui);
+
+        if self.is_shown(UiWindow::MeshConfiguration) {
+            ui.window("Mesh configuration").build(|| {
+                if self.generating_mesh {
+                    ui.text_colored([1.0, 1.0, 0.0, 1.0], "Generating...");
+                }
+                if ui.button("Export mesh") {
+                    message = Some(Message::ExportMesh)
+                }
+                if ui.is_item_hovered() {
+                    ui.tooltip_text("Writes the mesh to a Wavefront OBJ file");
+                }
+                ui.disabled(!self.wireframe_supported, || {
+                    if ui.checkbox("Show mesh", &mut self.show_mesh) {
+                        message = Some(Message::ReloadPipeline)
+                    };
+                });
+                if !self.wireframe_supported
+                    && ui.is_item_hovered_with_flags(ItemHoveredFlags::ALLOW_WHEN_DISABLED)
+                {
+                    ui.tooltip_text("This device doesn't support drawing lines (POLYGON_MODE_LINE)");
+                }
+                if self.show_mesh {
+                    ui.same_line();
+                    if ui.checkbox("Overlay", &mut self.mesh_overlay) {
+                        message = Some(Message::ReloadPipeline)
+                    }
+                    if ui.is_item_hovered() {
+                        ui.tooltip_text("Draws the wireframe over the shaded mesh instead of replacing it")
+                    }
+                }
+                if ui.checkbox("Show points", &mut self.show_points) {
+                    message = Some(Message::ReloadPipeline)
+                }
+                if ui.is_item_hovered() {
+                    ui.tooltip_text("Draws every vertex as a single pixel with the shader, instead of the triangles.\nThe wireframe overlay still draws the triangles")
+                }
+                ui.checkbox("Show grid", &mut self.draw_grid);
+                let camera_enabled = self.inputs.camera_enabled();
+                ui.disabled(!camera_enabled, || {
+                    let mut view_index = DebugView::ALL
+                        .iter()
+                        .position(|view| *view == self.debug_view)
+                        .unwrap_or(0);
+                    if ui.combo("Debug view", &mut view_index, DebugView::ALL, |view| {
+                        Cow::Borrowed(view.label())
+                    }) {
+                        self.debug_view = DebugView::ALL[view_index];
+                        message = Some(Message::RefreshDebugView)
+                    }
+                });
+                if ui.is_item_hovered_with_flags(ItemHoveredFlags::ALLOW_WHEN_DISABLED) {
+                    if camera_enabled {
+                        ui.tooltip_text("Draws the mesh with a built-in shader instead of the custom one.\nNormals: x y z as r g b\nUV checker: a checker projected along the normal, the meshes have no UVs\nDepth: white near the camera, black far from it");
+                    } else {
+                        ui.tooltip_text("Only available when the camera is enabled");
+                    }
+                }
+                ui.disabled(!camera_enabled, || {
+                    ui.checkbox("Show clip planes", &mut self.show_clip_planes);
+                });
+                if ui.is_item_hovered_with_flags(ItemHoveredFlags::ALLOW_WHEN_DISABLED) {
+                    if camera_enabled {
+                        ui.tooltip_text("Red: close to the near plane\nBlue: close to the far plane");
+                    } else {
+                        ui.tooltip_text("Only available when the camera is enabled");
+                    }
+                }
+                ui.disabled(!camera_enabled, || {
+                    ui.checkbox("Show bounding box", &mut self.show_bounding_box);
+                });
+                if !camera_enabled
+                    && ui.is_item_hovered_with_flags(ItemHoveredFlags::ALLOW_WHEN_DISABLED)
+                {
+                    ui.tooltip_text("Only available when the camera is enabled");
+                }
+                let mut blend_index = BlendMode::ALL
+                    .iter()
+                    .position(|mode| *mode == self.blend_mode)
+                    .unwrap_or(0);
+                if ui.combo("Blend mode", &mut blend_index, BlendMode::ALL, |mode| {
+                    Cow::Borrowed(mode.label())
+                }) {
+                    self.blend_mode = BlendMode::ALL[blend_index];
+                    message = Some(Message::ReloadPipeline)
+                }
+                if ui.checkbox("Depth test", &mut self.depth_test) {
+                    message = Some(Message::ReloadPipeline)
+                }
+                ui.same_line();
+                if ui.checkbox("Depth write", &mut self.depth_write) {
+                    message = Some(Message::ReloadPipeline)
+                }
+                ui.separator();
+
+                let mut instances = self.instances as i32;
+                if ui.input_int("Instances", &mut instances).build() {
+                    self.instances = instances.clamp(1, MAX_INSTANCES as i32) as u32;
+                    message = Some(Message::ReloadInstances)
+                }
+                if self.instances > 1
+                    && ui
+                        .input_float("Instance spacing", &mut self.instance_spacing)
+                        .build()
+                {
+                    message = Some(Message::ReloadInstances)
+                }
+                ui.text_disabled("Each instance has a vec3<f32> offset at @location(1),\nlaid out in a grid on the XZ plane");
+                ui.separator();
+
+                if ui.radio_button("Smooth normals", &mut self.normal_mode, NormalMode::Smooth) {
+                    message = Some(Message::ReloadMeshNormals)
+                }
+                ui.same_line();
+                if ui.radio_button("Flat normals", &mut self.normal_mode, NormalMode::Flat) {
+                    message = Some(Message::ReloadMeshNormals)
+                }
+                ui.text_disabled("The normal is a vec3<f32> at @location(2)");
+                ui.separator();
+
+                if ui.radio_button("2D whole screen", &mut self.mesh_type, MeshType::Screen2D) {
+                    self.mesh_config = MeshConfig::Screen2D;
+                    message = Some(Message::ReloadMeshBuffers);
+                };
+                if ui.radio_button("Plane", &mut self.mesh_type, MeshType::Plane) {
+                    self.mesh_config = MeshConfig::Plane((1.0, 1.0), (1, 1));
+                    self.applied_plane_resolution = (1, 1);
+                    self.confirm_plane_resolution = false;
+                    message = Some(Message::ReloadMeshBuffers);
+                };
+                if ui.radio_button("Cube", &mut self.mesh_type, MeshType::Cube) {
+                    self.mesh_config = MeshConfig::Cube(1.0, 1);
+                    message = Some(Message::ReloadMeshBuffers);
+                };
+                if ui.radio_button("Sphere", &mut self.mesh_type, MeshType::Sphere) {
+                    self.mesh_config = MeshConfig::Sphere(1.0, (32, 16));
+                    message = Some(Message::ReloadMeshBuffers);
+                };
+                if ui.radio_button("Cone", &mut self.mesh_type, MeshType::Cone) {
+                    self.mesh_config = MeshConfig::Cone((1.0, 2.0), 32);
+                    message = Some(Message::ReloadMeshBuffers);
+                };
+                if ui.radio_button("Cylinder", &mut self.mesh_type, MeshType::Cylinder) {
+                    self.mesh_config = MeshConfig::Cylinder((1.0, 2.0), 32);
+                    message = Some(Message::ReloadMeshBuffers);
+                };
+                if ui.radio_button("Torus", &mut self.mesh_type, MeshType::Torus) {
+                    self.mesh_config = MeshConfig::Torus((1.0, 0.3), (48, 16));
+                    message = Some(Message::ReloadMeshBuffers);
+                };
+                ui.separator();
+
+                match &mut self.mesh_config {
+                    MeshConfig::Screen2D => (),
+                    MeshConfig::Plane((x_size, y_size), (rows, columns)) => {
+                        let mut size = [*x_size, *y_size];
+                        if ui.input_float2("Size", &mut size).build() {
+                            *x_size = size[0];
+                            *y_size = size[1];
+                            // The rows and columns aren't confirmed yet, the size is applied
+                            // along with them or when they are cancelled
+                            if !self.confirm_plane_resolution {
+                                message = Some(Message::ReloadMeshBuffers)
+                            }
+                        };
+                        ui.text("Triangle resolution:");
+                        let mut resolution_changed = false;
+                        if ui
+                            .input_scalar("Resolution cap", &mut self.plane_resolution_cap)
+                            .build()
+                        {
+                            self.plane_resolution_cap =
+                                self.plane_resolution_cap.clamp(1, PLANE_RESOLUTION_LIMIT);
+                            if *rows > self.plane_resolution_cap || *columns > self.plane_resolution_cap {
+                                *rows = (*rows).min(self.plane_resolution_cap);
+                                *columns = (*columns).min(self.plane_resolution_cap);
+                                resolution_changed = true;
+                            }
+                        };
+                        if ui.slider("Rows", 1, self.plane_resolution_cap, rows) {
+                            resolution_changed = true;
+                        };
+                        if ui.slider("Columns", 1, self.plane_resolution_cap, columns) {
+                            resolution_changed = true;
+                        };
+
+                        let triangles = *rows as u64 * *columns as u64 * 2;
+                        ui.text(format!("{triangles} triangles"));
+                        if resolution_changed {
+                            if triangles > PLANE_TRIANGLE_WARNING {
+                                self.confirm_plane_resolution = true
+                            } else {
+                                self.confirm_plane_resolution = false;
+                                self.applied_plane_resolution = (*rows, *columns);
+                                message = Some(Message::ReloadMeshBuffers)
+                            }
+                        }
+
+                        if self.confirm_plane_resolution {
+                            ui.text_colored(
+                                [1.0, 1.0, 0.0, 1.0],
+                                "This many triangles may freeze the program\nor run out of memory",
+                            );
+                            if ui.button("Generate anyway") {
+                                self.confirm_plane_resolution = false;
+                                self.applied_plane_resolution = (*rows, *columns);
+                                message = Some(Message::ReloadMeshBuffers)
+                            }
+                            ui.same_line();
+                            if ui.button("Cancel") {
+                                self.confirm_plane_resolution = false;
+                                (*rows, *columns) = self.applied_plane_resolution;
+                                // The size may have changed meanwhile
+                                message = Some(Message::ReloadMeshBuffers)
+                            }
+                        }
+                    }
+                    MeshConfig::Sphere(radius, (segments, rings)) => {
+                        let mut changed = ui.slider("Radius", 0.1, 100.0, radius);
+                        changed |= ui.slider("Segments", 3, MAX_SEGMENTS, segments);
+                        changed |= ui.slider("Rings", 2, MAX_SEGMENTS, rings);
+                        if changed {
+                            message = Some(Message::ReloadMeshBuffers)
+                        }
+                    }
+                    MeshConfig::Cube(side, subdivisions) => {
+                        let mut changed = ui.slider("Side length", 0.1, 100.0, side);
+                        changed |= ui.slider("Squares per edge", 1, MAX_SEGMENTS, subdivisions);
+                        if changed {
+                            message = Some(Message::ReloadMeshBuffers)
+                        }
+                    }
+                    MeshConfig::Cylinder((radius, height), segments)
+                    | MeshConfig::Cone((radius, height), segments) => {
+                        let mut changed = ui.slider("Radius", 0.1, 100.0, radius);
+                        changed |= ui.slider("Height", 0.1, 100.0, height);
+                        changed |= ui.slider("Segments", 3, MAX_SEGMENTS, segments);
+                        if changed {
+                            message = Some(Message::ReloadMeshBuffers)
+                        }
+                    }
+                    MeshConfig::Torus((ring_radius, tube_radius), (segments, sides)) => {
+                        let mut changed = ui.slider("Ring radius", 0.1, 100.0, ring_radius);
+                        changed |= ui.slider("Tube radius", 0.01, 100.0, tube_radius);
+                        changed |= ui.slider("Segments", 3, MAX_SEGMENTS, segments);
+                        changed |= ui.slider("Sides", 3, MAX_SEGMENTS, sides);
+                        if changed {
+                            message = Some(Message::ReloadMeshBuffers)
+                        }
+                    }
+                }
+            });
+        }
+
+        if self.is_shown(UiWindow::Script) {
+            ui.window("Script")
+                .collapsed(true, Condition::FirstUseEver)
+                .build(|| self.inputs.script.show_editor(ui));
+        }
+
+        if self.is_shown(UiWindow::ShaderSource) {
+            ui.window("Shader source")
+                .collapsed(true, Condition::FirstUseEver)
+                .build(|| {
+                    if ui.button("Reload from disk") {
+                        message = Some(Message::ReloadShader)
+                    }
+                    ui.separator();
+                    ui.input_text_multiline(
+                        "##shader_source",
+                        &mut self.shader_source,
+                        [-1.0, -1.0],
+                    )
+                    .read_only(true)
+                    .build();
+                });
+        }
+
+        // Errors keep the Log window open until they are dismissed or fixed
+        if self.is_shown(UiWindow::Log) || self.log.has_undismissed_errors() {
+            self.log.show(ui, &self.appearance);
+        }
+
+        message
+    }
+
+    /// Size the Render image is drawn at. Fitting it leaves room for the widgets below it
+    fn preview_image_size(&self, ui: &Ui) -> [f32; 2] {
+        if self.preview_size == PreviewSize::Actual {
+            return [IMAGE_WIDTH, IMAGE_HEIGHT];
+        }
+
+        let mut footer = ui.frame_height_with_spacing();
+        if self.show_mesh || self.show_points {
+            footer += ui.text_line_height_with_spacing() * 3.0
+        }
+        // The border takes a pixel on each side
+        let [width, height] = ui.content_region_avail();
+        let scale = ((width - 2.0) / IMAGE_WIDTH)
+            .min((height - footer - 2.0) / IMAGE_HEIGHT)
+            .max(MIN_PREVIEW_SCALE);
+        [IMAGE_WIDTH * scale, IMAGE_HEIGHT * scale]
+    }
+
+    /// Slider over the loop length that shows the time, dragging it holds the time where it's
+    /// dropped until it's released
+    fn show_timeline(&mut self, ui: &Ui) -> Option<Message> {
+        let mut message = None;
+        let mut seconds = self.inputs.time.as_secs_f32();
+        ui.slider_config("Time", 0.0, self.timeline_length)
+            .display_format("%.2f s")
+            .build(&mut seconds);
+        let scrubbed = Duration::from_secs_f32(seconds.max(0.0).min(self.timeline_length));
+        if ui.is_item_active() {
+            message = Some(Message::ScrubTime(scrubbed))
+        } else if ui.is_item_deactivated() {
+            message = Some(Message::SeekTime(scrubbed))
+        }
+        if ui.is_item_hovered() {
+            ui.tooltip_text("Drag to scrub, time stands still until it's released.\nCamera paths and scripts follow it")
+        }
+
+        let length_changed = ui
+            .input_float("Loop length", &mut self.timeline_length)
+            .display_format("%.1f s")
+            .build();
+        if length_changed {
+            // max before min, so NaN becomes the minimum
+            self.timeline_length = self
+                .timeline_length
+                .max(MIN_TIMELINE_LENGTH)
+                .min(MAX_TIMELINE_LENGTH)
+        }
+        ui.same_line();
+        let loop_changed = ui.checkbox("Loop", &mut self.loop_time);
+        if loop_changed || (length_changed && self.loop_time) {
+            let length = Duration::from_secs_f32(self.timeline_length);
+            message = Some(Message::SetTimeLoop(self.loop_time.then_some(length)))
+        }
+        if ui.is_item_hovered() {
+            ui.tooltip_text("Time goes back to 0 at the end of the timeline")
         }
-    }
 
-    fn create_ui(&mut self, ui: &Ui, device: &Device, queue: &Queue) -> Option<Message> {
-        let mut message = None;
-        ui.dockspace_over_main_viewport();
-        ui.window("Render").build(|| {
-            let a = ui.push_style_var(StyleVar::FrameBorderSize(50.0));
-            Image::new(self.texture_id, mint::Vector2{ x: IMAGE_WIDTH, y: IMAGE_HEIGHT }).border_col([1.0;4]).build(ui);
-            a.pop();
-            if self.show_mesh {
-                ui.text_colored([1.0, 0.0, 0.0, 1.0], "Mesh rendering is enabled, turn it off\nin the \"Mesh configuration\" window to see\nthe expected output")
+        let mut step_changed = ui.checkbox("Deterministic", &mut self.deterministic);
+        if ui.is_item_hovered() {
+            ui.tooltip_text("Every frame advances the time by the same step, however long it took.\nTime starts over from 0 and the random builtin from the same seed,\nso the same frame always looks the same")
+        }
+        if self.deterministic {
+            ui.same_line();
+            ui.set_next_item_width(ui.calc_text_size("0.00000 s")[0] * 2.0);
+            if ui
+                .input_float("Step", &mut self.fixed_step)
+                .display_format("%.5f s")
+                .enter_returns_true(true)
+                .build()
+            {
+                self.fixed_step = self.fixed_step.max(MIN_FIXED_STEP).min(MAX_FIXED_STEP);
+                step_changed = true
             }
-        });
+            if ui.is_item_hovered() {
+                ui.tooltip_text("Seconds per frame, press enter to start over with it")
+            }
+        }
+        if step_changed {
+            let step = Duration::from_secs_f32(self.fixed_step);
+            message = Some(Message::SetFixedStep(self.deterministic.then_some(step)))
+        }
 
-        ui.window("Control").build(|| {
-            if ui.button("Reload shader") {
-                message = Some(Message::ReloadShader)
-            };
-            ui.separator();
-            if ui.input_text("Shader file", &mut self.shader_name).build() {
-                self.check_shader_exists()
+        message
+    }
+
+    /// Draws the translation gizmos over the Render image and moves their transforms when a handle
+    /// is dragged. Returns true while one is, so the image doesn't pan meanwhile
+    fn transform_gizmos(&mut self, ui: &Ui, image_size: [f32; 2]) -> bool {
+        if !ui.is_mouse_down(MouseButton::Left) {
+            self.gizmo_drag = None
+        }
+        let Some(view_projection) = self.inputs.camera_view_projection() else {
+            return false;
+        };
+
+        let image_min = ui.item_rect_min();
+        let io = ui.io();
+        let mut hovered = None;
+        for (location, translation) in self.inputs.gizmo_translations() {
+            let Some(gizmo) =
+                TranslationGizmo::new(view_projection, translation, image_min, image_size)
+            else {
+                continue;
             };
-            ui.disabled(!self.shader_exists, || {
-                if ui.button("Load") {
-                    message = Some(Message::LoadShader(self.shader_name.clone()))
-                };
-            });
-            if !self.shader_exists {
-                ui.text(format!("shaders/{} doesn't exist", self.shader_name));
-            }
-            ui.separator();
-            if ui.checkbox("Show always on top", &mut self.always_on_top) {
-                if self.always_on_top {
-                    message = Some(Message::ChangeWindowLevel(WindowLevel::AlwaysOnTop))
-                } else {
-                    message = Some(Message::ChangeWindowLevel(WindowLevel::Normal))
+            let dragged = self
+                .gizmo_drag
+                .filter(|(dragged, _)| *dragged == location)
+                .map(|(_, axis)| axis);
+            if let Some(axis) = dragged {
+                let distance = gizmo.drag_distance(axis, io.mouse_delta);
+                if distance != 0.0 {
+                    self.inputs
+                        .translate_binding(location, axis.direction() * distance)
                 }
             }
-        });
-
-        ui.window("Shader parameters").build(|| {
-            ui.color_edit4("Background color", &mut self.background_color);
-            let mut edit_event = None;
-            for (group_index, group) in self.inputs.groups.iter_mut().enumerate() {
-                if ui.collapsing_header(
-                    format!("Binding group {group_index}"),
-                    TreeNodeFlags::empty(),
-                ) {
-                    for (binding_index, uniform) in group.bindings.iter_mut().enumerate() {
-                        if let Some(event) = uniform.show_editor(ui, group_index, binding_index) {
-                            edit_event = Some(event);
-                        }
-                        ui.separator();
-                    }
-                    if ui.button(format!("Add parameter to this group##add_f32{group_index}")) {
-                        edit_event = Some(UniformEditEvent::AddUniform(group_index))
-                    };
-                }
+            let hovered_axis = ui
+                .is_item_hovered()
+                .then(|| gizmo.hovered_axis(io.mouse_pos))
+                .flatten();
+            if hovered.is_none() {
+                hovered = hovered_axis.map(|axis| (location, axis))
             }
+            gizmo.draw(ui, dragged.or(hovered_axis));
+        }
+        if self.gizmo_drag.is_none() && ui.is_item_clicked() {
+            self.gizmo_drag = hovered
+        }
 
-            ui.separator();
-            if ui.button("Add Bind Group") {
-                edit_event = Some(UniformEditEvent::AddBindGroup)
-            }
+        self.gizmo_drag.is_some()
+    }
 
-            if ui.button("Save parameters") {
-                message = Some(Message::SaveParameters)
-            }
+    /// Drag to pan and scroll to zoom the Render image, only in Screen2D
+    fn pan_zoom_input(&mut self, ui: &Ui, image_size: [f32; 2]) {
+        if self.mesh_type != MeshType::Screen2D {
+            return;
+        }
 
-            if let Some(event) = edit_event {
-                match event {
-                    UniformEditEvent::UpdateBuffer(g_index, b_index) => {
-                        self.inputs.update_buffer(g_index, b_index, queue)
-                    }
-                    UniformEditEvent::AddUniform(g_index) => self.inputs.add_f32(g_index, device),
-                    UniformEditEvent::AddBindGroup => self.inputs.add_bind_group(device),
-                    UniformEditEvent::ChangeType(unitype, g_index, b_index) => self
-                        .inputs
-                        .change_type(unitype, g_index, b_index, queue, device),
-                    UniformEditEvent::Increase(g_index, b_index) => {
-                        self.inputs.increase(g_index, b_index, queue)
-                    }
-                    UniformEditEvent::Decrease(g_index, b_index) => {
-                        self.inputs.decrease(g_index, b_index, queue)
-                    }
-                    UniformEditEvent::ChangeInnerType(inner_type, g_index, b_index) => self
-                        .inputs
-                        .change_inner_type(inner_type, g_index, b_index, device, queue),
-                    UniformEditEvent::ChangeMatrixSize(matrix_size, g_index, b_index) => self
-                        .inputs
-                        .change_matrix_size(matrix_size, g_index, b_index, device, queue),
-                };
-                message = Some(Message::ReloadPipeline);
-            }
-        });
+        let io = ui.io();
+        if ui.is_item_active() && (io.mouse_delta[0] != 0.0 || io.mouse_delta[1] != 0.0) {
+            let delta = [
+                io.mouse_delta[0] / image_size[0] * 2.0,
+                -io.mouse_delta[1] / image_size[1] * 2.0,
+            ];
+            self.inputs.pan_2d(delta)
+        }
 
-        ui.window("Mesh configuration").build(|| {
-            if ui.checkbox("Show mesh", &mut self.show_mesh) {
-                message = Some(Message::ReloadPipeline)
-            };
-            ui.checkbox("Show grid", &mut self.draw_grid);
-            ui.separator();
+        if ui.is_item_hovered() && io.mouse_wheel != 0.0 {
+            let image_min = ui.item_rect_min();
+            let cursor = [
+                (io.mouse_pos[0] - image_min[0]) / image_size[0] * 2.0 - 1.0,
+                1.0 - (io.mouse_pos[1] - image_min[1]) / image_size[1] * 2.0,
+            ];
+            self.inputs.zoom_2d(io.mouse_wheel, cursor)
+        }
+    }
 
-            if ui.radio_button("2D whole screen", &mut self.mesh_type, MeshType::Screen2D) {
-                self.mesh_config = MeshConfig::Screen2D;
-                message = Some(Message::ReloadMeshBuffers);
-            };
-            if ui.radio_button("Plane", &mut self.mesh_type, MeshType::Plane) {
-                self.mesh_config = MeshConfig::Plane((1.0, 1.0), (1, 1));
-                message = Some(Message::ReloadMeshBuffers);
-            };
-            let dis = ui.begin_disabled(true);
-            if ui.radio_button("Cube", &mut self.mesh_type, MeshType::Cube) {
-                self.mesh_config = MeshConfig::Cube;
-                message = Some(Message::ReloadMeshBuffers);
-            };
-            if ui.radio_button("Sphere", &mut self.mesh_type, MeshType::Sphere) {
-                self.mesh_config = MeshConfig::Sphere;
-                message = Some(Message::ReloadMeshBuffers);
-            };
-            if ui.radio_button("Cone", &mut self.mesh_type, MeshType::Cone) {
-                self.mesh_config = MeshConfig::Cone;
-                message = Some(Message::ReloadMeshBuffers);
-            };
-            if ui.radio_button("Cylinder", &mut self.mesh_type, MeshType::Cylinder) {
-                self.mesh_config = MeshConfig::Cylinder;
-                message = Some(Message::ReloadMeshBuffers);
-            };
-            if ui.radio_button("Torus", &mut self.mesh_type, MeshType::Torus) {
-                self.mesh_config = MeshConfig::Torus;
-                message = Some(Message::ReloadMeshBuffers);
-            };
-            dis.end();
-            ui.separator();
-
-            match &mut self.mesh_config {
-                MeshConfig::Screen2D => (),
-                MeshConfig::Plane((x_size, y_size), (rows, columns)) => {
-                    let mut size = [*x_size, *y_size];
-                    if ui.input_float2("Size", &mut size).build() {
-                        *x_size = size[0];
-                        *y_size = size[1];
-                        message = Some(Message::ReloadMeshBuffers)
-                    };
-                    ui.text("Triangle resolution:");
-                    if ui.slider("Rows", 1, 1_000, rows) {
-                        message = Some(Message::ReloadMeshBuffers)
-                    };
-                    if ui.slider("Columns", 1, 1_000, columns) {
-                        message = Some(Message::ReloadMeshBuffers)
-                    };
-                }
-                MeshConfig::Sphere => {
-                    ui.input_float("Radius", &mut 0.0).build();
-                    ui.slider("Triangle count", 4, 1_000_000, &mut 0);
-                }
-                MeshConfig::Cube => {
-                    ui.input_float("Side length", &mut 0.0).build();
-                    ui.slider("Triangles per side", 2, 1_000_000, &mut 0);
-                }
-                MeshConfig::Cylinder => {
-                    ui.slider("Radius", 0.1, 1000.0, &mut 1.0);
-                    ui.slider("height", 0.1, 1000.0, &mut 3.0);
-                }
-                MeshConfig::Cone => {
-                    ui.slider("Radius", 0.1, 1000.0, &mut 1.0);
-                    ui.slider("height", 0.1, 1000.0, &mut 3.0);
-                }
-                MeshConfig::Torus => {
-                    ui.slider("Inner radius", 0.1, 1000.0, &mut 1.0);
-                    ui.slider("Outer radius", 0.1, 1000.0, &mut 0.0);
-                }
-            }
-        });
+    pub(crate) fn always_on_top(&self) -> bool {
+        self.always_on_top
+    }
 
-        ui.window("Errors").focused(self.show_errors).build(|| {
-            self.show_errors = false;
-            for error in self.errors.iter() {
-                ui.text_wrapped(error)
-            }
-        });
+    pub(crate) fn toggle_always_on_top(&mut self) -> WindowLevel {
+        self.always_on_top = !self.always_on_top;
+        if self.always_on_top {
+            WindowLevel::AlwaysOnTop
+        } else {
+            WindowLevel::Normal
+        }
+    }
 
-        message
+    /// The window went fullscreen or back, maybe by other means than the UI
+    pub(crate) fn set_fullscreen(&mut self, fullscreen: bool) {
+        self.fullscreen = fullscreen
     }
 
     fn check_shader_exists(&mut self) {
-        let path = Path::new("shaders").join(&self.shader_name);
+        let path = self.paths.shader_path(&self.shader_name);
         self.shader_exists = path.exists();
     }
 
+    pub(crate) fn set_shader_name(&mut self, shader_name: &str) {
+        self.shader_name = shader_name.to_string();
+        self.check_shader_exists()
+    }
+
     pub(crate) fn load_uniforms(&mut self, shader_name: &str, device: &Device) {
-        self.inputs = match Uniforms::load(device, shader_name) {
+        self.refresh_saved_parameters(shader_name);
+        self.confirm_save = false;
+        let inputs = Uniforms::load(device, shader_name, &self.paths.save_file, &mut self.log);
+        self.inputs = match inputs {
             Some(inputs) => inputs,
-            None => Uniforms::new(device)
+            None => {
+                if self.saved_parameters.is_some() {
+                    self.log.push(
+                        Severity::Warning,
+                        format!("Couldn't load the saved parameters of {shader_name}, using defaults"),
+                    )
+                }
+                match Uniforms::new(device) {
+                    Some(inputs) => inputs,
+                    None => return,
+                }
+            }
+        };
+    }
+
+    /// Reads parameters that don't come from the save file, like a screenshot's
+    pub(crate) fn read_uniforms(
+        &mut self,
+        parameters: &JsonValue,
+        device: &Device,
+    ) -> Option<Uniforms> {
+        Uniforms::from_json(device, parameters, &mut self.log)
+    }
+
+    /// Bindings that differ from the saved parameters. They're only compared again after an edit
+    /// or a save, not every frame
+    fn unsaved_changes(&mut self) -> &[String] {
+        let edit = self.inputs.edit;
+        if !matches!(&self.unsaved_changes, Some((cached, _)) if *cached == edit) {
+            let changes = self.inputs.unsaved_changes(self.saved_parameters.as_ref());
+            self.unsaved_changes = Some((edit, changes))
         }
+
+        self.unsaved_changes
+            .as_ref()
+            .map_or(&[], |(_, changes)| changes.as_slice())
+    }
+
+    /// The parameters differ from the ones saved for this shader
+    pub(crate) fn has_unsaved_changes(&mut self) -> bool {
+        self.saved_parameters.is_some() && !self.unsaved_changes().is_empty()
+    }
+
+    /// Applies the OSC messages received since the last frame
+    pub(crate) fn osc_settings(&self) -> OscSettings {
+        self.osc.settings()
+    }
+
+    pub(crate) fn poll_osc(&mut self) -> Option<Message> {
+        let shader_name = self.osc.poll(&mut self.inputs)?;
+        self.set_shader_name(&shader_name);
+        Some(Message::LoadShader(shader_name))
+    }
+
+    pub(crate) fn apply_parameter_source(&mut self) {
+        self.inputs.apply_parameter_source(&mut self.log)
+    }
+
+    pub(crate) fn refresh_saved_parameters(&mut self, shader_name: &str) {
+        self.saved_parameters = Uniforms::read_saved(shader_name, &self.paths.save_file);
+        self.unsaved_changes = None
     }
 }
 
@@ -959,19 +4045,164 @@ pub struct ImState {
     platform: WinitPlatform,
     renderer: Renderer,
     pub ui: UiState,
+    /// imgui's style before the theme and scale are applied
+    base_style: Style,
+    /// What the context currently uses
+    appearance: Appearance,
+    /// Scale that makes the loaded font its size in logical pixels
+    font_scale: f32,
+}
+
+/// Whether `data` starts like a TrueType or OpenType font, imgui aborts on anything else
+fn is_font_data(data: &[u8]) -> bool {
+    const SIGNATURES: [&[u8]; 4] = [&[0, 1, 0, 0], b"true", b"OTTO", b"ttcf"];
+    SIGNATURES
+        .iter()
+        .any(|signature| data.starts_with(signature))
 }
 
 impl ImState {
-    pub fn new(window: &WinitWindow, gpu: &Gpu) -> ImState {
+    /// None if the GPU resources of the uniforms couldn't be created
+    pub(crate) fn new(
+        window: &WinitWindow,
+        gpu: &Gpu,
+        options: &StartupOptions,
+    ) -> Option<ImState> {
         let mut context = Context::create();
+        context.set_ini_filename(Some(PathBuf::from(IMGUI_INI_FILE)));
         context.io_mut().config_flags |= ConfigFlags::DOCKING_ENABLE;
         let mut platform = WinitPlatform::init(&mut context);
         platform.attach_window(context.io_mut(), window, HiDpiMode::Default);
+        let base_style = *context.style();
+        let appearance = options.settings.appearance.clone();
+        // The renderer builds the font texture, so the font has to be loaded before it
+        let (font_scale, font_error) = match Self::load_font(&mut context, &platform, &appearance) {
+            Ok(font_scale) => (font_scale, None),
+            Err(error) => (1.0, Some(error)),
+        };
+        Self::apply_style(&mut context, base_style, &appearance, font_scale);
+        let (renderer, texture_id) = Self::create_renderer(&mut context, gpu);
+
+        let mut ui = UiState::new(texture_id, gpu, options)?;
+        ui.scale_factor = platform.hidpi_factor();
+        if let Some(error) = font_error {
+            ui.log.push(Severity::Error, error)
+        }
+        Some(ImState {
+            context,
+            platform,
+            renderer,
+            ui,
+            base_style,
+            appearance,
+            font_scale,
+        })
+    }
+
+    /// Replaces the fonts of the atlas with the TTF font, or imgui's default font if there's none
+    /// or it can't be loaded. Returns the scale the font needs, the renderer's font texture has to
+    /// be rebuilt afterwards
+    fn load_font(
+        context: &mut Context,
+        platform: &WinitPlatform,
+        appearance: &Appearance,
+    ) -> Result<f32, String> {
+        let fonts = context.fonts();
+        fonts.clear();
+        let Some(path) = &appearance.font_path else {
+            fonts.add_font(&[FontSource::DefaultFontData { config: None }]);
+            return Ok(1.0);
+        };
+
+        match std::fs::read(path) {
+            Ok(data) if is_font_data(&data) => {
+                // Rasterized at the monitor's resolution so it stays sharp, then scaled back
+                let hidpi_factor = platform.hidpi_factor() as f32;
+                fonts.add_font(&[FontSource::TtfData {
+                    data: &data,
+                    size_pixels: appearance.font_size * hidpi_factor,
+                    config: None,
+                }]);
+                Ok(1.0 / hidpi_factor)
+            }
+            result => {
+                fonts.add_font(&[FontSource::DefaultFontData { config: None }]);
+                Err(match result {
+                    Ok(_) => format!("{} isn't a TTF or OTF font", path.display()),
+                    Err(err) => format!("Couldn't read the font {}: {err}", path.display()),
+                })
+            }
+        }
+    }
+
+    fn apply_style(
+        context: &mut Context,
+        base_style: Style,
+        appearance: &Appearance,
+        font_scale: f32,
+    ) {
+        let style = context.style_mut();
+        *style = base_style;
+        match appearance.theme {
+            UiTheme::Dark => style.use_dark_colors(),
+            UiTheme::Light => style.use_light_colors(),
+            UiTheme::Classic => style.use_classic_colors(),
+        };
+        style.scale_all_sizes(appearance.ui_scale);
+        context.io_mut().font_global_scale = appearance.ui_scale * font_scale;
+    }
+
+    pub(crate) fn set_appearance(&mut self, gpu: &Gpu, appearance: Appearance) {
+        let font_changed = appearance.font_path != self.appearance.font_path
+            || (appearance.font_path.is_some()
+                && appearance.font_size != self.appearance.font_size);
+        self.appearance = appearance;
+        if font_changed {
+            self.reload_font(gpu)
+        }
+        Self::apply_style(
+            &mut self.context,
+            self.base_style,
+            &self.appearance,
+            self.font_scale,
+        );
+    }
+
+    /// The window moved to a monitor with another scale factor. imgui-winit-support already
+    /// rescaled the UI, but a TTF font has to be rasterized again to stay sharp
+    pub(crate) fn scale_factor_changed(&mut self, gpu: &Gpu) {
+        self.ui.scale_factor = self.platform.hidpi_factor();
+        if self.appearance.font_path.is_some() {
+            self.reload_font(gpu);
+            Self::apply_style(
+                &mut self.context,
+                self.base_style,
+                &self.appearance,
+                self.font_scale,
+            );
+        }
+    }
+
+    fn reload_font(&mut self, gpu: &Gpu) {
+        self.font_scale = match Self::load_font(&mut self.context, &self.platform, &self.appearance)
+        {
+            Ok(font_scale) => font_scale,
+            Err(error) => {
+                self.push_error(error);
+                1.0
+            }
+        };
+        self.renderer
+            .reload_font_texture(&mut self.context, &gpu.device, &gpu.queue);
+    }
+
+    /// Creates the renderer and the texture the Render window shows
+    fn create_renderer(context: &mut Context, gpu: &Gpu) -> (Renderer, TextureId) {
         let renderer_config = RendererConfig {
             texture_format: gpu.config.format,
             ..Default::default()
         };
-        let mut renderer = Renderer::new(&mut context, &gpu.device, &gpu.queue, renderer_config);
+        let mut renderer = Renderer::new(context, &gpu.device, &gpu.queue, renderer_config);
 
         let texture = ImTexture::new(
             &gpu.device,
@@ -983,40 +4214,53 @@ impl ImState {
                     ..Default::default()
                 },
                 usage: wgpu::TextureUsages::RENDER_ATTACHMENT
-                    | wgpu::TextureUsages::TEXTURE_BINDING,
+                    | wgpu::TextureUsages::TEXTURE_BINDING
+                    | wgpu::TextureUsages::COPY_SRC,
                 ..Default::default()
             },
         );
         let texture_id = renderer.textures.insert(texture);
 
-        let ui = UiState::new(texture_id, &gpu.device);
-        ImState {
-            context,
-            platform,
-            renderer,
-            ui,
-        }
+        (renderer, texture_id)
     }
 
+    pub(crate) fn recreate_renderer(&mut self, gpu: &Gpu) {
+        let (renderer, texture_id) = Self::create_renderer(&mut self.context, gpu);
+        self.renderer = renderer;
+        self.ui.texture_id = texture_id;
+        self.ui.wireframe_supported = gpu.device.features().contains(Features::POLYGON_MODE_LINE);
+        self.ui.show_mesh &= self.ui.wireframe_supported;
+        self.ui.adapter = gpu.adapter.clone();
+        self.ui.adapters = gpu.adapters.clone();
+        self.ui.limits_fallback = gpu.limits_fallback.clone();
+        self.ui.present_mode = gpu.config.present_mode;
+        self.ui.present_modes = gpu.present_modes.clone();
+        self.ui.output_encoding = OutputEncoding::of(gpu.config.format);
+        self.ui.output_encodings = output_encodings(gpu);
+        self.ui.pipeline_cache = gpu.pipeline_cache.is_some();
+    }
+
+    /// None if the UI can't be drawn this frame, the error is in the log
     pub fn render(
         &mut self,
         window: &WinitWindow,
         gpu: &Gpu,
         view: &TextureView,
-    ) -> (CommandEncoder, Option<Message>) {
+    ) -> Option<(CommandEncoder, Option<Message>)> {
         self.platform
             .prepare_frame(self.context.io_mut(), window)
-            .expect("Failed to prepare frame");
-        let ui = self.context.frame();
-
-        let message = self.ui.create_ui(ui, &gpu.device, &gpu.queue);
-
+            .or_report()?;
         let mut encoder = gpu
             .device
             .create_command_encoder(&wgpu::CommandEncoderDescriptor {
                 label: Some("ImGui Render Encoder"),
             })
-            .unwrap();
+            .or_report()?;
+
+        let ui = self.context.frame();
+        let message = self.ui.create_ui(ui, &gpu.device, &gpu.queue);
+        // Ends the frame even if it can't be drawn, so the next one can start
+        let draw_data = self.context.render();
         {
             let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
                 label: None,
@@ -1031,17 +4275,12 @@ impl ImState {
                 depth_stencil_attachment: None,
                 occlusion_query_set: None,
                 timestamp_writes: None,
-            }).unwrap();
+            }).or_report()?;
             self.renderer
-                .render(
-                    self.context.render(),
-                    &gpu.queue,
-                    &gpu.device,
-                    &mut render_pass,
-                )
-                .expect("Rendering failed");
+                .render(draw_data, &gpu.queue, &gpu.device, &mut render_pass)
+                .or_report()?;
         }
-        (encoder, message)
+        Some((encoder, message))
     }
 
     pub fn handle_event(&mut self, event: &Event<()>, window: &WinitWindow) {
@@ -1049,21 +4288,389 @@ impl ImState {
             .handle_event(self.context.io_mut(), window, event);
     }
 
-    pub fn get_texture_view(&self) -> &TextureView {
+    /// Whether imgui is using the keyboard, e.g. because a text field is focused
+    pub(crate) fn wants_keyboard(&self) -> bool {
+        self.context.io().want_capture_keyboard
+    }
+
+    pub(crate) fn modifiers(&self) -> Modifiers {
+        let io = self.context.io();
+        Modifiers {
+            ctrl: io.key_ctrl,
+            shift: io.key_shift,
+            alt: io.key_alt,
+        }
+    }
+
+    /// The image of the Render window, None if the renderer lost it
+    pub(crate) fn get_texture(&self) -> Option<&wgpu::Texture> {
         self.renderer
             .textures
             .get(self.ui.texture_id)
-            .unwrap()
-            .view()
+            .map(|texture| texture.texture())
+    }
+
+    pub fn get_texture_view(&self) -> Option<&TextureView> {
+        self.renderer
+            .textures
+            .get(self.ui.texture_id)
+            .map(|texture| texture.view())
+    }
+
+    /// Called when the shader is reloaded successfully
+    pub(crate) fn resolve_errors(&mut self, shader_name: &str) {
+        self.ui.log.resolve_errors();
+        self.ui.log.push(Severity::Info, format!("Loaded {shader_name}"))
+    }
+
+    /// Called after the shader compiles
+    pub(crate) fn set_shader_source(&mut self, source: &str) {
+        self.ui.shader_source = source.to_string();
+        self.ui.used_bindings = reflection::declared_bindings(source);
+        self.ui.declared_sizes = reflection::uniform_sizes(source);
     }
 
-    pub(crate) fn destroy_errors(&mut self) {
-        self.ui.errors = Vec::new();
-        self.ui.show_errors = false;
+    pub(crate) fn set_post_shader_source(&mut self, source: Option<&str>) {
+        self.ui.post_used_bindings = source
+            .map(reflection::declared_bindings)
+            .unwrap_or_default();
+        self.ui.post_declared_sizes = source.map(reflection::uniform_sizes).unwrap_or_default();
     }
 
+    /// Naga formats its errors over several lines, each diagnostic is logged as its own error
     pub(crate) fn show_crate_shader_err(&mut self, err: CreateShaderModuleError) {
-        self.ui.show_errors = true;
-        self.ui.errors = vec![err.to_string()]
+        for error in log::split_errors(&err.to_string()) {
+            self.push_error(error)
+        }
+    }
+
+    /// True if there were errors since the last successful reload
+    pub(crate) fn has_errors(&self) -> bool {
+        self.ui.log.has_errors()
+    }
+
+    /// Logs the error, unless it's already been logged since the last successful reload
+    pub(crate) fn push_error(&mut self, error: String) {
+        self.ui.log.push(Severity::Error, error)
+    }
+
+    pub(crate) fn log(&mut self, severity: Severity, message: String) {
+        self.ui.log.push(severity, message)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use wgpu::{
+        Color, ColorTargetState, ColorWrites, CommandEncoderDescriptor, Extent3d, FragmentState,
+        LoadOp, MultisampleState, Operations, PipelineLayoutDescriptor, PrimitiveState,
+        RenderPassColorAttachment, RenderPassDescriptor, RenderPipelineDescriptor,
+        ShaderModuleDescriptor, ShaderSource, StoreOp, TextureDescriptor, TextureDimension,
+        TextureFormat, TextureUsages, TextureViewDescriptor, VertexState,
+    };
+
+    use std::{
+        collections::hash_map::DefaultHasher,
+        hash::{Hash, Hasher},
+    };
+
+    use super::{uniform_types::VecType, *};
+    use crate::state::{
+        capture::read_rgba,
+        tests::{assert_no_gpu_errors, headless_device},
+        TimeKeeper,
+    };
+
+    /// Fills the screen with the color of the bindings
+    const SHADER: &str = "
+@group(0) @binding(0) var<uniform> red: f32;
+@group(0) @binding(1) var<uniform> green: u32;
+@group(0) @binding(2) var<uniform> blue: f32;
+
+@vertex
+fn vs_main(@builtin(vertex_index) index: u32) -> @builtin(position) vec4<f32> {
+    let uv = vec2(f32((index << 1u) & 2u), f32(index & 2u));
+    return vec4(uv * 2.0 - 1.0, 0.0, 1.0);
+}
+
+@fragment
+fn fs_main() -> @location(0) vec4<f32> {
+    return vec4(red, f32(green) / 255.0, blue, 1.0);
+}
+";
+
+    /// Colors the screen with the time and the random builtin
+    const TIME_SHADER: &str = "
+@group(0) @binding(0) var<uniform> time: u32;
+@group(0) @binding(3) var<uniform> random: f32;
+
+@vertex
+fn vs_main(@builtin(vertex_index) index: u32) -> @builtin(position) vec4<f32> {
+    let uv = vec2(f32((index << 1u) & 2u), f32(index & 2u));
+    return vec4(uv * 2.0 - 1.0, 0.0, 1.0);
+}
+
+@fragment
+fn fs_main() -> @location(0) vec4<f32> {
+    return vec4(f32(time % 1000u) / 1000.0, random, 0.0, 1.0);
+}
+";
+
+    /// Draws the shader with the group into a single pixel
+    fn render(source: &str, device: &Device, queue: &Queue, group: &UniformGroup) -> Vec<u8> {
+        let shader = device
+            .create_shader_module(ShaderModuleDescriptor {
+                label: None,
+                source: ShaderSource::Wgsl(source.into()),
+            })
+            .unwrap();
+        let layout = device
+            .create_pipeline_layout(&PipelineLayoutDescriptor {
+                label: None,
+                bind_group_layouts: &[group.bg_layout()],
+                push_constant_ranges: &[],
+            })
+            .unwrap();
+        let pipeline = device
+            .create_render_pipeline(&RenderPipelineDescriptor {
+                label: None,
+                layout: Some(&layout),
+                vertex: VertexState {
+                    module: &shader,
+                    entry_point: Some("vs_main"),
+                    buffers: &[],
+                    compilation_options: Default::default(),
+                },
+                primitive: PrimitiveState::default(),
+                depth_stencil: None,
+                multisample: MultisampleState::default(),
+                fragment: Some(FragmentState {
+                    module: &shader,
+                    entry_point: Some("fs_main"),
+                    targets: &[Some(ColorTargetState {
+                        format: TextureFormat::Rgba8Unorm,
+                        blend: None,
+                        write_mask: ColorWrites::ALL,
+                    })],
+                    compilation_options: Default::default(),
+                }),
+                multiview: None,
+                cache: None,
+            })
+            .unwrap();
+        let texture = device
+            .create_texture(&TextureDescriptor {
+                label: None,
+                size: Extent3d {
+                    width: 1,
+                    height: 1,
+                    depth_or_array_layers: 1,
+                },
+                mip_level_count: 1,
+                sample_count: 1,
+                dimension: TextureDimension::D2,
+                format: TextureFormat::Rgba8Unorm,
+                usage: TextureUsages::RENDER_ATTACHMENT | TextureUsages::COPY_SRC,
+                view_formats: &[],
+            })
+            .unwrap();
+        let view = texture
+            .create_view(&TextureViewDescriptor::default())
+            .unwrap();
+
+        let mut encoder = device
+            .create_command_encoder(&CommandEncoderDescriptor { label: None })
+            .unwrap();
+        let mut render_pass = encoder
+            .begin_render_pass(&RenderPassDescriptor {
+                label: None,
+                color_attachments: &[Some(RenderPassColorAttachment {
+                    view: &view,
+                    resolve_target: None,
+                    ops: Operations {
+                        load: LoadOp::Clear(Color::BLACK),
+                        store: StoreOp::Store,
+                    },
+                })],
+                depth_stencil_attachment: None,
+                timestamp_writes: None,
+                occlusion_query_set: None,
+            })
+            .unwrap();
+        render_pass.set_pipeline(&pipeline).unwrap();
+        render_pass.set_bind_group(0, &group.bind_group, &[]);
+        render_pass.draw(0..3, 0..1).unwrap();
+        render_pass.end().unwrap();
+        queue.submit(Some(encoder.finish()));
+
+        read_rgba(device, queue, &texture).unwrap()
+    }
+
+    #[test]
+    #[ignore = "needs a GPU"]
+    fn packing_keeps_the_output() {
+        let (device, queue) = headless_device();
+
+        let mut group = UniformGroup::new(&device, 0).unwrap();
+        group.add_custom(&device, UniformValue::Scalar(ScalarUniformValue::F32(0.25)));
+        group.add_custom(&device, UniformValue::Scalar(ScalarUniformValue::U32(0)));
+        group.add_custom(&device, UniformValue::Scalar(ScalarUniformValue::F32(0.75)));
+        // Bytes other than the value, like the time builtin writes
+        group.write(1, &128u32.to_le_bytes(), &queue);
+        let unpacked = render(SHADER, &device, &queue, &group);
+        assert_eq!(unpacked[1], 128);
+
+        group.set_packed(true, &device);
+        assert!(group.packed.is_some());
+        assert_eq!(render(SHADER, &device, &queue, &group), unpacked);
+        let (packed_stats, unpacked_stats) = group.buffer_stats();
+        assert_eq!(packed_stats.buffers, 1);
+        assert_eq!(unpacked_stats.buffers, 3);
+
+        // Edits only write the binding's range
+        group.write(0, &0.5f32.to_le_bytes(), &queue);
+        let packed = render(SHADER, &device, &queue, &group);
+        assert_ne!(packed, unpacked);
+
+        // Resizing a binding lays the group out again, keeping the other bindings
+        let vec4 = UniformType::Vec(VecType::Vec4(ScalarType::F32));
+        group.change_type(vec4, 1, &queue, &device);
+        group.change_type(UniformType::Scalar(ScalarType::U32), 1, &queue, &device);
+        group.write(1, &128u32.to_le_bytes(), &queue);
+        assert_eq!(render(SHADER, &device, &queue, &group), packed);
+
+        group.set_packed(false, &device);
+        assert!(group.packed.is_none());
+        assert_eq!(render(SHADER, &device, &queue, &group), packed);
+
+        assert_no_gpu_errors(&device);
+    }
+
+    #[test]
+    #[ignore = "needs a GPU"]
+    fn fixed_steps_render_the_same_frames() {
+        let (device, queue) = headless_device();
+        // Hash of every frame, waiting `wait` between them
+        let run = |wait: Duration| {
+            let mut uniforms = Uniforms::new(&device).unwrap();
+            uniforms.reseed_random();
+            let mut time = TimeKeeper::new();
+            time.set_fixed_step(Some(Duration::from_secs_f32(DEFAULT_FIXED_STEP)));
+            let mut hasher = DefaultHasher::new();
+            for _ in 0..30 {
+                std::thread::sleep(wait);
+                time.update_time(&mut uniforms);
+                uniforms.flush_writes(&queue);
+                render(TIME_SHADER, &device, &queue, &uniforms.groups[0]).hash(&mut hasher);
+            }
+            hasher.finish()
+        };
+
+        assert_eq!(run(Duration::ZERO), run(Duration::from_millis(3)));
+        assert_no_gpu_errors(&device);
+    }
+
+    #[test]
+    #[ignore = "needs a GPU"]
+    fn repeated_increments_write_once_per_frame() {
+        let (device, queue) = headless_device();
+
+        let mut uniforms = Uniforms::new(&device).unwrap();
+        uniforms.add_f32(0, &device);
+        let b_index = uniforms.groups[0].bindings.len() - 1;
+        uniforms.flush_writes(&queue);
+
+        // Holding the key down repeats it several times in a frame
+        for _ in 0..10 {
+            uniforms.increase(0, b_index);
+        }
+        uniforms.decrease(0, b_index);
+        uniforms.flush_writes(&queue);
+
+        assert_eq!(
+            uniforms.groups[0].bindings[b_index].value,
+            UniformValue::Scalar(ScalarUniformValue::F32(9.0))
+        );
+        assert_eq!(
+            uniforms.write_stats(),
+            WriteStats {
+                requested: 11,
+                issued: 1
+            }
+        );
+        assert_no_gpu_errors(&device);
+    }
+
+    #[test]
+    fn filter_matches_substrings() {
+        assert!(name_matches("Sun_Direction", "sun_dir"));
+        assert!(name_matches("anything", ""));
+        assert!(!name_matches("speed", "sun"));
+    }
+
+    #[test]
+    fn only_fonts_are_loaded() {
+        assert!(is_font_data(&[0, 1, 0, 0, 0, 18]));
+        assert!(is_font_data(b"OTTO\0\x0b"));
+        assert!(!is_font_data(b"\x89PNG"));
+        assert!(!is_font_data(&[]));
+    }
+
+    #[test]
+    fn wgsl_identifiers_are_valid_and_unique() {
+        let names = ["speed", "loop", "speed", "__x", "sun dir", "speed_2"];
+        assert_eq!(
+            wgsl_identifiers(names.into_iter(), 3),
+            [
+                "speed",
+                "loop_",
+                "speed_2",
+                "binding_3_3",
+                "sun_dir",
+                "speed_2_5"
+            ]
+        );
+    }
+
+    #[test]
+    #[ignore = "needs a GPU"]
+    fn wgsl_declarations_have_the_group_name() {
+        let (device, _queue) = headless_device();
+
+        let mut group = UniformGroup::new(&device, 2).unwrap();
+        group.name = "lighting".to_string();
+        group.add_named(
+            &device,
+            UniformValue::Scalar(ScalarUniformValue::F32(0.0)),
+            "sun dir".to_string(),
+        );
+        group.add_f32(&device);
+        assert_eq!(
+            group.binding_label(0),
+            "group 2 'lighting' binding 0 'sun dir'"
+        );
+        assert_eq!(
+            group.wgsl_declarations(),
+            "// group 2 'lighting'
+@group(2) @binding(0) var<uniform> sun_dir: f32;
+@group(2) @binding(1) var<uniform> binding_2_1: f32;
+"
+        );
+    }
+
+    #[test]
+    #[ignore = "needs a GPU"]
+    fn only_saved_values_are_edits() {
+        let (device, _queue) = headless_device();
+
+        let mut uniforms = Uniforms::new(&device).unwrap();
+        let edit = uniforms.edit;
+        uniforms.update_random();
+        uniforms.update_time(Duration::from_secs(1));
+        assert_eq!(uniforms.edit, edit);
+
+        uniforms.enable_pan_zoom(true);
+        assert_ne!(uniforms.edit, edit);
+        assert_ne!(Uniforms::new(&device).unwrap().edit, uniforms.edit);
     }
 }
@@ -0,0 +1,106 @@
+use imgui::{TableFlags, Ui};
+
+/// One row of the byte view: offset, hex, and the word read as f32 and u32
+fn word_row(offset: usize, word: &[u8]) -> [String; 4] {
+    let hex: Vec<String> = word.iter().map(|byte| format!("{byte:02X}")).collect();
+    let (float, unsigned) = match <[u8; 4]>::try_from(word) {
+        Ok(word) => (
+            format!("{}", f32::from_le_bytes(word)),
+            format!("{}", u32::from_le_bytes(word)),
+        ),
+        Err(_) => (String::new(), String::new()),
+    };
+    [format!("{offset}"), hex.join(" "), float, unsigned]
+}
+
+/// What the shader declares doesn't fit the uploaded bytes
+fn size_mismatch(uploaded: u64, declared: u64) -> Option<String> {
+    if uploaded < declared {
+        Some(format!(
+            "The shader reads {declared} bytes, {} more than are uploaded",
+            declared - uploaded
+        ))
+    } else if uploaded > declared {
+        Some(format!(
+            "The shader only reads {declared} bytes, the last {} aren't used. Check the layout",
+            uploaded - declared
+        ))
+    } else {
+        None
+    }
+}
+
+/// Read-only view of the bytes last uploaded to a binding, every 4 byte word in a row.
+/// `declared` has the size of the binding in each shader that declares it with a known type
+pub(super) fn show_bytes(
+    ui: &Ui,
+    group_index: usize,
+    binding_index: usize,
+    bytes: &[u8],
+    declared: &[(&str, u64)],
+) {
+    let label = format!(
+        "Bytes ({})##bytes_{group_index}_{binding_index}",
+        bytes.len()
+    );
+    let Some(_node) = ui.tree_node(label) else {
+        return;
+    };
+
+    for (shader, size) in declared {
+        ui.text(format!("Declared in the {shader}: {size} bytes"));
+        if let Some(mismatch) = size_mismatch(bytes.len() as u64, *size) {
+            ui.text_colored([1.0, 1.0, 0.0, 1.0], mismatch)
+        }
+    }
+    if declared.is_empty() {
+        ui.text_disabled("The declared size isn't known");
+        if ui.is_item_hovered() {
+            ui.tooltip_text(
+                "The shader doesn't declare this binding as var<uniform>,\nor its type is an alias",
+            )
+        }
+    }
+
+    let flags = TableFlags::BORDERS | TableFlags::ROW_BG | TableFlags::SIZING_FIXED_FIT;
+    let id = format!("##bytes_table_{group_index}_{binding_index}");
+    let Some(_table) = ui.begin_table_with_flags(id, 4, flags) else {
+        return;
+    };
+    for header in ["Offset", "Hex", "f32", "u32"] {
+        ui.table_setup_column(header);
+    }
+    ui.table_headers_row();
+    for (index, word) in bytes.chunks(4).enumerate() {
+        for cell in word_row(index * 4, word) {
+            ui.table_next_column();
+            ui.text(cell);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn words_are_read_little_endian() {
+        let row = word_row(8, &1.5f32.to_le_bytes());
+        assert_eq!(
+            row,
+            ["8", "00 00 C0 3F", "1.5", "1069547520"].map(String::from)
+        );
+        // A partial word only has its hex
+        assert_eq!(
+            word_row(4, &[1, 2])[1..],
+            ["01 02", "", ""].map(String::from)
+        );
+    }
+
+    #[test]
+    fn mismatches_explain_the_difference() {
+        assert_eq!(size_mismatch(16, 16), None);
+        assert!(size_mismatch(12, 16).unwrap().contains("4 more"));
+        assert!(size_mismatch(16, 12).unwrap().contains("last 4"));
+    }
+}
@@ -0,0 +1,176 @@
+use cgmath::Point3;
+use imgui::{TreeNodeFlags, Ui};
+use serde_json::{Map, Value as JsonValue};
+
+use super::{camera_path::CameraKeyframe, default_camera_view};
+
+const DEFAULT_TRANSITION_SECONDS: f32 = 0.5;
+const MAX_TRANSITION_SECONDS: f32 = 5.0;
+
+/// A named camera view to come back to
+#[derive(Debug, Clone, PartialEq)]
+struct CameraBookmark {
+    name: String,
+    position: Point3<f32>,
+    yaw: f32,
+    pitch: f32,
+}
+
+impl CameraBookmark {
+    fn view(&self) -> CameraKeyframe {
+        CameraKeyframe {
+            time: 0.0,
+            position: self.position,
+            yaw: self.yaw,
+            pitch: self.pitch,
+        }
+    }
+
+    fn from_json(json_val: &JsonValue) -> Option<CameraBookmark> {
+        let json_position = json_val.get("position")?.as_array()?;
+        if json_position.len() != 3 {
+            return None;
+        }
+
+        Some(CameraBookmark {
+            name: json_val.get("name")?.as_str()?.to_string(),
+            position: Point3 {
+                x: json_position.get(0)?.as_f64()? as f32,
+                y: json_position.get(1)?.as_f64()? as f32,
+                z: json_position.get(2)?.as_f64()? as f32,
+            },
+            yaw: json_val.get("yaw")?.as_f64()? as f32,
+            pitch: json_val.get("pitch")?.as_f64()? as f32,
+        })
+    }
+
+    fn to_json(&self) -> JsonValue {
+        let mut json_obj = Map::new();
+        let position: Vec<JsonValue> = vec![
+            self.position.x.into(),
+            self.position.y.into(),
+            self.position.z.into(),
+        ];
+        json_obj.insert("name".into(), self.name.clone().into());
+        json_obj.insert("position".into(), position.into());
+        json_obj.insert("yaw".into(), self.yaw.into());
+        json_obj.insert("pitch".into(), self.pitch.into());
+        JsonValue::Object(json_obj)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct CameraBookmarks {
+    bookmarks: Vec<CameraBookmark>,
+    /// Name of the next bookmark, being typed in the editor
+    new_name: String,
+    /// How long recalling a view takes, 0 jumps to it
+    transition_seconds: f32,
+}
+
+impl Default for CameraBookmarks {
+    fn default() -> Self {
+        CameraBookmarks {
+            bookmarks: Vec::new(),
+            new_name: String::new(),
+            transition_seconds: DEFAULT_TRANSITION_SECONDS,
+        }
+    }
+}
+
+impl CameraBookmarks {
+    pub(crate) fn transition_seconds(&self) -> f32 {
+        self.transition_seconds
+    }
+
+    /// Returns the view to move the camera to, if a bookmark was recalled or the view reset
+    pub(crate) fn show_editor(
+        &mut self,
+        ui: &Ui,
+        current_camera: Option<CameraKeyframe>,
+    ) -> Option<CameraKeyframe> {
+        let mut recalled = None;
+        if !ui.collapsing_header("Camera bookmarks", TreeNodeFlags::empty()) {
+            return recalled;
+        }
+
+        ui.slider_config("Transition (s)", 0.0, MAX_TRANSITION_SECONDS)
+            .display_format("%.2f")
+            .build(&mut self.transition_seconds);
+        if ui.button("Reset view") {
+            recalled = Some(default_camera_view())
+        }
+
+        ui.input_text("##camera_bookmark_name", &mut self.new_name)
+            .hint("Bookmark name")
+            .build();
+        ui.same_line();
+        ui.disabled(current_camera.is_none(), || {
+            if ui.button("Save current view") {
+                if let Some(camera) = current_camera {
+                    let name = match self.new_name.trim() {
+                        "" => format!("View {}", self.bookmarks.len() + 1),
+                        name => name.to_string(),
+                    };
+                    self.bookmarks.push(CameraBookmark {
+                        name,
+                        position: camera.position,
+                        yaw: camera.yaw,
+                        pitch: camera.pitch,
+                    });
+                    self.new_name.clear()
+                }
+            }
+        });
+
+        let mut removed = None;
+        for (i, bookmark) in self.bookmarks.iter().enumerate() {
+            if ui.button(format!("Go##camera_bookmark_go_{i}")) {
+                recalled = Some(bookmark.view())
+            }
+            ui.same_line();
+            if ui.button(format!("Delete##camera_bookmark_delete_{i}")) {
+                removed = Some(i)
+            }
+            ui.same_line();
+            ui.text(&bookmark.name);
+            if ui.is_item_hovered() {
+                ui.tooltip_text(format!(
+                    "({:.2}, {:.2}, {:.2})\nyaw: {:.2}, pitch: {:.2}",
+                    bookmark.position.x,
+                    bookmark.position.y,
+                    bookmark.position.z,
+                    bookmark.yaw,
+                    bookmark.pitch
+                ));
+            }
+        }
+
+        if let Some(i) = removed {
+            self.bookmarks.remove(i);
+        }
+
+        recalled
+    }
+
+    pub(crate) fn from_json(json_val: &JsonValue) -> Option<CameraBookmarks> {
+        let bookmarks = json_val
+            .as_array()?
+            .iter()
+            .map(CameraBookmark::from_json)
+            .collect::<Option<Vec<_>>>()?;
+
+        Some(CameraBookmarks {
+            bookmarks,
+            ..Default::default()
+        })
+    }
+
+    pub(crate) fn to_json(&self) -> JsonValue {
+        self.bookmarks
+            .iter()
+            .map(CameraBookmark::to_json)
+            .collect::<Vec<_>>()
+            .into()
+    }
+}
@@ -0,0 +1,278 @@
+use std::f32::consts::PI;
+
+use cgmath::{InnerSpace, Point3, Quaternion, Vector3};
+use imgui::{TreeNodeFlags, Ui};
+use serde_json::{Map, Value as JsonValue};
+
+use super::UniformEditEvent;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) struct CameraKeyframe {
+    /// Seconds since the program started
+    pub(crate) time: f32,
+    pub(crate) position: Point3<f32>,
+    pub(crate) yaw: f32,
+    pub(crate) pitch: f32,
+}
+
+impl CameraKeyframe {
+    fn from_json(json_val: &JsonValue) -> Option<CameraKeyframe> {
+        let json_position = json_val.get("position")?.as_array()?;
+        if json_position.len() != 3 {
+            return None;
+        }
+
+        Some(CameraKeyframe {
+            time: json_val.get("time")?.as_f64()? as f32,
+            position: Point3 {
+                x: json_position.get(0)?.as_f64()? as f32,
+                y: json_position.get(1)?.as_f64()? as f32,
+                z: json_position.get(2)?.as_f64()? as f32,
+            },
+            yaw: json_val.get("yaw")?.as_f64()? as f32,
+            pitch: json_val.get("pitch")?.as_f64()? as f32,
+        })
+    }
+
+    /// Unit vector the camera looks at, the same one the camera uniform uses
+    fn direction(&self) -> Vector3<f32> {
+        Vector3::new(
+            self.yaw.cos() * self.pitch.cos(),
+            self.pitch.sin(),
+            self.yaw.sin() * self.pitch.cos(),
+        )
+        .normalize()
+    }
+
+    fn to_json(&self) -> JsonValue {
+        let mut json_obj = Map::new();
+        let position: Vec<JsonValue> = vec![
+            self.position.x.into(),
+            self.position.y.into(),
+            self.position.z.into(),
+        ];
+        json_obj.insert("time".into(), self.time.into());
+        json_obj.insert("position".into(), position.into());
+        json_obj.insert("yaw".into(), self.yaw.into());
+        json_obj.insert("pitch".into(), self.pitch.into());
+        JsonValue::Object(json_obj)
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub(crate) struct CameraPath {
+    keyframes: Vec<CameraKeyframe>,
+    pub(crate) enabled: bool,
+    looping: bool,
+}
+
+impl CameraPath {
+    /// Position and angles of the camera at `time` seconds, or None if there are no keyframes
+    pub(crate) fn sample(&self, time: f32) -> Option<CameraKeyframe> {
+        let first = self.keyframes.first()?;
+        let last = self.keyframes.last()?;
+        let duration = last.time - first.time;
+        if self.keyframes.len() == 1 || duration <= 0.0 {
+            return Some(*first);
+        }
+
+        let time = if self.looping {
+            first.time + (time - first.time).rem_euclid(duration)
+        } else {
+            time.clamp(first.time, last.time)
+        };
+
+        // Index of the keyframe that starts the current segment
+        let segment = self
+            .keyframes
+            .windows(2)
+            .position(|keys| time <= keys[1].time)
+            .unwrap_or(self.keyframes.len() - 2);
+
+        let k1 = self.keyframes[segment];
+        let k2 = self.keyframes[segment + 1];
+        let k0 = self.keyframes[segment.saturating_sub(1)];
+        let k3 = self.keyframes[(segment + 2).min(self.keyframes.len() - 1)];
+
+        let segment_duration = k2.time - k1.time;
+        let t = if segment_duration > 0.0 {
+            (time - k1.time) / segment_duration
+        } else {
+            0.0
+        };
+
+        Some(CameraKeyframe {
+            time,
+            position: catmull_rom(k0.position, k1.position, k2.position, k3.position, t),
+            yaw: lerp_angle(k1.yaw, k2.yaw, t),
+            pitch: lerp_angle(k1.pitch, k2.pitch, t),
+        })
+    }
+
+    fn sort(&mut self) {
+        self.keyframes.sort_by(|a, b| a.time.total_cmp(&b.time));
+    }
+
+    pub(crate) fn show_editor(
+        &mut self,
+        ui: &Ui,
+        current_camera: Option<CameraKeyframe>,
+        camera_location: (usize, usize),
+    ) -> Option<UniformEditEvent> {
+        let mut message = None;
+        if !ui.collapsing_header("Camera path", TreeNodeFlags::empty()) {
+            return message;
+        }
+
+        if ui.checkbox("Follow path", &mut self.enabled) && !self.enabled {
+            // Give the manual camera back its buffer
+            message = Some(UniformEditEvent::UpdateBuffer(
+                camera_location.0,
+                camera_location.1,
+            ))
+        }
+        ui.checkbox("Loop##camera_path", &mut self.looping);
+
+        ui.disabled(current_camera.is_none(), || {
+            if ui.button("Add keyframe from current camera") {
+                if let Some(mut keyframe) = current_camera {
+                    keyframe.time = self.keyframes.last().map_or(0.0, |k| k.time + 1.0);
+                    self.keyframes.push(keyframe);
+                }
+            }
+        });
+
+        let mut resort = false;
+        let mut removed = None;
+        for (i, keyframe) in self.keyframes.iter_mut().enumerate() {
+            ui.text(format!(
+                "{i}: ({:.2}, {:.2}, {:.2})",
+                keyframe.position.x, keyframe.position.y, keyframe.position.z
+            ));
+            ui.same_line();
+            ui.set_next_item_width(80.0);
+            if ui
+                .input_float(format!("s##keyframe_time_{i}"), &mut keyframe.time)
+                .build()
+            {
+                resort = true
+            }
+            ui.same_line();
+            if ui.button(format!("Remove##keyframe_remove_{i}")) {
+                removed = Some(i)
+            }
+        }
+
+        if let Some(i) = removed {
+            self.keyframes.remove(i);
+        }
+        if resort {
+            self.sort()
+        }
+
+        message
+    }
+
+    pub(crate) fn from_json(json_val: &JsonValue) -> Option<CameraPath> {
+        let keyframes = json_val
+            .get("keyframes")?
+            .as_array()?
+            .iter()
+            .map(CameraKeyframe::from_json)
+            .collect::<Option<Vec<_>>>()?;
+
+        let mut path = CameraPath {
+            keyframes,
+            enabled: json_val.get("enabled")?.as_bool()?,
+            looping: json_val.get("loop")?.as_bool()?,
+        };
+        path.sort();
+
+        Some(path)
+    }
+
+    pub(crate) fn to_json(&self) -> JsonValue {
+        let mut json_obj = Map::new();
+        let keyframes: Vec<JsonValue> = self.keyframes.iter().map(|k| k.to_json()).collect();
+        json_obj.insert("keyframes".into(), keyframes.into());
+        json_obj.insert("enabled".into(), self.enabled.into());
+        json_obj.insert("loop".into(), self.looping.into());
+        JsonValue::Object(json_obj)
+    }
+}
+
+/// Moves the camera from one view to another over a few frames
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct CameraTransition {
+    from: CameraKeyframe,
+    to: CameraKeyframe,
+    /// Seconds
+    duration: f32,
+    elapsed: f32,
+}
+
+impl CameraTransition {
+    pub(crate) fn new(from: CameraKeyframe, to: CameraKeyframe, duration: f32) -> CameraTransition {
+        CameraTransition {
+            from,
+            to,
+            duration,
+            elapsed: 0.0,
+        }
+    }
+
+    /// Advances the transition by `dt` seconds. Returns the view at that point, and whether
+    /// the transition has finished
+    pub(crate) fn advance(&mut self, dt: f32) -> (CameraKeyframe, bool) {
+        self.elapsed += dt;
+        let t = self.elapsed / self.duration;
+        if t >= 1.0 {
+            return (self.to, true);
+        }
+
+        // Eases in and out, so the camera doesn't start or stop abruptly
+        let t = t * t * (3.0 - 2.0 * t);
+        let position = self.from.position + (self.to.position - self.from.position) * t;
+
+        let from_direction = self.from.direction();
+        let rotation = Quaternion::from_arc(from_direction, self.to.direction(), None);
+        let direction = Quaternion::new(1.0, 0.0, 0.0, 0.0).slerp(rotation, t) * from_direction;
+
+        let view = CameraKeyframe {
+            time: 0.0,
+            position,
+            yaw: direction.z.atan2(direction.x),
+            pitch: direction.y.clamp(-1.0, 1.0).asin(),
+        };
+        (view, false)
+    }
+}
+
+fn catmull_rom(
+    p0: Point3<f32>,
+    p1: Point3<f32>,
+    p2: Point3<f32>,
+    p3: Point3<f32>,
+    t: f32,
+) -> Point3<f32> {
+    let t2 = t * t;
+    let t3 = t2 * t;
+    let v0 = Vector3::new(p0.x, p0.y, p0.z);
+    let v1 = Vector3::new(p1.x, p1.y, p1.z);
+    let v2 = Vector3::new(p2.x, p2.y, p2.z);
+    let v3 = Vector3::new(p3.x, p3.y, p3.z);
+
+    let v = (v1 * 2.0
+        + (v2 - v0) * t
+        + (v0 * 2.0 - v1 * 5.0 + v2 * 4.0 - v3) * t2
+        + (v1 * 3.0 - v0 - v2 * 3.0 + v3) * t3)
+        * 0.5;
+
+    Point3::new(v.x, v.y, v.z)
+}
+
+/// Interpolates between two angles (in radians) going through the shortest path
+fn lerp_angle(from: f32, to: f32, t: f32) -> f32 {
+    let delta = (to - from + PI).rem_euclid(2.0 * PI) - PI;
+    from + delta * t
+}
@@ -0,0 +1,188 @@
+use cgmath::{Matrix4, Vector3};
+use imgui::Ui;
+
+/// Length of the handles, as a fraction of the distance to the camera so they keep their size
+const HANDLE_SCALE: f32 = 0.15;
+/// Pixels from a handle at which it can still be grabbed
+const GRAB_DISTANCE: f32 = 6.0;
+
+/// Axis a handle of the translation gizmo moves along
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) enum GizmoAxis {
+    X,
+    Y,
+    Z,
+}
+
+impl GizmoAxis {
+    const ALL: [GizmoAxis; 3] = [GizmoAxis::X, GizmoAxis::Y, GizmoAxis::Z];
+
+    pub(super) fn direction(self) -> Vector3<f32> {
+        match self {
+            GizmoAxis::X => Vector3::unit_x(),
+            GizmoAxis::Y => Vector3::unit_y(),
+            GizmoAxis::Z => Vector3::unit_z(),
+        }
+    }
+
+    fn color(self) -> [f32; 4] {
+        match self {
+            GizmoAxis::X => [0.9, 0.2, 0.2, 1.0],
+            GizmoAxis::Y => [0.2, 0.9, 0.2, 1.0],
+            GizmoAxis::Z => [0.3, 0.4, 1.0, 1.0],
+        }
+    }
+}
+
+/// Where a point of the world is drawn on the image, None if it's behind the camera
+fn project(
+    view_projection: Matrix4<f32>,
+    point: Vector3<f32>,
+    image_min: [f32; 2],
+    image_size: [f32; 2],
+) -> Option<[f32; 2]> {
+    let clip = view_projection * point.extend(1.0);
+    if clip.w <= f32::EPSILON {
+        return None;
+    }
+    let (x, y) = (clip.x / clip.w, clip.y / clip.w);
+    Some([
+        image_min[0] + (x + 1.0) / 2.0 * image_size[0],
+        image_min[1] + (1.0 - y) / 2.0 * image_size[1],
+    ])
+}
+
+fn distance_to_segment(point: [f32; 2], start: [f32; 2], end: [f32; 2]) -> f32 {
+    let segment = [end[0] - start[0], end[1] - start[1]];
+    let to_point = [point[0] - start[0], point[1] - start[1]];
+    let length_sq = segment[0] * segment[0] + segment[1] * segment[1];
+    let t = if length_sq > 0.0 {
+        ((to_point[0] * segment[0] + to_point[1] * segment[1]) / length_sq).clamp(0.0, 1.0)
+    } else {
+        0.0
+    };
+    let closest = [start[0] + segment[0] * t, start[1] + segment[1] * t];
+    ((point[0] - closest[0]).powi(2) + (point[1] - closest[1]).powi(2)).sqrt()
+}
+
+/// Translation handles of a transform, in screen coordinates
+pub(super) struct TranslationGizmo {
+    origin: [f32; 2],
+    /// End of the handle of every axis
+    ends: [[f32; 2]; 3],
+    /// Length of the handles in the world
+    handle_length: f32,
+}
+
+impl TranslationGizmo {
+    /// None if the translation, or the end of a handle, is behind the camera
+    pub(super) fn new(
+        view_projection: Matrix4<f32>,
+        translation: Vector3<f32>,
+        image_min: [f32; 2],
+        image_size: [f32; 2],
+    ) -> Option<TranslationGizmo> {
+        let distance = (view_projection * translation.extend(1.0)).w;
+        let handle_length = distance * HANDLE_SCALE;
+        let origin = project(view_projection, translation, image_min, image_size)?;
+        let mut ends = [[0.0; 2]; 3];
+        for (end, axis) in ends.iter_mut().zip(GizmoAxis::ALL) {
+            let point = translation + axis.direction() * handle_length;
+            *end = project(view_projection, point, image_min, image_size)?;
+        }
+
+        Some(TranslationGizmo {
+            origin,
+            ends,
+            handle_length,
+        })
+    }
+
+    /// The handle under the mouse, the closest one if they overlap
+    pub(super) fn hovered_axis(&self, mouse: [f32; 2]) -> Option<GizmoAxis> {
+        GizmoAxis::ALL
+            .into_iter()
+            .map(|axis| {
+                let distance = distance_to_segment(mouse, self.origin, self.ends[axis as usize]);
+                (axis, distance)
+            })
+            .filter(|(_, distance)| *distance <= GRAB_DISTANCE)
+            .min_by(|(_, a), (_, b)| a.total_cmp(b))
+            .map(|(axis, _)| axis)
+    }
+
+    /// How far along `axis` the translation moves when the mouse moves `mouse_delta` pixels.
+    /// Only the part of the movement along the handle counts
+    pub(super) fn drag_distance(&self, axis: GizmoAxis, mouse_delta: [f32; 2]) -> f32 {
+        let end = self.ends[axis as usize];
+        let handle = [end[0] - self.origin[0], end[1] - self.origin[1]];
+        let length_sq = handle[0] * handle[0] + handle[1] * handle[1];
+        // A handle that points at the camera can't be dragged
+        if length_sq < 1.0 {
+            return 0.0;
+        }
+        (mouse_delta[0] * handle[0] + mouse_delta[1] * handle[1]) / length_sq * self.handle_length
+    }
+
+    /// `highlighted` is drawn thicker, it's hovered or being dragged
+    pub(super) fn draw(&self, ui: &Ui, highlighted: Option<GizmoAxis>) {
+        let draw_list = ui.get_window_draw_list();
+        for axis in GizmoAxis::ALL {
+            let thickness = if highlighted == Some(axis) { 4.0 } else { 2.0 };
+            let end = self.ends[axis as usize];
+            draw_list
+                .add_line(self.origin, end, axis.color())
+                .thickness(thickness)
+                .build();
+            draw_list
+                .add_circle(end, thickness + 2.0, axis.color())
+                .filled(true)
+                .build();
+        }
+        draw_list
+            .add_circle(self.origin, 3.0, [1.0, 1.0, 1.0, 1.0])
+            .filled(true)
+            .build();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use cgmath::SquareMatrix;
+
+    use super::*;
+
+    #[test]
+    fn handles_follow_the_projection() {
+        // Without a camera the world is drawn as it is, x right and y up
+        let identity = Matrix4::identity();
+        let gizmo = TranslationGizmo::new(
+            identity,
+            Vector3::new(0.0, 0.0, 0.0),
+            [10.0, 10.0],
+            [200.0, 200.0],
+        )
+        .unwrap();
+        let pixels = |point: [f32; 2]| point.map(f32::round);
+        assert_eq!(pixels(gizmo.origin), [110.0, 110.0]);
+        assert_eq!(pixels(gizmo.ends[GizmoAxis::X as usize]), [125.0, 110.0]);
+        assert_eq!(pixels(gizmo.ends[GizmoAxis::Y as usize]), [110.0, 95.0]);
+        assert_eq!(gizmo.hovered_axis([120.0, 112.0]), Some(GizmoAxis::X));
+        assert_eq!(gizmo.hovered_axis([150.0, 150.0]), None);
+
+        // Dragging the whole handle moves the translation by its length
+        assert!((gizmo.drag_distance(GizmoAxis::X, [15.0, 40.0]) - 0.15).abs() < 1e-6);
+        assert!((gizmo.drag_distance(GizmoAxis::Y, [0.0, 15.0]) + 0.15).abs() < 1e-6);
+        // The Z handle points at the screen
+        assert_eq!(gizmo.drag_distance(GizmoAxis::Z, [5.0, 5.0]), 0.0);
+    }
+
+    #[test]
+    fn nothing_is_drawn_behind_the_camera() {
+        let projection = crate::imgui_state::camera_projection();
+        let in_front = Vector3::new(0.0, 0.0, -5.0);
+        assert!(TranslationGizmo::new(projection, in_front, [0.0, 0.0], [100.0, 100.0]).is_some());
+        let behind = Vector3::new(0.0, 0.0, 5.0);
+        assert!(TranslationGizmo::new(projection, behind, [0.0, 0.0], [100.0, 100.0]).is_none());
+    }
+}
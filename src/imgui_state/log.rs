@@ -0,0 +1,304 @@
+use std::{collections::VecDeque, time::Instant};
+
+use imgui::{Condition, StyleColor, Ui};
+
+use crate::settings::{Appearance, UiTheme};
+
+/// Oldest entries are dropped after this many
+const MAX_LOG_ENTRIES: usize = 500;
+/// Longer errors scroll in their text box
+const MAX_ERROR_LINES: usize = 12;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Severity {
+    Error,
+    Warning,
+    Info,
+}
+
+impl Severity {
+    const ALL: [Severity; 3] = [Severity::Error, Severity::Warning, Severity::Info];
+
+    fn label(self) -> &'static str {
+        match self {
+            Severity::Error => "Error",
+            Severity::Warning => "Warning",
+            Severity::Info => "Info",
+        }
+    }
+
+    /// Light themes need darker colors to be readable
+    fn color(self, appearance: &Appearance) -> [f32; 4] {
+        let light = appearance.theme == UiTheme::Light;
+        match (self, light, appearance.high_contrast_errors) {
+            (Severity::Error, false, false) => [1.0, 0.4, 0.4, 1.0],
+            (Severity::Error, false, true) => [1.0, 0.15, 0.15, 1.0],
+            (Severity::Error, true, false) => [0.8, 0.1, 0.1, 1.0],
+            (Severity::Error, true, true) => [0.65, 0.0, 0.0, 1.0],
+            (Severity::Warning, false, false) => [1.0, 0.8, 0.3, 1.0],
+            (Severity::Warning, false, true) => [1.0, 0.85, 0.0, 1.0],
+            (Severity::Warning, true, false) => [0.7, 0.45, 0.0, 1.0],
+            (Severity::Warning, true, true) => [0.5, 0.3, 0.0, 1.0],
+            (Severity::Info, false, _) => [0.7, 0.7, 0.7, 1.0],
+            (Severity::Info, true, _) => [0.35, 0.35, 0.35, 1.0],
+        }
+    }
+}
+
+/// Every diagnostic of a formatted error, like naga's, as its own message. They start with
+/// `error:`, their newlines are kept but the blank lines around them aren't
+pub(crate) fn split_errors(text: &str) -> Vec<String> {
+    let mut errors: Vec<String> = Vec::new();
+    for line in text.lines() {
+        match errors.last_mut() {
+            Some(error) if !line.trim_start().starts_with("error:") => {
+                error.push('\n');
+                error.push_str(line)
+            }
+            _ => errors.push(line.to_string()),
+        }
+    }
+
+    errors
+        .into_iter()
+        .map(|error| error.trim_matches(['\n', '\r']).trim_end().to_string())
+        .filter(|error| !error.trim().is_empty())
+        .collect()
+}
+
+struct LogEntry {
+    severity: Severity,
+    /// Seconds since the program started
+    time: f32,
+    message: String,
+}
+
+impl LogEntry {
+    fn timestamp(&self) -> String {
+        let minutes = (self.time / 60.0) as u32;
+        let seconds = self.time % 60.0;
+        format!("{minutes:02}:{seconds:06.3}")
+    }
+
+    fn to_line(&self) -> String {
+        format!(
+            "[{}] {}: {}",
+            self.timestamp(),
+            self.severity.label(),
+            self.message
+        )
+    }
+}
+
+/// History of errors, warnings and info messages shown in the Log window
+pub(crate) struct Log {
+    start: Instant,
+    entries: VecDeque<LogEntry>,
+    /// Errors since the last successful reload, they mark the shader as broken
+    active_errors: Vec<String>,
+    /// The user dismissed the active errors, a new error shows them again
+    dismissed: bool,
+    /// Indexed like `Severity::ALL`
+    show_severity: [bool; 3],
+    focus: bool,
+}
+
+impl Log {
+    pub(crate) fn new() -> Log {
+        Log {
+            start: Instant::now(),
+            entries: VecDeque::new(),
+            active_errors: Vec::new(),
+            dismissed: false,
+            show_severity: [true; 3],
+            focus: false,
+        }
+    }
+
+    pub(crate) fn push(&mut self, severity: Severity, message: String) {
+        if severity == Severity::Error {
+            // The same error is reported every time the pipeline is retried
+            if self.active_errors.contains(&message) {
+                return;
+            }
+            self.active_errors.push(message.clone());
+            self.dismissed = false;
+            self.focus = true;
+        }
+
+        if self.entries.len() == MAX_LOG_ENTRIES {
+            self.entries.pop_front();
+        }
+        self.entries.push_back(LogEntry {
+            severity,
+            time: self.start.elapsed().as_secs_f32(),
+            message,
+        })
+    }
+
+    pub(crate) fn has_errors(&self) -> bool {
+        !self.active_errors.is_empty()
+    }
+
+    /// The errors are kept in the history, but no longer mark the shader as broken
+    pub(crate) fn resolve_errors(&mut self) {
+        self.active_errors.clear();
+        self.dismissed = false
+    }
+
+    /// Active errors the user hasn't dismissed yet, they keep the Log window open
+    pub(crate) fn has_undismissed_errors(&self) -> bool {
+        self.has_errors() && !self.dismissed
+    }
+
+    fn error_count(&self) -> String {
+        match self.active_errors.len() {
+            1 => "1 error".to_string(),
+            count => format!("{count} errors"),
+        }
+    }
+
+    /// Red count of the undismissed errors, with buttons to show the log and to dismiss them.
+    /// It's drawn on the same line as the previous item
+    pub(crate) fn show_error_badge(&mut self, ui: &Ui, appearance: &Appearance) {
+        if !self.has_undismissed_errors() {
+            return;
+        }
+
+        ui.same_line();
+        ui.text_colored(Severity::Error.color(appearance), self.error_count());
+        ui.same_line();
+        if ui.small_button("Show##show_errors") {
+            self.focus = true
+        }
+        ui.same_line();
+        if ui.small_button("Dismiss##dismiss_errors_badge") {
+            self.dismissed = true
+        }
+        if ui.is_item_hovered() {
+            ui.tooltip_text("Hides the errors until there's a new one, they stay in the log")
+        }
+    }
+
+    pub(crate) fn show(&mut self, ui: &Ui, appearance: &Appearance) {
+        ui.window("Log")
+            .size([500.0, 200.0], Condition::FirstUseEver)
+            .focused(self.focus)
+            .build(|| {
+                self.focus = false;
+                if self.has_undismissed_errors() {
+                    let color = Severity::Error.color(appearance);
+                    ui.text_colored(
+                        color,
+                        format!("{} since the last successful reload:", self.error_count()),
+                    );
+                    // Read-only text boxes, so the errors can be selected and copied as they are
+                    let line_height = ui.text_line_height();
+                    let padding = ui.clone_style().frame_padding[1] * 2.0;
+                    for (i, error) in self.active_errors.iter_mut().enumerate() {
+                        let lines = error.lines().count().clamp(1, MAX_ERROR_LINES) as f32;
+                        ui.input_text_multiline(
+                            format!("##active_error_{i}"),
+                            error,
+                            [-1.0, lines * line_height + padding],
+                        )
+                        .read_only(true)
+                        .build();
+                    }
+                    if ui.button("Dismiss") {
+                        self.dismissed = true
+                    }
+                    if ui.is_item_hovered() {
+                        ui.tooltip_text(
+                            "Hides the errors until there's a new one, they stay in the log",
+                        )
+                    }
+                    ui.separator();
+                }
+                for (severity, show) in Severity::ALL.iter().zip(self.show_severity.iter_mut()) {
+                    ui.checkbox(severity.label(), show);
+                    ui.same_line();
+                }
+                if ui.button("Copy all") {
+                    let text: Vec<String> = self.visible_entries().map(LogEntry::to_line).collect();
+                    ui.set_clipboard_text(text.join("\n"))
+                }
+                ui.same_line();
+                if ui.button("Clear") {
+                    self.entries.clear()
+                }
+                ui.separator();
+
+                ui.child_window("##log_entries").build(|| {
+                    for (i, entry) in self.visible_entries().enumerate() {
+                        if ui.small_button(format!("Copy##log_copy_{i}")) {
+                            ui.set_clipboard_text(entry.to_line())
+                        }
+                        ui.same_line();
+                        ui.text_disabled(entry.timestamp());
+                        ui.same_line();
+                        let color = entry.severity.color(appearance);
+                        ui.text_colored(color, entry.severity.label());
+                        ui.same_line();
+                        // High contrast colors the whole message of errors, not just the label
+                        if appearance.high_contrast_errors && entry.severity == Severity::Error {
+                            let _color = ui.push_style_color(StyleColor::Text, color);
+                            ui.text_wrapped(&entry.message)
+                        } else {
+                            ui.text_wrapped(&entry.message)
+                        }
+                    }
+                    // Follow new entries, unless the user scrolled up to read older ones
+                    if ui.scroll_y() >= ui.scroll_max_y() {
+                        ui.set_scroll_here_y_with_ratio(1.0)
+                    }
+                });
+            });
+    }
+
+    fn visible_entries(&self) -> impl Iterator<Item = &LogEntry> {
+        self.entries.iter().filter(|entry| {
+            let index = Severity::ALL
+                .iter()
+                .position(|severity| *severity == entry.severity)
+                .unwrap_or(0);
+            self.show_severity[index]
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn new_errors_undo_a_dismiss() {
+        let mut log = Log::new();
+        log.push(Severity::Error, "first".into());
+        log.dismissed = true;
+        assert!(log.has_errors() && !log.has_undismissed_errors());
+
+        // Retries report the same error again, it stays dismissed
+        log.push(Severity::Error, "first".into());
+        assert!(!log.has_undismissed_errors());
+        log.push(Severity::Error, "second".into());
+        assert!(log.has_undismissed_errors());
+
+        log.resolve_errors();
+        assert!(!log.has_errors() && !log.has_undismissed_errors());
+    }
+
+    #[test]
+    fn diagnostics_are_split() {
+        let text = "\nShader parsing error: expected ';'\n  ┌─ wgsl:3:5\n  │\n\nerror: unknown type\n  ┌─ wgsl:8:1\n\n";
+        let errors = split_errors(text);
+        assert_eq!(
+            errors,
+            [
+                "Shader parsing error: expected ';'\n  ┌─ wgsl:3:5\n  │",
+                "error: unknown type\n  ┌─ wgsl:8:1",
+            ]
+        );
+        assert_eq!(split_errors("\n\n"), Vec::<String>::new());
+    }
+}
@@ -0,0 +1,521 @@
+use std::{
+    collections::VecDeque,
+    io::ErrorKind,
+    net::{Ipv4Addr, SocketAddr, UdpSocket},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        mpsc::{self, Receiver},
+        Arc,
+    },
+    thread::{self, JoinHandle},
+    time::Duration,
+};
+
+use imgui::Ui;
+
+use crate::settings::OscSettings;
+
+use super::{
+    log::{Log, Severity},
+    script::{components, vector_from_components},
+    uniform_types::ScalarUniformValue,
+    UniformRequests, UniformValue, Uniforms, UniformsError,
+};
+
+/// Entries of the recent messages list
+const RECENT_MESSAGES: usize = 10;
+/// Biggest UDP payload
+const MAX_PACKET_SIZE: usize = 65536;
+/// How often the listening thread checks whether it has to stop
+const STOP_CHECK_INTERVAL: Duration = Duration::from_millis(100);
+
+#[derive(Debug, Clone, PartialEq)]
+enum OscArg {
+    Int(i32),
+    Float(f32),
+    String(String),
+    /// Any other type, it isn't used by any address
+    Other(char),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct OscMessage {
+    address: String,
+    args: Vec<OscArg>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum OscCommand {
+    SetUniform {
+        group: usize,
+        binding: usize,
+        values: Vec<f32>,
+    },
+    LoadShader(String),
+}
+
+impl OscMessage {
+    fn command(&self) -> Result<OscCommand, String> {
+        let parts: Vec<&str> = self.address.split('/').skip(1).collect();
+        match parts.as_slice() {
+            ["uniform", group, binding] => {
+                let (Ok(group), Ok(binding)) = (group.parse::<usize>(), binding.parse::<usize>())
+                else {
+                    return Err("The group and binding must be numbers".into());
+                };
+                let values = self
+                    .args
+                    .iter()
+                    .map(|arg| match arg {
+                        OscArg::Float(v) => Some(*v),
+                        OscArg::Int(v) => Some(*v as f32),
+                        _ => None,
+                    })
+                    .collect::<Option<Vec<_>>>()
+                    .filter(|values| (1..=4).contains(&values.len()))
+                    .ok_or("Expected 1 to 4 numbers")?;
+                Ok(OscCommand::SetUniform {
+                    group,
+                    binding,
+                    values,
+                })
+            }
+            ["shader", "load"] => match self.args.as_slice() {
+                [OscArg::String(shader_name)] => Ok(OscCommand::LoadShader(shader_name.clone())),
+                _ => Err("Expected the name of the shader".into()),
+            },
+            _ => Err("Unknown address".into()),
+        }
+    }
+
+    fn label(&self) -> String {
+        let args: Vec<String> = self
+            .args
+            .iter()
+            .map(|arg| match arg {
+                OscArg::Int(v) => v.to_string(),
+                OscArg::Float(v) => v.to_string(),
+                OscArg::String(v) => format!("\"{v}\""),
+                OscArg::Other(tag) => format!("<{tag}>"),
+            })
+            .collect();
+        format!("{} {}", self.address, args.join(" "))
+    }
+}
+
+/// Reads the OSC primitives of a packet, everything is big endian and 4 byte aligned
+struct Reader<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, len: usize) -> Option<&'a [u8]> {
+        if len > self.bytes.len() {
+            return None;
+        }
+        let (taken, rest) = self.bytes.split_at(len);
+        self.bytes = rest;
+        Some(taken)
+    }
+
+    fn i32(&mut self) -> Option<i32> {
+        Some(i32::from_be_bytes(self.take(4)?.try_into().ok()?))
+    }
+
+    fn f32(&mut self) -> Option<f32> {
+        Some(f32::from_be_bytes(self.take(4)?.try_into().ok()?))
+    }
+
+    /// Null terminated, padded to 4 bytes
+    fn string(&mut self) -> Option<String> {
+        let len = self.bytes.iter().position(|b| *b == 0)?;
+        let string = std::str::from_utf8(&self.bytes[..len]).ok()?.to_string();
+        self.take((len + 4) & !3)?;
+        Some(string)
+    }
+
+    /// Size, then the bytes padded to 4
+    fn blob(&mut self) -> Option<&'a [u8]> {
+        let len: usize = self.i32()?.try_into().ok()?;
+        let blob = self.take(len)?;
+        self.take((4 - len % 4) % 4)?;
+        Some(blob)
+    }
+}
+
+/// The messages of a packet, bundles are flattened. None if it's malformed
+fn parse_packet(packet: &[u8]) -> Option<Vec<OscMessage>> {
+    let mut messages = Vec::new();
+    parse_element(packet, &mut messages)?;
+    Some(messages)
+}
+
+fn parse_element(element: &[u8], messages: &mut Vec<OscMessage>) -> Option<()> {
+    let mut reader = Reader { bytes: element };
+    if element.starts_with(b"#bundle\0") {
+        reader.take(8 + 8)?; // The time tag is ignored, everything is applied on the next frame
+        while !reader.bytes.is_empty() {
+            parse_element(reader.blob()?, messages)?;
+        }
+        return Some(());
+    }
+
+    let address = reader.string()?;
+    // Some old senders don't send type tags for messages without arguments
+    let tags = if reader.bytes.is_empty() {
+        ",".to_string()
+    } else {
+        reader.string()?
+    };
+    let mut args = Vec::new();
+    for tag in tags.strip_prefix(',')?.chars() {
+        let arg = match tag {
+            'i' => OscArg::Int(reader.i32()?),
+            'f' => OscArg::Float(reader.f32()?),
+            's' | 'S' => OscArg::String(reader.string()?),
+            'd' => OscArg::Float(f64::from_be_bytes(reader.take(8)?.try_into().ok()?) as f32),
+            'h' | 't' => {
+                reader.take(8)?;
+                OscArg::Other(tag)
+            }
+            'b' => {
+                reader.blob()?;
+                OscArg::Other(tag)
+            }
+            'c' | 'r' | 'm' => {
+                reader.take(4)?;
+                OscArg::Other(tag)
+            }
+            'T' | 'F' | 'N' | 'I' => OscArg::Other(tag),
+            // The size of the rest of the arguments is unknown
+            _ => return None,
+        };
+        args.push(arg)
+    }
+    messages.push(OscMessage { address, args });
+
+    Some(())
+}
+
+/// The values mapped onto the type of the binding, integers are rounded
+fn uniform_value(current: &UniformValue, values: &[f32]) -> Option<UniformValue> {
+    let like = |current: &ScalarUniformValue, v: f32| match current {
+        ScalarUniformValue::U32(_) => ScalarUniformValue::U32(v.round() as u32),
+        ScalarUniformValue::I32(_) => ScalarUniformValue::I32(v.round() as i32),
+        ScalarUniformValue::F32(_) => ScalarUniformValue::F32(v),
+    };
+
+    match (current, values) {
+        (UniformValue::Scalar(scalar), &[v]) => Some(UniformValue::Scalar(like(scalar, v))),
+        (UniformValue::Vector(vector), values) => {
+            let current = components(vector);
+            if current.len() != values.len() {
+                return None;
+            }
+            let new: Vec<_> = current
+                .iter()
+                .zip(values)
+                .map(|(current, v)| like(current, *v))
+                .collect();
+            vector_from_components(vector, &new).map(UniformValue::Vector)
+        }
+        _ => None,
+    }
+}
+
+/// Receives packets on a thread until it's dropped
+struct Listener {
+    address: SocketAddr,
+    packets: Receiver<Vec<u8>>,
+    stop: Arc<AtomicBool>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl Listener {
+    /// Only other programs of this computer can send messages, unless `all_interfaces` is set
+    fn bind(port: u16, all_interfaces: bool) -> std::io::Result<Listener> {
+        let ip = if all_interfaces {
+            Ipv4Addr::UNSPECIFIED
+        } else {
+            Ipv4Addr::LOCALHOST
+        };
+        let socket = UdpSocket::bind((ip, port))?;
+        let address = socket.local_addr()?;
+        socket.set_read_timeout(Some(STOP_CHECK_INTERVAL))?;
+        let (sender, packets) = mpsc::channel();
+        let stop = Arc::new(AtomicBool::new(false));
+        let thread_stop = Arc::clone(&stop);
+        let thread = thread::spawn(move || {
+            let mut buffer = vec![0; MAX_PACKET_SIZE];
+            while !thread_stop.load(Ordering::Relaxed) {
+                match socket.recv(&mut buffer) {
+                    Ok(len) => {
+                        if sender.send(buffer[..len].to_vec()).is_err() {
+                            return;
+                        }
+                    }
+                    // Timing out lets it check whether it has to stop. Other errors, like a
+                    // reset of a previous send, don't stop the socket
+                    Err(err) => {
+                        if !matches!(err.kind(), ErrorKind::WouldBlock | ErrorKind::TimedOut) {
+                            thread::sleep(STOP_CHECK_INTERVAL)
+                        }
+                    }
+                }
+            }
+        });
+
+        Ok(Listener {
+            address,
+            packets,
+            stop,
+            thread: Some(thread),
+        })
+    }
+}
+
+impl Drop for Listener {
+    /// Waits for the thread, so the port is free once it returns
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+/// Listens for OSC messages that set uniforms and load shaders, for VJ software and the like.
+/// Messages are applied once per frame
+pub(crate) struct OscServer {
+    settings: OscSettings,
+    /// None while it's disabled, or if the port couldn't be bound
+    listener: Option<Listener>,
+    /// Port typed in the UI, it's applied with enter
+    port_input: i32,
+    packets: u64,
+    recent: VecDeque<String>,
+}
+
+impl OscServer {
+    pub(crate) fn new(settings: OscSettings, log: &mut Log) -> OscServer {
+        let mut server = OscServer {
+            settings,
+            listener: None,
+            port_input: settings.port.into(),
+            packets: 0,
+            recent: VecDeque::new(),
+        };
+        server.restart(log);
+        server
+    }
+
+    pub(crate) fn settings(&self) -> OscSettings {
+        self.settings
+    }
+
+    fn restart(&mut self, log: &mut Log) {
+        // The old socket has to be released before binding the port again
+        self.listener = None;
+        if !self.settings.enabled {
+            return;
+        }
+
+        match Listener::bind(self.settings.port, self.settings.all_interfaces) {
+            Ok(listener) => self.listener = Some(listener),
+            Err(err) => log.push(
+                Severity::Warning,
+                format!(
+                    "Couldn't listen for OSC on port {}: {err}",
+                    self.settings.port
+                ),
+            ),
+        }
+    }
+
+    fn record(&mut self, entry: String) {
+        if self.recent.len() == RECENT_MESSAGES {
+            self.recent.pop_front();
+        }
+        self.recent.push_back(entry)
+    }
+
+    /// Applies the messages received since the last call. Returns the last shader asked for
+    pub(crate) fn poll(&mut self, uniforms: &mut Uniforms) -> Option<String> {
+        let listener = self.listener.as_ref()?;
+        let packets: Vec<Vec<u8>> = listener.packets.try_iter().collect();
+
+        let mut shader_name = None;
+        for packet in packets {
+            self.packets += 1;
+            let Some(messages) = parse_packet(&packet) else {
+                self.record(format!("Malformed packet of {} bytes", packet.len()));
+                continue;
+            };
+            for message in messages {
+                let result = match message.command() {
+                    Ok(OscCommand::SetUniform {
+                        group,
+                        binding,
+                        values,
+                    }) => Self::set_uniform(uniforms, (group, binding), &values)
+                        .map_err(|err| err.to_string()),
+                    Ok(OscCommand::LoadShader(name)) => {
+                        shader_name = Some(name);
+                        Ok(())
+                    }
+                    Err(err) => Err(err),
+                };
+                match result {
+                    Ok(()) => self.record(message.label()),
+                    Err(err) => self.record(format!("{}: {err}", message.label())),
+                }
+            }
+        }
+
+        shader_name
+    }
+
+    fn set_uniform(
+        uniforms: &mut Uniforms,
+        location: (usize, usize),
+        values: &[f32],
+    ) -> Result<(), UniformsError> {
+        let current = UniformRequests::value(uniforms, location)?;
+        let value = uniform_value(&current, values).ok_or(UniformsError::TypeMismatch(current))?;
+        UniformRequests::set(uniforms, location, value)
+    }
+
+    /// Whether the settings were changed, so they have to be saved
+    pub(crate) fn show_status(&mut self, ui: &Ui, log: &mut Log) -> bool {
+        let mut changed = false;
+        if ui.checkbox("Listen for OSC", &mut self.settings.enabled) {
+            changed = true;
+            self.restart(log)
+        }
+        if ui.checkbox(
+            "Accept messages from other computers",
+            &mut self.settings.all_interfaces,
+        ) {
+            changed = true;
+            self.restart(log)
+        }
+        if ui.is_item_hovered() {
+            ui.tooltip_text("Listens on every network interface instead of only this computer's.\nAnyone on the network can then change the parameters and load any shader file")
+        }
+        if ui
+            .input_int("Port", &mut self.port_input)
+            .enter_returns_true(true)
+            .build()
+        {
+            match u16::try_from(self.port_input) {
+                Ok(port) if port != 0 => {
+                    self.settings.port = port;
+                    changed = true;
+                    self.restart(log)
+                }
+                _ => self.port_input = self.settings.port.into(),
+            }
+        }
+        match &self.listener {
+            Some(listener) => ui.text(format!("Listening on UDP {}", listener.address)),
+            None => ui.text_disabled("Not listening"),
+        }
+        ui.text(format!("Packets received: {}", self.packets));
+        ui.text_disabled(
+            "/uniform/<group>/<binding> with 1 to 4 numbers\n/shader/load with a shader name",
+        );
+        if !self.recent.is_empty() {
+            ui.text("Recent messages:");
+            for entry in self.recent.iter().rev() {
+                ui.bullet_text(entry);
+            }
+        }
+
+        changed
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Pads to 4 bytes like OSC strings
+    fn osc_string(string: &str) -> Vec<u8> {
+        let mut bytes = string.as_bytes().to_vec();
+        bytes.resize((string.len() + 4) & !3, 0);
+        bytes
+    }
+
+    fn uniform_message() -> Vec<u8> {
+        let mut packet = osc_string("/uniform/2/1");
+        packet.extend(osc_string(",fi"));
+        packet.extend(0.5f32.to_be_bytes());
+        packet.extend(3i32.to_be_bytes());
+        packet
+    }
+
+    #[test]
+    fn parses_messages() {
+        let messages = parse_packet(&uniform_message()).unwrap();
+        assert_eq!(
+            messages[0].command(),
+            Ok(OscCommand::SetUniform {
+                group: 2,
+                binding: 1,
+                values: vec![0.5, 3.0]
+            })
+        );
+
+        let mut packet = osc_string("/shader/load");
+        packet.extend(osc_string(",s"));
+        packet.extend(osc_string("tunnel.wgsl"));
+        let messages = parse_packet(&packet).unwrap();
+        assert_eq!(
+            messages[0].command(),
+            Ok(OscCommand::LoadShader("tunnel.wgsl".into()))
+        );
+    }
+
+    #[test]
+    fn parses_bundles() {
+        let message = uniform_message();
+        let mut packet = osc_string("#bundle");
+        packet.extend([0; 8]);
+        for _ in 0..2 {
+            packet.extend((message.len() as i32).to_be_bytes());
+            packet.extend(&message);
+        }
+
+        let messages = parse_packet(&packet).unwrap();
+        assert_eq!(messages.len(), 2);
+        assert_eq!(messages[0], messages[1]);
+    }
+
+    #[test]
+    fn rejects_truncated_packets() {
+        let message = uniform_message();
+        assert!(parse_packet(&message[..message.len() - 2]).is_none());
+    }
+
+    #[test]
+    fn listens_locally_and_frees_the_port() {
+        let listener = Listener::bind(0, false).unwrap();
+        let address = listener.address;
+        assert!(address.ip().is_loopback());
+
+        // Dropping it waits for the thread, so the port can be bound again right away
+        drop(listener);
+        let listener = Listener::bind(address.port(), false).unwrap();
+        assert_eq!(listener.address, address);
+    }
+
+    #[test]
+    fn values_take_the_binding_type() {
+        let current = UniformValue::Scalar(ScalarUniformValue::U32(0));
+        assert_eq!(
+            uniform_value(&current, &[2.6]),
+            Some(UniformValue::Scalar(ScalarUniformValue::U32(3)))
+        );
+        assert_eq!(uniform_value(&current, &[1.0, 2.0]), None);
+    }
+}
@@ -0,0 +1,218 @@
+use std::time::{Duration, Instant, SystemTime};
+
+use imgui::Ui;
+use serde_json::{Map, Value as JsonValue};
+
+use super::{
+    log::{Log, Severity},
+    script::{components, vector_from_components},
+    uniform_types::ScalarUniformValue,
+    UniformValue,
+};
+
+/// How often the file is checked for changes
+const POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+/// JSON file whose top-level keys are parameter names, mirrored into the parameters with
+/// those names every time it changes
+#[derive(Default)]
+pub(crate) struct ParameterSource {
+    path: String,
+    /// Manual edits of the parameters it sets are ignored
+    pub(super) lock_edits: bool,
+    last_poll: Option<Instant>,
+    /// When the file that was applied last was modified, None to apply it again
+    modified: Option<SystemTime>,
+    /// The file couldn't be read or parsed the last time, kept so it's reported once
+    error: Option<String>,
+}
+
+impl ParameterSource {
+    pub(super) fn is_active(&self) -> bool {
+        !self.path.is_empty()
+    }
+
+    /// The values in the file, if it changed since the last time they were returned.
+    /// Errors are reported when they change
+    pub(super) fn poll(&mut self, log: &mut Log) -> Option<Map<String, JsonValue>> {
+        if !self.is_active()
+            || self
+                .last_poll
+                .is_some_and(|last_poll| last_poll.elapsed() < POLL_INTERVAL)
+        {
+            return None;
+        }
+        self.last_poll = Some(Instant::now());
+
+        let modified = match std::fs::metadata(&self.path).and_then(|file| file.modified()) {
+            Ok(modified) => modified,
+            Err(err) => {
+                self.report(log, format!("Couldn't read {}: {err}", self.path));
+                return None;
+            }
+        };
+        if self.modified == Some(modified) {
+            return None;
+        }
+        self.modified = Some(modified);
+
+        let values = std::fs::read_to_string(&self.path)
+            .map_err(|err| format!("Couldn't read {}: {err}", self.path))
+            .and_then(|contents| {
+                serde_json::from_str(&contents)
+                    .map_err(|err| format!("Couldn't parse {}: {err}", self.path))
+            });
+        match values {
+            Ok(JsonValue::Object(values)) => {
+                self.error = None;
+                Some(values)
+            }
+            Ok(_) => {
+                let error = format!("{} has to be an object of parameter names", self.path);
+                self.report(log, error);
+                None
+            }
+            Err(error) => {
+                self.report(log, error);
+                None
+            }
+        }
+    }
+
+    fn report(&mut self, log: &mut Log, error: String) {
+        if self.error.as_ref() != Some(&error) {
+            log.push(Severity::Warning, error.clone());
+            self.error = Some(error)
+        }
+    }
+
+    /// Where a value of the file that doesn't fit its parameter is reported
+    pub(super) fn path(&self) -> &str {
+        &self.path
+    }
+
+    /// Returns true if the file changed, the parameters it set are no longer linked then
+    pub(crate) fn show_editor(&mut self, ui: &Ui) -> bool {
+        let changed = ui
+            .input_text("File##parameter_source", &mut self.path)
+            .enter_returns_true(true)
+            .build();
+        if ui.is_item_hovered() {
+            ui.tooltip_text("A JSON object of parameter names and values, numbers or arrays of\nnumbers. The parameters follow it every time it changes.\nPress enter to apply it, leave it empty to stop")
+        }
+        if changed {
+            self.modified = None;
+            self.last_poll = None;
+            self.error = None;
+        }
+        ui.checkbox(
+            "Lock linked parameters##parameter_source",
+            &mut self.lock_edits,
+        );
+        if let Some(error) = &self.error {
+            ui.text_colored([1.0, 1.0, 0.0, 1.0], error);
+        }
+
+        changed
+    }
+
+    pub(crate) fn from_json(json_val: &JsonValue) -> Option<ParameterSource> {
+        let path = json_val.get("path")?.as_str()?.to_string();
+        let lock_edits = json_val.get("lock_edits")?.as_bool()?;
+
+        Some(ParameterSource {
+            path,
+            lock_edits,
+            ..Default::default()
+        })
+    }
+
+    pub(crate) fn to_json(&self) -> JsonValue {
+        let mut json_obj = Map::new();
+        json_obj.insert("path".into(), self.path.clone().into());
+        json_obj.insert("lock_edits".into(), self.lock_edits.into());
+
+        JsonValue::Object(json_obj)
+    }
+}
+
+/// The value in the file cast to the type of the parameter. None if it isn't a number, or an
+/// array with as many numbers as the parameter has components
+pub(super) fn uniform_value(current: &UniformValue, new: &JsonValue) -> Option<UniformValue> {
+    let cast = |current: &ScalarUniformValue, new: &JsonValue| {
+        Some(scalar_value(new)?.cast_to_scalar(current.scalar_type()))
+    };
+
+    match (current, new) {
+        (UniformValue::Scalar(scalar), new) => cast(scalar, new).map(UniformValue::Scalar),
+        (UniformValue::Vector(vector), JsonValue::Array(new)) => {
+            let current = components(vector);
+            if current.len() != new.len() {
+                return None;
+            }
+            let new = current
+                .iter()
+                .zip(new)
+                .map(|(current, new)| cast(current, new))
+                .collect::<Option<Vec<_>>>()?;
+            vector_from_components(vector, &new).map(UniformValue::Vector)
+        }
+        _ => None,
+    }
+}
+
+/// Integers are kept as integers when they fit, so big ones aren't rounded to an f32
+fn scalar_value(value: &JsonValue) -> Option<ScalarUniformValue> {
+    if let Some(v) = value.as_u64().and_then(|v| u32::try_from(v).ok()) {
+        Some(ScalarUniformValue::U32(v))
+    } else if let Some(v) = value.as_i64().and_then(|v| i32::try_from(v).ok()) {
+        Some(ScalarUniformValue::I32(v))
+    } else {
+        value.as_f64().map(|v| ScalarUniformValue::F32(v as f32))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+    use crate::imgui_state::uniform_types::{Vec3UniformValue, VectorUniformValue};
+
+    #[test]
+    fn values_are_cast_to_the_parameter_type() {
+        let float = UniformValue::Scalar(ScalarUniformValue::F32(0.0));
+        let unsigned = UniformValue::Scalar(ScalarUniformValue::U32(0));
+        let vector = UniformValue::Vector(VectorUniformValue::Vec3(Vec3UniformValue::F32(
+            0.0, 0.0, 0.0,
+        )));
+
+        assert_eq!(
+            uniform_value(&float, &json!(3)),
+            Some(UniformValue::Scalar(ScalarUniformValue::F32(3.0)))
+        );
+        assert_eq!(
+            uniform_value(&unsigned, &json!(2.7)),
+            Some(UniformValue::Scalar(ScalarUniformValue::U32(2)))
+        );
+        assert_eq!(
+            uniform_value(&vector, &json!([1, 0.5, -2])),
+            Some(UniformValue::Vector(VectorUniformValue::Vec3(
+                Vec3UniformValue::F32(1.0, 0.5, -2.0)
+            )))
+        );
+    }
+
+    #[test]
+    fn mismatches_are_rejected() {
+        let float = UniformValue::Scalar(ScalarUniformValue::F32(0.0));
+        let vector = UniformValue::Vector(VectorUniformValue::Vec3(Vec3UniformValue::F32(
+            0.0, 0.0, 0.0,
+        )));
+
+        assert_eq!(uniform_value(&float, &json!("fast")), None);
+        assert_eq!(uniform_value(&float, &json!([1.0])), None);
+        assert_eq!(uniform_value(&vector, &json!([1.0, 2.0])), None);
+        assert_eq!(uniform_value(&vector, &json!(1.0)), None);
+    }
+}
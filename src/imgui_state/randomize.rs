@@ -0,0 +1,138 @@
+use super::{
+    script::{components, vector_from_components},
+    uniform_types::{Rng, ScalarUniformValue},
+    UniformValue,
+};
+
+/// Randomize picks every component in this range, bindings don't have a range of their own
+pub(crate) const RANDOM_RANGE: [f32; 2] = [0.0, 1.0];
+/// A nudge moves every component by up to this fraction of its magnitude, or of the random
+/// range if it's closer to 0
+const NUDGE_FRACTION: f32 = 0.05;
+
+/// How a value is changed at random
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Perturbation {
+    /// A new value in `RANDOM_RANGE`
+    Randomize,
+    /// A small change of the current value
+    Nudge,
+}
+
+impl Perturbation {
+    fn component(self, value: f32, rng: &mut Rng) -> f32 {
+        let [min, max] = RANDOM_RANGE;
+        match self {
+            Perturbation::Randomize => min + (max - min) * rng.next_f32(),
+            Perturbation::Nudge => {
+                let amount = value.abs().max(max - min) * NUDGE_FRACTION;
+                value + amount * (2.0 * rng.next_f32() - 1.0)
+            }
+        }
+    }
+}
+
+/// Whether `perturb` can change the value, only f32 scalars and vectors can
+pub(crate) fn can_perturb(value: &UniformValue) -> bool {
+    match value {
+        UniformValue::Scalar(ScalarUniformValue::F32(_)) => true,
+        UniformValue::Vector(v) => components(v)
+            .iter()
+            .all(|c| matches!(c, ScalarUniformValue::F32(_))),
+        _ => false,
+    }
+}
+
+/// The value with every component perturbed, None if it isn't an f32 scalar or vector
+pub(crate) fn perturb(
+    value: &UniformValue,
+    perturbation: Perturbation,
+    rng: &mut Rng,
+) -> Option<UniformValue> {
+    if !can_perturb(value) {
+        return None;
+    }
+
+    match value {
+        UniformValue::Scalar(ScalarUniformValue::F32(v)) => Some(UniformValue::Scalar(
+            ScalarUniformValue::F32(perturbation.component(*v, rng)),
+        )),
+        UniformValue::Vector(v) => {
+            let components: Vec<ScalarUniformValue> = components(v)
+                .into_iter()
+                .map(|c| match c {
+                    ScalarUniformValue::F32(c) => {
+                        ScalarUniformValue::F32(perturbation.component(c, rng))
+                    }
+                    c => c,
+                })
+                .collect();
+            vector_from_components(v, &components).map(UniformValue::Vector)
+        }
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::imgui_state::uniform_types::{Vec3UniformValue, VectorUniformValue};
+
+    #[test]
+    fn randomize_stays_in_range() {
+        let mut rng = Rng::from_seed(7);
+        let vector = UniformValue::Vector(VectorUniformValue::Vec3(Vec3UniformValue::F32(
+            -5.0, 0.0, 5.0,
+        )));
+        for _ in 0..100 {
+            let Some(UniformValue::Vector(v)) = perturb(&vector, Perturbation::Randomize, &mut rng)
+            else {
+                panic!("f32 vectors can be randomized")
+            };
+            for c in components(&v) {
+                let ScalarUniformValue::F32(c) = c else {
+                    panic!("the components stay f32")
+                };
+                assert!((RANDOM_RANGE[0]..RANDOM_RANGE[1]).contains(&c));
+            }
+        }
+    }
+
+    #[test]
+    fn nudges_are_small() {
+        let mut rng = Rng::from_seed(7);
+        for value in [0.0, 0.5, 100.0] {
+            let scalar = UniformValue::Scalar(ScalarUniformValue::F32(value));
+            let Some(UniformValue::Scalar(ScalarUniformValue::F32(nudged))) =
+                perturb(&scalar, Perturbation::Nudge, &mut rng)
+            else {
+                panic!("f32 scalars can be nudged")
+            };
+            let limit = value.abs().max(1.0) * NUDGE_FRACTION;
+            assert!((nudged - value).abs() <= limit);
+        }
+    }
+
+    #[test]
+    fn same_seed_same_values() {
+        let scalar = UniformValue::Scalar(ScalarUniformValue::F32(0.0));
+        let values = |seed| {
+            let mut rng = Rng::from_seed(seed);
+            [0; 4].map(|_| perturb(&scalar, Perturbation::Randomize, &mut rng))
+        };
+        assert_eq!(values(3), values(3));
+        assert_ne!(values(3), values(4));
+    }
+
+    #[test]
+    fn only_f32_values_change() {
+        let mut rng = Rng::from_seed(0);
+        let unsigned = UniformValue::Scalar(ScalarUniformValue::U32(3));
+        assert!(!can_perturb(&unsigned));
+        assert_eq!(perturb(&unsigned, Perturbation::Nudge, &mut rng), None);
+        assert_eq!(
+            perturb(&UniformValue::Raw(16), Perturbation::Randomize, &mut rng),
+            None
+        );
+    }
+}
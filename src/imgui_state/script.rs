@@ -0,0 +1,352 @@
+use std::{
+    cell::Cell,
+    fmt::Display,
+    rc::Rc,
+    time::{Duration, Instant},
+};
+
+use imgui::Ui;
+use rhai::{
+    Array, Dynamic, Engine, EvalAltResult, Map as RhaiMap, Position, Scope, AST, FLOAT, INT,
+};
+use serde_json::{Map, Value as JsonValue};
+
+use super::uniform_types::{
+    ScalarUniformValue, Vec2UniformValue, Vec3UniformValue, Vec4UniformValue, VectorUniformValue,
+};
+use super::UniformValue;
+
+/// A run that takes longer than this is stopped, so a runaway script slows the app down
+/// instead of hanging it
+const TIME_LIMIT: Duration = Duration::from_millis(10);
+
+/// Script run every frame to drive the uniforms. It sees `time` and `dt` in seconds,
+/// and the scalar and vector uniforms by name in the `u` map
+pub(crate) struct Script {
+    source: String,
+    enabled: bool,
+    engine: Engine,
+    /// None if the source doesn't compile
+    ast: Option<AST>,
+    error: Option<String>,
+    /// How long the last run took
+    last_run: Duration,
+    /// When the running script gets stopped
+    deadline: Rc<Cell<Option<Instant>>>,
+}
+
+impl Default for Script {
+    fn default() -> Self {
+        Script::new(String::new(), false)
+    }
+}
+
+impl Script {
+    fn new(source: String, enabled: bool) -> Script {
+        let deadline = Rc::new(Cell::new(None));
+        let mut engine = Engine::new();
+        let engine_deadline = Rc::clone(&deadline);
+        engine.on_progress(move |_| {
+            let expired = engine_deadline
+                .get()
+                .is_some_and(|deadline| Instant::now() > deadline);
+            expired.then_some(Dynamic::UNIT)
+        });
+
+        let mut script = Script {
+            source,
+            enabled,
+            engine,
+            ast: None,
+            error: None,
+            last_run: Duration::ZERO,
+            deadline,
+        };
+        script.compile();
+        script
+    }
+
+    fn compile(&mut self) {
+        self.ast = match self.engine.compile(&self.source) {
+            Ok(ast) => {
+                self.error = None;
+                Some(ast)
+            }
+            Err(error) => {
+                self.error = Some(at_line(error.1, &error.0));
+                None
+            }
+        }
+    }
+
+    /// The script will run this frame
+    pub(super) fn is_runnable(&self) -> bool {
+        self.enabled && self.ast.is_some()
+    }
+
+    /// Runs the script with the uniforms in `u`, returns them as the script left them.
+    /// None if the script failed
+    pub(super) fn run(
+        &mut self,
+        time: Duration,
+        dt: Duration,
+        uniforms: RhaiMap,
+    ) -> Option<RhaiMap> {
+        let ast = self.ast.as_ref().filter(|_| self.enabled)?;
+        let mut scope = Scope::new();
+        scope.push_constant("time", time.as_secs_f64() as FLOAT);
+        scope.push_constant("dt", dt.as_secs_f64() as FLOAT);
+        scope.push("u", uniforms);
+
+        let start = Instant::now();
+        self.deadline.set(Some(start + TIME_LIMIT));
+        let result = self.engine.run_ast_with_scope(&mut scope, ast);
+        self.deadline.set(None);
+        self.last_run = start.elapsed();
+
+        match result {
+            Ok(()) => {
+                self.error = None;
+                scope.get_value("u")
+            }
+            Err(error) => {
+                self.error = Some(describe(*error));
+                None
+            }
+        }
+    }
+
+    pub(super) fn set_error(&mut self, error: String) {
+        self.error = Some(error)
+    }
+
+    pub(crate) fn show_editor(&mut self, ui: &Ui) {
+        ui.checkbox("Enabled", &mut self.enabled);
+        if self.is_runnable() {
+            ui.same_line();
+            ui.text(format!(
+                "Last run: {:.2} ms",
+                self.last_run.as_secs_f64() * 1000.0
+            ));
+        }
+        ui.text_disabled("time and dt are in seconds, uniforms are in u by name:");
+        ui.text_disabled("u.speed = sin(time);");
+        if let Some(error) = &self.error {
+            ui.text_colored([1.0, 0.0, 0.0, 1.0], error);
+        }
+        ui.separator();
+        if ui
+            .input_text_multiline("##script_source", &mut self.source, [-1.0, -1.0])
+            .allow_tab_input(true)
+            .build()
+        {
+            self.compile()
+        }
+    }
+
+    pub(crate) fn from_json(json_val: &JsonValue) -> Option<Script> {
+        let source = json_val.get("source")?.as_str()?.to_string();
+        let enabled = json_val.get("enabled")?.as_bool()?;
+
+        Some(Script::new(source, enabled))
+    }
+
+    pub(crate) fn to_json(&self) -> JsonValue {
+        let mut json_obj = Map::new();
+        json_obj.insert("source".into(), self.source.clone().into());
+        json_obj.insert("enabled".into(), self.enabled.into());
+
+        JsonValue::Object(json_obj)
+    }
+}
+
+fn at_line(position: Position, message: impl Display) -> String {
+    match position.line() {
+        Some(line) => format!("Line {line}: {message}"),
+        None => message.to_string(),
+    }
+}
+
+fn describe(mut error: EvalAltResult) -> String {
+    let position = error.take_position();
+    match error {
+        EvalAltResult::ErrorTerminated(..) => at_line(
+            position,
+            format_args!("Stopped after {} ms", TIME_LIMIT.as_millis()),
+        ),
+        error => at_line(position, error),
+    }
+}
+
+/// How the script sees a uniform. Only scalars and vectors are exposed
+pub(super) fn to_dynamic(value: &UniformValue) -> Option<Dynamic> {
+    match value {
+        UniformValue::Scalar(scalar) => Some(scalar_to_dynamic(*scalar)),
+        UniformValue::Vector(vector) => {
+            let components: Array = components(vector)
+                .into_iter()
+                .map(scalar_to_dynamic)
+                .collect();
+            Some(components.into())
+        }
+        _ => None,
+    }
+}
+
+/// The value the script gave to a uniform, None if it doesn't fit the uniform's type
+pub(super) fn from_dynamic(current: &UniformValue, new: &Dynamic) -> Option<UniformValue> {
+    match current {
+        UniformValue::Scalar(scalar) => scalar_from_dynamic(scalar, new).map(UniformValue::Scalar),
+        UniformValue::Vector(vector) => {
+            let new = new.read_lock::<Array>()?;
+            let current = components(vector);
+            if new.len() != current.len() {
+                return None;
+            }
+            let new = current
+                .iter()
+                .zip(new.iter())
+                .map(|(current, new)| scalar_from_dynamic(current, new))
+                .collect::<Option<Vec<_>>>()?;
+            vector_from_components(vector, &new).map(UniformValue::Vector)
+        }
+        _ => None,
+    }
+}
+
+fn scalar_to_dynamic(value: ScalarUniformValue) -> Dynamic {
+    match value {
+        ScalarUniformValue::U32(v) => Dynamic::from_int(v as INT),
+        ScalarUniformValue::I32(v) => Dynamic::from_int(v as INT),
+        ScalarUniformValue::F32(v) => Dynamic::from_float(v as FLOAT),
+    }
+}
+
+/// Integers are accepted for floats, but not the other way around
+fn scalar_from_dynamic(current: &ScalarUniformValue, new: &Dynamic) -> Option<ScalarUniformValue> {
+    match current {
+        ScalarUniformValue::U32(_) => {
+            Some(ScalarUniformValue::U32(new.as_int().ok()?.try_into().ok()?))
+        }
+        ScalarUniformValue::I32(_) => {
+            Some(ScalarUniformValue::I32(new.as_int().ok()?.try_into().ok()?))
+        }
+        ScalarUniformValue::F32(_) => {
+            let v = match new.as_float() {
+                Ok(v) => v,
+                Err(_) => new.as_int().ok()? as FLOAT,
+            };
+            Some(ScalarUniformValue::F32(v as f32))
+        }
+    }
+}
+
+pub(super) fn components(vector: &VectorUniformValue) -> Vec<ScalarUniformValue> {
+    use ScalarUniformValue::{F32, I32, U32};
+    match *vector {
+        VectorUniformValue::Vec2(Vec2UniformValue::U32(x, y)) => vec![U32(x), U32(y)],
+        VectorUniformValue::Vec2(Vec2UniformValue::I32(x, y)) => vec![I32(x), I32(y)],
+        VectorUniformValue::Vec2(Vec2UniformValue::F32(x, y)) => vec![F32(x), F32(y)],
+        VectorUniformValue::Vec3(Vec3UniformValue::U32(x, y, z)) => vec![U32(x), U32(y), U32(z)],
+        VectorUniformValue::Vec3(Vec3UniformValue::I32(x, y, z)) => vec![I32(x), I32(y), I32(z)],
+        VectorUniformValue::Vec3(Vec3UniformValue::F32(x, y, z)) => vec![F32(x), F32(y), F32(z)],
+        VectorUniformValue::Vec4(Vec4UniformValue::U32(x, y, z, w)) => {
+            vec![U32(x), U32(y), U32(z), U32(w)]
+        }
+        VectorUniformValue::Vec4(Vec4UniformValue::I32(x, y, z, w)) => {
+            vec![I32(x), I32(y), I32(z), I32(w)]
+        }
+        VectorUniformValue::Vec4(Vec4UniformValue::F32(x, y, z, w)) => {
+            vec![F32(x), F32(y), F32(z), F32(w)]
+        }
+    }
+}
+
+/// A vector like `current` made of `components`, None if they don't match
+pub(super) fn vector_from_components(
+    current: &VectorUniformValue,
+    components: &[ScalarUniformValue],
+) -> Option<VectorUniformValue> {
+    use ScalarUniformValue::{F32, I32, U32};
+    let vector = match (current, components) {
+        (VectorUniformValue::Vec2(_), &[U32(x), U32(y)]) => {
+            VectorUniformValue::Vec2(Vec2UniformValue::U32(x, y))
+        }
+        (VectorUniformValue::Vec2(_), &[I32(x), I32(y)]) => {
+            VectorUniformValue::Vec2(Vec2UniformValue::I32(x, y))
+        }
+        (VectorUniformValue::Vec2(_), &[F32(x), F32(y)]) => {
+            VectorUniformValue::Vec2(Vec2UniformValue::F32(x, y))
+        }
+        (VectorUniformValue::Vec3(_), &[U32(x), U32(y), U32(z)]) => {
+            VectorUniformValue::Vec3(Vec3UniformValue::U32(x, y, z))
+        }
+        (VectorUniformValue::Vec3(_), &[I32(x), I32(y), I32(z)]) => {
+            VectorUniformValue::Vec3(Vec3UniformValue::I32(x, y, z))
+        }
+        (VectorUniformValue::Vec3(_), &[F32(x), F32(y), F32(z)]) => {
+            VectorUniformValue::Vec3(Vec3UniformValue::F32(x, y, z))
+        }
+        (VectorUniformValue::Vec4(_), &[U32(x), U32(y), U32(z), U32(w)]) => {
+            VectorUniformValue::Vec4(Vec4UniformValue::U32(x, y, z, w))
+        }
+        (VectorUniformValue::Vec4(_), &[I32(x), I32(y), I32(z), I32(w)]) => {
+            VectorUniformValue::Vec4(Vec4UniformValue::I32(x, y, z, w))
+        }
+        (VectorUniformValue::Vec4(_), &[F32(x), F32(y), F32(z), F32(w)]) => {
+            VectorUniformValue::Vec4(Vec4UniformValue::F32(x, y, z, w))
+        }
+        _ => return None,
+    };
+
+    Some(vector)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn values_round_trip() {
+        let values = [
+            UniformValue::Scalar(ScalarUniformValue::F32(1.5)),
+            UniformValue::Scalar(ScalarUniformValue::U32(7)),
+            UniformValue::Vector(VectorUniformValue::Vec3(Vec3UniformValue::I32(-1, 0, 1))),
+        ];
+        for value in values {
+            let dynamic = to_dynamic(&value).unwrap();
+            assert_eq!(from_dynamic(&value, &dynamic), Some(value));
+        }
+    }
+
+    #[test]
+    fn script_writes_uniforms() {
+        let mut script = Script::new("u.speed = time * 2;".into(), true);
+        let mut uniforms = RhaiMap::new();
+        uniforms.insert("speed".into(), Dynamic::from_float(0.0));
+
+        let uniforms = script
+            .run(Duration::from_secs(3), Duration::ZERO, uniforms)
+            .unwrap();
+        let speed = UniformValue::Scalar(ScalarUniformValue::F32(0.0));
+        assert_eq!(
+            from_dynamic(&speed, &uniforms["speed"]),
+            Some(UniformValue::Scalar(ScalarUniformValue::F32(6.0)))
+        );
+    }
+
+    #[test]
+    fn errors_have_line_numbers() {
+        let script = Script::new("let a = 1;\nlet b = ;".into(), true);
+        assert!(script.error.unwrap().starts_with("Line 2: "));
+    }
+
+    #[test]
+    fn runaway_scripts_are_stopped() {
+        let mut script = Script::new("loop {}".into(), true);
+        assert!(script
+            .run(Duration::ZERO, Duration::ZERO, RhaiMap::new())
+            .is_none());
+        assert!(script.error.unwrap().contains("Stopped"));
+    }
+}
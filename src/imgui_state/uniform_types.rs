@@ -1,42 +1,253 @@
-use std::borrow::Cow;
+use std::{borrow::Cow, mem::discriminant, time::Duration};
 
 use cgmath::{Deg, InnerSpace, Matrix4, Point3, Rad, SquareMatrix, Transform, Vector3};
 use imgui::Ui;
 use serde_json::{Map, Value as JsonValue};
 
-use crate::imgui_state::UniformEditEvent;
+use crate::{gpu_errors, imgui_state::UniformEditEvent};
 
+pub use self::{
+    matrix::{Column2, Column3, Column4, MatrixUniformValue},
+    scalar::ScalarUniformValue,
+    transform::TransformUniformValue,
+    vec::{Vec2UniformValue, Vec3UniformValue, Vec4UniformValue, VectorUniformValue},
+};
 pub(crate) use self::{
-    matrix::MatrixType,
-    scalar::{ScalarType, ScalarUniformValue},
-    vec::VecType,
+    color::ColorOptions,
+    matrix::{MatrixType, MatrixView},
+    scalar::ScalarType,
+    vec::{VecDisplay, VecType},
 };
-use self::{matrix::MatrixUniformValue, transform::TransformUniformValue, vec::VectorUniformValue};
 
 use super::{
-    CameraUniform, ImguiMatrix, ImguiScalar, ImguiUniformSelectable, ImguiVec, DEFAULT_U32_UNIFORM,
+    log::{Log, Severity},
+    wgsl_identifier, CameraUniform, ImguiMatrix, ImguiScalar, ImguiUniformSelectable, ImguiVec,
+    DEFAULT_U32_UNIFORM, MAX_ZOOM, MIN_ZOOM,
 };
 
+mod color;
 mod matrix;
 mod scalar;
 mod transform;
 mod vec;
 
 #[derive(Debug, Clone, Copy, PartialEq)]
-pub(crate) enum BuiltinValue {
-    Time,
+pub enum BuiltinValue {
+    Time(TimeUnit),
+    /// Size of the window's surface in pixels
+    WindowSize,
+    /// Pan and zoom of the Screen2D mesh, identity in 3D meshes
+    PanZoom {
+        offset: [f32; 2],
+        zoom: f32,
+        enabled: bool,
+    },
     Camera {
         position: Point3<f32>,
         yaw: f32,
         pitch: f32,
         enabled: bool,
     },
+    /// A new random value every frame, unless frozen
+    Random {
+        kind: RandomType,
+        frozen: bool,
+        /// Bits of the current value, kept so it can be held while frozen
+        bits: u32,
+    },
+    /// A point light. Unlike the other builtins a group can have any number of them
+    Light {
+        position: [f32; 3],
+        color: [f32; 3],
+        intensity: f32,
+        /// Light that reaches every surface, whatever its direction
+        ambient: f32,
+    },
+}
+
+/// WGSL declaration of the light. Must match the layout of `BuiltinValue::light_values`
+pub(crate) const LIGHT_STRUCT: &str = "struct Light {
+    position: vec3<f32>,
+    intensity: f32,
+    color: vec3<f32>,
+    ambient: f32,
+}
+";
+/// How the time builtin is uploaded, it has to match the type the shader declares
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TimeUnit {
+    /// u32, wraps around after ~49 days
+    Milliseconds,
+    /// f32, like Shadertoy's `iTime`
+    Seconds,
+}
+
+impl TimeUnit {
+    const ALL: [TimeUnit; 2] = [TimeUnit::Milliseconds, TimeUnit::Seconds];
+
+    fn label(self) -> &'static str {
+        match self {
+            TimeUnit::Milliseconds => "u32 milliseconds",
+            TimeUnit::Seconds => "f32 seconds",
+        }
+    }
+
+    pub(crate) fn to_le_bytes(self, elapsed: Duration) -> [u8; 4] {
+        match self {
+            TimeUnit::Milliseconds => (elapsed.as_millis() as u32).to_le_bytes(),
+            TimeUnit::Seconds => elapsed.as_secs_f32().to_le_bytes(),
+        }
+    }
+}
+
+/// How the random builtin is uploaded, it has to match the type the shader declares
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RandomType {
+    /// f32 in [0, 1)
+    F32,
+    /// u32 in its whole range
+    U32,
+}
+
+impl RandomType {
+    const ALL: [RandomType; 2] = [RandomType::F32, RandomType::U32];
+
+    fn label(self) -> &'static str {
+        match self {
+            RandomType::F32 => "f32 in [0, 1)",
+            RandomType::U32 => "u32",
+        }
+    }
+
+    fn to_le_bytes(self, bits: u32) -> [u8; 4] {
+        match self {
+            // The top 24 bits fit exactly in the mantissa
+            RandomType::F32 => ((bits >> 8) as f32 / (1 << 24) as f32).to_le_bytes(),
+            RandomType::U32 => bits.to_le_bytes(),
+        }
+    }
+}
+
+/// SplitMix64, good enough for shader noise and doesn't need a dependency
+pub(crate) struct Rng(u64);
+
+impl Rng {
+    /// Seeded from the current time, so every run gets different values
+    pub(crate) fn from_time() -> Rng {
+        let seed = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map_or(0, |time| time.as_nanos() as u64);
+        Rng(seed)
+    }
+
+    /// The same seed always gives the same values
+    pub(crate) fn from_seed(seed: u64) -> Rng {
+        Rng(seed)
+    }
+
+    pub(crate) fn next_u32(&mut self) -> u32 {
+        self.0 = self.0.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        ((z ^ (z >> 31)) >> 32) as u32
+    }
+
+    /// In [0, 1)
+    pub(crate) fn next_f32(&mut self) -> f32 {
+        (self.next_u32() >> 8) as f32 / (1 << 24) as f32
+    }
 }
+
 impl BuiltinValue {
+    fn label(&self) -> &'static str {
+        match self {
+            BuiltinValue::Time(_) => "Time",
+            BuiltinValue::WindowSize => "Window size (vec2<f32>)",
+            BuiltinValue::PanZoom { .. } => "2D view (vec4<f32>, offset + scale)",
+            BuiltinValue::Camera { .. } => "Camera (struct Camera)",
+            BuiltinValue::Random { .. } => "Random",
+            BuiltinValue::Light { .. } => "Light (struct Light)",
+        }
+    }
+
+    /// The app updates the value by itself, so it's hidden with the builtins. A light is only
+    /// edited by the user
+    pub(crate) fn is_automatic(&self) -> bool {
+        !matches!(self, BuiltinValue::Light { .. })
+    }
+
+    /// Two vec4s, the position with the intensity and the color with the ambient term
+    fn light_values(position: [f32; 3], color: [f32; 3], intensity: f32, ambient: f32) -> [f32; 8] {
+        let [x, y, z] = position;
+        let [r, g, b] = color;
+        [x, y, z, intensity, r, g, b, ambient]
+    }
+
     fn to_le_bytes(self) -> Vec<u8> {
         match self {
-            BuiltinValue::Time => 0u32.to_le_bytes().into(),
+            BuiltinValue::Time(unit) => unit.to_le_bytes(Duration::ZERO).into(),
+            BuiltinValue::WindowSize => [0f32, 0f32].iter().flat_map(|v| v.to_le_bytes()).collect(),
+            BuiltinValue::PanZoom { offset, zoom, enabled } => {
+                // (offset, scale), so shaders can do `p * view.zw + view.xy`
+                let view = if enabled {
+                    [offset[0], offset[1], 1.0 / zoom, 1.0 / zoom]
+                } else {
+                    [0.0, 0.0, 1.0, 1.0]
+                };
+                view.iter().flat_map(|v| v.to_le_bytes()).collect()
+            }
             BuiltinValue::Camera { .. } => self.calc_matrix().to_le_bytes(),
+            BuiltinValue::Random { kind, bits, .. } => kind.to_le_bytes(bits).into(),
+            BuiltinValue::Light {
+                position,
+                color,
+                intensity,
+                ambient,
+            } => BuiltinValue::light_values(position, color, intensity, ambient)
+                .iter()
+                .flat_map(|v| v.to_le_bytes())
+                .collect(),
+        }
+    }
+
+    fn write_le_bytes(self, out: &mut Vec<u8>) {
+        match self {
+            BuiltinValue::Time(unit) => out.extend(unit.to_le_bytes(Duration::ZERO)),
+            BuiltinValue::WindowSize => {
+                out.extend([0f32, 0f32].iter().flat_map(|v| v.to_le_bytes()))
+            }
+            BuiltinValue::PanZoom { offset, zoom, enabled } => {
+                let view = if enabled {
+                    [offset[0], offset[1], 1.0 / zoom, 1.0 / zoom]
+                } else {
+                    [0.0, 0.0, 1.0, 1.0]
+                };
+                out.extend(view.iter().flat_map(|v| v.to_le_bytes()))
+            }
+            BuiltinValue::Camera { .. } => self.calc_matrix().write_le_bytes(out),
+            BuiltinValue::Random { kind, bits, .. } => out.extend(kind.to_le_bytes(bits)),
+            BuiltinValue::Light {
+                position,
+                color,
+                intensity,
+                ambient,
+            } => out.extend(
+                BuiltinValue::light_values(position, color, intensity, ambient)
+                    .iter()
+                    .flat_map(|v| v.to_le_bytes()),
+            ),
+        }
+    }
+
+    /// Projection times view of a camera, what places a point of the world on the screen
+    pub(crate) fn view_projection(&self) -> Option<Matrix4<f32>> {
+        match self {
+            BuiltinValue::Camera { .. } => {
+                let camera = self.calc_matrix();
+                Some(camera.projection_matrix * camera.view_matrix)
+            }
+            _ => None,
         }
     }
 
@@ -48,8 +259,17 @@ impl BuiltinValue {
                 pitch,
                 enabled,
             } => {
+                let finite = position.x.is_finite()
+                    && position.y.is_finite()
+                    && position.z.is_finite()
+                    && yaw.is_finite()
+                    && pitch.is_finite();
+                if *enabled && !finite {
+                    gpu_errors::report("The camera has NaN or infinite parameters, ignoring it")
+                }
+
                 #[rustfmt::skip]
-                let (view_matrix, projection_matrix, inverse_view, inverse_proj) = if *enabled {
+                let (view_matrix, projection_matrix, inverse_view, inverse_proj) = if *enabled && finite {
                     let view = Matrix4::look_to_rh(
                         *position,
                         Vector3::new(
@@ -61,17 +281,17 @@ impl BuiltinValue {
                         Vector3::unit_y(),
                     );
 
-                    let projection = cgmath::perspective(Rad::from(Deg(45.0)), 1.0, 0.1, 100.0);
+                    let projection = camera_projection();
 
                     (
                         view,
                         projection,
                         view.inverse_transform().unwrap_or_else(|| {
-                            println!("Uninversable matrix!");
+                            gpu_errors::report("The camera's view matrix can't be inverted");
                             Matrix4::identity()
                         }),
                         projection.inverse_transform().unwrap_or_else(|| {
-                            println!("Uninversable matrix!");
+                            gpu_errors::report("The camera's projection matrix can't be inverted");
                             Matrix4::identity()
                         }),
                     )
@@ -104,7 +324,7 @@ impl BuiltinValue {
                 };
 
                 CameraUniform {
-                    position: *position,
+                    position: if finite { *position } else { Point3::new(0.0, 0.0, 0.0) },
                     view_matrix,
                     projection_matrix,
                     inverse_view_matrix: inverse_view,
@@ -115,13 +335,40 @@ impl BuiltinValue {
         }
     }
 
-    fn from_json(uniform: &Map<String, JsonValue>) -> Option<BuiltinValue> {
+    fn from_json(uniform: &Map<String, JsonValue>, log: &mut Log) -> Option<BuiltinValue> {
         let inner_type = uniform.get("innertype")?;
-        if let None = inner_type.as_str() {
-            println!("Couldn't load saved data because innertype of builtin isn't a string");
-        }
-        match inner_type.as_str()? {
-            "time" => Some(BuiltinValue::Time),
+        let Some(inner_type) = inner_type.as_str() else {
+            log.push(
+                Severity::Warning,
+                "Couldn't load saved data because innertype of builtin isn't a string".into(),
+            );
+            return None;
+        };
+        match inner_type {
+            "time" => {
+                // Older saves don't have a unit, they always used milliseconds
+                let unit = match uniform.get("unit").and_then(JsonValue::as_str) {
+                    Some("seconds") => TimeUnit::Seconds,
+                    _ => TimeUnit::Milliseconds,
+                };
+                Some(BuiltinValue::Time(unit))
+            },
+            "window_size" => Some(BuiltinValue::WindowSize),
+            "pan_zoom" => {
+                let json_offset = uniform.get("offset")?.as_array()?;
+                if json_offset.len() != 2 {
+                    return None
+                }
+
+                let offset = [
+                    json_offset.get(0)?.as_f64()? as f32,
+                    json_offset.get(1)?.as_f64()? as f32,
+                ];
+                let zoom = uniform.get("zoom")?.as_f64()? as f32;
+                let enabled = uniform.get("enabled")?.as_bool()?;
+
+                Some(BuiltinValue::PanZoom { offset, zoom, enabled })
+            },
             "camera" => {
                 let json_position = uniform.get("position")?.as_array()?;
                 let yaw = uniform.get("yaw")?;
@@ -144,8 +391,46 @@ impl BuiltinValue {
 
                 Some(BuiltinValue::Camera { position, yaw, pitch, enabled })
             },
+            "random" => {
+                let kind = match uniform.get("type")?.as_str()? {
+                    "f32" => RandomType::F32,
+                    "u32" => RandomType::U32,
+                    _ => return None,
+                };
+                let frozen = uniform.get("frozen")?.as_bool()?;
+                // Only frozen values are saved, the rest start from a new value anyway
+                let bits = uniform
+                    .get("value")
+                    .and_then(JsonValue::as_u64)
+                    .and_then(|bits| u32::try_from(bits).ok())
+                    .unwrap_or(0);
+
+                Some(BuiltinValue::Random { kind, frozen, bits })
+            },
+            "light" => {
+                let vec3 = |key: &str| -> Option<[f32; 3]> {
+                    let json_vec = uniform.get(key)?.as_array()?;
+                    if json_vec.len() != 3 {
+                        return None
+                    }
+                    Some([
+                        json_vec.get(0)?.as_f64()? as f32,
+                        json_vec.get(1)?.as_f64()? as f32,
+                        json_vec.get(2)?.as_f64()? as f32,
+                    ])
+                };
+                let position = vec3("position")?;
+                let color = vec3("color")?;
+                let intensity = uniform.get("intensity")?.as_f64()? as f32;
+                let ambient = uniform.get("ambient")?.as_f64()? as f32;
+
+                Some(BuiltinValue::Light { position, color, intensity, ambient })
+            },
             _ => {
-                println!("Couldn't load saved data because of invalid innertype of builtin");
+                log.push(
+                    Severity::Warning,
+                    format!("Couldn't load saved data because of invalid innertype of builtin {inner_type}"),
+                );
                 None
             }
         }
@@ -153,12 +438,29 @@ impl BuiltinValue {
 
     fn to_json(&self, json_obj: &mut serde_json::Map<String, serde_json::Value>) {
         match self {
-            BuiltinValue::Time => json_obj.insert("innertype".into(), "time".into()),
+            BuiltinValue::Time(_) => json_obj.insert("innertype".into(), "time".into()),
+            BuiltinValue::WindowSize => json_obj.insert("innertype".into(), "window_size".into()),
+            BuiltinValue::PanZoom { .. } => json_obj.insert("innertype".into(), "pan_zoom".into()),
             BuiltinValue::Camera { .. } => json_obj.insert("innertype".into(), "camera".into()),
+            BuiltinValue::Random { .. } => json_obj.insert("innertype".into(), "random".into()),
+            BuiltinValue::Light { .. } => json_obj.insert("innertype".into(), "light".into()),
         };
 
         match self {
-            BuiltinValue::Time => (),
+            BuiltinValue::Time(unit) => {
+                let unit = match unit {
+                    TimeUnit::Milliseconds => "milliseconds",
+                    TimeUnit::Seconds => "seconds",
+                };
+                json_obj.insert("unit".into(), unit.into());
+            },
+            BuiltinValue::WindowSize => (),
+            BuiltinValue::PanZoom { offset, zoom, enabled } => {
+                let offset: Vec<serde_json::Value> = vec![offset[0].into(), offset[1].into()];
+                json_obj.insert("offset".into(), offset.into());
+                json_obj.insert("zoom".into(), serde_json::Value::from(*zoom));
+                json_obj.insert("enabled".into(), serde_json::Value::from(*enabled));
+            },
             BuiltinValue::Camera { position, yaw, pitch, enabled } => {
                 let position: Vec<serde_json::Value> = vec![position.x.into(), position.y.into(), position.z.into()];
                 json_obj.insert("position".into(), position.into());
@@ -166,10 +468,46 @@ impl BuiltinValue {
                 json_obj.insert("pitch".into(), serde_json::Value::from(*pitch));
                 json_obj.insert("enabled".into(), serde_json::Value::from(*enabled));
             },
+            BuiltinValue::Random { kind, frozen, bits } => {
+                let kind = match kind {
+                    RandomType::F32 => "f32",
+                    RandomType::U32 => "u32",
+                };
+                json_obj.insert("type".into(), kind.into());
+                json_obj.insert("frozen".into(), serde_json::Value::from(*frozen));
+                if *frozen {
+                    json_obj.insert("value".into(), serde_json::Value::from(*bits));
+                }
+            },
+            BuiltinValue::Light { position, color, intensity, ambient } => {
+                json_obj.insert("position".into(), position.to_vec().into());
+                json_obj.insert("color".into(), color.to_vec().into());
+                json_obj.insert("intensity".into(), serde_json::Value::from(*intensity));
+                json_obj.insert("ambient".into(), serde_json::Value::from(*ambient));
+            },
         }
     }
 }
 
+/// Replaces NaN with 0 and infinity with the largest finite value, so they never reach a buffer.
+/// Returns true if the value was replaced
+fn sanitize_f32(v: &mut f32) -> bool {
+    if v.is_finite() {
+        return false;
+    }
+
+    *v = if v.is_nan() { 0.0 } else { v.clamp(f32::MIN, f32::MAX) };
+    true
+}
+
+/// Distances of the camera's clip planes
+const CAMERA_NEAR: f32 = 0.1;
+const CAMERA_FAR: f32 = 100.0;
+
+pub(crate) fn camera_projection() -> Matrix4<f32> {
+    cgmath::perspective(Rad::from(Deg(45.0)), 1.0, CAMERA_NEAR, CAMERA_FAR)
+}
+
 fn cast_f32_u32(v: f32) -> u32 {
     let v = (v as i32).try_into();
     v.unwrap_or(DEFAULT_U32_UNIFORM)
@@ -181,12 +519,14 @@ fn cast_i32_u32(v: i32) -> u32 {
 }
 
 #[derive(Debug, Clone, Copy, PartialEq)]
-pub(crate) enum UniformValue {
+pub enum UniformValue {
     BuiltIn(BuiltinValue),
     Scalar(ScalarUniformValue),
     Vector(VectorUniformValue),
     Matrix(MatrixUniformValue),
     Transform(TransformUniformValue),
+    /// Zero-filled placeholder of the given size in bytes, for sizes no other type matches
+    Raw(u64),
 }
 
 trait ExtendedUi {
@@ -196,10 +536,26 @@ trait ExtendedUi {
         current_item: &mut usize,
         items: &[V],
         label_fn: L,
+        tooltip_fn: impl Fn(&V) -> Tooltip,
         column_amount: i32,
     ) -> bool
     where
         for<'b> L: Fn(&'b V) -> Cow<'b, str>;
+
+    /// Like `combo`, every entry shows a tooltip when hovered
+    fn tooltip_combo<V, L>(
+        &self,
+        label: impl AsRef<str>,
+        current_item: &mut usize,
+        items: &[V],
+        label_fn: L,
+        tooltip_fn: impl Fn(&V) -> Tooltip,
+    ) -> bool
+    where
+        for<'b> L: Fn(&'b V) -> Cow<'b, str>,
+    {
+        self.matrix_combo(label, current_item, items, label_fn, tooltip_fn, 1)
+    }
 }
 
 impl ExtendedUi for Ui {
@@ -209,6 +565,7 @@ impl ExtendedUi for Ui {
         current_item: &mut usize,
         items: &[V],
         label_fn: L,
+        tooltip_fn: impl Fn(&V) -> Tooltip,
         column_amount: i32,
     ) -> bool
     where
@@ -217,8 +574,8 @@ impl ExtendedUi for Ui {
         let mut ret = false;
         let mut selected = label_fn(&items[*current_item]);
         if let Some(_cb) = self.begin_combo(label, selected.clone()) {
-            for (i, cur) in items.iter().enumerate() {
-                let cur = label_fn(cur);
+            for (i, item) in items.iter().enumerate() {
+                let cur = label_fn(item);
                 if selected == cur {
                     // Auto-scroll to selected item
                     self.set_item_default_focus();
@@ -229,6 +586,7 @@ impl ExtendedUi for Ui {
                     .selectable_config(cur.clone())
                     .selected(selected == cur)
                     .build();
+                tooltip_fn(item).show_on_hover(self);
                 // When item is clicked, store it
                 if clicked {
                     ret = true;
@@ -251,6 +609,267 @@ pub(crate) enum UniformType {
     Transform,
 }
 
+impl UniformType {
+    /// Default type of a binding the shader declares with `size` bytes, if any type has that size
+    pub(crate) fn from_size(size: u64) -> Option<UniformType> {
+        /*
+        Matrix sizes:
+        2x2: 4*f32 = 4*4 = 16
+        2x3: 6*f32 = 6*4 = 24
+        3x2: 6*f32 = 6*4 = 24
+        2x4: 8*f32 = 8*4 = 32
+        4x2: 8*f32 = 8*4 = 32
+        3x3: 9*f32 = 9*4 = 36
+        3x4: 12*f32 = 12*4 = 48
+        4x3: 12*f32 = 12*4 = 48
+        4x4: 16*f32 = 16*4 = 64
+        */
+        match size {
+            4 => Some(UniformType::Scalar(ScalarType::F32)),
+            8 => Some(UniformType::Vec(VecType::Vec2(ScalarType::F32))),
+            12 => Some(UniformType::Vec(VecType::Vec3(ScalarType::F32))),
+            16 => Some(UniformType::Vec(VecType::Vec4(ScalarType::F32))),
+            24 => Some(UniformType::Matrix(MatrixType::M2x3)),
+            32 => Some(UniformType::Matrix(MatrixType::M2x4)),
+            36 => Some(UniformType::Matrix(MatrixType::M3x3)),
+            48 => Some(UniformType::Matrix(MatrixType::M3x4)),
+            64 => Some(UniformType::Matrix(MatrixType::M4x4)),
+            _ => None,
+        }
+    }
+
+    /// WGSL type, size and alignment in bytes, following the WGSL memory layout rules
+    pub(crate) fn layout(&self) -> (String, u64, u64) {
+        // A vec3 is aligned like a vec4
+        let vec_layout = |components: u64| match components {
+            2 => (8, 8),
+            3 => (12, 16),
+            _ => (16, 16),
+        };
+        match self {
+            UniformType::Scalar(s) => (Cow::from(s).into_owned(), 4, 4),
+            UniformType::Vec(v) => {
+                let (components, scalar) = match v {
+                    VecType::Vec2(s) => (2, s),
+                    VecType::Vec3(s) => (3, s),
+                    VecType::Vec4(s) => (4, s),
+                };
+                let (size, align) = vec_layout(components);
+                (
+                    format!("vec{components}<{}>", Cow::from(scalar)),
+                    size,
+                    align,
+                )
+            }
+            UniformType::Matrix(m) => {
+                let (columns, rows) = m.columns_rows();
+                // Every column is padded to the alignment of its vector
+                let (_, align) = vec_layout(rows);
+                (format!("mat{columns}x{rows}<f32>"), columns * align, align)
+            }
+            UniformType::Transform => ("mat4x4<f32>".to_string(), 64, 16),
+        }
+    }
+}
+
+/// What the hover text of a uniform editor explains
+#[derive(Debug, Clone, Copy)]
+pub(crate) enum Tooltip {
+    /// The `(n)` in front of every binding
+    BindingIndex {
+        group_index: usize,
+        binding_index: usize,
+    },
+    /// An entry of a type combo
+    Type(UniformType),
+    /// The matrix size combo
+    MatrixSize,
+    Builtin(BuiltinValue),
+}
+
+impl Tooltip {
+    /// Every hover text of the editors is written here
+    fn text(self) -> String {
+        match self {
+            Tooltip::BindingIndex {
+                group_index,
+                binding_index,
+            } => format!(
+                "@binding index of this uniform in @group({group_index}).\nThe shader declares it as\n@group({group_index}) @binding({binding_index}) var<uniform> name: type;"
+            ),
+            Tooltip::Type(UniformType::Transform) => format!(
+                "{}\nThe model matrix of the position, rotation and scale",
+                Tooltip::Type(UniformType::Matrix(MatrixType::M4x4)).text()
+            ),
+            Tooltip::Type(unitype) => {
+                let (wgsl_type, size, align) = unitype.layout();
+                format!("{wgsl_type} in WGSL\n{size} bytes, aligned to {align} bytes")
+            }
+            Tooltip::MatrixSize => "Columns x rows, like WGSL's matCxR.\nmat2x3<f32> has 2 columns of vec3<f32>".to_string(),
+            Tooltip::Builtin(builtin) => match builtin {
+                BuiltinValue::Time(TimeUnit::Milliseconds) => "u32 in WGSL, milliseconds since the start\n4 bytes, aligned to 4 bytes".to_string(),
+                BuiltinValue::Time(TimeUnit::Seconds) => "f32 in WGSL, seconds since the start\n4 bytes, aligned to 4 bytes".to_string(),
+                BuiltinValue::WindowSize => "vec2<f32> in WGSL, width and height of the render in pixels\n8 bytes, aligned to 8 bytes".to_string(),
+                BuiltinValue::PanZoom { .. } => "vec4<f32> in WGSL, xy is the offset and zw the scale:\np * view.zw + view.xy\n16 bytes, aligned to 16 bytes".to_string(),
+                BuiltinValue::Camera { .. } => "struct Camera, 272 bytes, aligned to 16 bytes\n  offset   0: pos: vec3<f32>, then 4 bytes of padding\n  offset  16: projection: mat4x4<f32>\n  offset  80: view: mat4x4<f32>\n  offset 144: inverse_view: mat4x4<f32>\n  offset 208: inverse_proj: mat4x4<f32>".to_string(),
+                BuiltinValue::Random { kind, .. } => format!(
+                    "{} in WGSL, a new value every frame unless frozen\n4 bytes, aligned to 4 bytes",
+                    kind.label()
+                ),
+                BuiltinValue::Light { .. } => "struct Light, 32 bytes, aligned to 16 bytes\n  offset  0: position: vec3<f32>\n  offset 12: intensity: f32\n  offset 16: color: vec3<f32>\n  offset 28: ambient: f32".to_string(),
+            },
+        }
+    }
+
+    /// Shows the text while the last item is hovered
+    pub(crate) fn show_on_hover(self, ui: &Ui) {
+        if ui.is_item_hovered() {
+            ui.tooltip_text(self.text())
+        }
+    }
+}
+
+impl UniformValue {
+    /// Replaces NaN and infinity, returns true if there were any
+    pub(crate) fn sanitize(&mut self) -> bool {
+        match self {
+            UniformValue::BuiltIn(BuiltinValue::Camera {
+                position,
+                yaw,
+                pitch,
+                ..
+            }) => {
+                sanitize_f32(&mut position.x)
+                    | sanitize_f32(&mut position.y)
+                    | sanitize_f32(&mut position.z)
+                    | sanitize_f32(yaw)
+                    | sanitize_f32(pitch)
+            }
+            UniformValue::BuiltIn(BuiltinValue::PanZoom { offset, zoom, .. }) => {
+                let replaced = sanitize_f32(&mut offset[0]) | sanitize_f32(&mut offset[1]);
+                if zoom.is_nan() {
+                    *zoom = 1.0;
+                    return true;
+                }
+                if !(MIN_ZOOM..=MAX_ZOOM).contains(zoom) {
+                    *zoom = zoom.clamp(MIN_ZOOM, MAX_ZOOM);
+                    return true;
+                }
+                replaced
+            }
+            UniformValue::BuiltIn(BuiltinValue::Light {
+                position,
+                color,
+                intensity,
+                ambient,
+            }) => position
+                .iter_mut()
+                .chain(color.iter_mut())
+                .chain([intensity, ambient])
+                .fold(false, |replaced, v| sanitize_f32(v) | replaced),
+            UniformValue::BuiltIn(_) | UniformValue::Raw(_) => false,
+            UniformValue::Scalar(s) => s.sanitize(),
+            UniformValue::Vector(v) => v.sanitize(),
+            UniformValue::Matrix(m) => m.sanitize(),
+            UniformValue::Transform(t) => t.sanitize(),
+        }
+    }
+
+    /// Whether `other` has the same WGSL type, so it can replace this value in its buffer.
+    /// Builtins are never replaced
+    pub(crate) fn same_type(&self, other: &UniformValue) -> bool {
+        match (self, other) {
+            (UniformValue::Scalar(a), UniformValue::Scalar(b)) => {
+                discriminant(a) == discriminant(b)
+            }
+            (UniformValue::Vector(a), UniformValue::Vector(b)) => match (a, b) {
+                (VectorUniformValue::Vec2(a), VectorUniformValue::Vec2(b)) => {
+                    discriminant(a) == discriminant(b)
+                }
+                (VectorUniformValue::Vec3(a), VectorUniformValue::Vec3(b)) => {
+                    discriminant(a) == discriminant(b)
+                }
+                (VectorUniformValue::Vec4(a), VectorUniformValue::Vec4(b)) => {
+                    discriminant(a) == discriminant(b)
+                }
+                _ => false,
+            },
+            (UniformValue::Matrix(a), UniformValue::Matrix(b)) => {
+                discriminant(a) == discriminant(b)
+            }
+            (UniformValue::Transform(_), UniformValue::Transform(_)) => true,
+            (UniformValue::Raw(a), UniformValue::Raw(b)) => a == b,
+            _ => false,
+        }
+    }
+
+    /// Type of the `var<uniform>` declaration, None for the camera and light structs and raw bytes
+    pub(crate) fn wgsl_type(&self) -> Option<String> {
+        let wgsl_type = match self {
+            UniformValue::BuiltIn(BuiltinValue::Time(TimeUnit::Milliseconds))
+            | UniformValue::BuiltIn(BuiltinValue::Random {
+                kind: RandomType::U32,
+                ..
+            }) => "u32".to_string(),
+            UniformValue::BuiltIn(BuiltinValue::Time(TimeUnit::Seconds))
+            | UniformValue::BuiltIn(BuiltinValue::Random {
+                kind: RandomType::F32,
+                ..
+            }) => "f32".to_string(),
+            UniformValue::BuiltIn(BuiltinValue::WindowSize) => "vec2<f32>".to_string(),
+            UniformValue::BuiltIn(BuiltinValue::PanZoom { .. }) => "vec4<f32>".to_string(),
+            UniformValue::BuiltIn(BuiltinValue::Camera { .. } | BuiltinValue::Light { .. })
+            | UniformValue::Raw(_) => return None,
+            UniformValue::Scalar(s) => Cow::from(&s.scalar_type()).into_owned(),
+            UniformValue::Vector(v) => {
+                let components = super::script::components(v);
+                let scalar = Cow::from(&components[0].scalar_type());
+                format!("vec{}<{scalar}>", components.len())
+            }
+            UniformValue::Matrix(m) => {
+                let size = match m {
+                    MatrixUniformValue::M2x2(..) => "2x2",
+                    MatrixUniformValue::M2x3(..) => "2x3",
+                    MatrixUniformValue::M2x4(..) => "2x4",
+                    MatrixUniformValue::M3x2(..) => "3x2",
+                    MatrixUniformValue::M3x3(..) => "3x3",
+                    MatrixUniformValue::M3x4(..) => "3x4",
+                    MatrixUniformValue::M4x2(..) => "4x2",
+                    MatrixUniformValue::M4x3(..) => "4x3",
+                    MatrixUniformValue::M4x4(..) => "4x4",
+                };
+                format!("mat{size}<f32>")
+            }
+            UniformValue::Transform(_) => "mat4x4<f32>".to_string(),
+        };
+
+        Some(wgsl_type)
+    }
+
+    /// Like `show_editor`, `display` and `color` pick the editors of f32 vec3 and vec4,
+    /// `matrix_view` the layout of the matrix editor
+    pub(crate) fn show_editor_with_display(
+        &mut self,
+        ui: &Ui,
+        group_index: usize,
+        binding_index: usize,
+        val_name: &mut String,
+        display: &mut VecDisplay,
+        color: &mut ColorOptions,
+        matrix_view: &mut MatrixView,
+    ) -> Option<UniformEditEvent> {
+        match self {
+            UniformValue::Vector(v) => {
+                v.show_editor_with_display(ui, group_index, binding_index, val_name, display, color)
+            }
+            UniformValue::Matrix(m) => {
+                m.show_editor_with_view(ui, group_index, binding_index, val_name, matrix_view)
+            }
+            value => value.show_editor(ui, group_index, binding_index, val_name),
+        }
+    }
+}
+
 impl ImguiUniformSelectable for UniformValue {
     fn cast_to(&self, casted_type: UniformType) -> UniformValue {
         match self {
@@ -259,6 +878,7 @@ impl ImguiUniformSelectable for UniformValue {
             UniformValue::Matrix(m) => m.cast_to(casted_type),
             UniformValue::BuiltIn(_) => unreachable!(),
             UniformValue::Transform(t) => t.cast_to(casted_type),
+            UniformValue::Raw(_) => ScalarUniformValue::U32(0).cast_to(casted_type),
         }
     }
 
@@ -269,11 +889,45 @@ impl ImguiUniformSelectable for UniformValue {
         binding_index: usize,
         val_name: &mut String,
     ) -> Option<UniformEditEvent> {
+        if let UniformValue::BuiltIn(builtin) = self {
+            UniformValue::show_builtin_label(ui, group_index, binding_index, *builtin);
+        }
         match self {
             UniformValue::BuiltIn(builtin) => match builtin {
-                BuiltinValue::Time => {
-                    ui.text(format!("({binding_index}) Time (u32)"));
-                    None
+                BuiltinValue::Time(unit) => {
+                    let mut message = None;
+                    ui.same_line();
+                    let mut unit_index =
+                        TimeUnit::ALL.iter().position(|u| *u == *unit).unwrap_or(0);
+                    if ui.combo(
+                        format!("##time_unit_{group_index}_{binding_index}"),
+                        &mut unit_index,
+                        &TimeUnit::ALL,
+                        |unit| Cow::Borrowed(unit.label()),
+                    ) {
+                        *unit = TimeUnit::ALL[unit_index];
+                        message = Some(UniformEditEvent::UpdateBuffer(group_index, binding_index))
+                    }
+                    message
+                }
+                BuiltinValue::WindowSize => None,
+                BuiltinValue::PanZoom { offset, zoom, enabled } => {
+                    let mut message = None;
+                    if *enabled {
+                        ui.text(format!(
+                            "Offset: ({:.3}, {:.3}), zoom: {:.2}x",
+                            offset[0], offset[1], zoom
+                        ));
+                        ui.text("Drag the render to pan, scroll to zoom");
+                        if ui.button(format!("Reset view##pan_zoom_{group_index}_{binding_index}")) {
+                            *offset = [0.0, 0.0];
+                            *zoom = 1.0;
+                            message = Some(UniformEditEvent::UpdateBuffer(group_index, binding_index))
+                        }
+                    } else {
+                        ui.text("Only active with the Screen2D mesh");
+                    }
+                    message
                 }
                 BuiltinValue::Camera {
                     position,
@@ -282,7 +936,9 @@ impl ImguiUniformSelectable for UniformValue {
                     enabled,
                 } => {
                     let mut message = None;
-                    ui.text(format!("({binding_index}) Camera (struct {{\n    vec4<f32>,\n    mat4x4<f32>,\n    mat4x4<f32>,\n    mat4x4<f32>,\n    mat4x4<f32>\n}})"));
+                    if ui.button(format!("Copy camera struct##copy_camera_{group_index}_{binding_index}")) {
+                        ui.set_clipboard_text(CameraUniform::wgsl_declaration(group_index, binding_index))
+                    }
                     if ui.checkbox("Enabled", enabled) {
                         message = Some(UniformEditEvent::UpdateBuffer(group_index, binding_index))
                     }
@@ -337,11 +993,98 @@ impl ImguiUniformSelectable for UniformValue {
                     }
                     message
                 }
+                BuiltinValue::Random { kind, frozen, bits } => {
+                    let mut message = None;
+                    ui.same_line();
+                    let mut kind_index =
+                        RandomType::ALL.iter().position(|k| *k == *kind).unwrap_or(0);
+                    if ui.combo(
+                        format!("##random_type_{group_index}_{binding_index}"),
+                        &mut kind_index,
+                        &RandomType::ALL,
+                        |kind| Cow::Borrowed(kind.label()),
+                    ) {
+                        *kind = RandomType::ALL[kind_index];
+                        message = Some(UniformEditEvent::UpdateBuffer(group_index, binding_index))
+                    }
+                    if ui.checkbox(format!("Freeze##random_{group_index}_{binding_index}"), frozen) {
+                        message = Some(UniformEditEvent::UpdateBuffer(group_index, binding_index))
+                    }
+                    if *frozen {
+                        ui.same_line();
+                        match kind {
+                            RandomType::F32 => ui.text(format!(
+                                "{}",
+                                f32::from_le_bytes(kind.to_le_bytes(*bits))
+                            )),
+                            RandomType::U32 => ui.text(format!("{bits}")),
+                        }
+                    }
+                    message
+                }
+                BuiltinValue::Light {
+                    position,
+                    color,
+                    intensity,
+                    ambient,
+                } => {
+                    const VAR_NAME_WIDTH: f32 = 150.0;
+                    let mut message = None;
+                    ui.same_line();
+                    ui.set_next_item_width(VAR_NAME_WIDTH);
+                    ui.input_text(
+                        format!("##name_edit{group_index}_{binding_index}"),
+                        val_name,
+                    )
+                    .build();
+                    ui.same_line();
+                    if ui.button(format!(
+                        "Copy light struct##copy_light_{group_index}_{binding_index}"
+                    )) {
+                        let name = wgsl_identifier(val_name, group_index, binding_index);
+                        ui.set_clipboard_text(format!(
+                            "{LIGHT_STRUCT}\n@group({group_index}) @binding({binding_index})\nvar<uniform> {name}: Light;\n"
+                        ))
+                    }
+                    let changed = ui
+                        .input_float3(
+                            format!("Position##light_position_{group_index}_{binding_index}"),
+                            position,
+                        )
+                        .build()
+                        | ui.color_edit3(
+                            format!("Color##light_color_{group_index}_{binding_index}"),
+                            color,
+                        )
+                        | ui.slider(
+                            format!("Intensity##light_intensity_{group_index}_{binding_index}"),
+                            0.0,
+                            10.0,
+                            intensity,
+                        )
+                        | ui.slider(
+                            format!("Ambient##light_ambient_{group_index}_{binding_index}"),
+                            0.0,
+                            1.0,
+                            ambient,
+                        );
+                    if changed {
+                        message = Some(UniformEditEvent::UpdateBuffer(group_index, binding_index))
+                    }
+                    message
+                }
             },
             UniformValue::Scalar(s) => s.show_editor(ui, group_index, binding_index, val_name),
             UniformValue::Vector(v) => v.show_editor(ui, group_index, binding_index, val_name),
             UniformValue::Matrix(m) => m.show_editor(ui, group_index, binding_index, val_name),
             UniformValue::Transform(t) => t.show_editor(ui, group_index, binding_index, val_name),
+            UniformValue::Raw(size) => {
+                UniformValue::show_binding_index(ui, group_index, binding_index);
+                ui.same_line();
+                ui.text(format!("{val_name} ({size} bytes)"));
+                ui.text_disabled("No editable type has this size, the buffer is filled with zeros");
+                None
+            }
         }
     }
 
@@ -352,6 +1095,18 @@ impl ImguiUniformSelectable for UniformValue {
             UniformValue::Vector(v) => v.to_le_bytes(),
             UniformValue::Matrix(m) => m.to_le_bytes(),
             UniformValue::Transform(t) => t.to_le_bytes(),
+            UniformValue::Raw(size) => vec![0; *size as usize],
+        }
+    }
+
+    fn write_le_bytes(&self, out: &mut Vec<u8>) {
+        match self {
+            UniformValue::BuiltIn(b) => b.write_le_bytes(out),
+            UniformValue::Scalar(s) => s.write_le_bytes(out),
+            UniformValue::Vector(v) => v.write_le_bytes(out),
+            UniformValue::Matrix(m) => m.write_le_bytes(out),
+            UniformValue::Transform(t) => t.write_le_bytes(out),
+            UniformValue::Raw(size) => out.resize(out.len() + *size as usize, 0),
         }
     }
 }
@@ -364,6 +1119,7 @@ impl ImguiScalar for UniformValue {
             UniformValue::BuiltIn(_) => unreachable!(),
             UniformValue::Vector(_) => unreachable!(),
             UniformValue::Transform(_) => unreachable!(),
+            UniformValue::Raw(_) => unreachable!(),
         }
     }
 
@@ -374,6 +1130,7 @@ impl ImguiScalar for UniformValue {
             UniformValue::BuiltIn(_) => unreachable!(),
             UniformValue::Vector(_) => unreachable!(),
             UniformValue::Transform(_) => unreachable!(),
+            UniformValue::Raw(_) => unreachable!(),
         }
     }
 }
@@ -386,6 +1143,7 @@ impl ImguiVec for UniformValue {
             UniformValue::BuiltIn(_) => unreachable!(),
             UniformValue::Scalar(_) => unreachable!(),
             UniformValue::Transform(_) => unreachable!(),
+            UniformValue::Raw(_) => unreachable!(),
         }
     }
 }
@@ -398,11 +1156,35 @@ impl ImguiMatrix for UniformValue {
             UniformValue::Vector(_) => unreachable!(),
             UniformValue::Matrix(m) => m.change_matrix_size(matrix_size),
             UniformValue::Transform(_) => unreachable!(),
+            UniformValue::Raw(_) => unreachable!(),
         }
     }
 }
 
 impl UniformValue {
+    /// The `(n)` in front of a binding, with a tooltip explaining it
+    fn show_binding_index(ui: &Ui, group_index: usize, binding_index: usize) {
+        ui.text(format!("({binding_index})"));
+        Tooltip::BindingIndex {
+            group_index,
+            binding_index,
+        }
+        .show_on_hover(ui)
+    }
+
+    /// Name of a builtin after its binding index, with its layout as tooltip
+    fn show_builtin_label(
+        ui: &Ui,
+        group_index: usize,
+        binding_index: usize,
+        builtin: BuiltinValue,
+    ) {
+        UniformValue::show_binding_index(ui, group_index, binding_index);
+        ui.same_line();
+        ui.text(builtin.label());
+        Tooltip::Builtin(builtin).show_on_hover(ui)
+    }
+
     fn show_primitive_selector(
         ui: &Ui,
         group_index: usize,
@@ -424,7 +1206,7 @@ impl UniformValue {
         const COMBO_WIDTH: f32 = 95.0;
         const VAR_NAME_WIDTH: f32 = 150.0;
 
-        ui.text(format!("({binding_index})"));
+        UniformValue::show_binding_index(ui, group_index, binding_index);
         ui.same_line();
         ui.set_next_item_width(VAR_NAME_WIDTH);
         ui.input_text(
@@ -434,11 +1216,12 @@ impl UniformValue {
         .build();
         ui.set_next_item_width(COMBO_WIDTH);
         let mut selection = type_index;
-        if ui.combo(
+        if ui.tooltip_combo(
             format!("##combo_g{group_index}_b{binding_index}"),
             &mut selection,
             TYPES,
             |unitype| unitype.into(),
+            |unitype| Tooltip::Type(*unitype),
         ) {
             let selected_type = TYPES[selection];
             if selected_type != TYPES[type_index] {
@@ -451,16 +1234,25 @@ impl UniformValue {
         };
     }
 
-    pub(crate) fn from_json(uniform: &Map<String, JsonValue>) -> Option<UniformValue> {
+    pub(crate) fn from_json(
+        uniform: &Map<String, JsonValue>,
+        log: &mut Log,
+    ) -> Option<UniformValue> {
         let outer_type = uniform.get("outer_type")?;
         match outer_type.as_str()? {
-            "builtin" => Some(UniformValue::BuiltIn(BuiltinValue::from_json(uniform)?)),
+            "builtin" => Some(UniformValue::BuiltIn(BuiltinValue::from_json(uniform, log)?)),
             "matrix" => Some(UniformValue::Matrix(MatrixUniformValue::from_json(uniform)?)),
             "scalar" => Some(UniformValue::Scalar(ScalarUniformValue::from_json(uniform)?)),
             "transform" => Some(UniformValue::Transform(TransformUniformValue::from_json(uniform)?)),
             "vector" => Some(UniformValue::Vector(VectorUniformValue::from_json(uniform)?)),
-            _ => {
-                println!("Couldn't load saved data because outer_type is not valid");
+            "raw" => Some(UniformValue::Raw(uniform.get("size")?.as_u64()?)),
+            outer_type => {
+                log.push(
+                    Severity::Warning,
+                    format!(
+                        "Couldn't load saved data because outer_type {outer_type} is not valid"
+                    ),
+                );
                 None
             }
         }
@@ -475,6 +1267,7 @@ impl UniformValue {
             UniformValue::Vector(_) => json_obj.insert("outer_type".into(), "vector".into()),
             UniformValue::Matrix(_) => json_obj.insert("outer_type".into(), "matrix".into()),
             UniformValue::Transform(_) => json_obj.insert("outer_type".into(), "transform".into()),
+            UniformValue::Raw(_) => json_obj.insert("outer_type".into(), "raw".into()),
         };
 
         match self {
@@ -483,6 +1276,9 @@ impl UniformValue {
             UniformValue::Vector(v) => v.to_json(json_obj),
             UniformValue::Matrix(m) => m.to_json(json_obj),
             UniformValue::Transform(t) => t.to_json(json_obj),
+            UniformValue::Raw(size) => {
+                json_obj.insert("size".into(), (*size).into());
+            }
         };
 
         JsonValue::Object(json_o)
@@ -499,3 +1295,302 @@ impl<'a> From<&'a UniformType> for Cow<'static, str> {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn primitive_sizes() {
+        assert_eq!(
+            UniformType::from_size(4),
+            Some(UniformType::Scalar(ScalarType::F32))
+        );
+        assert_eq!(
+            UniformType::from_size(8),
+            Some(UniformType::Vec(VecType::Vec2(ScalarType::F32)))
+        );
+        assert_eq!(
+            UniformType::from_size(12),
+            Some(UniformType::Vec(VecType::Vec3(ScalarType::F32)))
+        );
+        assert_eq!(
+            UniformType::from_size(16),
+            Some(UniformType::Vec(VecType::Vec4(ScalarType::F32)))
+        );
+        assert_eq!(
+            UniformType::from_size(24),
+            Some(UniformType::Matrix(MatrixType::M2x3))
+        );
+        assert_eq!(
+            UniformType::from_size(32),
+            Some(UniformType::Matrix(MatrixType::M2x4))
+        );
+        assert_eq!(
+            UniformType::from_size(36),
+            Some(UniformType::Matrix(MatrixType::M3x3))
+        );
+        assert_eq!(
+            UniformType::from_size(48),
+            Some(UniformType::Matrix(MatrixType::M3x4))
+        );
+        assert_eq!(
+            UniformType::from_size(64),
+            Some(UniformType::Matrix(MatrixType::M4x4))
+        );
+    }
+
+    #[test]
+    fn other_sizes_are_raw() {
+        for size in [0, 3, 5, 20, 80, 144, 65536] {
+            assert_eq!(UniformType::from_size(size), None);
+        }
+        assert_eq!(UniformValue::Raw(80).to_le_bytes(), vec![0; 80]);
+    }
+
+    #[test]
+    fn non_finite_scalars_are_replaced() {
+        let mut value = UniformValue::Scalar(ScalarUniformValue::F32(f32::NAN));
+        assert!(value.sanitize());
+        assert_eq!(value.to_le_bytes(), 0f32.to_le_bytes());
+
+        let mut value = UniformValue::Scalar(ScalarUniformValue::F32(f32::INFINITY));
+        assert!(value.sanitize());
+        assert_eq!(value.to_le_bytes(), f32::MAX.to_le_bytes());
+
+        let mut value = UniformValue::Scalar(ScalarUniformValue::F32(f32::NEG_INFINITY));
+        assert!(value.sanitize());
+        assert_eq!(value.to_le_bytes(), f32::MIN.to_le_bytes());
+    }
+
+    #[test]
+    fn finite_values_are_kept() {
+        let mut value = UniformValue::Scalar(ScalarUniformValue::F32(1.5));
+        assert!(!value.sanitize());
+        assert_eq!(value.to_le_bytes(), 1.5f32.to_le_bytes());
+
+        let mut value = UniformValue::Scalar(ScalarUniformValue::U32(u32::MAX));
+        assert!(!value.sanitize());
+        assert_eq!(value.to_le_bytes(), u32::MAX.to_le_bytes());
+    }
+
+    #[test]
+    fn every_component_is_replaced() {
+        let mut value = UniformValue::Vector(VectorUniformValue::Vec3(
+            vec::Vec3UniformValue::F32(f32::NAN, 2.0, f32::INFINITY),
+        ));
+        assert!(value.sanitize());
+        let expected: Vec<u8> = [0.0, 2.0, f32::MAX]
+            .iter()
+            .flat_map(|v: &f32| v.to_le_bytes())
+            .collect();
+        assert_eq!(value.to_le_bytes(), expected);
+    }
+
+    #[test]
+    fn zoom_stays_in_range() {
+        let pan_zoom = |zoom| {
+            UniformValue::BuiltIn(BuiltinValue::PanZoom {
+                offset: [0.0, 0.0],
+                zoom,
+                enabled: true,
+            })
+        };
+        for (zoom, expected) in [
+            (0.0, MIN_ZOOM),
+            (-2.0, MIN_ZOOM),
+            (1e9, MAX_ZOOM),
+            (f32::NAN, 1.0),
+        ] {
+            let mut value = pan_zoom(zoom);
+            assert!(value.sanitize());
+            assert_eq!(value, pan_zoom(expected));
+        }
+
+        let mut value = pan_zoom(2.0);
+        assert!(!value.sanitize());
+    }
+
+    fn every_type() -> Vec<UniformType> {
+        let scalars = [ScalarType::U32, ScalarType::I32, ScalarType::F32];
+        let matrices = [
+            MatrixType::M2x2,
+            MatrixType::M2x3,
+            MatrixType::M2x4,
+            MatrixType::M3x2,
+            MatrixType::M3x3,
+            MatrixType::M3x4,
+            MatrixType::M4x2,
+            MatrixType::M4x3,
+            MatrixType::M4x4,
+        ];
+        let types = scalars
+            .iter()
+            .flat_map(|s| {
+                [
+                    UniformType::Scalar(*s),
+                    UniformType::Vec(VecType::Vec2(*s)),
+                    UniformType::Vec(VecType::Vec3(*s)),
+                    UniformType::Vec(VecType::Vec4(*s)),
+                ]
+            })
+            .chain(matrices.iter().map(|m| UniformType::Matrix(*m)))
+            .chain([UniformType::Transform]);
+        types.collect()
+    }
+
+    fn every_value() -> Vec<UniformValue> {
+        let source = UniformValue::Vector(VectorUniformValue::Vec4(vec::Vec4UniformValue::F32(
+            1.5, -2.0, 3.25, 4.0,
+        )));
+
+        let builtins = TimeUnit::ALL
+            .iter()
+            .map(|unit| BuiltinValue::Time(*unit))
+            .chain(RandomType::ALL.iter().map(|kind| BuiltinValue::Random {
+                kind: *kind,
+                frozen: false,
+                bits: 0xdead_beef,
+            }))
+            .chain([
+                BuiltinValue::WindowSize,
+                BuiltinValue::PanZoom {
+                    offset: [0.5, -0.25],
+                    zoom: 4.0,
+                    enabled: true,
+                },
+                BuiltinValue::PanZoom {
+                    offset: [0.5, -0.25],
+                    zoom: 4.0,
+                    enabled: false,
+                },
+                BuiltinValue::Camera {
+                    position: Point3::new(1.0, 2.0, 3.0),
+                    yaw: 30.0,
+                    pitch: -10.0,
+                    enabled: true,
+                },
+                BuiltinValue::Light {
+                    position: [2.0, 4.0, 3.0],
+                    color: [1.0, 0.5, 0.25],
+                    intensity: 2.0,
+                    ambient: 0.1,
+                },
+            ]);
+
+        every_type()
+            .into_iter()
+            .map(|t| source.cast_to(t))
+            .chain(builtins.map(UniformValue::BuiltIn))
+            .chain([UniformValue::Raw(80)])
+            .collect()
+    }
+
+    #[test]
+    fn layouts_match_the_shader_compiler() {
+        for ty in every_type() {
+            let (wgsl_type, size, _) = ty.layout();
+            let source = format!("@group(0) @binding(0) var<uniform> value: {wgsl_type};");
+            let sizes = crate::reflection::uniform_sizes(&source);
+            assert_eq!(sizes.get(&(0, 0)), Some(&size), "{wgsl_type}");
+            // What is uploaded is what the shader reads
+            let value = UniformValue::Scalar(ScalarUniformValue::F32(1.5)).cast_to(ty);
+            assert_eq!(value.to_le_bytes().len() as u64, size, "{wgsl_type}");
+        }
+    }
+
+    #[test]
+    fn write_le_bytes_matches_to_le_bytes() {
+        for value in every_value() {
+            let mut out = Vec::new();
+            value.write_le_bytes(&mut out);
+            assert_eq!(out, value.to_le_bytes(), "{value:?}");
+        }
+    }
+
+    #[test]
+    fn light_matches_its_struct() {
+        let light = BuiltinValue::Light {
+            position: [2.0, 4.0, 3.0],
+            color: [1.0, 0.5, 0.25],
+            intensity: 2.0,
+            ambient: 0.1,
+        };
+        let source = format!("{LIGHT_STRUCT}@group(0) @binding(0) var<uniform> light: Light;");
+        let sizes = crate::reflection::uniform_sizes(&source);
+        assert_eq!(
+            sizes.get(&(0, 0)),
+            Some(&(light.to_le_bytes().len() as u64))
+        );
+
+        let mut json = Map::new();
+        light.to_json(&mut json);
+        let mut log = Log::new();
+        assert_eq!(BuiltinValue::from_json(&json, &mut log), Some(light));
+    }
+
+    #[test]
+    fn write_le_bytes_appends() {
+        let mut out = vec![0xff; 3];
+        let mut expected = out.clone();
+        for value in every_value() {
+            value.write_le_bytes(&mut out);
+            expected.extend(value.to_le_bytes());
+        }
+        assert_eq!(out, expected);
+    }
+
+    #[test]
+    fn wgsl_types() {
+        let wgsl_type = |value: UniformValue| value.wgsl_type();
+        assert_eq!(
+            wgsl_type(UniformValue::Scalar(ScalarUniformValue::I32(0))).as_deref(),
+            Some("i32")
+        );
+        assert_eq!(
+            wgsl_type(UniformValue::Vector(VectorUniformValue::Vec3(
+                Vec3UniformValue::U32(0, 0, 0)
+            )))
+            .as_deref(),
+            Some("vec3<u32>")
+        );
+        assert_eq!(
+            wgsl_type(UniformValue::Matrix(MatrixUniformValue::M2x3(
+                Column3(0.0, 0.0, 0.0),
+                Column3(0.0, 0.0, 0.0)
+            )))
+            .as_deref(),
+            Some("mat2x3<f32>")
+        );
+        assert_eq!(
+            wgsl_type(UniformValue::BuiltIn(BuiltinValue::Time(TimeUnit::Seconds))).as_deref(),
+            Some("f32")
+        );
+        assert_eq!(wgsl_type(UniformValue::Raw(48)), None);
+    }
+
+    #[test]
+    fn layouts_follow_the_wgsl_rules() {
+        let layout = |unitype: UniformType| unitype.layout();
+        assert_eq!(
+            layout(UniformType::Scalar(ScalarType::U32)),
+            ("u32".to_string(), 4, 4)
+        );
+        assert_eq!(
+            layout(UniformType::Vec(VecType::Vec3(ScalarType::F32))),
+            ("vec3<f32>".to_string(), 12, 16)
+        );
+        assert_eq!(
+            layout(UniformType::Matrix(MatrixType::M2x3)),
+            ("mat2x3<f32>".to_string(), 32, 16)
+        );
+        assert_eq!(
+            layout(UniformType::Matrix(MatrixType::M3x2)),
+            ("mat3x2<f32>".to_string(), 24, 8)
+        );
+        assert_eq!(
+            layout(UniformType::Transform),
+            ("mat4x4<f32>".to_string(), 64, 16)
+        );
+    }
+}
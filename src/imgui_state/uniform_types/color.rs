@@ -0,0 +1,266 @@
+use std::borrow::Cow;
+
+use imgui::{ColorEditDisplayMode, Ui};
+use mint::{Vector3, Vector4};
+use serde_json::{Map, Value as JsonValue};
+
+use super::{
+    vec::{Vec3UniformValue, Vec4UniformValue, VectorUniformValue},
+    UniformValue,
+};
+
+/// How the color picker of an f32 vec3 or vec4 shows the color
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub(crate) enum ColorMode {
+    #[default]
+    Rgb,
+    Hsv,
+}
+
+impl ColorMode {
+    const ALL: [ColorMode; 2] = [ColorMode::Rgb, ColorMode::Hsv];
+
+    fn label(self) -> &'static str {
+        match self {
+            ColorMode::Rgb => "RGB",
+            ColorMode::Hsv => "HSV",
+        }
+    }
+}
+
+/// Color settings of a binding, saved with it
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub(crate) struct ColorOptions {
+    pub(crate) mode: ColorMode,
+    /// The color is picked in sRGB and converted to linear before it's uploaded
+    pub(crate) srgb: bool,
+}
+
+impl ColorOptions {
+    pub(crate) fn from_json(json_val: &JsonValue) -> Option<ColorOptions> {
+        let json_val = json_val.as_object()?;
+        let mode = match json_val.get("mode")?.as_str()? {
+            "rgb" => ColorMode::Rgb,
+            "hsv" => ColorMode::Hsv,
+            _ => return None,
+        };
+        let srgb = json_val.get("srgb")?.as_bool()?;
+        Some(ColorOptions { mode, srgb })
+    }
+
+    pub(crate) fn to_json(self) -> JsonValue {
+        let mut json_val = Map::new();
+        let mode = match self.mode {
+            ColorMode::Rgb => "rgb",
+            ColorMode::Hsv => "hsv",
+        };
+        json_val.insert("mode".into(), mode.into());
+        json_val.insert("srgb".into(), self.srgb.into());
+        JsonValue::Object(json_val)
+    }
+
+    /// The value the shader gets. Only the color channels are converted, not the alpha
+    pub(crate) fn encode(self, value: UniformValue) -> UniformValue {
+        if !self.srgb {
+            return value;
+        }
+
+        match value {
+            UniformValue::Vector(VectorUniformValue::Vec3(Vec3UniformValue::F32(r, g, b))) => {
+                UniformValue::Vector(VectorUniformValue::Vec3(Vec3UniformValue::F32(
+                    srgb_to_linear(r),
+                    srgb_to_linear(g),
+                    srgb_to_linear(b),
+                )))
+            }
+            UniformValue::Vector(VectorUniformValue::Vec4(Vec4UniformValue::F32(r, g, b, a))) => {
+                UniformValue::Vector(VectorUniformValue::Vec4(Vec4UniformValue::F32(
+                    srgb_to_linear(r),
+                    srgb_to_linear(g),
+                    srgb_to_linear(b),
+                    a,
+                )))
+            }
+            value => value,
+        }
+    }
+}
+
+fn srgb_to_linear(c: f32) -> f32 {
+    if c <= 0.04045 {
+        c / 12.92
+    } else {
+        ((c + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+/// `#RRGGBB`, or `#RRGGBBAA` with 4 components. Components are clamped to [0, 1]
+fn to_hex(components: &[f32]) -> String {
+    let hex: String = components
+        .iter()
+        .map(|c| format!("{:02X}", (c.clamp(0.0, 1.0) * 255.0).round() as u8))
+        .collect();
+    format!("#{hex}")
+}
+
+/// Components of `#RRGGBB` or `#RRGGBBAA`, the `#` is optional
+fn parse_hex(hex: &str) -> Option<Vec<f32>> {
+    let hex = hex.trim();
+    let hex = hex.strip_prefix('#').unwrap_or(hex);
+    if !(hex.len() == 6 || hex.len() == 8) || !hex.is_ascii() {
+        return None;
+    }
+
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| {
+            u8::from_str_radix(&hex[i..i + 2], 16)
+                .ok()
+                .map(|c| c as f32 / 255.0)
+        })
+        .collect()
+}
+
+/// Color picker, hex input and color options of an f32 vec3 or vec4.
+/// Returns true if the components, or how they are uploaded, changed
+pub(super) fn show_color_editor(
+    ui: &Ui,
+    group_index: usize,
+    binding_index: usize,
+    components: &mut [f32],
+    options: &mut ColorOptions,
+) -> bool {
+    const HEX_WIDTH: f32 = 90.0;
+    const MODE_WIDTH: f32 = 55.0;
+
+    let label = format!("##color_{group_index}_{binding_index}");
+    let display_mode = match options.mode {
+        ColorMode::Rgb => ColorEditDisplayMode::Rgb,
+        ColorMode::Hsv => ColorEditDisplayMode::Hsv,
+    };
+    let mut edited = match components {
+        [r, g, b] => {
+            let mut color = Vector3 {
+                x: *r,
+                y: *g,
+                z: *b,
+            };
+            let edited = ui
+                .color_edit3_config(label, &mut color)
+                .display_mode(display_mode)
+                .build();
+            [*r, *g, *b] = [color.x, color.y, color.z];
+            edited
+        }
+        [r, g, b, a] => {
+            let mut color = Vector4 {
+                x: *r,
+                y: *g,
+                z: *b,
+                w: *a,
+            };
+            let edited = ui
+                .color_edit4_config(label, &mut color)
+                .display_mode(display_mode)
+                .build();
+            [*r, *g, *b, *a] = [color.x, color.y, color.z, color.w];
+            edited
+        }
+        _ => false,
+    };
+
+    let mut hex = to_hex(components);
+    ui.set_next_item_width(HEX_WIDTH);
+    if ui
+        .input_text(format!("##hex_{group_index}_{binding_index}"), &mut hex)
+        .enter_returns_true(true)
+        .build()
+    {
+        if let Some(parsed) = parse_hex(&hex) {
+            // A vec3 ignores the alpha, a vec4 keeps its alpha if there's none
+            for (component, parsed) in components.iter_mut().zip(parsed) {
+                *component = parsed
+            }
+            edited = true
+        }
+    }
+    if ui.is_item_hovered() {
+        ui.tooltip_text("#RRGGBB or #RRGGBBAA, press enter to apply")
+    }
+
+    ui.same_line();
+    ui.set_next_item_width(MODE_WIDTH);
+    let mut selection = ColorMode::ALL
+        .iter()
+        .position(|mode| *mode == options.mode)
+        .unwrap_or(0);
+    if ui.combo(
+        format!("##color_mode_{group_index}_{binding_index}"),
+        &mut selection,
+        &ColorMode::ALL,
+        |mode| Cow::Borrowed(mode.label()),
+    ) {
+        options.mode = ColorMode::ALL[selection]
+    }
+
+    ui.same_line();
+    if ui.checkbox(
+        format!("sRGB##srgb_{group_index}_{binding_index}"),
+        &mut options.srgb,
+    ) {
+        edited = true
+    }
+    if ui.is_item_hovered() {
+        ui.tooltip_text(
+            "The color is picked in sRGB, the shader gets it in linear space. \
+            Use it when the shader's output is written to an sRGB surface",
+        )
+    }
+
+    edited
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hex_round_trips() {
+        assert_eq!(to_hex(&[1.0, 0.0, 0.5]), "#FF0080");
+        assert_eq!(to_hex(&[2.0, -1.0, 0.0, 1.0]), "#FF0000FF");
+        assert_eq!(parse_hex("#FF0080"), Some(vec![1.0, 0.0, 128.0 / 255.0]));
+        assert_eq!(parse_hex("ff000080").map(|c| c.len()), Some(4));
+        for invalid in ["#FF00", "#GG0000", "#FF00000", "#ÿÿÿ"] {
+            assert_eq!(parse_hex(invalid), None, "{invalid}");
+        }
+    }
+
+    #[test]
+    fn only_srgb_colors_are_converted() {
+        let color = UniformValue::Vector(VectorUniformValue::Vec4(Vec4UniformValue::F32(
+            0.5, 1.0, 0.0, 0.5,
+        )));
+        assert_eq!(ColorOptions::default().encode(color), color);
+
+        let srgb = ColorOptions {
+            srgb: true,
+            ..Default::default()
+        };
+        let UniformValue::Vector(VectorUniformValue::Vec4(Vec4UniformValue::F32(r, g, b, a))) =
+            srgb.encode(color)
+        else {
+            panic!("the type doesn't change")
+        };
+        assert!((r - 0.2140).abs() < 1e-4);
+        assert_eq!((g, b, a), (1.0, 0.0, 0.5));
+    }
+
+    #[test]
+    fn options_round_trip() {
+        let options = ColorOptions {
+            mode: ColorMode::Hsv,
+            srgb: true,
+        };
+        assert_eq!(ColorOptions::from_json(&options.to_json()), Some(options));
+    }
+}
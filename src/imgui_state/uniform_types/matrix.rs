@@ -1,6 +1,6 @@
 use std::borrow::Cow;
 
-use imgui::Ui;
+use imgui::{ItemHoveredFlags, TableColumnFlags, TableColumnSetup, TableFlags, Ui};
 use serde_json::{Map, Value as JsonValue};
 
 use crate::imgui_state::{
@@ -8,25 +8,77 @@ use crate::imgui_state::{
 };
 
 use super::{
+    sanitize_f32,
     scalar::ScalarUniformValue,
     transform::TransformUniformValue,
     vec::{Vec2UniformValue, Vec3UniformValue, Vec4UniformValue, VectorUniformValue},
-    ScalarType, UniformType, UniformValue, VecType,
+    ScalarType, Tooltip, UniformType, UniformValue, VecType,
 };
 
+/// Order of the size combo, a row for each number of rows
+const MATRIX_SIZES: &[MatrixType] = &[
+    MatrixType::M2x2,
+    MatrixType::M3x2,
+    MatrixType::M4x2,
+    MatrixType::M2x3,
+    MatrixType::M3x3,
+    MatrixType::M4x3,
+    MatrixType::M2x4,
+    MatrixType::M3x4,
+    MatrixType::M4x4,
+];
+
+/// How the matrix editor is laid out, it doesn't change the value
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) struct MatrixView {
+    /// Every row of the table is a column of the matrix, like they are stored
+    pub(crate) column_major: bool,
+    /// What "Scale" multiplies the components by, it isn't saved
+    pub(crate) scale_factor: f32,
+}
+
+impl Default for MatrixView {
+    fn default() -> Self {
+        MatrixView {
+            column_major: false,
+            scale_factor: 2.0,
+        }
+    }
+}
+
+impl MatrixView {
+    pub(crate) fn from_json(json_val: &JsonValue) -> Option<MatrixView> {
+        let column_major = json_val.as_object()?.get("column_major")?.as_bool()?;
+        Some(MatrixView {
+            column_major,
+            ..Default::default()
+        })
+    }
+
+    pub(crate) fn to_json(self) -> JsonValue {
+        let mut json_val = Map::new();
+        json_val.insert("column_major".into(), self.column_major.into());
+        JsonValue::Object(json_val)
+    }
+}
+
 trait MatrixColumn {
     fn to_le_bytes(&self) -> Vec<u8>;
+    fn write_le_bytes(&self, out: &mut Vec<u8>);
     fn values(&self) -> Vec<f32>;
+    /// Sets the components in order, `values` has as many as the column
+    fn set_values(&mut self, values: &[f32]);
+    fn sanitize(&mut self) -> bool;
     fn from_json(json_val: &Vec<JsonValue>) -> Option<Self> where Self: Sized;
     fn to_json(&self) -> JsonValue;
 }
 
 #[derive(Debug, Clone, Copy, PartialEq)]
-pub(crate) struct Column2(pub f32, pub f32);
+pub struct Column2(pub f32, pub f32);
 #[derive(Debug, Clone, Copy, PartialEq)]
-pub(crate) struct Column3(pub f32, pub f32, pub f32);
+pub struct Column3(pub f32, pub f32, pub f32);
 #[derive(Debug, Clone, Copy, PartialEq)]
-pub(crate) struct Column4(pub f32, pub f32, pub f32, pub f32);
+pub struct Column4(pub f32, pub f32, pub f32, pub f32);
 
 impl MatrixColumn for Column2 {
     fn to_le_bytes(&self) -> Vec<u8> {
@@ -37,10 +89,24 @@ impl MatrixColumn for Column2 {
             .collect()
     }
 
+    fn write_le_bytes(&self, out: &mut Vec<u8>) {
+        out.extend(self.0.to_le_bytes().into_iter().chain(self.1.to_le_bytes()))
+    }
+
     fn values(&self) -> Vec<f32> {
         vec![self.0, self.1]
     }
 
+    fn set_values(&mut self, values: &[f32]) {
+        if let [r0, r1] = *values {
+            *self = Column2(r0, r1)
+        }
+    }
+
+    fn sanitize(&mut self) -> bool {
+        sanitize_f32(&mut self.0) | sanitize_f32(&mut self.1)
+    }
+
     fn from_json(json_val: &Vec<JsonValue>) -> Option<Self> {
         if json_val.len() != 2 {
             return None;
@@ -56,6 +122,7 @@ impl MatrixColumn for Column2 {
     }
 }
 
+/// A vec3 column is aligned like a vec4, so it's followed by 4 bytes of padding
 impl MatrixColumn for Column3 {
     fn to_le_bytes(&self) -> Vec<u8> {
         self.0
@@ -63,13 +130,35 @@ impl MatrixColumn for Column3 {
             .into_iter()
             .chain(self.1.to_le_bytes())
             .chain(self.2.to_le_bytes())
+            .chain([0; 4])
             .collect()
     }
 
+    fn write_le_bytes(&self, out: &mut Vec<u8>) {
+        out.extend(
+            self.0
+                .to_le_bytes()
+                .into_iter()
+                .chain(self.1.to_le_bytes())
+                .chain(self.2.to_le_bytes())
+                .chain([0; 4]),
+        )
+    }
+
     fn values(&self) -> Vec<f32> {
         vec![self.0, self.1, self.2]
     }
 
+    fn set_values(&mut self, values: &[f32]) {
+        if let [r0, r1, r2] = *values {
+            *self = Column3(r0, r1, r2)
+        }
+    }
+
+    fn sanitize(&mut self) -> bool {
+        sanitize_f32(&mut self.0) | sanitize_f32(&mut self.1) | sanitize_f32(&mut self.2)
+    }
+
     fn from_json(json_val: &Vec<JsonValue>) -> Option<Self> {
         if json_val.len() != 3 {
             return None;
@@ -97,10 +186,34 @@ impl MatrixColumn for Column4 {
             .collect()
     }
 
+    fn write_le_bytes(&self, out: &mut Vec<u8>) {
+        out.extend(
+            self.0
+                .to_le_bytes()
+                .into_iter()
+                .chain(self.1.to_le_bytes())
+                .chain(self.2.to_le_bytes())
+                .chain(self.3.to_le_bytes()),
+        )
+    }
+
     fn values(&self) -> Vec<f32> {
         vec![self.0, self.1, self.2, self.3]
     }
 
+    fn set_values(&mut self, values: &[f32]) {
+        if let [r0, r1, r2, r3] = *values {
+            *self = Column4(r0, r1, r2, r3)
+        }
+    }
+
+    fn sanitize(&mut self) -> bool {
+        sanitize_f32(&mut self.0)
+            | sanitize_f32(&mut self.1)
+            | sanitize_f32(&mut self.2)
+            | sanitize_f32(&mut self.3)
+    }
+
     fn from_json(json_val: &Vec<JsonValue>) -> Option<Self> {
         if json_val.len() != 4 {
             return None;
@@ -119,7 +232,7 @@ impl MatrixColumn for Column4 {
 }
 
 #[derive(Debug, Clone, Copy, PartialEq)]
-pub(crate) enum MatrixUniformValue {
+pub enum MatrixUniformValue {
     // Column x Row
     M2x2(Column2, Column2),
     M2x3(Column3, Column3),
@@ -134,6 +247,287 @@ pub(crate) enum MatrixUniformValue {
     M4x4(Column4, Column4, Column4, Column4),
 }
 impl MatrixUniformValue {
+    /// Replaces NaN and infinity, returns true if there were any
+    pub(super) fn sanitize(&mut self) -> bool {
+        match self {
+            MatrixUniformValue::M2x2(c1, c2) => c1.sanitize() | c2.sanitize(),
+            MatrixUniformValue::M2x3(c1, c2) => c1.sanitize() | c2.sanitize(),
+            MatrixUniformValue::M2x4(c1, c2) => c1.sanitize() | c2.sanitize(),
+            MatrixUniformValue::M3x2(c1, c2, c3) => c1.sanitize() | c2.sanitize() | c3.sanitize(),
+            MatrixUniformValue::M3x3(c1, c2, c3) => c1.sanitize() | c2.sanitize() | c3.sanitize(),
+            MatrixUniformValue::M3x4(c1, c2, c3) => c1.sanitize() | c2.sanitize() | c3.sanitize(),
+            MatrixUniformValue::M4x2(c1, c2, c3, c4) => {
+                c1.sanitize() | c2.sanitize() | c3.sanitize() | c4.sanitize()
+            }
+            MatrixUniformValue::M4x3(c1, c2, c3, c4) => {
+                c1.sanitize() | c2.sanitize() | c3.sanitize() | c4.sanitize()
+            }
+            MatrixUniformValue::M4x4(c1, c2, c3, c4) => {
+                c1.sanitize() | c2.sanitize() | c3.sanitize() | c4.sanitize()
+            }
+        }
+    }
+
+    pub(crate) fn matrix_type(&self) -> MatrixType {
+        match self {
+            MatrixUniformValue::M2x2(..) => MatrixType::M2x2,
+            MatrixUniformValue::M2x3(..) => MatrixType::M2x3,
+            MatrixUniformValue::M2x4(..) => MatrixType::M2x4,
+            MatrixUniformValue::M3x2(..) => MatrixType::M3x2,
+            MatrixUniformValue::M3x3(..) => MatrixType::M3x3,
+            MatrixUniformValue::M3x4(..) => MatrixType::M3x4,
+            MatrixUniformValue::M4x2(..) => MatrixType::M4x2,
+            MatrixUniformValue::M4x3(..) => MatrixType::M4x3,
+            MatrixUniformValue::M4x4(..) => MatrixType::M4x4,
+        }
+    }
+
+    /// Components of every column, in the order they are stored
+    fn columns(&self) -> Vec<Vec<f32>> {
+        match self {
+            MatrixUniformValue::M2x2(c1, c2) => vec![c1.values(), c2.values()],
+            MatrixUniformValue::M2x3(c1, c2) => vec![c1.values(), c2.values()],
+            MatrixUniformValue::M2x4(c1, c2) => vec![c1.values(), c2.values()],
+            MatrixUniformValue::M3x2(c1, c2, c3) => vec![c1.values(), c2.values(), c3.values()],
+            MatrixUniformValue::M3x3(c1, c2, c3) => vec![c1.values(), c2.values(), c3.values()],
+            MatrixUniformValue::M3x4(c1, c2, c3) => vec![c1.values(), c2.values(), c3.values()],
+            MatrixUniformValue::M4x2(c1, c2, c3, c4) => {
+                vec![c1.values(), c2.values(), c3.values(), c4.values()]
+            }
+            MatrixUniformValue::M4x3(c1, c2, c3, c4) => {
+                vec![c1.values(), c2.values(), c3.values(), c4.values()]
+            }
+            MatrixUniformValue::M4x4(c1, c2, c3, c4) => {
+                vec![c1.values(), c2.values(), c3.values(), c4.values()]
+            }
+        }
+    }
+
+    /// Writes `columns` back through the Column structs, they must have the same size as
+    /// the matrix
+    fn set_columns(&mut self, columns: &[Vec<f32>]) {
+        match self {
+            MatrixUniformValue::M2x2(c1, c2) => {
+                for (column, values) in [c1, c2].into_iter().zip(columns) {
+                    column.set_values(values)
+                }
+            }
+            MatrixUniformValue::M2x3(c1, c2) => {
+                for (column, values) in [c1, c2].into_iter().zip(columns) {
+                    column.set_values(values)
+                }
+            }
+            MatrixUniformValue::M2x4(c1, c2) => {
+                for (column, values) in [c1, c2].into_iter().zip(columns) {
+                    column.set_values(values)
+                }
+            }
+            MatrixUniformValue::M3x2(c1, c2, c3) => {
+                for (column, values) in [c1, c2, c3].into_iter().zip(columns) {
+                    column.set_values(values)
+                }
+            }
+            MatrixUniformValue::M3x3(c1, c2, c3) => {
+                for (column, values) in [c1, c2, c3].into_iter().zip(columns) {
+                    column.set_values(values)
+                }
+            }
+            MatrixUniformValue::M3x4(c1, c2, c3) => {
+                for (column, values) in [c1, c2, c3].into_iter().zip(columns) {
+                    column.set_values(values)
+                }
+            }
+            MatrixUniformValue::M4x2(c1, c2, c3, c4) => {
+                for (column, values) in [c1, c2, c3, c4].into_iter().zip(columns) {
+                    column.set_values(values)
+                }
+            }
+            MatrixUniformValue::M4x3(c1, c2, c3, c4) => {
+                for (column, values) in [c1, c2, c3, c4].into_iter().zip(columns) {
+                    column.set_values(values)
+                }
+            }
+            MatrixUniformValue::M4x4(c1, c2, c3, c4) => {
+                for (column, values) in [c1, c2, c3, c4].into_iter().zip(columns) {
+                    column.set_values(values)
+                }
+            }
+        }
+    }
+
+    /// Ones in the diagonal, zeros elsewhere. Non square matrices get the ones they fit
+    fn set_identity(&mut self) {
+        let mut columns = self.columns();
+        for (c, column) in columns.iter_mut().enumerate() {
+            for (r, value) in column.iter_mut().enumerate() {
+                *value = if c == r { 1.0 } else { 0.0 }
+            }
+        }
+        self.set_columns(&columns)
+    }
+
+    /// Only square matrices, transposing the others would change their size
+    fn transpose(&mut self) {
+        let columns = self.columns();
+        if columns.len() != columns[0].len() {
+            return;
+        }
+        let transposed: Vec<Vec<f32>> = (0..columns.len())
+            .map(|r| columns.iter().map(|column| column[r]).collect())
+            .collect();
+        self.set_columns(&transposed)
+    }
+
+    fn scale(&mut self, factor: f32) {
+        let mut columns = self.columns();
+        for value in columns.iter_mut().flatten() {
+            *value *= factor
+        }
+        self.set_columns(&columns)
+    }
+
+    /// Every component in a cell of a table. The rows of the table are the rows of the matrix,
+    /// unless `column_major`. Returns true if any was edited
+    fn show_table(
+        ui: &Ui,
+        group_index: usize,
+        binding_index: usize,
+        columns: &mut [Vec<f32>],
+        column_major: bool,
+    ) -> bool {
+        let column_count = columns.len();
+        let row_count = columns[0].len();
+        let (table_rows, table_columns, row_label, column_label) = if column_major {
+            (column_count, row_count, "col", "row")
+        } else {
+            (row_count, column_count, "row", "col")
+        };
+
+        let flags = TableFlags::BORDERS | TableFlags::SIZING_STRETCH_SAME;
+        let id = format!("##m_table_{group_index}_{binding_index}");
+        let Some(_table) = ui.begin_table_with_flags(id, table_columns + 1, flags) else {
+            return false;
+        };
+        ui.table_setup_column_with(TableColumnSetup {
+            flags: TableColumnFlags::WIDTH_FIXED,
+            ..TableColumnSetup::new("")
+        });
+        for j in 0..table_columns {
+            ui.table_setup_column(format!("{column_label} {j}"));
+        }
+        ui.table_headers_row();
+
+        let mut edited = false;
+        for i in 0..table_rows {
+            ui.table_next_column();
+            ui.text(format!("{row_label} {i}"));
+            for j in 0..table_columns {
+                let (c, r) = if column_major { (i, j) } else { (j, i) };
+                ui.table_next_column();
+                ui.set_next_item_width(-f32::MIN_POSITIVE);
+                edited |= ui
+                    .input_float(
+                        format!("##m_edit_{c}_{r}_{group_index}_{binding_index}"),
+                        &mut columns[c][r],
+                    )
+                    .build();
+            }
+        }
+
+        edited
+    }
+
+    /// Like `show_editor`, `view` picks how the table is laid out and keeps the scale factor
+    pub(crate) fn show_editor_with_view(
+        &mut self,
+        ui: &Ui,
+        group_index: usize,
+        binding_index: usize,
+        val_name: &mut String,
+        view: &mut MatrixView,
+    ) -> Option<UniformEditEvent> {
+        const FACTOR_WIDTH: f32 = 60.0;
+
+        let mut message = None;
+        UniformValue::show_primitive_selector(
+            ui,
+            group_index,
+            binding_index,
+            &mut message,
+            6,
+            val_name,
+        );
+        ui.same_line();
+        let size_index = MATRIX_SIZES
+            .iter()
+            .position(|size| *size == self.matrix_type())
+            .unwrap_or(0);
+        MatrixUniformValue::show_size_selector(
+            ui,
+            group_index,
+            binding_index,
+            size_index,
+            &mut message,
+        );
+        ui.same_line();
+        ui.checkbox(
+            format!("Column-major##m_view_{group_index}_{binding_index}"),
+            &mut view.column_major,
+        );
+        if ui.is_item_hovered() {
+            ui.tooltip_text("Show every column of the matrix as a row, the order they are stored in.\nThe value doesn't change")
+        }
+
+        let mut columns = self.columns();
+        if MatrixUniformValue::show_table(
+            ui,
+            group_index,
+            binding_index,
+            &mut columns,
+            view.column_major,
+        ) {
+            self.set_columns(&columns);
+            message = Some(UniformEditEvent::UpdateBuffer(group_index, binding_index))
+        }
+
+        if ui.small_button(format!(
+            "Identity##m_identity_{group_index}_{binding_index}"
+        )) {
+            self.set_identity();
+            message = Some(UniformEditEvent::UpdateBuffer(group_index, binding_index))
+        }
+        ui.same_line();
+        let square = columns.len() == columns[0].len();
+        ui.disabled(!square, || {
+            if ui.small_button(format!(
+                "Transpose##m_transpose_{group_index}_{binding_index}"
+            )) {
+                self.transpose();
+                message = Some(UniformEditEvent::UpdateBuffer(group_index, binding_index))
+            }
+        });
+        if !square && ui.is_item_hovered_with_flags(ItemHoveredFlags::ALLOW_WHEN_DISABLED) {
+            ui.tooltip_text("Only square matrices can be transposed, the others would change size")
+        }
+        ui.same_line();
+        if ui.small_button(format!("Scale##m_scale_{group_index}_{binding_index}")) {
+            self.scale(view.scale_factor);
+            message = Some(UniformEditEvent::UpdateBuffer(group_index, binding_index))
+        }
+        if ui.is_item_hovered() {
+            ui.tooltip_text("Multiplies every component by the factor")
+        }
+        ui.same_line();
+        ui.set_next_item_width(FACTOR_WIDTH);
+        ui.input_float(
+            format!("##m_scale_factor_{group_index}_{binding_index}"),
+            &mut view.scale_factor,
+        )
+        .build();
+
+        message
+    }
+
     fn cast_to_scalar(&self, s: ScalarType) -> UniformValue {
         UniformValue::Scalar(match s {
             ScalarType::U32 => ScalarUniformValue::U32(0),
@@ -216,18 +610,6 @@ impl MatrixUniformValue {
         size_index: usize,
         message: &mut Option<UniformEditEvent>,
     ) {
-        const MATRIX_SIZES: &[MatrixType] = &[
-            MatrixType::M2x2,
-            MatrixType::M3x2,
-            MatrixType::M4x2,
-            MatrixType::M2x3,
-            MatrixType::M3x3,
-            MatrixType::M4x3,
-            MatrixType::M2x4,
-            MatrixType::M3x4,
-            MatrixType::M4x4,
-        ];
-
         const COMBO_WIDTH: f32 = 150.0;
 
         ui.set_next_item_width(COMBO_WIDTH);
@@ -237,6 +619,7 @@ impl MatrixUniformValue {
             &mut selection,
             MATRIX_SIZES,
             |unitype| unitype.into(),
+            |size| Tooltip::Type(UniformType::Matrix(*size)),
             3,
         ) {
             let selected_type = MATRIX_SIZES[selection];
@@ -248,6 +631,7 @@ impl MatrixUniformValue {
                 ))
             }
         };
+        Tooltip::MatrixSize.show_on_hover(ui)
     }
 
     fn cast_to_transform(&self) -> UniformValue {
@@ -347,397 +731,13 @@ impl ImguiUniformSelectable for MatrixUniformValue {
         binding_index: usize,
         val_name: &mut String,
     ) -> Option<UniformEditEvent> {
-        let mut message = None;
-        UniformValue::show_primitive_selector(
+        self.show_editor_with_view(
             ui,
             group_index,
             binding_index,
-            &mut message,
-            6,
             val_name,
-        );
-        ui.same_line();
-        match self {
-            MatrixUniformValue::M2x2(c1, c2) => {
-                MatrixUniformValue::show_size_selector(
-                    ui,
-                    group_index,
-                    binding_index,
-                    0,
-                    &mut message,
-                );
-                let vc1 = c1.values();
-                let vc2 = c2.values();
-                let mut r1 = [vc1[0], vc2[0]];
-                if ui
-                    .input_float2(format!("##m_edit_1_{group_index}_{binding_index}"), &mut r1)
-                    .build()
-                {
-                    c1.0 = r1[0];
-                    c2.0 = r1[1];
-                    message = Some(UniformEditEvent::UpdateBuffer(group_index, binding_index))
-                }
-                let mut r2 = [vc1[1], vc2[1]];
-                if ui
-                    .input_float2(format!("##m_edit_2_{group_index}_{binding_index}"), &mut r2)
-                    .build()
-                {
-                    c1.1 = r2[0];
-                    c2.1 = r2[1];
-                    message = Some(UniformEditEvent::UpdateBuffer(group_index, binding_index))
-                }
-            }
-            MatrixUniformValue::M3x2(c1, c2, c3) => {
-                MatrixUniformValue::show_size_selector(
-                    ui,
-                    group_index,
-                    binding_index,
-                    1,
-                    &mut message,
-                );
-                let vc1 = c1.values();
-                let vc2 = c2.values();
-                let vc3 = c3.values();
-                let mut r1 = [vc1[0], vc2[0], vc3[0]];
-                if ui
-                    .input_float3(format!("##m_edit_1_{group_index}_{binding_index}"), &mut r1)
-                    .build()
-                {
-                    c1.0 = r1[0];
-                    c2.0 = r1[1];
-                    c3.0 = r1[2];
-                    message = Some(UniformEditEvent::UpdateBuffer(group_index, binding_index))
-                }
-                let mut r2 = [vc1[1], vc2[1], vc3[1]];
-                if ui
-                    .input_float3(format!("##m_edit_2_{group_index}_{binding_index}"), &mut r2)
-                    .build()
-                {
-                    c1.1 = r2[0];
-                    c2.1 = r2[1];
-                    c3.1 = r2[2];
-                    message = Some(UniformEditEvent::UpdateBuffer(group_index, binding_index))
-                }
-            }
-            MatrixUniformValue::M4x2(c1, c2, c3, c4) => {
-                MatrixUniformValue::show_size_selector(
-                    ui,
-                    group_index,
-                    binding_index,
-                    2,
-                    &mut message,
-                );
-                let vc1 = c1.values();
-                let vc2 = c2.values();
-                let vc3 = c3.values();
-                let vc4 = c4.values();
-                let mut r1 = [vc1[0], vc2[0], vc3[0], vc4[0]];
-                if ui
-                    .input_float4(format!("##m_edit_1_{group_index}_{binding_index}"), &mut r1)
-                    .build()
-                {
-                    c1.0 = r1[0];
-                    c2.0 = r1[1];
-                    c3.0 = r1[2];
-                    c4.0 = r1[3];
-                    message = Some(UniformEditEvent::UpdateBuffer(group_index, binding_index))
-                }
-                let mut r2 = [vc1[1], vc2[1], vc3[1], vc4[1]];
-                if ui
-                    .input_float4(format!("##m_edit_2_{group_index}_{binding_index}"), &mut r2)
-                    .build()
-                {
-                    c1.1 = r2[0];
-                    c2.1 = r2[1];
-                    c3.1 = r2[2];
-                    c4.1 = r2[3];
-                    message = Some(UniformEditEvent::UpdateBuffer(group_index, binding_index))
-                }
-            }
-            MatrixUniformValue::M2x3(c1, c2) => {
-                MatrixUniformValue::show_size_selector(
-                    ui,
-                    group_index,
-                    binding_index,
-                    3,
-                    &mut message,
-                );
-                let vc1 = c1.values();
-                let vc2 = c2.values();
-                let mut r1 = [vc1[0], vc2[0]];
-                if ui
-                    .input_float2(format!("##m_edit_1_{group_index}_{binding_index}"), &mut r1)
-                    .build()
-                {
-                    c1.0 = r1[0];
-                    c2.0 = r1[1];
-                    message = Some(UniformEditEvent::UpdateBuffer(group_index, binding_index))
-                }
-                let mut r2 = [vc1[1], vc2[1]];
-                if ui
-                    .input_float2(format!("##m_edit_2_{group_index}_{binding_index}"), &mut r2)
-                    .build()
-                {
-                    c1.1 = r2[0];
-                    c2.1 = r2[1];
-                    message = Some(UniformEditEvent::UpdateBuffer(group_index, binding_index))
-                }
-                let mut r3 = [vc1[2], vc2[2]];
-                if ui
-                    .input_float2(format!("##m_edit_3_{group_index}_{binding_index}"), &mut r3)
-                    .build()
-                {
-                    c1.2 = r3[0];
-                    c2.2 = r3[1];
-                    message = Some(UniformEditEvent::UpdateBuffer(group_index, binding_index))
-                }
-            }
-            MatrixUniformValue::M3x3(c1, c2, c3) => {
-                MatrixUniformValue::show_size_selector(
-                    ui,
-                    group_index,
-                    binding_index,
-                    4,
-                    &mut message,
-                );
-                let vc1 = c1.values();
-                let vc2 = c2.values();
-                let vc3 = c3.values();
-                let mut r1 = [vc1[0], vc2[0], vc3[0]];
-                if ui
-                    .input_float3(format!("##m_edit_1_{group_index}_{binding_index}"), &mut r1)
-                    .build()
-                {
-                    c1.0 = r1[0];
-                    c2.0 = r1[1];
-                    c3.0 = r1[2];
-                    message = Some(UniformEditEvent::UpdateBuffer(group_index, binding_index))
-                }
-                let mut r2 = [vc1[1], vc2[1], vc3[1]];
-                if ui
-                    .input_float3(format!("##m_edit_2_{group_index}_{binding_index}"), &mut r2)
-                    .build()
-                {
-                    c1.1 = r2[0];
-                    c2.1 = r2[1];
-                    c3.1 = r2[2];
-                    message = Some(UniformEditEvent::UpdateBuffer(group_index, binding_index))
-                }
-                let mut r3 = [vc1[2], vc2[2], vc3[2]];
-                if ui
-                    .input_float3(format!("##m_edit_3_{group_index}_{binding_index}"), &mut r3)
-                    .build()
-                {
-                    c1.2 = r3[0];
-                    c2.2 = r3[1];
-                    c3.2 = r3[2];
-                    message = Some(UniformEditEvent::UpdateBuffer(group_index, binding_index))
-                }
-            }
-            MatrixUniformValue::M4x3(c1, c2, c3, c4) => {
-                MatrixUniformValue::show_size_selector(
-                    ui,
-                    group_index,
-                    binding_index,
-                    5,
-                    &mut message,
-                );
-                let vc1 = c1.values();
-                let vc2 = c2.values();
-                let vc3 = c3.values();
-                let vc4 = c4.values();
-                let mut r1 = [vc1[0], vc2[0], vc3[0], vc4[0]];
-                if ui
-                    .input_float4(format!("##m_edit_1_{group_index}_{binding_index}"), &mut r1)
-                    .build()
-                {
-                    c1.0 = r1[0];
-                    c2.0 = r1[1];
-                    c3.0 = r1[2];
-                    c4.0 = r1[3];
-                    message = Some(UniformEditEvent::UpdateBuffer(group_index, binding_index))
-                }
-                let mut r2 = [vc1[1], vc2[1], vc3[1], vc4[1]];
-                if ui
-                    .input_float4(format!("##m_edit_2_{group_index}_{binding_index}"), &mut r2)
-                    .build()
-                {
-                    c1.1 = r2[0];
-                    c2.1 = r2[1];
-                    c3.1 = r2[2];
-                    c4.1 = r2[3];
-                    message = Some(UniformEditEvent::UpdateBuffer(group_index, binding_index))
-                }
-                let mut r3 = [vc1[2], vc2[2], vc3[2], vc4[2]];
-                if ui
-                    .input_float4(format!("##m_edit_3_{group_index}_{binding_index}"), &mut r3)
-                    .build()
-                {
-                    c1.2 = r3[0];
-                    c2.2 = r3[1];
-                    c3.2 = r3[2];
-                    c4.2 = r3[3];
-                    message = Some(UniformEditEvent::UpdateBuffer(group_index, binding_index))
-                }
-            }
-            MatrixUniformValue::M2x4(c1, c2) => {
-                MatrixUniformValue::show_size_selector(
-                    ui,
-                    group_index,
-                    binding_index,
-                    6,
-                    &mut message,
-                );
-                let vc1 = c1.values();
-                let vc2 = c2.values();
-                let mut r1 = [vc1[0], vc2[0]];
-                if ui
-                    .input_float2(format!("##m_edit_1_{group_index}_{binding_index}"), &mut r1)
-                    .build()
-                {
-                    c1.0 = r1[0];
-                    c2.0 = r1[1];
-                    message = Some(UniformEditEvent::UpdateBuffer(group_index, binding_index))
-                }
-                let mut r2 = [vc1[1], vc2[1]];
-                if ui
-                    .input_float2(format!("##m_edit_2_{group_index}_{binding_index}"), &mut r2)
-                    .build()
-                {
-                    c1.1 = r2[0];
-                    c2.1 = r2[1];
-                    message = Some(UniformEditEvent::UpdateBuffer(group_index, binding_index))
-                }
-                let mut r3 = [vc1[2], vc2[2]];
-                if ui
-                    .input_float2(format!("##m_edit_3_{group_index}_{binding_index}"), &mut r3)
-                    .build()
-                {
-                    c1.2 = r3[0];
-                    c2.2 = r3[1];
-                    message = Some(UniformEditEvent::UpdateBuffer(group_index, binding_index))
-                }
-                let mut r4 = [vc1[3], vc2[3]];
-                if ui
-                    .input_float2(format!("##m_edit_4_{group_index}_{binding_index}"), &mut r4)
-                    .build()
-                {
-                    c1.3 = r4[0];
-                    c2.3 = r4[1];
-                    message = Some(UniformEditEvent::UpdateBuffer(group_index, binding_index))
-                }
-            }
-            MatrixUniformValue::M3x4(c1, c2, c3) => {
-                MatrixUniformValue::show_size_selector(
-                    ui,
-                    group_index,
-                    binding_index,
-                    7,
-                    &mut message,
-                );
-                let vc1 = c1.values();
-                let vc2 = c2.values();
-                let vc3 = c3.values();
-                let mut r1 = [vc1[0], vc2[0], vc3[0]];
-                if ui
-                    .input_float3(format!("##m_edit_1_{group_index}_{binding_index}"), &mut r1)
-                    .build()
-                {
-                    c1.0 = r1[0];
-                    c2.0 = r1[1];
-                    c3.0 = r1[2];
-                    message = Some(UniformEditEvent::UpdateBuffer(group_index, binding_index))
-                }
-                let mut r2 = [vc1[1], vc2[1], vc3[1]];
-                if ui
-                    .input_float3(format!("##m_edit_2_{group_index}_{binding_index}"), &mut r2)
-                    .build()
-                {
-                    c1.1 = r2[0];
-                    c2.1 = r2[1];
-                    c3.1 = r2[2];
-                    message = Some(UniformEditEvent::UpdateBuffer(group_index, binding_index))
-                }
-                let mut r3 = [vc1[2], vc2[2], vc3[2]];
-                if ui
-                    .input_float3(format!("##m_edit_3_{group_index}_{binding_index}"), &mut r3)
-                    .build()
-                {
-                    c1.2 = r3[0];
-                    c2.2 = r3[1];
-                    c3.2 = r3[2];
-                    message = Some(UniformEditEvent::UpdateBuffer(group_index, binding_index))
-                }
-                let mut r4 = [vc1[3], vc2[3], vc3[3]];
-                if ui
-                    .input_float3(format!("##m_edit_4_{group_index}_{binding_index}"), &mut r4)
-                    .build()
-                {
-                    c1.3 = r4[0];
-                    c2.3 = r4[1];
-                    c3.3 = r4[2];
-                    message = Some(UniformEditEvent::UpdateBuffer(group_index, binding_index))
-                }
-            }
-            MatrixUniformValue::M4x4(c1, c2, c3, c4) => {
-                MatrixUniformValue::show_size_selector(
-                    ui,
-                    group_index,
-                    binding_index,
-                    8,
-                    &mut message,
-                );
-                let vc1 = c1.values();
-                let vc2 = c2.values();
-                let vc3 = c3.values();
-                let vc4 = c4.values();
-                let mut r1 = [vc1[0], vc2[0], vc3[0], vc4[0]];
-                if ui
-                    .input_float4(format!("##m_edit_1_{group_index}_{binding_index}"), &mut r1)
-                    .build()
-                {
-                    c1.0 = r1[0];
-                    c2.0 = r1[1];
-                    c3.0 = r1[2];
-                    c4.0 = r1[3];
-                    message = Some(UniformEditEvent::UpdateBuffer(group_index, binding_index))
-                }
-                let mut r2 = [vc1[1], vc2[1], vc3[1], vc4[1]];
-                if ui
-                    .input_float4(format!("##m_edit_2_{group_index}_{binding_index}"), &mut r2)
-                    .build()
-                {
-                    c1.1 = r2[0];
-                    c2.1 = r2[1];
-                    c3.1 = r2[2];
-                    c4.1 = r2[3];
-                    message = Some(UniformEditEvent::UpdateBuffer(group_index, binding_index))
-                }
-                let mut r3 = [vc1[2], vc2[2], vc3[2], vc4[2]];
-                if ui
-                    .input_float4(format!("##m_edit_3_{group_index}_{binding_index}"), &mut r3)
-                    .build()
-                {
-                    c1.2 = r3[0];
-                    c2.2 = r3[1];
-                    c3.2 = r3[2];
-                    c4.2 = r3[3];
-                    message = Some(UniformEditEvent::UpdateBuffer(group_index, binding_index))
-                }
-                let mut r4 = [vc1[3], vc2[3], vc3[3], vc4[3]];
-                if ui
-                    .input_float4(format!("##m_edit_4_{group_index}_{binding_index}"), &mut r4)
-                    .build()
-                {
-                    c1.3 = r4[0];
-                    c2.3 = r4[1];
-                    c3.3 = r4[2];
-                    c4.3 = r4[3];
-                    message = Some(UniformEditEvent::UpdateBuffer(group_index, binding_index))
-                }
-            }
-        };
-        message
+            &mut MatrixView::default(),
+        )
     }
 
     fn to_le_bytes(&self) -> Vec<u8> {
@@ -776,6 +776,56 @@ impl ImguiUniformSelectable for MatrixUniformValue {
 
         columns.into_iter().flatten().collect()
     }
+
+    fn write_le_bytes(&self, out: &mut Vec<u8>) {
+        match self {
+            MatrixUniformValue::M2x2(c1, c2) => {
+                for column in [c1, c2] {
+                    column.write_le_bytes(out)
+                }
+            }
+            MatrixUniformValue::M2x3(c1, c2) => {
+                for column in [c1, c2] {
+                    column.write_le_bytes(out)
+                }
+            }
+            MatrixUniformValue::M2x4(c1, c2) => {
+                for column in [c1, c2] {
+                    column.write_le_bytes(out)
+                }
+            }
+            MatrixUniformValue::M3x2(c1, c2, c3) => {
+                for column in [c1, c2, c3] {
+                    column.write_le_bytes(out)
+                }
+            }
+            MatrixUniformValue::M3x3(c1, c2, c3) => {
+                for column in [c1, c2, c3] {
+                    column.write_le_bytes(out)
+                }
+            }
+            MatrixUniformValue::M3x4(c1, c2, c3) => {
+                for column in [c1, c2, c3] {
+                    column.write_le_bytes(out)
+                }
+            }
+            MatrixUniformValue::M4x2(c1, c2, c3, c4) => {
+                for column in [c1, c2, c3, c4] {
+                    column.write_le_bytes(out)
+                }
+            }
+            MatrixUniformValue::M4x3(c1, c2, c3, c4) => {
+                for column in [c1, c2, c3, c4] {
+                    column.write_le_bytes(out)
+                }
+            }
+            MatrixUniformValue::M4x4(c1, c2, c3, c4) => {
+                for column in [c1, c2, c3, c4] {
+                    column.write_le_bytes(out)
+                }
+            }
+        }
+    }
 }
 
 impl ImguiMatrix for MatrixUniformValue {
@@ -786,6 +836,7 @@ impl ImguiMatrix for MatrixUniformValue {
             UniformValue::Vector(_) => unreachable!(),
             UniformValue::Matrix(m) => *self = m,
             UniformValue::Transform(_) => unreachable!(),
+            UniformValue::Raw(_) => unreachable!(),
         }
     }
 }
@@ -805,6 +856,24 @@ pub(crate) enum MatrixType {
     M4x4,
 }
 
+impl MatrixType {
+    pub(crate) fn columns_rows(&self) -> (u64, u64) {
+        match self {
+            MatrixType::M2x2 => (2, 2),
+            MatrixType::M2x3 => (2, 3),
+            MatrixType::M2x4 => (2, 4),
+
+            MatrixType::M3x2 => (3, 2),
+            MatrixType::M3x3 => (3, 3),
+            MatrixType::M3x4 => (3, 4),
+
+            MatrixType::M4x2 => (4, 2),
+            MatrixType::M4x3 => (4, 3),
+            MatrixType::M4x4 => (4, 4),
+        }
+    }
+}
+
 impl<'a> From<&'a MatrixType> for Cow<'static, str> {
     fn from(val: &'a MatrixType) -> Cow<'static, str> {
         match val {
@@ -822,3 +891,93 @@ impl<'a> From<&'a MatrixType> for Cow<'static, str> {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn zero(size: MatrixType) -> MatrixUniformValue {
+        let UniformValue::Matrix(m) =
+            MatrixUniformValue::M2x2(Column2(0.0, 0.0), Column2(0.0, 0.0)).cast_to_matrix(size)
+        else {
+            unreachable!()
+        };
+        m
+    }
+
+    #[test]
+    fn table_edits_write_the_same_bytes() {
+        for size in MATRIX_SIZES {
+            let (column_count, row_count) = size.columns_rows();
+            for c in 0..column_count as usize {
+                for r in 0..row_count as usize {
+                    // Like the table does when the cell in column c and row r is edited
+                    let mut matrix = zero(*size);
+                    let mut columns = matrix.columns();
+                    columns[c][r] = 7.0;
+                    matrix.set_columns(&columns);
+
+                    // Columns are stored one after the other, a vec3 column takes 16 bytes
+                    let stride = row_count.next_power_of_two() as usize;
+                    let mut expected = vec![0.0f32; column_count as usize * stride];
+                    expected[c * stride + r] = 7.0;
+                    let expected: Vec<u8> = expected.iter().flat_map(|v| v.to_le_bytes()).collect();
+                    assert_eq!(matrix.to_le_bytes(), expected, "{size:?} c{c} r{r}");
+                    let (_, declared_size, _) = UniformType::Matrix(*size).layout();
+                    assert_eq!(expected.len() as u64, declared_size, "{size:?}");
+
+                    let mut written = Vec::new();
+                    matrix.write_le_bytes(&mut written);
+                    assert_eq!(written, expected, "{size:?} c{c} r{r}");
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn helpers_keep_the_layout() {
+        for size in MATRIX_SIZES {
+            let mut matrix = zero(*size);
+            let mut columns = matrix.columns();
+            for (i, value) in columns.iter_mut().flatten().enumerate() {
+                *value = i as f32
+            }
+            matrix.set_columns(&columns);
+            let before = matrix.to_le_bytes();
+
+            let mut transposed = matrix;
+            transposed.transpose();
+            transposed.transpose();
+            assert_eq!(transposed.to_le_bytes(), before, "{size:?}");
+
+            let mut scaled = matrix;
+            scaled.scale(2.0);
+            let doubled: Vec<Vec<f32>> = columns
+                .iter()
+                .map(|column| column.iter().map(|v| v * 2.0).collect())
+                .collect();
+            assert_eq!(scaled.columns(), doubled, "{size:?}");
+
+            let mut identity = matrix;
+            identity.set_identity();
+            for (c, column) in identity.columns().iter().enumerate() {
+                for (r, value) in column.iter().enumerate() {
+                    assert_eq!(*value, if c == r { 1.0 } else { 0.0 }, "{size:?}")
+                }
+            }
+        }
+
+        let mut m = MatrixUniformValue::M2x2(Column2(1.0, 2.0), Column2(3.0, 4.0));
+        m.transpose();
+        assert_eq!(
+            m,
+            MatrixUniformValue::M2x2(Column2(1.0, 3.0), Column2(2.0, 4.0))
+        );
+        // Non square matrices keep their value
+        let mut m =
+            MatrixUniformValue::M3x2(Column2(1.0, 2.0), Column2(3.0, 4.0), Column2(5.0, 6.0));
+        let before = m;
+        m.transpose();
+        assert_eq!(m, before);
+    }
+}
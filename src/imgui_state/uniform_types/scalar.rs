@@ -8,6 +8,7 @@ use crate::imgui_state::{ImguiScalar, ImguiUniformSelectable, UniformEditEvent};
 use super::{
     cast_f32_u32, cast_i32_u32,
     matrix::{Column2, Column3, Column4, MatrixUniformValue},
+    sanitize_f32,
     transform::TransformUniformValue,
     vec::{Vec2UniformValue, Vec3UniformValue, Vec4UniformValue},
     MatrixType, UniformType, UniformValue, VecType, VectorUniformValue,
@@ -20,7 +21,7 @@ pub(crate) union ScalarPrimitive {
 }
 
 #[derive(Debug, Clone, Copy, PartialEq)]
-pub(crate) enum ScalarUniformValue {
+pub enum ScalarUniformValue {
     U32(u32),
     I32(i32),
     F32(f32),
@@ -135,10 +136,34 @@ impl ImguiUniformSelectable for ScalarUniformValue {
             ScalarUniformValue::F32(s) => s.to_le_bytes().into(),
         }
     }
+
+    fn write_le_bytes(&self, out: &mut Vec<u8>) {
+        match self {
+            ScalarUniformValue::U32(s) => out.extend(s.to_le_bytes()),
+            ScalarUniformValue::I32(s) => out.extend(s.to_le_bytes()),
+            ScalarUniformValue::F32(s) => out.extend(s.to_le_bytes()),
+        }
+    }
 }
 
 impl ScalarUniformValue {
-    fn cast_to_scalar(self, s: ScalarType) -> ScalarUniformValue {
+    /// Replaces NaN and infinity, returns true if there were any
+    pub(super) fn sanitize(&mut self) -> bool {
+        match self {
+            ScalarUniformValue::F32(v) => sanitize_f32(v),
+            ScalarUniformValue::U32(_) | ScalarUniformValue::I32(_) => false,
+        }
+    }
+
+    pub(crate) fn scalar_type(&self) -> ScalarType {
+        match self {
+            ScalarUniformValue::U32(_) => ScalarType::U32,
+            ScalarUniformValue::I32(_) => ScalarType::I32,
+            ScalarUniformValue::F32(_) => ScalarType::F32,
+        }
+    }
+
+    pub(crate) fn cast_to_scalar(self, s: ScalarType) -> ScalarUniformValue {
         match (self, s) {
             (ScalarUniformValue::U32(v), ScalarType::I32) => ScalarUniformValue::I32(v as i32),
             (ScalarUniformValue::U32(v), ScalarType::F32) => ScalarUniformValue::F32(v as f32),
@@ -5,12 +5,13 @@ use crate::imgui_state::{ImguiUniformSelectable, UniformEditEvent};
 
 use super::{
     matrix::{Column2, Column3, Column4, MatrixUniformValue},
+    sanitize_f32,
     vec::{Vec2UniformValue, Vec3UniformValue, Vec4UniformValue, VectorUniformValue},
     MatrixType, ScalarType, ScalarUniformValue, UniformType, UniformValue, VecType,
 };
 
 #[derive(Debug, Clone, Copy, PartialEq)]
-pub(crate) struct TransformUniformValue {
+pub struct TransformUniformValue {
     translation: Vector3<f32>,
     x_scale: f32,
     y_scale: f32,
@@ -19,6 +20,28 @@ pub(crate) struct TransformUniformValue {
 }
 
 impl TransformUniformValue {
+    /// Replaces NaN and infinity, returns true if there were any
+    pub(super) fn sanitize(&mut self) -> bool {
+        sanitize_f32(&mut self.translation.x)
+            | sanitize_f32(&mut self.translation.y)
+            | sanitize_f32(&mut self.translation.z)
+            | sanitize_f32(&mut self.x_scale)
+            | sanitize_f32(&mut self.y_scale)
+            | sanitize_f32(&mut self.z_scale)
+            | sanitize_f32(&mut self.rotation.v.x)
+            | sanitize_f32(&mut self.rotation.v.y)
+            | sanitize_f32(&mut self.rotation.v.z)
+            | sanitize_f32(&mut self.rotation.s)
+    }
+
+    pub(crate) fn translation(&self) -> Vector3<f32> {
+        self.translation
+    }
+
+    pub(crate) fn translate(&mut self, offset: Vector3<f32>) {
+        self.translation += offset
+    }
+
     fn cast_to_scalar(&self, s: ScalarType) -> UniformValue {
         UniformValue::Scalar(match s {
             ScalarType::U32 => ScalarUniformValue::U32(0),
@@ -147,12 +170,17 @@ impl TransformUniformValue {
 
 trait Byteable {
     fn to_le_bytes(&self) -> Vec<u8>;
+    fn write_le_bytes(&self, out: &mut Vec<u8>);
 }
 
 impl Byteable for Matrix4<f32> {
     fn to_le_bytes(&self) -> Vec<u8> {
         self.x.to_le_bytes()
     }
+
+    fn write_le_bytes(&self, out: &mut Vec<u8>) {
+        self.x.write_le_bytes(out)
+    }
 }
 
 impl Byteable for Vector4<f32> {
@@ -165,6 +193,17 @@ impl Byteable for Vector4<f32> {
             .chain(self.w.to_le_bytes())
             .collect()
     }
+
+    fn write_le_bytes(&self, out: &mut Vec<u8>) {
+        out.extend(
+            self.x
+                .to_le_bytes()
+                .into_iter()
+                .chain(self.y.to_le_bytes())
+                .chain(self.z.to_le_bytes())
+                .chain(self.w.to_le_bytes()),
+        )
+    }
 }
 
 impl ImguiUniformSelectable for TransformUniformValue {
@@ -272,6 +311,13 @@ impl ImguiUniformSelectable for TransformUniformValue {
             * Matrix4::from_nonuniform_scale(self.x_scale, self.y_scale, self.z_scale);
         transfrom.to_le_bytes()
     }
+
+    fn write_le_bytes(&self, out: &mut Vec<u8>) {
+        let transfrom = Matrix4::from_translation(self.translation)
+            * Matrix4::from(self.rotation)
+            * Matrix4::from_nonuniform_scale(self.x_scale, self.y_scale, self.z_scale);
+        transfrom.write_le_bytes(out)
+    }
 }
 
 impl Default for TransformUniformValue {
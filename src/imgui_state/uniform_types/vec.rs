@@ -1,20 +1,24 @@
 use std::borrow::Cow;
 
-use imgui::Ui;
-use mint::{Vector3, Vector4};
+use imgui::{ItemHoveredFlags, Ui};
 use serde_json::{Map, Value as JsonValue};
 
 use crate::imgui_state::{
-    uniform_types::{scalar::ScalarPrimitive, ScalarType},
+    uniform_types::{
+        color::{show_color_editor, ColorOptions},
+        scalar::ScalarPrimitive,
+        ScalarType,
+    },
     ImguiUniformSelectable, ImguiVec, UniformEditEvent,
 };
 
 use super::{
     cast_f32_u32, cast_i32_u32,
     matrix::{Column2, Column3, Column4, MatrixUniformValue},
+    sanitize_f32,
     scalar::ScalarUniformValue,
     transform::TransformUniformValue,
-    MatrixType, UniformType, UniformValue,
+    ExtendedUi, MatrixType, Tooltip, UniformType, UniformValue,
 };
 
 trait VecUniformValue {
@@ -23,10 +27,13 @@ trait VecUniformValue {
         ui: &Ui,
         group_index: usize,
         binding_index: usize,
+        display: VecDisplay,
+        color: &mut ColorOptions,
         message: &mut Option<UniformEditEvent>,
     );
     fn change_inner_type(&mut self, inner_type: ScalarType);
     fn to_le_bytes(&self) -> Vec<u8>;
+    fn write_le_bytes(&self, out: &mut Vec<u8>);
     fn cast_to_scalar(&self, s: ScalarType) -> UniformValue;
     fn cast_to_vec(&self, v: VecType) -> UniformValue;
     fn cast_to_matrix(&self, m: MatrixType) -> UniformValue;
@@ -35,8 +42,101 @@ trait VecUniformValue {
     fn to_json(&self, json_obj: &mut Map<String, JsonValue>);
 }
 
+/// Shows the magnitude of the vector and a button to normalize it, returns true if it was
+fn show_normalize(
+    ui: &Ui,
+    group_index: usize,
+    binding_index: usize,
+    components: &mut [f32],
+) -> bool {
+    let magnitude = components.iter().map(|c| c * c).sum::<f32>().sqrt();
+    ui.text(format!("Magnitude: {magnitude:.4}"));
+    ui.same_line();
+    let can_normalize = magnitude > 0.0 && magnitude.is_finite();
+    let mut normalized = false;
+    ui.disabled(!can_normalize, || {
+        if ui.small_button(format!("Normalize##normalize_{group_index}_{binding_index}")) {
+            for component in components.iter_mut() {
+                *component /= magnitude
+            }
+            normalized = true
+        }
+    });
+    if !can_normalize && ui.is_item_hovered_with_flags(ItemHoveredFlags::ALLOW_WHEN_DISABLED) {
+        ui.tooltip_text("A zero vector has no direction, it's left as it is")
+    }
+
+    normalized
+}
+
+/// Numeric inputs in a collapsed tree node, for colors that only show the color picker.
+/// Returns true if they were edited
+fn show_exact_values(
+    ui: &Ui,
+    group_index: usize,
+    binding_index: usize,
+    components: &mut [f32],
+) -> bool {
+    let Some(_node) = ui.tree_node(format!("Exact values##exact_{group_index}_{binding_index}"))
+    else {
+        return false;
+    };
+    ui.input_scalar_n(
+        format!("##exact_edit_{group_index}_{binding_index}"),
+        components,
+    )
+    .build()
+}
+
+/// Which editors an f32 vec3 or vec4 gets
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum VecDisplay {
+    /// Numeric inputs and a color picker
+    Both,
+    Color,
+    Vector,
+}
+
+impl VecDisplay {
+    const ALL: [VecDisplay; 3] = [VecDisplay::Both, VecDisplay::Color, VecDisplay::Vector];
+
+    fn label(self) -> &'static str {
+        match self {
+            VecDisplay::Both => "Both",
+            VecDisplay::Color => "Color",
+            VecDisplay::Vector => "Vector",
+        }
+    }
+
+    fn shows_numbers(self) -> bool {
+        self != VecDisplay::Color
+    }
+
+    pub(crate) fn shows_color(self) -> bool {
+        self != VecDisplay::Vector
+    }
+
+    pub(crate) fn from_json(json_val: &JsonValue) -> Option<VecDisplay> {
+        match json_val.as_str()? {
+            "both" => Some(VecDisplay::Both),
+            "color" => Some(VecDisplay::Color),
+            "vector" => Some(VecDisplay::Vector),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn to_json(self) -> JsonValue {
+        match self {
+            VecDisplay::Both => "both",
+            VecDisplay::Color => "color",
+            VecDisplay::Vector => "vector",
+        }
+        .into()
+    }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq)]
-pub(crate) enum Vec2UniformValue {
+pub enum Vec2UniformValue {
     U32(u32, u32),
     I32(i32, i32),
     F32(f32, f32),
@@ -48,6 +148,8 @@ impl VecUniformValue for Vec2UniformValue {
         ui: &Ui,
         group_index: usize,
         binding_index: usize,
+        _display: VecDisplay,
+        _color: &mut ColorOptions,
         message: &mut Option<UniformEditEvent>,
     ) {
         match self {
@@ -125,6 +227,20 @@ impl VecUniformValue for Vec2UniformValue {
         }
     }
 
+    fn write_le_bytes(&self, out: &mut Vec<u8>) {
+        match self {
+            Vec2UniformValue::U32(x, y) => {
+                out.extend(x.to_le_bytes().into_iter().chain(y.to_le_bytes()))
+            }
+            Vec2UniformValue::I32(x, y) => {
+                out.extend(x.to_le_bytes().into_iter().chain(y.to_le_bytes()))
+            }
+            Vec2UniformValue::F32(x, y) => {
+                out.extend(x.to_le_bytes().into_iter().chain(y.to_le_bytes()))
+            }
+        }
+    }
+
     fn cast_to_scalar(&self, s: ScalarType) -> UniformValue {
         UniformValue::Scalar(match self {
             Vec2UniformValue::U32(x, _) => match s {
@@ -349,7 +465,7 @@ impl VecUniformValue for Vec2UniformValue {
 }
 
 #[derive(Debug, Clone, Copy, PartialEq)]
-pub(crate) enum Vec3UniformValue {
+pub enum Vec3UniformValue {
     U32(u32, u32, u32),
     I32(i32, i32, i32),
     F32(f32, f32, f32),
@@ -361,6 +477,8 @@ impl VecUniformValue for Vec3UniformValue {
         ui: &Ui,
         group_index: usize,
         binding_index: usize,
+        display: VecDisplay,
+        color: &mut ColorOptions,
         message: &mut Option<UniformEditEvent>,
     ) {
         match self {
@@ -389,28 +507,46 @@ impl VecUniformValue for Vec3UniformValue {
                 }
             }
             Vec3UniformValue::F32(x, y, z) => {
-                let mut c_vars = Vector3 {
-                    x: *x,
-                    y: *y,
-                    z: *z,
-                };
-                let mut vars = [*x, *y, *z];
-                if ui
-                    .input_scalar_n(format!("##v3edit_{group_index}_{binding_index}"), &mut vars)
-                    .build()
+                if display.shows_numbers() {
+                    let mut vars = [*x, *y, *z];
+                    if ui
+                        .input_scalar_n(
+                            format!("##v3edit_{group_index}_{binding_index}"),
+                            &mut vars,
+                        )
+                        .build()
+                    {
+                        *x = vars[0];
+                        *y = vars[1];
+                        *z = vars[2];
+                        *message = Some(UniformEditEvent::UpdateBuffer(group_index, binding_index))
+                    }
+                }
+                let mut components = [*x, *y, *z];
+                if display.shows_color()
+                    && show_color_editor(ui, group_index, binding_index, &mut components, color)
                 {
-                    *x = vars[0];
-                    *y = vars[1];
-                    *z = vars[2];
+                    *x = components[0];
+                    *y = components[1];
+                    *z = components[2];
                     *message = Some(UniformEditEvent::UpdateBuffer(group_index, binding_index))
                 }
-                if ui.color_edit3(
-                    format!("##v3edit_{group_index}_{binding_index}"),
-                    &mut c_vars,
-                ) {
-                    *x = c_vars.x;
-                    *y = c_vars.y;
-                    *z = c_vars.z;
+                let mut components = [*x, *y, *z];
+                if !display.shows_numbers()
+                    && show_exact_values(ui, group_index, binding_index, &mut components)
+                {
+                    *x = components[0];
+                    *y = components[1];
+                    *z = components[2];
+                    *message = Some(UniformEditEvent::UpdateBuffer(group_index, binding_index))
+                }
+                let mut components = [*x, *y, *z];
+                if display.shows_numbers()
+                    && show_normalize(ui, group_index, binding_index, &mut components)
+                {
+                    *x = components[0];
+                    *y = components[1];
+                    *z = components[2];
                     *message = Some(UniformEditEvent::UpdateBuffer(group_index, binding_index))
                 }
             }
@@ -466,6 +602,29 @@ impl VecUniformValue for Vec3UniformValue {
         }
     }
 
+    fn write_le_bytes(&self, out: &mut Vec<u8>) {
+        match self {
+            Vec3UniformValue::U32(x, y, z) => out.extend(
+                x.to_le_bytes()
+                    .into_iter()
+                    .chain(y.to_le_bytes())
+                    .chain(z.to_le_bytes()),
+            ),
+            Vec3UniformValue::I32(x, y, z) => out.extend(
+                x.to_le_bytes()
+                    .into_iter()
+                    .chain(y.to_le_bytes())
+                    .chain(z.to_le_bytes()),
+            ),
+            Vec3UniformValue::F32(x, y, z) => out.extend(
+                x.to_le_bytes()
+                    .into_iter()
+                    .chain(y.to_le_bytes())
+                    .chain(z.to_le_bytes()),
+            ),
+        }
+    }
+
     fn cast_to_scalar(&self, s: ScalarType) -> UniformValue {
         UniformValue::Scalar(match self {
             Vec3UniformValue::U32(x, _, _) => match s {
@@ -706,7 +865,7 @@ impl VecUniformValue for Vec3UniformValue {
 }
 
 #[derive(Debug, Clone, Copy, PartialEq)]
-pub(crate) enum Vec4UniformValue {
+pub enum Vec4UniformValue {
     U32(u32, u32, u32, u32),
     I32(i32, i32, i32, i32),
     F32(f32, f32, f32, f32),
@@ -718,6 +877,8 @@ impl VecUniformValue for Vec4UniformValue {
         ui: &Ui,
         group_index: usize,
         binding_index: usize,
+        display: VecDisplay,
+        color: &mut ColorOptions,
         message: &mut Option<UniformEditEvent>,
     ) {
         match self {
@@ -748,31 +909,50 @@ impl VecUniformValue for Vec4UniformValue {
                 }
             }
             Vec4UniformValue::F32(x, y, z, w) => {
-                let mut c_vars = Vector4 {
-                    x: *x,
-                    y: *y,
-                    z: *z,
-                    w: *w,
-                };
-                let mut vars = [*x, *y, *z, *w];
-                if ui
-                    .input_scalar_n(format!("##v4edit_{group_index}_{binding_index}"), &mut vars)
-                    .build()
+                if display.shows_numbers() {
+                    let mut vars = [*x, *y, *z, *w];
+                    if ui
+                        .input_scalar_n(
+                            format!("##v4edit_{group_index}_{binding_index}"),
+                            &mut vars,
+                        )
+                        .build()
+                    {
+                        *x = vars[0];
+                        *y = vars[1];
+                        *z = vars[2];
+                        *w = vars[3];
+                        *message = Some(UniformEditEvent::UpdateBuffer(group_index, binding_index))
+                    }
+                }
+                let mut components = [*x, *y, *z, *w];
+                if display.shows_color()
+                    && show_color_editor(ui, group_index, binding_index, &mut components, color)
                 {
-                    *x = vars[0];
-                    *y = vars[1];
-                    *z = vars[2];
-                    *w = vars[3];
+                    *x = components[0];
+                    *y = components[1];
+                    *z = components[2];
+                    *w = components[3];
+                    *message = Some(UniformEditEvent::UpdateBuffer(group_index, binding_index))
+                }
+                let mut components = [*x, *y, *z, *w];
+                if !display.shows_numbers()
+                    && show_exact_values(ui, group_index, binding_index, &mut components)
+                {
+                    *x = components[0];
+                    *y = components[1];
+                    *z = components[2];
+                    *w = components[3];
                     *message = Some(UniformEditEvent::UpdateBuffer(group_index, binding_index))
                 }
-                if ui.color_edit4(
-                    format!("##v4edit_{group_index}_{binding_index}"),
-                    &mut c_vars,
-                ) {
-                    *x = c_vars.x;
-                    *y = c_vars.y;
-                    *z = c_vars.z;
-                    *w = c_vars.w;
+                let mut components = [*x, *y, *z, *w];
+                if display.shows_numbers()
+                    && show_normalize(ui, group_index, binding_index, &mut components)
+                {
+                    *x = components[0];
+                    *y = components[1];
+                    *z = components[2];
+                    *w = components[3];
                     *message = Some(UniformEditEvent::UpdateBuffer(group_index, binding_index))
                 }
             }
@@ -847,6 +1027,32 @@ impl VecUniformValue for Vec4UniformValue {
         }
     }
 
+    fn write_le_bytes(&self, out: &mut Vec<u8>) {
+        match self {
+            Vec4UniformValue::U32(x, y, z, w) => out.extend(
+                x.to_le_bytes()
+                    .into_iter()
+                    .chain(y.to_le_bytes())
+                    .chain(z.to_le_bytes())
+                    .chain(w.to_le_bytes()),
+            ),
+            Vec4UniformValue::I32(x, y, z, w) => out.extend(
+                x.to_le_bytes()
+                    .into_iter()
+                    .chain(y.to_le_bytes())
+                    .chain(z.to_le_bytes())
+                    .chain(w.to_le_bytes()),
+            ),
+            Vec4UniformValue::F32(x, y, z, w) => out.extend(
+                x.to_le_bytes()
+                    .into_iter()
+                    .chain(y.to_le_bytes())
+                    .chain(z.to_le_bytes())
+                    .chain(w.to_le_bytes()),
+            ),
+        }
+    }
+
     fn cast_to_scalar(&self, s: ScalarType) -> UniformValue {
         UniformValue::Scalar(match self {
             Vec4UniformValue::U32(x, _, _, _) => match s {
@@ -1093,7 +1299,7 @@ impl VecUniformValue for Vec4UniformValue {
 }
 
 #[derive(Debug, Clone, Copy, PartialEq)]
-pub(crate) enum VectorUniformValue {
+pub enum VectorUniformValue {
     Vec2(Vec2UniformValue),
     Vec3(Vec3UniformValue),
     Vec4(Vec4UniformValue),
@@ -1131,6 +1337,61 @@ impl ImguiUniformSelectable for VectorUniformValue {
         group_index: usize,
         binding_index: usize,
         val_name: &mut String,
+    ) -> Option<UniformEditEvent> {
+        self.show_editor_with_display(
+            ui,
+            group_index,
+            binding_index,
+            val_name,
+            &mut VecDisplay::Both,
+            &mut ColorOptions::default(),
+        )
+    }
+
+    fn to_le_bytes(&self) -> Vec<u8> {
+        match self {
+            VectorUniformValue::Vec2(v) => v.to_le_bytes(),
+            VectorUniformValue::Vec3(v) => v.to_le_bytes(),
+            VectorUniformValue::Vec4(v) => v.to_le_bytes(),
+        }
+    }
+
+    fn write_le_bytes(&self, out: &mut Vec<u8>) {
+        match self {
+            VectorUniformValue::Vec2(v) => v.write_le_bytes(out),
+            VectorUniformValue::Vec3(v) => v.write_le_bytes(out),
+            VectorUniformValue::Vec4(v) => v.write_le_bytes(out),
+        }
+    }
+}
+
+impl VectorUniformValue {
+    /// Replaces NaN and infinity, returns true if there were any
+    pub(super) fn sanitize(&mut self) -> bool {
+        match self {
+            VectorUniformValue::Vec2(Vec2UniformValue::F32(x, y)) => {
+                sanitize_f32(x) | sanitize_f32(y)
+            }
+            VectorUniformValue::Vec3(Vec3UniformValue::F32(x, y, z)) => {
+                sanitize_f32(x) | sanitize_f32(y) | sanitize_f32(z)
+            }
+            VectorUniformValue::Vec4(Vec4UniformValue::F32(x, y, z, w)) => {
+                sanitize_f32(x) | sanitize_f32(y) | sanitize_f32(z) | sanitize_f32(w)
+            }
+            _ => false,
+        }
+    }
+
+    /// Like `show_editor`, f32 vec3 and vec4 also get a selector of which editors they show,
+    /// and the color options of their color picker
+    pub(crate) fn show_editor_with_display(
+        &mut self,
+        ui: &Ui,
+        group_index: usize,
+        binding_index: usize,
+        val_name: &mut String,
+        display: &mut VecDisplay,
+        color: &mut ColorOptions,
     ) -> Option<UniformEditEvent> {
         let mut message = None;
         match self {
@@ -1154,8 +1415,16 @@ impl ImguiUniformSelectable for VectorUniformValue {
                     binding_index,
                     &mut message,
                     inner_type_index,
+                    VecType::Vec2,
+                );
+                v.show_editor(
+                    ui,
+                    group_index,
+                    binding_index,
+                    *display,
+                    color,
+                    &mut message,
                 );
-                v.show_editor(ui, group_index, binding_index, &mut message);
             }
             VectorUniformValue::Vec3(v) => {
                 UniformValue::show_primitive_selector(
@@ -1177,8 +1446,24 @@ impl ImguiUniformSelectable for VectorUniformValue {
                     binding_index,
                     &mut message,
                     inner_type_index,
+                    VecType::Vec3,
+                );
+                if let Vec3UniformValue::F32(..) = v {
+                    VectorUniformValue::show_display_selector(
+                        ui,
+                        group_index,
+                        binding_index,
+                        display,
+                    );
+                }
+                v.show_editor(
+                    ui,
+                    group_index,
+                    binding_index,
+                    *display,
+                    color,
+                    &mut message,
                 );
-                v.show_editor(ui, group_index, binding_index, &mut message);
             }
             VectorUniformValue::Vec4(v) => {
                 UniformValue::show_primitive_selector(
@@ -1200,29 +1485,60 @@ impl ImguiUniformSelectable for VectorUniformValue {
                     binding_index,
                     &mut message,
                     inner_type_index,
+                    VecType::Vec4,
+                );
+                if let Vec4UniformValue::F32(..) = v {
+                    VectorUniformValue::show_display_selector(
+                        ui,
+                        group_index,
+                        binding_index,
+                        display,
+                    );
+                }
+                v.show_editor(
+                    ui,
+                    group_index,
+                    binding_index,
+                    *display,
+                    color,
+                    &mut message,
                 );
-                v.show_editor(ui, group_index, binding_index, &mut message);
             }
         };
         message
     }
 
-    fn to_le_bytes(&self) -> Vec<u8> {
-        match self {
-            VectorUniformValue::Vec2(v) => v.to_le_bytes(),
-            VectorUniformValue::Vec3(v) => v.to_le_bytes(),
-            VectorUniformValue::Vec4(v) => v.to_le_bytes(),
+    fn show_display_selector(
+        ui: &Ui,
+        group_index: usize,
+        binding_index: usize,
+        display: &mut VecDisplay,
+    ) {
+        const COMBO_WIDTH: f32 = 70.0;
+
+        ui.set_next_item_width(COMBO_WIDTH);
+        ui.same_line();
+        let mut selection = VecDisplay::ALL
+            .iter()
+            .position(|d| *d == *display)
+            .unwrap_or(0);
+        if ui.combo(
+            format!("##vec_display_{group_index}_{binding_index}"),
+            &mut selection,
+            &VecDisplay::ALL,
+            |display| Cow::Borrowed(display.label()),
+        ) {
+            *display = VecDisplay::ALL[selection]
         }
     }
-}
 
-impl VectorUniformValue {
     fn show_scalar_selector(
         ui: &Ui,
         group_index: usize,
         binding_index: usize,
         message: &mut Option<UniformEditEvent>,
         type_index: usize,
+        vec_type: fn(ScalarType) -> VecType,
     ) {
         const TYPES: &[ScalarType] = &[ScalarType::U32, ScalarType::I32, ScalarType::F32];
         const COMBO_WIDTH: f32 = 50.0;
@@ -1230,11 +1546,12 @@ impl VectorUniformValue {
         ui.set_next_item_width(COMBO_WIDTH);
         let mut selection = type_index;
         ui.same_line();
-        if ui.combo(
+        if ui.tooltip_combo(
             format!("##scalar_combo_{group_index}_{binding_index}"),
             &mut selection,
             TYPES,
             |unitype| unitype.into(),
+            |scalar| Tooltip::Type(UniformType::Vec(vec_type(*scalar))),
         ) {
             let selected_type = TYPES[selection];
             if selected_type != TYPES[type_index] {
@@ -0,0 +1,204 @@
+use std::ops::Range;
+
+/// Uniform writes of the current frame. They are issued together right before submitting,
+/// so a binding that changes several times in a frame, like while dragging a slider,
+/// is only written once
+#[derive(Debug, Default)]
+pub(super) struct UniformWrites {
+    /// Bytes of the pending writes, back to back. Reused every frame
+    scratch: Vec<u8>,
+    /// Bindings whose value changed this frame
+    dirty: Vec<(usize, usize)>,
+    /// Writes of bytes that aren't the binding's value, like the elapsed time.
+    /// They replace the write of the value
+    overrides: Vec<((usize, usize), Range<usize>)>,
+    /// Writes requested this frame
+    requested: u32,
+    last_frame: WriteStats,
+}
+
+/// Where the pending writes end up
+pub(super) trait WriteTarget {
+    /// Appends the value of the binding, returns false if it doesn't exist anymore
+    fn value(&self, location: (usize, usize), out: &mut Vec<u8>) -> bool;
+    /// Returns false if the bytes couldn't be written
+    fn write(&mut self, location: (usize, usize), bytes: &[u8]) -> bool;
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct WriteStats {
+    /// Writes requested during the frame, each of them used to be a write_buffer call
+    pub(crate) requested: u32,
+    /// write_buffer calls actually issued
+    pub(crate) issued: u32,
+}
+
+impl UniformWrites {
+    /// The binding's value will be written before the next submit
+    pub(super) fn mark_dirty(&mut self, location: (usize, usize)) {
+        self.requested += 1;
+        if !self.dirty.contains(&location) {
+            self.dirty.push(location)
+        }
+    }
+
+    /// Bytes other than the binding's value will be written before the next submit, instead
+    /// of its value
+    pub(super) fn override_with(
+        &mut self,
+        location: (usize, usize),
+        write: impl FnOnce(&mut Vec<u8>),
+    ) {
+        let start = self.scratch.len();
+        write(&mut self.scratch);
+        let range = start..self.scratch.len();
+        self.requested += 1;
+        match self
+            .overrides
+            .iter_mut()
+            .find(|(other, _)| *other == location)
+        {
+            Some((_, old_range)) => *old_range = range,
+            None => self.overrides.push((location, range)),
+        }
+    }
+
+    /// Hands every pending write to the target, at most once per binding
+    pub(super) fn flush(&mut self, target: &mut impl WriteTarget) {
+        let mut issued = 0;
+        for location in self.dirty.drain(..) {
+            if self.overrides.iter().any(|(other, _)| *other == location) {
+                continue;
+            }
+
+            let start = self.scratch.len();
+            if target.value(location, &mut self.scratch)
+                && target.write(location, &self.scratch[start..])
+            {
+                issued += 1
+            }
+            self.scratch.truncate(start);
+        }
+
+        for (location, range) in self.overrides.drain(..) {
+            if target.write(location, &self.scratch[range]) {
+                issued += 1
+            }
+        }
+
+        self.scratch.clear();
+        self.last_frame = WriteStats {
+            requested: std::mem::take(&mut self.requested),
+            issued,
+        };
+    }
+
+    pub(super) fn last_frame(&self) -> WriteStats {
+        self.last_frame
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Stands in for the queue, recording every write it gets.
+    /// The value of every binding is its location
+    #[derive(Default)]
+    struct MockQueue {
+        writes: Vec<((usize, usize), Vec<u8>)>,
+        removed: Vec<(usize, usize)>,
+    }
+
+    impl WriteTarget for MockQueue {
+        fn value(&self, location: (usize, usize), out: &mut Vec<u8>) -> bool {
+            if self.removed.contains(&location) {
+                return false;
+            }
+            out.extend([location.0 as u8, location.1 as u8]);
+            true
+        }
+
+        fn write(&mut self, location: (usize, usize), bytes: &[u8]) -> bool {
+            self.writes.push((location, bytes.to_vec()));
+            true
+        }
+    }
+
+    #[test]
+    fn dragging_writes_once_per_frame() {
+        let mut writes = UniformWrites::default();
+        let mut queue = MockQueue::default();
+        for _ in 0..100 {
+            writes.mark_dirty((0, 1));
+        }
+        writes.flush(&mut queue);
+
+        assert_eq!(queue.writes, vec![((0, 1), vec![0, 1])]);
+        assert_eq!(
+            writes.last_frame(),
+            WriteStats {
+                requested: 100,
+                issued: 1
+            }
+        );
+    }
+
+    #[test]
+    fn every_dirty_binding_is_written() {
+        let mut writes = UniformWrites::default();
+        let mut queue = MockQueue::default();
+        writes.mark_dirty((0, 1));
+        writes.mark_dirty((1, 0));
+        writes.mark_dirty((0, 1));
+        writes.flush(&mut queue);
+
+        assert_eq!(
+            queue.writes,
+            vec![((0, 1), vec![0, 1]), ((1, 0), vec![1, 0])]
+        );
+    }
+
+    #[test]
+    fn nothing_is_written_twice() {
+        let mut writes = UniformWrites::default();
+        let mut queue = MockQueue::default();
+        writes.mark_dirty((0, 0));
+        writes.flush(&mut queue);
+        writes.flush(&mut queue);
+
+        assert_eq!(queue.writes.len(), 1);
+        assert_eq!(writes.last_frame(), WriteStats::default());
+    }
+
+    #[test]
+    fn overrides_replace_the_value() {
+        let mut writes = UniformWrites::default();
+        let mut queue = MockQueue::default();
+        writes.mark_dirty((0, 0));
+        writes.override_with((0, 0), |out| out.extend([1, 2, 3, 4]));
+        writes.override_with((0, 0), |out| out.extend([5, 6, 7, 8]));
+        writes.flush(&mut queue);
+
+        assert_eq!(queue.writes, vec![((0, 0), vec![5, 6, 7, 8])]);
+        assert_eq!(
+            writes.last_frame(),
+            WriteStats {
+                requested: 3,
+                issued: 1
+            }
+        );
+    }
+
+    #[test]
+    fn removed_bindings_are_skipped() {
+        let mut writes = UniformWrites::default();
+        let mut queue = MockQueue::default();
+        writes.mark_dirty((3, 3));
+        queue.removed.push((3, 3));
+        writes.flush(&mut queue);
+
+        assert!(queue.writes.is_empty());
+        assert_eq!(writes.last_frame().issued, 0);
+    }
+}
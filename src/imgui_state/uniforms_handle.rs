@@ -0,0 +1,359 @@
+use std::{
+    error::Error,
+    fmt::{self, Display},
+    sync::mpsc::{self, Receiver, Sender},
+};
+
+use super::{
+    uniform_types::{ScalarUniformValue, Vec4UniformValue, VectorUniformValue},
+    UniformBinding, UniformValue, Uniforms,
+};
+
+/// Why a request of a `UniformsHandle` failed
+#[derive(Debug, Clone, PartialEq)]
+pub enum UniformsError {
+    GroupOutOfRange(usize),
+    /// Group and binding
+    BindingOutOfRange(usize, usize),
+    /// The value doesn't have the binding's type, which is given
+    TypeMismatch(UniformValue),
+    /// Builtins are updated by the app
+    Builtin,
+    /// Locked bindings ignore edits
+    Locked,
+    /// The event loop isn't running anymore
+    Disconnected,
+}
+
+impl Display for UniformsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            UniformsError::GroupOutOfRange(group) => write!(f, "There is no group {group}"),
+            UniformsError::BindingOutOfRange(group, binding) => {
+                write!(f, "Group {group} has no binding {binding}")
+            }
+            UniformsError::TypeMismatch(current) => {
+                write!(
+                    f,
+                    "The value doesn't have the binding's type, it's {current:?}"
+                )
+            }
+            UniformsError::Builtin => write!(f, "Builtin uniforms can't be changed"),
+            UniformsError::Locked => write!(f, "The binding is locked"),
+            UniformsError::Disconnected => write!(f, "The event loop isn't running"),
+        }
+    }
+}
+
+impl Error for UniformsError {}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct BindingInfo {
+    pub group: usize,
+    pub binding: usize,
+    pub name: String,
+    pub value: UniformValue,
+}
+
+enum Request {
+    Set {
+        location: (usize, usize),
+        value: UniformValue,
+        /// Changes the binding even if it's locked
+        override_lock: bool,
+        reply: Sender<Result<(), UniformsError>>,
+    },
+    Get {
+        location: (usize, usize),
+        reply: Sender<Result<UniformValue, UniformsError>>,
+    },
+    List {
+        reply: Sender<Vec<BindingInfo>>,
+    },
+}
+
+/// Changes the uniforms from any thread, without going through the UI. Requests are applied
+/// once per frame by the event loop, so they block until the next frame and mustn't be made
+/// from the event loop's thread
+#[derive(Debug, Clone)]
+pub struct UniformsHandle {
+    sender: Sender<Request>,
+}
+
+impl UniformsHandle {
+    fn request<T>(&self, request: impl FnOnce(Sender<T>) -> Request) -> Result<T, UniformsError> {
+        let (reply, response) = mpsc::channel();
+        self.sender
+            .send(request(reply))
+            .map_err(|_| UniformsError::Disconnected)?;
+        response.recv().map_err(|_| UniformsError::Disconnected)
+    }
+
+    /// Replaces the value of a binding, `value` must have the binding's type
+    pub fn set(
+        &self,
+        group: usize,
+        binding: usize,
+        value: UniformValue,
+    ) -> Result<(), UniformsError> {
+        self.request(|reply| Request::Set {
+            location: (group, binding),
+            value,
+            override_lock: false,
+            reply,
+        })?
+    }
+
+    /// Like `set`, but also replaces the value of a locked binding
+    pub fn force_set(
+        &self,
+        group: usize,
+        binding: usize,
+        value: UniformValue,
+    ) -> Result<(), UniformsError> {
+        self.request(|reply| Request::Set {
+            location: (group, binding),
+            value,
+            override_lock: true,
+            reply,
+        })?
+    }
+
+    pub fn set_f32(&self, group: usize, binding: usize, value: f32) -> Result<(), UniformsError> {
+        self.set(
+            group,
+            binding,
+            UniformValue::Scalar(ScalarUniformValue::F32(value)),
+        )
+    }
+
+    pub fn set_vec4(
+        &self,
+        group: usize,
+        binding: usize,
+        [x, y, z, w]: [f32; 4],
+    ) -> Result<(), UniformsError> {
+        let value = VectorUniformValue::Vec4(Vec4UniformValue::F32(x, y, z, w));
+        self.set(group, binding, UniformValue::Vector(value))
+    }
+
+    pub fn get(&self, group: usize, binding: usize) -> Result<UniformValue, UniformsError> {
+        self.request(|reply| Request::Get {
+            location: (group, binding),
+            reply,
+        })?
+    }
+
+    /// Every binding of every group, builtins included
+    pub fn list_bindings(&self) -> Result<Vec<BindingInfo>, UniformsError> {
+        self.request(|reply| Request::List { reply })
+    }
+}
+
+/// The event loop's end of the handles
+pub(crate) struct UniformRequests {
+    sender: Sender<Request>,
+    receiver: Receiver<Request>,
+}
+
+impl Default for UniformRequests {
+    fn default() -> Self {
+        let (sender, receiver) = mpsc::channel();
+        UniformRequests { sender, receiver }
+    }
+}
+
+impl UniformRequests {
+    pub(crate) fn handle(&self) -> UniformsHandle {
+        UniformsHandle {
+            sender: self.sender.clone(),
+        }
+    }
+
+    /// Applies every pending request. Values are written with the rest of the frame's writes
+    pub(crate) fn apply(&self, uniforms: &mut Uniforms) {
+        for request in self.receiver.try_iter() {
+            // A handle that stopped waiting for the reply isn't an error
+            match request {
+                Request::Set {
+                    location,
+                    value,
+                    override_lock,
+                    reply,
+                } => {
+                    let _ = reply.send(Self::write(uniforms, location, value, override_lock));
+                }
+                Request::Get { location, reply } => {
+                    let _ = reply.send(Self::value(uniforms, location));
+                }
+                Request::List { reply } => {
+                    let _ = reply.send(Self::list(uniforms));
+                }
+            }
+        }
+    }
+
+    fn binding(
+        uniforms: &Uniforms,
+        (g_index, b_index): (usize, usize),
+    ) -> Result<&UniformBinding, UniformsError> {
+        uniforms
+            .groups
+            .get(g_index)
+            .ok_or(UniformsError::GroupOutOfRange(g_index))?
+            .bindings
+            .get(b_index)
+            .ok_or(UniformsError::BindingOutOfRange(g_index, b_index))
+    }
+
+    pub(super) fn value(
+        uniforms: &Uniforms,
+        location: (usize, usize),
+    ) -> Result<UniformValue, UniformsError> {
+        Self::binding(uniforms, location).map(|binding| binding.value)
+    }
+
+    /// Locked bindings are left as they are
+    pub(super) fn set(
+        uniforms: &mut Uniforms,
+        location: (usize, usize),
+        value: UniformValue,
+    ) -> Result<(), UniformsError> {
+        Self::write(uniforms, location, value, false)
+    }
+
+    fn write(
+        uniforms: &mut Uniforms,
+        location: (usize, usize),
+        value: UniformValue,
+        override_lock: bool,
+    ) -> Result<(), UniformsError> {
+        let binding = Self::binding(uniforms, location)?;
+        if let UniformValue::BuiltIn(_) = binding.value {
+            return Err(UniformsError::Builtin);
+        }
+        if binding.locked && !override_lock {
+            return Err(UniformsError::Locked);
+        }
+        if !binding.value.same_type(&value) {
+            return Err(UniformsError::TypeMismatch(binding.value));
+        }
+
+        // Same type, so it fits in the binding's buffer like an edit in the UI
+        let binding = &mut uniforms.groups[location.0].bindings[location.1];
+        binding.value = value;
+        binding.sanitized = binding.value.sanitize();
+        uniforms.update_buffer(location.0, location.1);
+        Ok(())
+    }
+
+    fn list(uniforms: &Uniforms) -> Vec<BindingInfo> {
+        uniforms
+            .groups
+            .iter()
+            .enumerate()
+            .flat_map(|(group, bindings)| {
+                bindings
+                    .bindings
+                    .iter()
+                    .enumerate()
+                    .map(move |(binding, uniform)| BindingInfo {
+                        group,
+                        binding,
+                        name: uniform.name.clone(),
+                        value: uniform.value,
+                    })
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{thread, time::Duration};
+
+    use super::*;
+    use crate::{
+        imgui_state::Message,
+        state::tests::{assert_no_gpu_errors, headless_device, with_state},
+    };
+
+    #[test]
+    #[ignore = "needs a GPU and a window"]
+    fn set_then_get_round_trips() {
+        with_state("uniforms_handle", |state, _window| {
+            let value = UniformValue::Scalar(ScalarUniformValue::F32(0.0));
+            let group = state.im_state.ui.inputs.groups.len();
+            state
+                .im_state
+                .ui
+                .inputs
+                .push_value(&state.gpu.device, group, value)
+                .unwrap();
+            state.handle_message(Message::ReloadPipeline);
+            let handle = state.uniforms_handle();
+            let remote = thread::spawn(move || {
+                handle.set_f32(group, 0, 1.5)?;
+                let value = handle.get(group, 0)?;
+                let errors = [
+                    handle.set_f32(group + 1, 0, 1.0),
+                    handle.set_f32(group, 1, 1.0),
+                    handle.set_vec4(group, 0, [1.0; 4]),
+                    handle.set_f32(0, 0, 1.0),
+                ];
+                Ok::<_, UniformsError>((value, errors, handle.list_bindings()?))
+            });
+
+            // The part of the event loop that serves the handles
+            while !remote.is_finished() {
+                state.apply_uniform_requests();
+                state.im_state.ui.inputs.flush_writes(&state.gpu.queue);
+                thread::sleep(Duration::from_millis(1))
+            }
+            let (value, errors, bindings) = remote.join().unwrap().unwrap();
+
+            assert_eq!(value, UniformValue::Scalar(ScalarUniformValue::F32(1.5)));
+            let inputs = &state.im_state.ui.inputs;
+            assert_eq!(
+                inputs.groups[group].bindings[0].contents,
+                1.5f32.to_le_bytes().to_vec()
+            );
+            assert_eq!(
+                errors,
+                [
+                    Err(UniformsError::GroupOutOfRange(group + 1)),
+                    Err(UniformsError::BindingOutOfRange(group, 1)),
+                    Err(UniformsError::TypeMismatch(value)),
+                    Err(UniformsError::Builtin),
+                ]
+            );
+            let count: usize = inputs.groups.iter().map(|group| group.bindings.len()).sum();
+            assert_eq!(bindings.len(), count);
+            assert_eq!(bindings.last().map(|binding| binding.value), Some(value));
+            assert_no_gpu_errors(&state.gpu.device);
+        })
+    }
+
+    #[test]
+    #[ignore = "needs a GPU"]
+    fn locks_need_an_override() {
+        let (device, _queue) = headless_device();
+
+        let mut uniforms = Uniforms::new(&device).unwrap();
+        let value = UniformValue::Scalar(ScalarUniformValue::F32(0.0));
+        uniforms.push_value(&device, 2, value).unwrap();
+        uniforms.groups[2].set_locked(0, true);
+        let new_value = UniformValue::Scalar(ScalarUniformValue::F32(2.0));
+
+        assert_eq!(
+            UniformRequests::set(&mut uniforms, (2, 0), new_value),
+            Err(UniformsError::Locked)
+        );
+        assert_eq!(UniformRequests::value(&uniforms, (2, 0)), Ok(value));
+        assert_eq!(
+            UniformRequests::write(&mut uniforms, (2, 0), new_value, true),
+            Ok(())
+        );
+        assert_eq!(UniformRequests::value(&uniforms, (2, 0)), Ok(new_value));
+    }
+}
@@ -0,0 +1,389 @@
+use imgui::Ui;
+use serde_json::{Map, Value as JsonValue};
+use winit::keyboard::KeyCode;
+
+use crate::{imgui_state::Message, settings::SETTINGS_FILE};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Action {
+    ReloadShader,
+    SaveParameters,
+    ToggleGrid,
+    ToggleMesh,
+    ToggleAlwaysOnTop,
+    ToggleFullscreen,
+    TogglePause,
+    ResetTime,
+}
+
+impl Action {
+    pub(crate) const ALL: &'static [Action] = &[
+        Action::ReloadShader,
+        Action::SaveParameters,
+        Action::ToggleGrid,
+        Action::ToggleMesh,
+        Action::ToggleAlwaysOnTop,
+        Action::ToggleFullscreen,
+        Action::TogglePause,
+        Action::ResetTime,
+    ];
+
+    pub(crate) fn description(&self) -> &'static str {
+        match self {
+            Action::ReloadShader => "Reload shader",
+            Action::SaveParameters => "Save parameters",
+            Action::ToggleGrid => "Toggle grid",
+            Action::ToggleMesh => "Toggle mesh wireframe",
+            Action::ToggleAlwaysOnTop => "Toggle always on top",
+            Action::ToggleFullscreen => "Toggle borderless fullscreen",
+            Action::TogglePause => "Pause/resume time",
+            Action::ResetTime => "Reset time",
+        }
+    }
+
+    fn json_name(&self) -> &'static str {
+        match self {
+            Action::ReloadShader => "reload_shader",
+            Action::SaveParameters => "save_parameters",
+            Action::ToggleGrid => "toggle_grid",
+            Action::ToggleMesh => "toggle_mesh",
+            Action::ToggleAlwaysOnTop => "toggle_always_on_top",
+            Action::ToggleFullscreen => "toggle_fullscreen",
+            Action::TogglePause => "toggle_pause",
+            Action::ResetTime => "reset_time",
+        }
+    }
+
+    pub(crate) fn message(&self) -> Message {
+        match self {
+            Action::ReloadShader => Message::ReloadShader,
+            Action::SaveParameters => Message::SaveParameters,
+            Action::ToggleGrid => Message::ToggleGrid,
+            Action::ToggleMesh => Message::ToggleMesh,
+            Action::ToggleAlwaysOnTop => Message::ToggleAlwaysOnTop,
+            Action::ToggleFullscreen => Message::ToggleFullscreen,
+            Action::TogglePause => Message::TogglePause,
+            Action::ResetTime => Message::ResetTime,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub(crate) struct Modifiers {
+    pub(crate) ctrl: bool,
+    pub(crate) shift: bool,
+    pub(crate) alt: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct KeyBinding {
+    key: KeyCode,
+    modifiers: Modifiers,
+}
+
+impl KeyBinding {
+    const fn new(key: KeyCode) -> KeyBinding {
+        KeyBinding {
+            key,
+            modifiers: Modifiers {
+                ctrl: false,
+                shift: false,
+                alt: false,
+            },
+        }
+    }
+
+    const fn ctrl(key: KeyCode) -> KeyBinding {
+        KeyBinding {
+            key,
+            modifiers: Modifiers {
+                ctrl: true,
+                shift: false,
+                alt: false,
+            },
+        }
+    }
+
+    pub(crate) fn label(&self) -> String {
+        let mut label = String::new();
+        if self.modifiers.ctrl {
+            label.push_str("Ctrl+")
+        }
+        if self.modifiers.shift {
+            label.push_str("Shift+")
+        }
+        if self.modifiers.alt {
+            label.push_str("Alt+")
+        }
+        label.push_str(key_name(self.key).unwrap_or("?"));
+
+        label
+    }
+
+    fn from_json(json_val: &JsonValue) -> Option<KeyBinding> {
+        let key = json_val.get("key")?.as_str()?;
+        let Some(key) = key_from_name(key) else {
+            log::warn!("Unknown key \"{key}\" in {SETTINGS_FILE}");
+            return None;
+        };
+
+        Some(KeyBinding {
+            key,
+            modifiers: Modifiers {
+                ctrl: json_val.get("ctrl")?.as_bool()?,
+                shift: json_val.get("shift")?.as_bool()?,
+                alt: json_val.get("alt")?.as_bool()?,
+            },
+        })
+    }
+
+    fn to_json(&self) -> JsonValue {
+        let mut json_obj = Map::new();
+        json_obj.insert("key".into(), key_name(self.key).unwrap_or("?").into());
+        json_obj.insert("ctrl".into(), self.modifiers.ctrl.into());
+        json_obj.insert("shift".into(), self.modifiers.shift.into());
+        json_obj.insert("alt".into(), self.modifiers.alt.into());
+        JsonValue::Object(json_obj)
+    }
+}
+
+pub(crate) struct Keybindings {
+    bindings: Vec<(Action, KeyBinding)>,
+    /// Action waiting for the next pressed key
+    capturing: Option<Action>,
+}
+
+impl Keybindings {
+    pub(crate) fn defaults() -> Vec<(Action, KeyBinding)> {
+        vec![
+            (Action::ReloadShader, KeyBinding::new(KeyCode::KeyQ)),
+            (Action::SaveParameters, KeyBinding::ctrl(KeyCode::KeyS)),
+            (Action::ToggleGrid, KeyBinding::new(KeyCode::KeyG)),
+            (Action::ToggleMesh, KeyBinding::new(KeyCode::KeyM)),
+            (Action::ToggleAlwaysOnTop, KeyBinding::new(KeyCode::KeyT)),
+            (Action::ToggleFullscreen, KeyBinding::new(KeyCode::F11)),
+            (Action::TogglePause, KeyBinding::new(KeyCode::Space)),
+            (Action::ResetTime, KeyBinding::new(KeyCode::KeyR)),
+        ]
+    }
+
+    pub(crate) fn new(bindings: Vec<(Action, KeyBinding)>) -> Keybindings {
+        Keybindings {
+            bindings,
+            capturing: None,
+        }
+    }
+
+    pub(crate) fn bindings(&self) -> &[(Action, KeyBinding)] {
+        &self.bindings
+    }
+
+    /// Anything missing or invalid keeps its default
+    pub(crate) fn bindings_from_json(json_val: &JsonValue) -> Vec<(Action, KeyBinding)> {
+        let mut bindings = Self::defaults();
+        let Some(json_bindings) = json_val.as_object() else {
+            return bindings;
+        };
+
+        for (action, binding) in bindings.iter_mut() {
+            if let Some(json_binding) = json_bindings.get(action.json_name()) {
+                match KeyBinding::from_json(json_binding) {
+                    Some(new_binding) => *binding = new_binding,
+                    None => log::warn!(
+                        "Invalid keybinding for \"{}\", using the default",
+                        action.json_name()
+                    ),
+                }
+            }
+        }
+
+        bindings
+    }
+
+    pub(crate) fn bindings_to_json(bindings: &[(Action, KeyBinding)]) -> JsonValue {
+        let mut json_bindings = Map::new();
+        for (action, binding) in bindings.iter() {
+            json_bindings.insert(action.json_name().into(), binding.to_json());
+        }
+        JsonValue::Object(json_bindings)
+    }
+
+    pub(crate) fn action_for(&self, key: KeyCode, modifiers: Modifiers) -> Option<Action> {
+        let pressed = KeyBinding { key, modifiers };
+        self.bindings
+            .iter()
+            .find(|(_, binding)| *binding == pressed)
+            .map(|(action, _)| *action)
+    }
+
+    pub(crate) fn is_capturing(&self) -> bool {
+        self.capturing.is_some()
+    }
+
+    /// Binds the pressed key to the action being captured. Escape cancels the capture.
+    /// Whether the bindings were changed, so they have to be saved
+    pub(crate) fn capture(&mut self, key: KeyCode, modifiers: Modifiers) -> bool {
+        let Some(action) = self.capturing else {
+            return false;
+        };
+
+        match key {
+            KeyCode::Escape => {
+                self.capturing = None;
+                false
+            }
+            // Wait for the actual key while modifiers are being held
+            KeyCode::ControlLeft
+            | KeyCode::ControlRight
+            | KeyCode::ShiftLeft
+            | KeyCode::ShiftRight
+            | KeyCode::AltLeft
+            | KeyCode::AltRight
+            | KeyCode::SuperLeft
+            | KeyCode::SuperRight => false,
+            key if key_name(key).is_none() => false,
+            key => {
+                for (bound_action, binding) in self.bindings.iter_mut() {
+                    if *bound_action == action {
+                        *binding = KeyBinding { key, modifiers }
+                    }
+                }
+                self.capturing = None;
+                true
+            }
+        }
+    }
+
+    fn conflict(&self, action: Action) -> Option<Action> {
+        let (_, binding) = self.bindings.iter().find(|(a, _)| *a == action)?;
+        self.bindings
+            .iter()
+            .find(|(other, other_binding)| *other != action && other_binding == binding)
+            .map(|(other, _)| *other)
+    }
+
+    /// Whether the bindings were changed, so they have to be saved
+    pub(crate) fn show_editor(&mut self, ui: &Ui) -> bool {
+        for (action, binding) in self.bindings.iter() {
+            ui.text(action.description());
+            ui.same_line_with_pos(180.0);
+            let label = if self.capturing == Some(*action) {
+                "Press a key...".to_string()
+            } else {
+                binding.label()
+            };
+            if ui.button(format!("{label}##rebind_{}", action.json_name())) {
+                self.capturing = Some(*action)
+            }
+            if let Some(other) = self.conflict(*action) {
+                ui.same_line();
+                ui.text_colored(
+                    [1.0, 0.0, 0.0, 1.0],
+                    format!("Conflicts with \"{}\"", other.description()),
+                );
+            }
+        }
+
+        if self.capturing.is_some() {
+            ui.text("Press Escape to cancel");
+        }
+        ui.separator();
+        if ui.button("Reset to defaults") {
+            self.bindings = Self::defaults();
+            self.capturing = None;
+            return true;
+        }
+
+        false
+    }
+}
+
+const KEY_NAMES: &[(KeyCode, &str)] = &[
+    (KeyCode::KeyA, "A"),
+    (KeyCode::KeyB, "B"),
+    (KeyCode::KeyC, "C"),
+    (KeyCode::KeyD, "D"),
+    (KeyCode::KeyE, "E"),
+    (KeyCode::KeyF, "F"),
+    (KeyCode::KeyG, "G"),
+    (KeyCode::KeyH, "H"),
+    (KeyCode::KeyI, "I"),
+    (KeyCode::KeyJ, "J"),
+    (KeyCode::KeyK, "K"),
+    (KeyCode::KeyL, "L"),
+    (KeyCode::KeyM, "M"),
+    (KeyCode::KeyN, "N"),
+    (KeyCode::KeyO, "O"),
+    (KeyCode::KeyP, "P"),
+    (KeyCode::KeyQ, "Q"),
+    (KeyCode::KeyR, "R"),
+    (KeyCode::KeyS, "S"),
+    (KeyCode::KeyT, "T"),
+    (KeyCode::KeyU, "U"),
+    (KeyCode::KeyV, "V"),
+    (KeyCode::KeyW, "W"),
+    (KeyCode::KeyX, "X"),
+    (KeyCode::KeyY, "Y"),
+    (KeyCode::KeyZ, "Z"),
+    (KeyCode::Digit0, "0"),
+    (KeyCode::Digit1, "1"),
+    (KeyCode::Digit2, "2"),
+    (KeyCode::Digit3, "3"),
+    (KeyCode::Digit4, "4"),
+    (KeyCode::Digit5, "5"),
+    (KeyCode::Digit6, "6"),
+    (KeyCode::Digit7, "7"),
+    (KeyCode::Digit8, "8"),
+    (KeyCode::Digit9, "9"),
+    (KeyCode::F1, "F1"),
+    (KeyCode::F2, "F2"),
+    (KeyCode::F3, "F3"),
+    (KeyCode::F4, "F4"),
+    (KeyCode::F5, "F5"),
+    (KeyCode::F6, "F6"),
+    (KeyCode::F7, "F7"),
+    (KeyCode::F8, "F8"),
+    (KeyCode::F9, "F9"),
+    (KeyCode::F10, "F10"),
+    (KeyCode::F11, "F11"),
+    (KeyCode::F12, "F12"),
+    (KeyCode::Space, "Space"),
+    (KeyCode::Enter, "Enter"),
+    (KeyCode::Tab, "Tab"),
+    (KeyCode::Backspace, "Backspace"),
+    (KeyCode::Delete, "Delete"),
+    (KeyCode::Insert, "Insert"),
+    (KeyCode::Home, "Home"),
+    (KeyCode::End, "End"),
+    (KeyCode::PageUp, "PageUp"),
+    (KeyCode::PageDown, "PageDown"),
+    (KeyCode::ArrowUp, "Up"),
+    (KeyCode::ArrowDown, "Down"),
+    (KeyCode::ArrowLeft, "Left"),
+    (KeyCode::ArrowRight, "Right"),
+    (KeyCode::Minus, "-"),
+    (KeyCode::Equal, "="),
+    (KeyCode::Comma, ","),
+    (KeyCode::Period, "."),
+    (KeyCode::Slash, "/"),
+    (KeyCode::Semicolon, ";"),
+    (KeyCode::Quote, "'"),
+    (KeyCode::BracketLeft, "["),
+    (KeyCode::BracketRight, "]"),
+    (KeyCode::Backslash, "\\"),
+    (KeyCode::Backquote, "`"),
+];
+
+fn key_name(key: KeyCode) -> Option<&'static str> {
+    KEY_NAMES
+        .iter()
+        .find(|(code, _)| *code == key)
+        .map(|(_, name)| *name)
+}
+
+fn key_from_name(name: &str) -> Option<KeyCode> {
+    KEY_NAMES
+        .iter()
+        .find(|(_, key_name)| *key_name == name)
+        .map(|(code, _)| *code)
+}
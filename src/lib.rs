@@ -0,0 +1,112 @@
+//! A playground for WGSL shaders, with an imgui editor for their uniforms.
+//!
+//! [`run`] opens it in its own window, like the binary does. To embed it in another app, create
+//! a [`Gpu`] and a [`State`] for a window of the host, and hand them every event of that window
+//! with [`run_event_loop`], or with [`ImState::handle_event`] and [`handle_window_event`] to
+//! handle some events first. Uniforms can be added with [`Uniforms::push_value`], and changed
+//! from other threads with a [`UniformsHandle`].
+
+use winit::{
+    dpi,
+    event_loop::EventLoopBuilder,
+    monitor::MonitorHandle,
+    window::{Window, WindowLevel},
+};
+
+pub use crate::{
+    event_handling::{handle_window_event, run_event_loop},
+    imgui_state::{
+        BindingInfo, BuiltinValue, Column2, Column3, Column4, ImState, MatrixUniformValue, Message,
+        RandomType, ScalarUniformValue, TimeUnit, TransformUniformValue, UniformValue, Uniforms,
+        UniformsError, UniformsHandle, Vec2UniformValue, Vec3UniformValue, Vec4UniformValue,
+        VectorUniformValue,
+    },
+    rendering::{handle_message, handle_render_message, render, RenderMessage},
+    state::{Error, Gpu, GpuOptions, Paths, StartupOptions, State},
+};
+
+const SCREEN_WIDTH: u32 = 768;
+const SCREEN_HEIGHT: u32 = 768;
+
+mod event_handling;
+mod gpu_errors;
+mod imgui_state;
+mod keybindings;
+mod reflection;
+mod rendering;
+mod rust_export;
+mod settings;
+mod state;
+mod web_export;
+
+/// Moves the window so it is fully inside the monitor that contains `position`.
+/// If no monitor contains it (e.g. it was disconnected), the primary monitor is used instead
+fn clamp_to_monitor(
+    window: &Window,
+    position: dpi::PhysicalPosition<i32>,
+    size: dpi::PhysicalSize<u32>,
+) -> dpi::PhysicalPosition<i32> {
+    let contains = |monitor: &MonitorHandle| {
+        let origin = monitor.position();
+        let monitor_size = monitor.size();
+        position.x >= origin.x
+            && position.y >= origin.y
+            && position.x < origin.x + monitor_size.width as i32
+            && position.y < origin.y + monitor_size.height as i32
+    };
+    let monitor = window
+        .available_monitors()
+        .find(contains)
+        .or_else(|| window.primary_monitor())
+        .or_else(|| window.available_monitors().next());
+    let Some(monitor) = monitor else {
+        return position;
+    };
+
+    // winit doesn't expose the work area, so the whole monitor is used
+    let origin = monitor.position();
+    let monitor_size = monitor.size();
+    let max_x = origin.x + monitor_size.width as i32 - size.width as i32;
+    let max_y = origin.y + monitor_size.height as i32 - size.height as i32;
+    dpi::PhysicalPosition::new(
+        position.x.clamp(origin.x, max_x.max(origin.x)),
+        position.y.clamp(origin.y, max_y.max(origin.y)),
+    )
+}
+
+/// Opens the playground in a new window and runs it until the window is closed.
+/// `size` overrides the size the window had last time
+pub fn run(options: StartupOptions, size: Option<dpi::PhysicalSize<u32>>) -> Result<(), Error> {
+    let event_loop = EventLoopBuilder::default()
+        .build()
+        .map_err(Error::EventLoop)?;
+
+    let saved_window = options.settings.window;
+    let size = size
+        .or(saved_window.size)
+        .unwrap_or(dpi::PhysicalSize::new(SCREEN_WIDTH, SCREEN_HEIGHT));
+
+    let window_level = if options.starts_on_top() {
+        WindowLevel::AlwaysOnTop
+    } else {
+        WindowLevel::Normal
+    };
+    let window = event_loop
+        .create_window(
+            Window::default_attributes()
+                .with_inner_size(size)
+                .with_window_level(window_level)
+                .with_visible(false),
+        )
+        .map_err(Error::Window)?;
+    // The window is hidden until it's placed, so it doesn't jump around
+    if let Some(position) = saved_window.position {
+        window.set_outer_position(clamp_to_monitor(&window, position, size))
+    }
+    window.set_visible(true);
+    let gpu = Gpu::new(&window, size, options.gpu.clone())?;
+    let mut state = State::new(gpu, &window, &options)?;
+    event_loop
+        .run(|event, window_target| run_event_loop(event, window_target, &window, &mut state))
+        .map_err(Error::EventLoop)
+}
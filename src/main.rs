@@ -1,73 +1,99 @@
-use state::{Gpu, State};
-use wgpu::{
-    Backends, CompositeAlphaMode, DeviceDescriptor, Dx12Compiler, Features, Gles3MinorVersion,
-    Instance, InstanceDescriptor, InstanceFlags, Limits, PowerPreference, PresentMode,
-    RequestAdapterOptions, TextureUsages,
-};
-use winit::{dpi, event_loop::EventLoopBuilder, window::Window};
+use te_shader_play::{GpuOptions, StartupOptions};
+use winit::dpi;
 
-use crate::event_handling::run_event_loop;
+const USAGE: &str = "Usage: te-shader-play [OPTIONS]
 
-const SCREEN_WIDTH: u32 = 768;
-const SCREEN_HEIGHT: u32 = 768;
+Options:
+    --shader <name>       Shader to load at startup, relative to the shaders directory
+                          unless it is an absolute path [default: shader.wgsl]
+    --size <W>x<H>        Initial window size [default: last size, or 768x768]
+    --shaders-dir <dir>   Directory shader names are relative to [default: the one in
+                          settings.json, or shaders]
+    --always-on-top       Start with the window always on top
+    --no-always-on-top    Start with the window not always on top [default: as it
+                          was last time]
+    --save-file <path>    File where parameters are saved [default: save.json]
+    --downlevel           Use the conservative WebGL2 device limits instead of the
+                          adapter's, to test compatibility
+    --adapter <name>      Use the first adapter whose name contains <name>
+    --backend <backend>   Only look for adapters of vulkan, dx12, metal or gl
+    --power <low|high>    Prefer the integrated (low) or the dedicated (high) GPU
+                          when no adapter is named
+    -h, --help            Print this message";
 
-mod event_handling;
-mod imgui_state;
-mod rendering;
-mod state;
+struct Args {
+    /// None if it wasn't given in the command line
+    size: Option<dpi::PhysicalSize<u32>>,
+    startup: StartupOptions,
+}
 
-fn main() {
-    env_logger::init();
-    let event_loop = EventLoopBuilder::default()
-        .build()
-        .expect("Couldn't create event loop");
+fn parse_args() -> Result<Args, String> {
+    let mut args = Args {
+        size: None,
+        startup: StartupOptions::from_settings(),
+    };
+
+    let mut cli_args = std::env::args().skip(1);
+    while let Some(arg) = cli_args.next() {
+        let mut value = || {
+            cli_args
+                .next()
+                .ok_or_else(|| format!("Missing value for {arg}"))
+        };
+        match arg.as_str() {
+            "--shader" => args.startup.shader = value()?,
+            "--size" => args.size = Some(parse_size(&value()?)?),
+            "--shaders-dir" => args.startup.paths.shaders_dir = value()?.into(),
+            "--save-file" => args.startup.paths.save_file = value()?.into(),
+            "--always-on-top" => args.startup.always_on_top = Some(true),
+            "--no-always-on-top" => args.startup.always_on_top = Some(false),
+            "--downlevel" => args.startup.gpu.downlevel = true,
+            "--adapter" => args.startup.gpu.adapter = Some(value()?),
+            "--backend" => args.startup.gpu.backends = GpuOptions::parse_backend(&value()?)?,
+            "--power" => {
+                args.startup.gpu.power_preference =
+                    GpuOptions::parse_power_preference(&value()?)?
+            }
+            "-h" | "--help" => {
+                println!("{USAGE}");
+                std::process::exit(0)
+            }
+            _ => return Err(format!("Unknown argument {arg}")),
+        }
+    }
 
-    let window = event_loop.create_window(Window::default_attributes().with_inner_size(dpi::PhysicalSize::new(SCREEN_WIDTH, SCREEN_HEIGHT))).expect("Couldn't create window");
-    let instance = Instance::new(InstanceDescriptor {
-        backends: Backends::all(),
-        flags: InstanceFlags::default(),
-        dx12_shader_compiler: Dx12Compiler::Fxc,
-        gles_minor_version: Gles3MinorVersion::Automatic,
-    });
+    let shader_path = args.startup.paths.shader_path(&args.startup.shader);
+    if !shader_path.is_file() {
+        return Err(format!("{} doesn't exist", shader_path.display()));
+    }
 
-    let surface = instance
-        .create_surface(&window)
-        .expect("Unable to create surface");
+    Ok(args)
+}
 
-    let adapter = pollster::block_on(instance.request_adapter(&RequestAdapterOptions {
-        power_preference: PowerPreference::default(),
-        force_fallback_adapter: false,
-        compatible_surface: Some(&surface),
-    }))
-    .expect("Unable to request adapter");
+fn parse_size(size: &str) -> Result<dpi::PhysicalSize<u32>, String> {
+    let invalid_size = || format!("Invalid size {size}, expected something like 768x768");
+    let (width, height) = size.split_once('x').ok_or_else(invalid_size)?;
+    let width: u32 = width.parse().map_err(|_| invalid_size())?;
+    let height: u32 = height.parse().map_err(|_| invalid_size())?;
+    if width == 0 || height == 0 {
+        return Err(invalid_size());
+    }
 
-    let (device, queue) = pollster::block_on(adapter.request_device(
-        &DeviceDescriptor {
-            label: None,
-            required_features: Features::default() | Features::POLYGON_MODE_LINE,
-            required_limits: Limits::downlevel_webgl2_defaults(),
-            memory_hints: Default::default(),
-        },
-        None,
-    ))
-    .expect("Unable to request device");
+    Ok(dpi::PhysicalSize::new(width, height))
+}
 
-    let config = wgpu::SurfaceConfiguration {
-        usage: TextureUsages::RENDER_ATTACHMENT,
-        format: surface.get_capabilities(&adapter).formats[0],
-        width: SCREEN_WIDTH,
-        height: SCREEN_HEIGHT,
-        present_mode: PresentMode::Fifo,
-        alpha_mode: CompositeAlphaMode::Auto,
-        view_formats: vec![surface.get_capabilities(&adapter).formats[0]],
-        desired_maximum_frame_latency: 2,
+fn main() {
+    env_logger::init();
+    let args = match parse_args() {
+        Ok(args) => args,
+        Err(err) => {
+            eprintln!("{err}\n\n{USAGE}");
+            std::process::exit(2)
+        }
     };
 
-    surface.configure(&device, &config);
-
-    let gpu = Gpu::new(surface, device, queue, config);
-    let mut state = State::new(gpu, &window);
-    event_loop
-        .run(|event, window_target| run_event_loop(event, window_target, &window, &mut state))
-        .unwrap()
+    if let Err(err) = te_shader_play::run(args.startup, args.size) {
+        eprintln!("{err}");
+        std::process::exit(1)
+    }
 }
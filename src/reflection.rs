@@ -0,0 +1,142 @@
+use std::collections::{HashMap, HashSet};
+
+use wgpu::naga::{front::wgsl, proc::Layouter, AddressSpace, Module, ShaderStage};
+
+/// None if the source doesn't parse. Only called with shaders that compiled
+fn parse(source: &str) -> Option<Module> {
+    wgsl::parse_str(source).ok()
+}
+
+/// (group, binding) of every resource declared in a WGSL source
+pub(crate) fn declared_bindings(source: &str) -> HashSet<(u32, u32)> {
+    let Some(module) = parse(source) else {
+        return HashSet::new();
+    };
+    module
+        .global_variables
+        .iter()
+        .filter_map(|(_, variable)| variable.binding.as_ref())
+        .map(|binding| (binding.group, binding.binding))
+        .collect()
+}
+
+/// Names of the entry points declared in a WGSL source, with their stage
+pub(crate) fn entry_points(source: &str) -> Vec<(&'static str, String)> {
+    let Some(module) = parse(source) else {
+        return Vec::new();
+    };
+    module
+        .entry_points
+        .into_iter()
+        .map(|entry_point| {
+            let stage = match entry_point.stage {
+                ShaderStage::Vertex => "vertex",
+                ShaderStage::Fragment => "fragment",
+                ShaderStage::Compute => "compute",
+            };
+            (stage, entry_point.name)
+        })
+        .collect()
+}
+
+/// Size in bytes of every `var<uniform>` declared in a WGSL source, following the WGSL memory
+/// layout rules
+pub(crate) fn uniform_sizes(source: &str) -> HashMap<(u32, u32), u64> {
+    let Some(module) = parse(source) else {
+        return HashMap::new();
+    };
+    let mut layouter = Layouter::default();
+    if layouter.update(module.to_ctx()).is_err() {
+        return HashMap::new();
+    }
+
+    module
+        .global_variables
+        .iter()
+        .filter(|(_, variable)| variable.space == AddressSpace::Uniform)
+        .filter_map(|(_, variable)| {
+            let binding = variable.binding.as_ref()?;
+            let size = layouter[variable.ty].size;
+            Some(((binding.group, binding.binding), size.into()))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn uniform_sizes_follow_the_wgsl_rules() {
+        let source = "
+            // @group(9) @binding(9) var<uniform> commented: f32;
+            @group(0) @binding(0) var<uniform> color: vec3<f32>;
+            @group(0) @binding(1) var<uniform> light: Light;
+            @binding(2) @group(0) var<uniform> padded: Padded;
+            @group(1) @binding(0) var<uniform> m: mat3x3f;
+            @group(1) @binding(1) var<uniform> points: array<vec4<f32>, 4u>;
+            @group(1) @binding(2) var<uniform> aliased: Alias;
+            @group(1) @binding(3) var t: texture_2d<f32>;
+
+            struct Light {
+                direction: vec3<f32>,
+                intensity: f32,
+            }
+            struct Padded { a: f32, b: vec3f, @size(32) c: Light }
+            alias Alias = f32;
+        ";
+        let sizes = uniform_sizes(source);
+        assert_eq!(sizes.get(&(0, 0)), Some(&12));
+        assert_eq!(sizes.get(&(0, 1)), Some(&16));
+        assert_eq!(sizes.get(&(0, 2)), Some(&64));
+        assert_eq!(sizes.get(&(1, 0)), Some(&48));
+        assert_eq!(sizes.get(&(1, 1)), Some(&64));
+        assert_eq!(sizes.get(&(1, 2)), Some(&4));
+        assert_eq!(sizes.get(&(1, 3)), None);
+        assert_eq!(sizes.get(&(9, 9)), None);
+    }
+
+    #[test]
+    fn entry_points_name_the_stage() {
+        let source = "
+            // @vertex fn commented() -> @builtin(position) vec4f { return vec4f(); }
+            @vertex
+            fn vertex_main() -> @builtin(position) vec4f { return vec4f(); }
+            @fragment fn fs_main() -> @location(0) vec4f { return vec4f(); }
+            @compute @workgroup_size(1) fn cs_main() {}
+        ";
+        assert_eq!(
+            entry_points(source),
+            vec![
+                ("vertex", "vertex_main".to_string()),
+                ("fragment", "fs_main".to_string()),
+                ("compute", "cs_main".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn broken_shaders_have_nothing_to_reflect() {
+        let missing_vs_main = "@fragment fn fs_main() -> @location(0) vec4f { return vec4f(); }";
+        let names: Vec<String> = entry_points(missing_vs_main)
+            .into_iter()
+            .map(|(_, name)| name)
+            .collect();
+        assert_eq!(names, vec!["fs_main".to_string()]);
+
+        let missing_fs_main =
+            "@vertex fn vs_main() -> @builtin(position) vec4f { return vec4f(); }";
+        assert_eq!(
+            entry_points(missing_fs_main),
+            vec![("vertex", "vs_main".to_string())]
+        );
+
+        let syntax_error = "
+            @group(0) @binding(0) var<uniform> color: vec3<f32>;
+            @vertex fn vs_main() -> @builtin(position) vec4f { return vec4f() }
+        ";
+        assert!(entry_points(syntax_error).is_empty());
+        assert!(declared_bindings(syntax_error).is_empty());
+        assert!(uniform_sizes(syntax_error).is_empty());
+    }
+}
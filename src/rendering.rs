@@ -1,76 +1,133 @@
 use std::error::Error;
 
 use wgpu::{
-    core::command::{RenderPassError, RenderPassErrorInner}, CommandEncoder, CommandEncoderDescriptor, IndexFormat, LoadOp, Operations, RenderPassColorAttachment, RenderPassDepthStencilAttachment, RenderPassDescriptor, StoreOp, SurfaceTexture, TextureView, TextureViewDescriptor
+    core::command::{RenderPassError, RenderPassErrorInner}, CommandEncoder, CommandEncoderDescriptor, IndexFormat, LoadOp, Operations, RenderPass, RenderPassColorAttachment, RenderPassDepthStencilAttachment, RenderPassDescriptor, RenderPipeline, StoreOp, SurfaceTexture, TextureView, TextureViewDescriptor
 };
-use winit::window::{Window, WindowLevel};
+use winit::window::{Fullscreen, Window, WindowLevel};
 
-use crate::{imgui_state::Message, State};
+use crate::{
+    gpu_errors::OrReport,
+    imgui_state::Message,
+    state::post_process::{PostProcess, PostTarget},
+    State,
+};
 
-pub(crate) enum RenderMessage {
+/// Changes to the window, which the host applies
+pub enum RenderMessage {
     ChangeWindowLevel(WindowLevel),
+    SetFullscreen(bool),
+    /// Fullscreen if the window isn't, as the window says
+    ToggleFullscreen,
+    SetTitle(String),
 }
 
+/// Draws a frame of the playground and its UI on `output`
 pub fn render(output: SurfaceTexture, state: &mut State, window: &Window) {
     let handle_render_pass_err = |state: &mut State, err: Result<(), RenderPassError>| {
         if let Err(err) = err {
-            if let Some(source) = err.source() {
-                let source = source.downcast_ref::<RenderPassErrorInner>();
-                if let Some(err) = source {
-                    state.handle_render_pass_err(err)
-                } else {
-                    panic!("Error")
-                }
+            let source = err
+                .source()
+                .and_then(|source| source.downcast_ref::<RenderPassErrorInner>());
+            if let Some(source) = source {
+                state.handle_render_pass_err(source)
             } else {
-                panic!("Error")
+                state.im_state.push_error(format!("Couldn't render: {err}"));
+                None
             }
         } else {
             None
         }
     };
 
-    let view = output
+    state.follow_camera();
+    // Failures are in the log, the frame is skipped
+    let Some(view) = output
         .texture
         .create_view(&wgpu::TextureViewDescriptor::default())
-        .unwrap();
-    let mut encoder1 = state
+        .or_report()
+    else {
+        return;
+    };
+    let Some(mut encoder1) = state
         .gpu
         .device
         .create_command_encoder(&CommandEncoderDescriptor { label: None })
-        .unwrap();
-    let depth_view = state.depth_textures.background.create_view(&TextureViewDescriptor::default()).unwrap();
-    let res = draw_image(state, &mut encoder1, &view, &depth_view);
+        .or_report()
+    else {
+        return;
+    };
+    let Some(depth_view) = state
+        .depth_textures
+        .background
+        .create_view(&TextureViewDescriptor::default())
+        .or_report()
+    else {
+        return;
+    };
+    let res = draw_image(state, &mut encoder1, &view, &depth_view, PostTarget::Background);
     let message = handle_render_pass_err(state, res);
     handle_message(state, message, window);
-    let (imgui_encoder, message) = state.im_state.render(window, &state.gpu, &view);
-    handle_message(state, message, window);
-    let view = state.im_state.get_texture_view();
-    let depth_view = state.depth_textures.imgui.create_view(&TextureViewDescriptor::default()).unwrap();
-    let mut encoder2 = state
+    // Without the UI the frame still shows the background
+    let imgui_encoder = match state.im_state.render(window, &state.gpu, &view) {
+        Some((encoder, message)) => {
+            handle_message(state, message, window);
+            Some(encoder)
+        }
+        None => None,
+    };
+    let Some(view) = state.im_state.get_texture_view() else {
+        return;
+    };
+    let Some(depth_view) = state
+        .depth_textures
+        .imgui
+        .create_view(&TextureViewDescriptor::default())
+        .or_report()
+    else {
+        return;
+    };
+    let Some(mut encoder2) = state
         .gpu
         .device
         .create_command_encoder(&CommandEncoderDescriptor { label: None })
-        .unwrap();
-    let res = draw_image(state, &mut encoder2, view, &depth_view);
+        .or_report()
+    else {
+        return;
+    };
+    let res = draw_image(state, &mut encoder2, view, &depth_view, PostTarget::Imgui);
     let message = handle_render_pass_err(state, res);
     handle_message(state, message, window);
+    state.im_state.ui.inputs.flush_writes(&state.gpu.queue);
     state.gpu.queue.submit(
-        vec![encoder1.finish(), encoder2.finish(), imgui_encoder.finish()]
+        [Some(encoder1), Some(encoder2), imgui_encoder]
             .into_iter()
-            .filter_map(|encoder| encoder.ok()),
+            .flatten()
+            .filter_map(|encoder| encoder.finish().ok()),
     );
     output.present();
+    #[cfg(feature = "shared-output")]
+    state.share_output();
 }
 
-fn handle_message(state: &mut State, message: Option<Message>, window: &Window) {
+pub fn handle_message(state: &mut State, message: Option<Message>, window: &Window) {
     if let Some(message) = message {
         if let Some(message) = state.handle_message(message) {
-            match message {
-                RenderMessage::ChangeWindowLevel(window_level) => {
-                    window.set_window_level(window_level)
-                }
-            }
+            handle_render_message(message, window)
+        }
+    }
+}
+
+pub fn handle_render_message(message: RenderMessage, window: &Window) {
+    match message {
+        RenderMessage::ChangeWindowLevel(window_level) => window.set_window_level(window_level),
+        RenderMessage::SetFullscreen(fullscreen) => {
+            window.set_fullscreen(fullscreen.then_some(Fullscreen::Borderless(None)))
+        }
+        RenderMessage::ToggleFullscreen => {
+            let fullscreen = window.fullscreen().is_none();
+            window.set_fullscreen(fullscreen.then_some(Fullscreen::Borderless(None)))
         }
+        RenderMessage::SetTitle(title) => window.set_title(&title),
     }
 }
 
@@ -79,13 +136,141 @@ fn draw_image(
     encoder: &mut CommandEncoder,
     view: &TextureView,
     depth_view: &TextureView,
+    target: PostTarget,
 ) -> Result<(), RenderPassError> {
-    draw_custom_shader(state, encoder, view, &depth_view)?;
-    if state.im_state.ui.draw_grid {
-        draw_grid(state, encoder, view, &depth_view)
+    let custom_shader = &state.pipelines.custom_shader;
+    let ui = &state.im_state.ui;
+    let debug_view = if ui.inputs.camera_enabled() {
+        state.pipelines.debug_views.get(&ui.debug_view)
     } else {
-        Ok(())
+        None
+    };
+    match (debug_view, &state.post) {
+        // A debug view replaces both the custom and the post shader
+        (Some(debug_view), _) => draw_custom_shader(state, debug_view, encoder, view, &depth_view)?,
+        (None, Some(post)) if post.pipeline.is_some() => {
+            let post_texture = post.target(target);
+            draw_custom_shader(
+                state,
+                custom_shader,
+                encoder,
+                &post_texture.view,
+                &depth_view,
+            )?;
+            draw_post_process(state, post, target, encoder, view)?;
+        }
+        _ => draw_custom_shader(state, custom_shader, encoder, view, &depth_view)?,
+    }
+    if let Some(pipeline) = &state.pipelines.mesh_overlay {
+        draw_mesh_overlay(state, pipeline, encoder, view, depth_view)?;
+    }
+    if state.im_state.ui.show_clip_planes && state.im_state.ui.inputs.camera_enabled() {
+        draw_clip_planes(state, target, encoder, view)?;
+    }
+    if state.im_state.ui.draw_grid {
+        draw_grid(state, encoder, view, &depth_view)?;
+    }
+    if state.im_state.ui.show_bounding_box && state.im_state.ui.inputs.camera_enabled() {
+        draw_bounding_box(state, encoder, view, &depth_view)?;
     }
+    Ok(())
+}
+
+/// Binds the uniform groups and the debug texture, returns the number of groups bound
+fn set_shared_bind_groups(state: &State, render_pass: &mut RenderPass) -> u32 {
+    let mut group_count = 0;
+    for group in state.im_state.ui.inputs.enabled_groups() {
+        render_pass.set_bind_group(group_count, &group.bind_group, &[]);
+        group_count += 1;
+    }
+    if let Some(debug_texture) = &state.debug_texture {
+        render_pass.set_bind_group(group_count, &debug_texture.bind_group, &[]);
+        group_count += 1;
+    }
+
+    group_count
+}
+
+fn draw_bounding_box(
+    state: &State,
+    encoder: &mut CommandEncoder,
+    view: &TextureView,
+    depth_view: &TextureView,
+) -> Result<(), RenderPassError> {
+    let Some(mut render_pass) = encoder.begin_render_pass(&RenderPassDescriptor {
+        label: Some("Bounding box"),
+        color_attachments: &[Some(RenderPassColorAttachment {
+            view,
+            resolve_target: None,
+            ops: Operations {
+                load: LoadOp::Load,
+                store: StoreOp::Store,
+            },
+        })],
+        depth_stencil_attachment: Some(RenderPassDepthStencilAttachment {
+            view: depth_view,
+            depth_ops: Some(Operations {
+                load: LoadOp::Load,
+                store: StoreOp::Store,
+            }),
+            stencil_ops: None,
+        }),
+        timestamp_writes: None,
+        occlusion_query_set: None,
+    }).or_report() else {
+        return Ok(());
+    };
+    render_pass.set_pipeline(&state.pipelines.bounding_box)?;
+    set_shared_bind_groups(state, &mut render_pass);
+
+    let bounding_box = &state.vertices.bounding_box;
+    render_pass.set_vertex_buffer(0, bounding_box.vertex_buffer.slice(..))?;
+    render_pass.set_index_buffer(bounding_box.index_buffer.slice(..), IndexFormat::Uint32)?;
+    render_pass.draw_indexed(0..bounding_box.indices.len() as u32, 0, 0..1)?;
+    render_pass.end()
+}
+
+/// Wireframe of the custom shader's mesh over the shaded one, and over the post shader's output
+fn draw_mesh_overlay(
+    state: &State,
+    pipeline: &RenderPipeline,
+    encoder: &mut CommandEncoder,
+    view: &TextureView,
+    depth_view: &TextureView,
+) -> Result<(), RenderPassError> {
+    let Some(mut render_pass) = encoder.begin_render_pass(&RenderPassDescriptor {
+        label: Some("Mesh overlay"),
+        color_attachments: &[Some(RenderPassColorAttachment {
+            view,
+            resolve_target: None,
+            ops: Operations {
+                load: LoadOp::Load,
+                store: StoreOp::Store,
+            },
+        })],
+        depth_stencil_attachment: Some(RenderPassDepthStencilAttachment {
+            view: depth_view,
+            depth_ops: Some(Operations {
+                load: LoadOp::Load,
+                store: StoreOp::Store,
+            }),
+            stencil_ops: None,
+        }),
+        timestamp_writes: None,
+        occlusion_query_set: None,
+    }).or_report() else {
+        return Ok(());
+    };
+    render_pass.set_pipeline(pipeline)?;
+    set_shared_bind_groups(state, &mut render_pass);
+
+    let mesh = &state.vertices.custom_shader;
+    let instances = &state.vertices.instances;
+    render_pass.set_vertex_buffer(0, mesh.vertex_buffer.slice(..))?;
+    render_pass.set_vertex_buffer(1, instances.buffer.slice(..))?;
+    render_pass.set_index_buffer(mesh.index_buffer.slice(..), IndexFormat::Uint32)?;
+    render_pass.draw_indexed(0..mesh.indices.len() as u32, 0, 0..instances.count)?;
+    render_pass.end()
 }
 
 fn draw_grid(
@@ -95,7 +280,7 @@ fn draw_grid(
     depth_view: &TextureView,
 ) -> Result<(), RenderPassError> {
     assert!(state.im_state.ui.draw_grid);
-    let mut render_pass = encoder.begin_render_pass(&RenderPassDescriptor {
+    let Some(mut render_pass) = encoder.begin_render_pass(&RenderPassDescriptor {
         label: None,
         color_attachments: &[Some(RenderPassColorAttachment {
             view,
@@ -115,20 +300,91 @@ fn draw_grid(
         }),
         timestamp_writes: None,
         occlusion_query_set: None,
-    }).unwrap();
-    render_pass.set_pipeline(&state.pipelines.grid);
-    for (g_index, group) in state.im_state.ui.inputs.groups.iter().enumerate() {
-        render_pass.set_bind_group(g_index as u32, &group.bind_group, &[]);
-    }
+    }).or_report() else {
+        return Ok(());
+    };
+    render_pass.set_pipeline(&state.pipelines.grid)?;
+    set_shared_bind_groups(state, &mut render_pass);
+
+    render_pass.set_vertex_buffer(0, state.vertices.grid.vertex_buffer.slice(..))?;
+    render_pass.set_index_buffer(state.vertices.grid.index_buffer.slice(..), IndexFormat::Uint32)?;
+    render_pass.draw_indexed(0..state.vertices.grid.indices.len() as u32, 0, 0..1)?;
+    render_pass.end()
+}
+
+fn draw_clip_planes(
+    state: &State,
+    target: PostTarget,
+    encoder: &mut CommandEncoder,
+    view: &TextureView,
+) -> Result<(), RenderPassError> {
+    let clip_planes = &state.clip_planes;
+    let Some(mut render_pass) = encoder.begin_render_pass(&RenderPassDescriptor {
+        label: Some("clip planes pass"),
+        color_attachments: &[Some(RenderPassColorAttachment {
+            view,
+            resolve_target: None,
+            ops: Operations {
+                load: LoadOp::Load,
+                store: StoreOp::Store,
+            },
+        })],
+        depth_stencil_attachment: None,
+        timestamp_writes: None,
+        occlusion_query_set: None,
+    }).or_report() else {
+        return Ok(());
+    };
+    render_pass.set_pipeline(&clip_planes.pipeline)?;
+    render_pass.set_bind_group(0, clip_planes.bind_group(target), &[]);
+
+    render_pass.set_vertex_buffer(0, clip_planes.quad.vertex_buffer.slice(..))?;
+    render_pass.set_index_buffer(clip_planes.quad.index_buffer.slice(..), IndexFormat::Uint32)?;
+    render_pass.draw_indexed(0..clip_planes.quad.indices.len() as u32, 0, 0..1)?;
+    render_pass.end()
+}
+
+fn draw_post_process(
+    state: &State,
+    post: &PostProcess,
+    target: PostTarget,
+    encoder: &mut CommandEncoder,
+    view: &TextureView,
+) -> Result<(), RenderPassError> {
+    let Some(pipeline) = &post.pipeline else {
+        return Ok(());
+    };
+
+    let Some(mut render_pass) = encoder.begin_render_pass(&RenderPassDescriptor {
+        label: Some("post-process pass"),
+        color_attachments: &[Some(RenderPassColorAttachment {
+            view,
+            resolve_target: None,
+            ops: Operations {
+                load: LoadOp::Clear(state.get_background_color()),
+                store: StoreOp::Store,
+            },
+        })],
+        depth_stencil_attachment: None,
+        timestamp_writes: None,
+        occlusion_query_set: None,
+    }).or_report() else {
+        return Ok(());
+    };
+    render_pass.set_pipeline(pipeline)?;
+    let group_count = set_shared_bind_groups(state, &mut render_pass);
+    render_pass.set_bind_group(group_count, &post.target(target).bind_group, &[]);
 
-    render_pass.set_vertex_buffer(0, state.vertices.grid.vertex_buffer.slice(..));
-    render_pass.set_index_buffer(state.vertices.grid.index_buffer.slice(..), IndexFormat::Uint32);
-    render_pass.draw_indexed(0..state.vertices.grid.indices.len() as u32, 0, 0..1);
+    render_pass.set_vertex_buffer(0, post.quad.vertex_buffer.slice(..))?;
+    render_pass.set_index_buffer(post.quad.index_buffer.slice(..), IndexFormat::Uint32)?;
+    render_pass.draw_indexed(0..post.quad.indices.len() as u32, 0, 0..1)?;
     render_pass.end()
 }
 
+/// Draws the mesh with `pipeline`, either the custom shader or the one that shows the normals
 fn draw_custom_shader(
     state: &State,
+    pipeline: &RenderPipeline,
     encoder: &mut CommandEncoder,
     view: &TextureView,
     depth_view: &TextureView,
@@ -147,7 +403,7 @@ fn draw_custom_shader(
         stencil_ops: None,
     });
 
-    let mut render_pass = encoder.begin_render_pass(&RenderPassDescriptor {
+    let Some(mut render_pass) = encoder.begin_render_pass(&RenderPassDescriptor {
         label: None,
         color_attachments: &[Some(RenderPassColorAttachment {
             view,
@@ -157,14 +413,16 @@ fn draw_custom_shader(
         depth_stencil_attachment,
         timestamp_writes: None,
         occlusion_query_set: None,
-    }).unwrap();
-    render_pass.set_pipeline(&state.pipelines.custom_shader).unwrap();
-    for (g_index, group) in state.im_state.ui.inputs.groups.iter().enumerate() {
-        render_pass.set_bind_group(g_index as u32, &group.bind_group, &[]);
-    }
+    }).or_report() else {
+        return Ok(());
+    };
+    render_pass.set_pipeline(pipeline)?;
+    set_shared_bind_groups(state, &mut render_pass);
 
-    render_pass.set_vertex_buffer(0, state.vertices.custom_shader.vertex_buffer.slice(..)).unwrap();
-    render_pass.set_index_buffer(state.vertices.custom_shader.index_buffer.slice(..), IndexFormat::Uint32).unwrap();
-    render_pass.draw_indexed(0..state.vertices.custom_shader.indices.len() as u32, 0, 0..1).unwrap();
+    let instances = &state.vertices.instances;
+    render_pass.set_vertex_buffer(0, state.vertices.custom_shader.vertex_buffer.slice(..))?;
+    render_pass.set_vertex_buffer(1, instances.buffer.slice(..))?;
+    render_pass.set_index_buffer(state.vertices.custom_shader.index_buffer.slice(..), IndexFormat::Uint32)?;
+    render_pass.draw_indexed(0..state.vertices.custom_shader.indices.len() as u32, 0, 0..instances.count)?;
     render_pass.end()
 }
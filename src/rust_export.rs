@@ -0,0 +1,603 @@
+use std::fmt::{Display, Write};
+
+use wgpu::{
+    BlendComponent, BlendFactor, BlendOperation, BlendState, PolygonMode, PrimitiveState,
+    PrimitiveTopology,
+};
+
+/// Words that can't be field names
+const KEYWORDS: &[&str] = &[
+    "as", "async", "await", "box", "break", "const", "continue", "crate", "dyn", "else", "enum",
+    "extern", "false", "fn", "for", "gen", "if", "impl", "in", "let", "loop", "match", "mod",
+    "move", "mut", "priv", "pub", "ref", "return", "self", "static", "struct", "super", "trait",
+    "true", "try", "type", "typeof", "unsafe", "use", "virtual", "where", "while", "yield",
+];
+
+/// What the contents of a buffer are made of
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum ElementType {
+    U32,
+    I32,
+    F32,
+    Byte,
+}
+
+/// Contents of a binding's buffer
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum RustValues {
+    U32(Vec<u32>),
+    I32(Vec<i32>),
+    F32(Vec<f32>),
+    /// Contents that aren't numbers, like zero-filled placeholders
+    Bytes(Vec<u8>),
+}
+
+impl RustValues {
+    /// Splits `bytes` in numbers. Contents that can't be split stay bytes
+    pub(crate) fn from_le_bytes(element_type: ElementType, bytes: &[u8]) -> RustValues {
+        if bytes.len() % 4 != 0 {
+            return RustValues::Bytes(bytes.to_vec());
+        }
+        let words = bytes
+            .chunks_exact(4)
+            .map(|word| [word[0], word[1], word[2], word[3]]);
+        match element_type {
+            ElementType::U32 => RustValues::U32(words.map(u32::from_le_bytes).collect()),
+            ElementType::I32 => RustValues::I32(words.map(i32::from_le_bytes).collect()),
+            ElementType::F32 => RustValues::F32(words.map(f32::from_le_bytes).collect()),
+            ElementType::Byte => RustValues::Bytes(bytes.to_vec()),
+        }
+    }
+
+    fn element_type(&self) -> &'static str {
+        match self {
+            RustValues::U32(_) => "u32",
+            RustValues::I32(_) => "i32",
+            RustValues::F32(_) => "f32",
+            RustValues::Bytes(_) => "u8",
+        }
+    }
+
+    fn len(&self) -> usize {
+        match self {
+            RustValues::U32(values) => values.len(),
+            RustValues::I32(values) => values.len(),
+            RustValues::F32(values) => values.len(),
+            RustValues::Bytes(values) => values.len(),
+        }
+    }
+
+    /// Single numbers aren't arrays
+    fn is_scalar(&self) -> bool {
+        self.len() == 1 && !matches!(self, RustValues::Bytes(_))
+    }
+
+    fn rust_type(&self) -> String {
+        if self.is_scalar() {
+            self.element_type().to_string()
+        } else {
+            format!("[{}; {}]", self.element_type(), self.len())
+        }
+    }
+
+    /// Literal of the values, `indent` is the indentation of the line it starts on
+    fn literal(&self, indent: &str) -> String {
+        fn list<T: Display>(values: &[T], indent: &str) -> String {
+            let values: Vec<String> = values.iter().map(T::to_string).collect();
+            if values.len() <= 4 {
+                return format!("[{}]", values.join(", "));
+            }
+            let rows: Vec<String> = values
+                .chunks(4)
+                .map(|row| format!("{indent}    {},\n", row.join(", ")))
+                .collect();
+            format!("[\n{}{indent}]", rows.concat())
+        }
+
+        match self {
+            RustValues::U32(values) if values.len() == 1 => values[0].to_string(),
+            RustValues::I32(values) if values.len() == 1 => values[0].to_string(),
+            RustValues::F32(values) if values.len() == 1 => float(values[0]),
+            RustValues::U32(values) => list(values, indent),
+            RustValues::I32(values) => list(values, indent),
+            RustValues::F32(values) => {
+                let values: Vec<Float> = values.iter().copied().map(Float).collect();
+                list(&values, indent)
+            }
+            RustValues::Bytes(values) if values.iter().all(|b| *b == 0) => {
+                format!("[0; {}]", values.len())
+            }
+            RustValues::Bytes(values) => list(values, indent),
+        }
+    }
+
+    fn to_bytes_expression(&self, field: &str) -> String {
+        match self {
+            RustValues::Bytes(_) => format!("self.{field}.to_vec()"),
+            _ if self.is_scalar() => format!("self.{field}.to_le_bytes().to_vec()"),
+            _ => format!("self.{field}.iter().flat_map(|v| v.to_le_bytes()).collect()"),
+        }
+    }
+}
+
+/// An f32 that's always written as a float literal
+struct Float(f32);
+
+impl Display for Float {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&float(self.0))
+    }
+}
+
+fn float(value: f32) -> String {
+    if value.is_nan() {
+        "f32::NAN".to_string()
+    } else if value == f32::INFINITY {
+        "f32::INFINITY".to_string()
+    } else if value == f32::NEG_INFINITY {
+        "f32::NEG_INFINITY".to_string()
+    } else {
+        // Debug always has a decimal point or an exponent
+        format!("{value:?}")
+    }
+}
+
+pub(crate) struct RustBinding {
+    pub(crate) name: String,
+    /// What the playground updates the builtins with
+    pub(crate) builtin: Option<&'static str>,
+    pub(crate) values: RustValues,
+}
+
+/// Rust code that builds what the playground uses for the custom shader with plain wgpu: the
+/// bind group layouts, structs with the uniform values and the pipeline settings
+pub(crate) struct RustExport<'a> {
+    pub(crate) shader_path: &'a str,
+    /// Bindings of every enabled group, in order
+    pub(crate) groups: Vec<Vec<RustBinding>>,
+    pub(crate) blend: BlendState,
+    /// Topology and polygon mode of the custom shader's pipeline
+    pub(crate) primitive: PrimitiveState,
+    pub(crate) depth_test: bool,
+    pub(crate) depth_write: bool,
+}
+
+impl RustExport<'_> {
+    pub(crate) fn to_rust(&self) -> String {
+        let mut code = String::new();
+        // Writing to a String can't fail
+        let _ = self.write_rust(&mut code);
+        code
+    }
+
+    fn write_rust(&self, code: &mut String) -> std::fmt::Result {
+        writeln!(code, "// Generated by te-shader-play.")?;
+        writeln!(
+            code,
+            "// Every binding has its own uniform buffer, visible to the vertex and fragment stages."
+        )?;
+        writeln!(code)?;
+        writeln!(
+            code,
+            "pub const SHADER_PATH: &str = {:?};",
+            self.shader_path
+        )?;
+        for (g_index, bindings) in self.groups.iter().enumerate() {
+            writeln!(code)?;
+            self.write_group(code, g_index, bindings)?;
+        }
+        writeln!(code)?;
+        code.push_str(UNIFORM_ENTRY);
+        writeln!(code)?;
+        code.push_str(VERTEX_BUFFERS);
+        writeln!(code)?;
+        writeln!(
+            code,
+            "pub const PRIMITIVE: wgpu::PrimitiveState = wgpu::PrimitiveState {{"
+        )?;
+        writeln!(
+            code,
+            "    topology: wgpu::PrimitiveTopology::{},",
+            topology_name(self.primitive.topology)
+        )?;
+        code.push_str(PRIMITIVE_MIDDLE);
+        writeln!(
+            code,
+            "    polygon_mode: wgpu::PolygonMode::{},",
+            polygon_mode_name(self.primitive.polygon_mode)
+        )?;
+        writeln!(code, "    conservative: false,")?;
+        writeln!(code, "}};")?;
+        writeln!(code)?;
+        writeln!(
+            code,
+            "pub const DEPTH_STENCIL: wgpu::DepthStencilState = wgpu::DepthStencilState {{"
+        )?;
+        writeln!(code, "    format: wgpu::TextureFormat::Depth32Float,")?;
+        writeln!(code, "    depth_write_enabled: {},", self.depth_write)?;
+        let compare = if self.depth_test { "Less" } else { "Always" };
+        writeln!(code, "    depth_compare: wgpu::CompareFunction::{compare},")?;
+        code.push_str(DEPTH_STENCIL_REST);
+        writeln!(code)?;
+        writeln!(
+            code,
+            "pub const BLEND: wgpu::BlendState = wgpu::BlendState {{"
+        )?;
+        write_blend_component(code, "color", self.blend.color)?;
+        write_blend_component(code, "alpha", self.blend.alpha)?;
+        writeln!(code, "}};")
+    }
+
+    fn write_group(
+        &self,
+        code: &mut String,
+        g_index: usize,
+        bindings: &[RustBinding],
+    ) -> std::fmt::Result {
+        let fields = field_names(bindings);
+        let name = format!("Group{g_index}");
+
+        writeln!(code, "/// @group({g_index})")?;
+        writeln!(code, "#[derive(Debug, Clone, Copy, PartialEq)]")?;
+        writeln!(code, "pub struct {name} {{")?;
+        for (b_index, (binding, field)) in bindings.iter().zip(&fields).enumerate() {
+            match binding.builtin {
+                Some(builtin) => writeln!(
+                    code,
+                    "    /// @binding({b_index}), the playground updates it with {builtin}"
+                )?,
+                None => writeln!(code, "    /// @binding({b_index})")?,
+            }
+            writeln!(code, "    pub {field}: {},", binding.values.rust_type())?;
+        }
+        writeln!(code, "}}")?;
+        writeln!(code)?;
+
+        writeln!(code, "impl {name} {{")?;
+        writeln!(
+            code,
+            "    pub const LAYOUT: wgpu::BindGroupLayoutDescriptor<'static> = wgpu::BindGroupLayoutDescriptor {{"
+        )?;
+        writeln!(code, "        label: Some(\"Group {g_index}\"),")?;
+        writeln!(code, "        entries: &[")?;
+        for b_index in 0..bindings.len() {
+            writeln!(code, "            uniform_entry({b_index}),")?;
+        }
+        writeln!(code, "        ],")?;
+        writeln!(code, "    }};")?;
+        writeln!(code)?;
+        writeln!(
+            code,
+            "    /// Contents of the buffer of each binding, in binding order"
+        )?;
+        writeln!(code, "    pub fn to_bytes(&self) -> Vec<Vec<u8>> {{")?;
+        writeln!(code, "        vec![")?;
+        for (binding, field) in bindings.iter().zip(&fields) {
+            writeln!(
+                code,
+                "            {},",
+                binding.values.to_bytes_expression(field)
+            )?;
+        }
+        writeln!(code, "        ]")?;
+        writeln!(code, "    }}")?;
+        writeln!(code, "}}")?;
+        writeln!(code)?;
+
+        writeln!(code, "impl Default for {name} {{")?;
+        writeln!(code, "    /// The values the playground had")?;
+        writeln!(code, "    fn default() -> Self {{")?;
+        writeln!(code, "        {name} {{")?;
+        for (binding, field) in bindings.iter().zip(&fields) {
+            let indent = "            ";
+            writeln!(code, "{indent}{field}: {},", binding.values.literal(indent))?;
+        }
+        writeln!(code, "        }}")?;
+        writeln!(code, "    }}")?;
+        writeln!(code, "}}")
+    }
+}
+
+/// Binding names as snake case identifiers, made unique with a number starting at the binding
+/// index
+fn field_names(bindings: &[RustBinding]) -> Vec<String> {
+    let mut fields: Vec<String> = Vec::new();
+    for (b_index, binding) in bindings.iter().enumerate() {
+        let mut name = sanitize_name(&binding.name);
+        if name.is_empty() {
+            name = format!("binding_{b_index}")
+        }
+        let mut field = name.clone();
+        let mut suffix = b_index;
+        while fields.contains(&field) {
+            field = format!("{name}_{suffix}");
+            suffix += 1
+        }
+        fields.push(field)
+    }
+
+    fields
+}
+
+fn sanitize_name(name: &str) -> String {
+    let mut field = String::new();
+    for c in name.trim().chars() {
+        if c.is_ascii_alphanumeric() {
+            field.push(c.to_ascii_lowercase())
+        } else if !field.ends_with('_') {
+            field.push('_')
+        }
+    }
+    let field = field.trim_matches('_');
+    if field.is_empty() {
+        String::new()
+    } else if field.starts_with(|c: char| c.is_ascii_digit()) {
+        format!("_{field}")
+    } else if KEYWORDS.contains(&field) {
+        format!("{field}_")
+    } else {
+        field.to_string()
+    }
+}
+
+fn write_blend_component(
+    code: &mut String,
+    name: &str,
+    component: BlendComponent,
+) -> std::fmt::Result {
+    writeln!(code, "    {name}: wgpu::BlendComponent {{")?;
+    writeln!(
+        code,
+        "        src_factor: wgpu::BlendFactor::{},",
+        factor_name(component.src_factor)
+    )?;
+    writeln!(
+        code,
+        "        dst_factor: wgpu::BlendFactor::{},",
+        factor_name(component.dst_factor)
+    )?;
+    writeln!(
+        code,
+        "        operation: wgpu::BlendOperation::{},",
+        operation_name(component.operation)
+    )?;
+    writeln!(code, "    }},")
+}
+
+fn factor_name(factor: BlendFactor) -> &'static str {
+    match factor {
+        BlendFactor::Zero => "Zero",
+        BlendFactor::One => "One",
+        BlendFactor::Src => "Src",
+        BlendFactor::OneMinusSrc => "OneMinusSrc",
+        BlendFactor::SrcAlpha => "SrcAlpha",
+        BlendFactor::OneMinusSrcAlpha => "OneMinusSrcAlpha",
+        BlendFactor::Dst => "Dst",
+        BlendFactor::OneMinusDst => "OneMinusDst",
+        BlendFactor::DstAlpha => "DstAlpha",
+        BlendFactor::OneMinusDstAlpha => "OneMinusDstAlpha",
+        BlendFactor::SrcAlphaSaturated => "SrcAlphaSaturated",
+        BlendFactor::Constant => "Constant",
+        BlendFactor::OneMinusConstant => "OneMinusConstant",
+        BlendFactor::Src1 => "Src1",
+        BlendFactor::OneMinusSrc1 => "OneMinusSrc1",
+        BlendFactor::Src1Alpha => "Src1Alpha",
+        BlendFactor::OneMinusSrc1Alpha => "OneMinusSrc1Alpha",
+    }
+}
+
+fn topology_name(topology: PrimitiveTopology) -> &'static str {
+    match topology {
+        PrimitiveTopology::PointList => "PointList",
+        PrimitiveTopology::LineList => "LineList",
+        PrimitiveTopology::LineStrip => "LineStrip",
+        PrimitiveTopology::TriangleList => "TriangleList",
+        PrimitiveTopology::TriangleStrip => "TriangleStrip",
+    }
+}
+
+fn polygon_mode_name(mode: PolygonMode) -> &'static str {
+    match mode {
+        PolygonMode::Fill => "Fill",
+        PolygonMode::Line => "Line",
+        PolygonMode::Point => "Point",
+    }
+}
+
+fn operation_name(operation: BlendOperation) -> &'static str {
+    match operation {
+        BlendOperation::Add => "Add",
+        BlendOperation::Subtract => "Subtract",
+        BlendOperation::ReverseSubtract => "ReverseSubtract",
+        BlendOperation::Min => "Min",
+        BlendOperation::Max => "Max",
+    }
+}
+
+const UNIFORM_ENTRY: &str = "const fn uniform_entry(binding: u32) -> wgpu::BindGroupLayoutEntry {
+    wgpu::BindGroupLayoutEntry {
+        binding,
+        visibility: wgpu::ShaderStages::VERTEX_FRAGMENT,
+        ty: wgpu::BindingType::Buffer {
+            ty: wgpu::BufferBindingType::Uniform,
+            has_dynamic_offset: false,
+            min_binding_size: None,
+        },
+        count: None,
+    }
+}
+";
+
+/// Mirrors `Vertex` and the instance offsets
+const VERTEX_BUFFERS: &str =
+    "/// The position (location 0) and normal (location 2) of each vertex, then the offset
+/// (location 1) of each instance
+pub const VERTEX_BUFFERS: [wgpu::VertexBufferLayout<'static>; 2] = [
+    wgpu::VertexBufferLayout {
+        array_stride: 24,
+        step_mode: wgpu::VertexStepMode::Vertex,
+        attributes: &[
+            wgpu::VertexAttribute {
+                format: wgpu::VertexFormat::Float32x3,
+                offset: 0,
+                shader_location: 0,
+            },
+            wgpu::VertexAttribute {
+                format: wgpu::VertexFormat::Float32x3,
+                offset: 12,
+                shader_location: 2,
+            },
+        ],
+    },
+    wgpu::VertexBufferLayout {
+        array_stride: 12,
+        step_mode: wgpu::VertexStepMode::Instance,
+        attributes: &[wgpu::VertexAttribute {
+            format: wgpu::VertexFormat::Float32x3,
+            offset: 0,
+            shader_location: 1,
+        }],
+    },
+];
+";
+
+/// The fields of `PRIMITIVE` the playground never changes
+const PRIMITIVE_MIDDLE: &str = "    strip_index_format: None,
+    front_face: wgpu::FrontFace::Ccw,
+    cull_mode: None,
+    unclipped_depth: false,
+";
+
+const DEPTH_STENCIL_REST: &str = "    stencil: wgpu::StencilState {
+        front: wgpu::StencilFaceState::IGNORE,
+        back: wgpu::StencilFaceState::IGNORE,
+        read_mask: 0,
+        write_mask: 0,
+    },
+    bias: wgpu::DepthBiasState {
+        constant: 0,
+        slope_scale: 0.0,
+        clamp: 0.0,
+    },
+};
+";
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The bindings the example in `tests/fixtures` was generated from
+    fn example() -> RustExport<'static> {
+        RustExport {
+            shader_path: "shaders/shader.wgsl",
+            groups: vec![
+                vec![
+                    RustBinding {
+                        name: "unnamed".into(),
+                        builtin: Some("the milliseconds since it started"),
+                        values: RustValues::U32(vec![1234]),
+                    },
+                    RustBinding {
+                        name: "unnamed".into(),
+                        builtin: Some("the window size"),
+                        values: RustValues::F32(vec![768.0, 512.0]),
+                    },
+                ],
+                vec![
+                    RustBinding {
+                        name: "Speed".into(),
+                        builtin: None,
+                        values: RustValues::F32(vec![0.5]),
+                    },
+                    RustBinding {
+                        name: "tint color".into(),
+                        builtin: None,
+                        values: RustValues::F32(vec![1.0, 0.25, 0.0, 1.0, -2.5, 3.0]),
+                    },
+                    RustBinding {
+                        name: "type".into(),
+                        builtin: None,
+                        values: RustValues::I32(vec![-3, 4]),
+                    },
+                    RustBinding {
+                        name: "2nd pad".into(),
+                        builtin: None,
+                        values: RustValues::Bytes(vec![0; 8]),
+                    },
+                ],
+            ],
+            blend: BlendState::ALPHA_BLENDING,
+            primitive: PrimitiveState::default(),
+            depth_test: true,
+            depth_write: false,
+        }
+    }
+
+    #[test]
+    fn matches_the_compiled_example() {
+        // tests/rust_export.rs compiles the example against wgpu
+        let expected = include_str!("../tests/fixtures/rust_export.rs");
+        assert_eq!(example().to_rust(), expected);
+    }
+
+    #[test]
+    fn names_are_identifiers() {
+        assert_eq!(sanitize_name("Tint Color!"), "tint_color");
+        assert_eq!(sanitize_name("3d offset"), "_3d_offset");
+        assert_eq!(sanitize_name("fn"), "fn_");
+        assert_eq!(sanitize_name("¿?"), "");
+    }
+
+    #[test]
+    fn field_names_are_unique() {
+        let bindings: Vec<RustBinding> = ["x", "x_2", "x", "", "binding_3"]
+            .iter()
+            .map(|name| RustBinding {
+                name: name.to_string(),
+                builtin: None,
+                values: RustValues::U32(vec![0]),
+            })
+            .collect();
+        assert_eq!(
+            field_names(&bindings),
+            ["x", "x_2", "x_3", "binding_3", "binding_3_4"]
+        );
+    }
+
+    #[test]
+    fn special_floats_are_constants() {
+        assert_eq!(float(f32::NAN), "f32::NAN");
+        assert_eq!(float(f32::INFINITY), "f32::INFINITY");
+        assert_eq!(float(f32::NEG_INFINITY), "f32::NEG_INFINITY");
+        assert_eq!(float(1.0), "1.0");
+    }
+
+    #[test]
+    fn primitive_follows_the_pipeline() {
+        let mut export = example();
+        export.primitive = PrimitiveState {
+            topology: PrimitiveTopology::PointList,
+            polygon_mode: PolygonMode::Line,
+            ..PrimitiveState::default()
+        };
+        let code = export.to_rust();
+        assert!(code.contains("topology: wgpu::PrimitiveTopology::PointList,"));
+        assert!(code.contains("polygon_mode: wgpu::PolygonMode::Line,"));
+    }
+
+    #[test]
+    fn contents_keep_their_type() {
+        let bytes: Vec<u8> = [1.5f32, -2.0]
+            .iter()
+            .flat_map(|v| v.to_le_bytes())
+            .collect();
+        assert_eq!(
+            RustValues::from_le_bytes(ElementType::F32, &bytes),
+            RustValues::F32(vec![1.5, -2.0])
+        );
+        assert_eq!(
+            RustValues::from_le_bytes(ElementType::U32, &[0; 3]),
+            RustValues::Bytes(vec![0; 3])
+        );
+    }
+}
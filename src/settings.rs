@@ -0,0 +1,601 @@
+use std::{
+    ffi::OsString,
+    io::Write,
+    path::{Path, PathBuf},
+};
+
+use serde_json::{Map, Value as JsonValue};
+use wgpu::{PresentMode, TextureFormat};
+use winit::dpi::{PhysicalPosition, PhysicalSize};
+
+use crate::keybindings::{Action, KeyBinding, Keybindings};
+
+pub(crate) const SETTINGS_FILE: &str = "settings.json";
+const DEFAULT_OSC_PORT: u16 = 9000;
+/// Where imgui keeps the docking layout
+pub(crate) const IMGUI_INI_FILE: &str = "imgui.ini";
+
+/// Preferences that apply to every shader, unlike save.json which holds the parameters of each.
+/// It's loaded once at startup
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct Settings {
+    pub(crate) window: WindowSettings,
+    pub(crate) present_mode: PresentMode,
+    pub(crate) output_encoding: OutputEncoding,
+    /// Directory shader names are relative to, unless `--shaders-dir` is given
+    pub(crate) shaders_dir: PathBuf,
+    /// Windows of the UI that aren't shown
+    pub(crate) hidden_windows: Vec<UiWindow>,
+    pub(crate) appearance: Appearance,
+    /// Parameter edits are uploaded once the field is released, instead of on every change
+    pub(crate) defer_edits: bool,
+    pub(crate) keybindings: Vec<(Action, KeyBinding)>,
+    pub(crate) osc: OscSettings,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Settings {
+            window: WindowSettings::default(),
+            present_mode: PresentMode::Fifo,
+            output_encoding: OutputEncoding::Srgb,
+            shaders_dir: PathBuf::from("shaders"),
+            hidden_windows: Vec::new(),
+            appearance: Appearance::default(),
+            defer_edits: false,
+            keybindings: Keybindings::defaults(),
+            osc: OscSettings::default(),
+        }
+    }
+}
+
+impl Settings {
+    pub(crate) fn load() -> Settings {
+        Settings::from_json(&read_settings())
+    }
+
+    /// Keys that aren't part of the struct are kept as they were
+    pub(crate) fn save(&self) -> std::io::Result<()> {
+        let mut settings = read_settings();
+        self.to_json(&mut settings);
+        let json = serde_json::to_vec_pretty(&settings)?;
+        write_atomically(Path::new(SETTINGS_FILE), &json)
+    }
+
+    /// Missing or invalid keys get their default
+    fn from_json(settings: &Map<String, JsonValue>) -> Settings {
+        let default = Settings::default();
+        Settings {
+            window: settings
+                .get("window")
+                .map(WindowSettings::from_json)
+                .unwrap_or(default.window),
+            present_mode: settings
+                .get("present_mode")
+                .and_then(JsonValue::as_str)
+                .and_then(present_mode_from_label)
+                .unwrap_or(default.present_mode),
+            output_encoding: settings
+                .get("output_encoding")
+                .and_then(JsonValue::as_str)
+                .and_then(|label| {
+                    OutputEncoding::ALL
+                        .iter()
+                        .copied()
+                        .find(|encoding| encoding.label() == label)
+                })
+                .unwrap_or(default.output_encoding),
+            shaders_dir: settings
+                .get("shaders_dir")
+                .and_then(JsonValue::as_str)
+                .filter(|dir| !dir.is_empty())
+                .map_or(default.shaders_dir, PathBuf::from),
+            hidden_windows: settings
+                .get("hidden_windows")
+                .and_then(JsonValue::as_array)
+                .map(|windows| {
+                    UiWindow::ALL
+                        .iter()
+                        .copied()
+                        .filter(|window| windows.contains(&window.title().into()))
+                        .collect()
+                })
+                .unwrap_or(default.hidden_windows),
+            appearance: settings
+                .get("appearance")
+                .map(Appearance::from_json)
+                .unwrap_or(default.appearance),
+            defer_edits: settings
+                .get("defer_edits")
+                .and_then(JsonValue::as_bool)
+                .unwrap_or(default.defer_edits),
+            keybindings: settings
+                .get("keybindings")
+                .map(Keybindings::bindings_from_json)
+                .unwrap_or(default.keybindings),
+            osc: settings
+                .get("osc")
+                .and_then(OscSettings::from_json)
+                .unwrap_or(default.osc),
+        }
+    }
+
+    fn to_json(&self, settings: &mut Map<String, JsonValue>) {
+        settings.insert("window".into(), self.window.to_json());
+        settings.insert(
+            "present_mode".into(),
+            present_mode_label(self.present_mode).into(),
+        );
+        settings.insert(
+            "output_encoding".into(),
+            self.output_encoding.label().into(),
+        );
+        settings.insert(
+            "shaders_dir".into(),
+            self.shaders_dir.to_string_lossy().into(),
+        );
+        let hidden_windows: Vec<JsonValue> = self
+            .hidden_windows
+            .iter()
+            .map(|window| window.title().into())
+            .collect();
+        settings.insert("hidden_windows".into(), hidden_windows.into());
+        settings.insert("appearance".into(), self.appearance.to_json());
+        settings.insert("defer_edits".into(), self.defer_edits.into());
+        settings.insert(
+            "keybindings".into(),
+            Keybindings::bindings_to_json(&self.keybindings),
+        );
+        settings.insert("osc".into(), self.osc.to_json());
+    }
+}
+
+/// Windows of the UI, which can be hidden from the Windows menu
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum UiWindow {
+    Render,
+    Control,
+    ShaderParameters,
+    MeshConfiguration,
+    Log,
+    Keybindings,
+    Settings,
+    Script,
+    ShaderSource,
+}
+
+impl UiWindow {
+    pub(crate) const ALL: &'static [UiWindow] = &[
+        UiWindow::Render,
+        UiWindow::Control,
+        UiWindow::ShaderParameters,
+        UiWindow::MeshConfiguration,
+        UiWindow::Log,
+        UiWindow::Keybindings,
+        UiWindow::Settings,
+        UiWindow::Script,
+        UiWindow::ShaderSource,
+    ];
+
+    /// Also the name imgui keeps the window's position under, so it can't change
+    pub(crate) fn title(&self) -> &'static str {
+        match self {
+            UiWindow::Render => "Render",
+            UiWindow::Control => "Control",
+            UiWindow::ShaderParameters => "Shader parameters",
+            UiWindow::MeshConfiguration => "Mesh configuration",
+            UiWindow::Log => "Log",
+            UiWindow::Keybindings => "Keybindings",
+            UiWindow::Settings => "Settings",
+            UiWindow::Script => "Script",
+            UiWindow::ShaderSource => "Shader source",
+        }
+    }
+}
+
+/// Color scheme of the UI, one of imgui's styles
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum UiTheme {
+    Dark,
+    Light,
+    Classic,
+}
+
+impl UiTheme {
+    pub(crate) const ALL: &'static [UiTheme] = &[UiTheme::Dark, UiTheme::Light, UiTheme::Classic];
+
+    pub(crate) fn label(&self) -> &'static str {
+        match self {
+            UiTheme::Dark => "Dark",
+            UiTheme::Light => "Light",
+            UiTheme::Classic => "Classic",
+        }
+    }
+}
+
+pub(crate) const MIN_UI_SCALE: f32 = 0.5;
+pub(crate) const MAX_UI_SCALE: f32 = 3.0;
+pub(crate) const MIN_FONT_SIZE: f32 = 8.0;
+pub(crate) const MAX_FONT_SIZE: f32 = 48.0;
+
+/// How the UI looks, applied at startup and whenever it's changed in the Settings window
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct Appearance {
+    pub(crate) theme: UiTheme,
+    /// Multiplies the font and the sizes of the style
+    pub(crate) ui_scale: f32,
+    /// TTF font used instead of imgui's default one
+    pub(crate) font_path: Option<PathBuf>,
+    /// Size of the TTF font in pixels, before the UI scale
+    pub(crate) font_size: f32,
+    /// Errors and warnings of the Log window are drawn in stronger colors
+    pub(crate) high_contrast_errors: bool,
+}
+
+impl Default for Appearance {
+    fn default() -> Self {
+        Appearance {
+            theme: UiTheme::Dark,
+            ui_scale: 1.0,
+            font_path: None,
+            font_size: 16.0,
+            high_contrast_errors: false,
+        }
+    }
+}
+
+impl Appearance {
+    fn from_json(json_val: &JsonValue) -> Appearance {
+        let default = Appearance::default();
+        let number = |key: &str, min: f32, max: f32| {
+            json_val
+                .get(key)
+                .and_then(JsonValue::as_f64)
+                .map(|number| (number as f32).clamp(min, max))
+        };
+        Appearance {
+            theme: json_val
+                .get("theme")
+                .and_then(JsonValue::as_str)
+                .and_then(|label| {
+                    UiTheme::ALL
+                        .iter()
+                        .copied()
+                        .find(|theme| theme.label() == label)
+                })
+                .unwrap_or(default.theme),
+            ui_scale: number("ui_scale", MIN_UI_SCALE, MAX_UI_SCALE).unwrap_or(default.ui_scale),
+            font_path: json_val
+                .get("font_path")
+                .and_then(JsonValue::as_str)
+                .filter(|path| !path.is_empty())
+                .map(PathBuf::from),
+            font_size: number("font_size", MIN_FONT_SIZE, MAX_FONT_SIZE)
+                .unwrap_or(default.font_size),
+            high_contrast_errors: json_val
+                .get("high_contrast_errors")
+                .and_then(JsonValue::as_bool)
+                .unwrap_or(default.high_contrast_errors),
+        }
+    }
+
+    fn to_json(&self) -> JsonValue {
+        let mut json_obj = Map::new();
+        json_obj.insert("theme".into(), self.theme.label().into());
+        json_obj.insert("ui_scale".into(), self.ui_scale.into());
+        if let Some(font_path) = &self.font_path {
+            json_obj.insert("font_path".into(), font_path.to_string_lossy().into());
+        }
+        json_obj.insert("font_size".into(), self.font_size.into());
+        json_obj.insert(
+            "high_contrast_errors".into(),
+            self.high_contrast_errors.into(),
+        );
+        JsonValue::Object(json_obj)
+    }
+}
+
+/// Present modes in the order the Settings window lists them
+pub(crate) const PRESENT_MODES: [PresentMode; 5] = [
+    PresentMode::Fifo,
+    PresentMode::FifoRelaxed,
+    PresentMode::Mailbox,
+    PresentMode::Immediate,
+    PresentMode::AutoNoVsync,
+];
+
+pub(crate) fn present_mode_label(mode: PresentMode) -> &'static str {
+    match mode {
+        PresentMode::AutoVsync => "Auto vsync",
+        PresentMode::AutoNoVsync => "Auto no vsync",
+        PresentMode::Fifo => "Fifo (vsync)",
+        PresentMode::FifoRelaxed => "Fifo relaxed",
+        PresentMode::Immediate => "Immediate",
+        PresentMode::Mailbox => "Mailbox",
+    }
+}
+
+fn present_mode_from_label(label: &str) -> Option<PresentMode> {
+    [PresentMode::AutoVsync]
+        .into_iter()
+        .chain(PRESENT_MODES)
+        .find(|mode| present_mode_label(*mode) == label)
+}
+
+/// How the colors written to the window are encoded
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum OutputEncoding {
+    /// The window's format is sRGB, so the linear colors of the shaders are encoded when written
+    Srgb,
+    /// The colors are written as they are
+    Linear,
+}
+
+impl OutputEncoding {
+    pub(crate) const ALL: &'static [OutputEncoding] =
+        &[OutputEncoding::Srgb, OutputEncoding::Linear];
+
+    pub(crate) fn label(&self) -> &'static str {
+        match self {
+            OutputEncoding::Srgb => "sRGB",
+            OutputEncoding::Linear => "Linear",
+        }
+    }
+
+    pub(crate) fn of(format: TextureFormat) -> OutputEncoding {
+        if format.is_srgb() {
+            OutputEncoding::Srgb
+        } else {
+            OutputEncoding::Linear
+        }
+    }
+
+    /// The first of the surface's `formats` with this encoding, None if it has none
+    pub(crate) fn format(&self, formats: &[TextureFormat]) -> Option<TextureFormat> {
+        formats
+            .iter()
+            .copied()
+            .find(|format| OutputEncoding::of(*format) == *self)
+    }
+}
+
+/// What the OSC server listens on
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct OscSettings {
+    pub(crate) enabled: bool,
+    pub(crate) port: u16,
+    /// Listen on every network interface instead of only this computer's
+    pub(crate) all_interfaces: bool,
+}
+
+impl Default for OscSettings {
+    fn default() -> Self {
+        OscSettings {
+            enabled: false,
+            port: DEFAULT_OSC_PORT,
+            all_interfaces: false,
+        }
+    }
+}
+
+impl OscSettings {
+    fn from_json(json_val: &JsonValue) -> Option<OscSettings> {
+        Some(OscSettings {
+            enabled: json_val.get("enabled")?.as_bool()?,
+            port: json_val.get("port")?.as_u64()?.try_into().ok()?,
+            all_interfaces: json_val
+                .get("all_interfaces")
+                .and_then(JsonValue::as_bool)
+                .unwrap_or_default(),
+        })
+    }
+
+    fn to_json(&self) -> JsonValue {
+        let mut json_obj = Map::new();
+        json_obj.insert("enabled".into(), self.enabled.into());
+        json_obj.insert("port".into(), self.port.into());
+        json_obj.insert("all_interfaces".into(), self.all_interfaces.into());
+        JsonValue::Object(json_obj)
+    }
+}
+
+/// Window state that is restored at startup
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub(crate) struct WindowSettings {
+    pub(crate) size: Option<PhysicalSize<u32>>,
+    pub(crate) position: Option<PhysicalPosition<i32>>,
+    pub(crate) always_on_top: bool,
+}
+
+impl WindowSettings {
+    fn from_json(json_val: &JsonValue) -> WindowSettings {
+        let pair = |key: &str| {
+            let pair = json_val.get(key)?.as_array()?;
+            if pair.len() != 2 {
+                return None;
+            }
+            Some((pair.first()?.as_i64()?, pair.get(1)?.as_i64()?))
+        };
+
+        WindowSettings {
+            size: pair("size").and_then(|(width, height)| {
+                Some(PhysicalSize::new(
+                    width.try_into().ok()?,
+                    height.try_into().ok()?,
+                ))
+            }),
+            position: pair("position").and_then(|(x, y)| {
+                Some(PhysicalPosition::new(x.try_into().ok()?, y.try_into().ok()?))
+            }),
+            always_on_top: json_val
+                .get("always_on_top")
+                .and_then(JsonValue::as_bool)
+                .unwrap_or(false),
+        }
+    }
+
+    fn to_json(&self) -> JsonValue {
+        let mut json_obj = Map::new();
+        if let Some(size) = self.size {
+            let size: Vec<JsonValue> = vec![size.width.into(), size.height.into()];
+            json_obj.insert("size".into(), size.into());
+        }
+        if let Some(position) = self.position {
+            let position: Vec<JsonValue> = vec![position.x.into(), position.y.into()];
+            json_obj.insert("position".into(), position.into());
+        }
+        json_obj.insert("always_on_top".into(), self.always_on_top.into());
+        JsonValue::Object(json_obj)
+    }
+}
+
+fn read_settings() -> Map<String, JsonValue> {
+    std::fs::read_to_string(SETTINGS_FILE)
+        .ok()
+        .and_then(|settings| serde_json::from_str(&settings).ok())
+        .and_then(|settings: JsonValue| match settings {
+            JsonValue::Object(settings) => Some(settings),
+            _ => None,
+        })
+        .unwrap_or_default()
+}
+
+/// Writes a temporary file next to `path` and renames it, so a failed write doesn't leave `path`
+/// half written
+pub(crate) fn write_atomically(path: &Path, contents: &[u8]) -> std::io::Result<()> {
+    let mut temp_path = OsString::from(path);
+    temp_path.push(".tmp");
+    let temp_path = PathBuf::from(temp_path);
+
+    let mut file = std::fs::File::create(&temp_path)?;
+    file.write_all(contents)?;
+    file.sync_all()?;
+    drop(file);
+    std::fs::rename(&temp_path, path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn settings_round_trip() {
+        let settings = Settings {
+            window: WindowSettings {
+                size: Some(PhysicalSize::new(1280, 720)),
+                position: Some(PhysicalPosition::new(-20, 40)),
+                always_on_top: true,
+            },
+            present_mode: PresentMode::Mailbox,
+            output_encoding: OutputEncoding::Linear,
+            shaders_dir: PathBuf::from("my shaders"),
+            hidden_windows: vec![UiWindow::ShaderParameters, UiWindow::Log],
+            appearance: Appearance {
+                theme: UiTheme::Light,
+                ui_scale: 1.5,
+                font_path: Some(PathBuf::from("fonts/Roboto.ttf")),
+                font_size: 18.0,
+                high_contrast_errors: true,
+            },
+            defer_edits: true,
+            keybindings: Keybindings::defaults().into_iter().rev().collect(),
+            osc: OscSettings {
+                enabled: true,
+                port: 9100,
+                all_interfaces: true,
+            },
+        };
+        let mut json = Map::new();
+        settings.to_json(&mut json);
+
+        assert_eq!(Settings::from_json(&json), settings);
+        assert_eq!(Settings::from_json(&Map::new()), Settings::default());
+    }
+
+    #[test]
+    fn invalid_keys_are_defaulted() {
+        let json = serde_json::json!({
+            "window": {"size": [800], "position": [10, 20], "always_on_top": "yes"},
+            "present_mode": "Sometimes",
+            "output_encoding": "Gamma",
+            "shaders_dir": 3,
+            "hidden_windows": ["Log", "Toolbox"],
+            "appearance": {"theme": "Neon", "ui_scale": 10.0, "font_path": "", "font_size": "big"},
+            "defer_edits": "yes",
+            "keybindings": {"reload_shader": "Q"},
+            "osc": {"enabled": true, "port": 70000},
+        });
+        let JsonValue::Object(json) = json else {
+            unreachable!()
+        };
+        let settings = Settings::from_json(&json);
+
+        assert_eq!(
+            settings.window,
+            WindowSettings {
+                size: None,
+                position: Some(PhysicalPosition::new(10, 20)),
+                always_on_top: false,
+            }
+        );
+        assert_eq!(settings.present_mode, PresentMode::Fifo);
+        assert_eq!(settings.output_encoding, OutputEncoding::Srgb);
+        assert_eq!(settings.shaders_dir, PathBuf::from("shaders"));
+        assert_eq!(settings.hidden_windows, [UiWindow::Log]);
+        assert_eq!(
+            settings.appearance,
+            Appearance {
+                ui_scale: MAX_UI_SCALE,
+                ..Appearance::default()
+            }
+        );
+        assert!(!settings.defer_edits);
+        assert_eq!(settings.keybindings, Keybindings::defaults());
+        assert_eq!(settings.osc, OscSettings::default());
+    }
+
+    #[test]
+    fn other_keys_are_kept() {
+        let mut json = Map::new();
+        json.insert("plugin".into(), serde_json::json!({"port": 9000}));
+        Settings::default().to_json(&mut json);
+
+        assert_eq!(json["plugin"]["port"], 9000);
+        assert_eq!(json["present_mode"], "Fifo (vsync)");
+    }
+
+    #[test]
+    fn atomic_writes_replace_the_file() {
+        let path =
+            std::env::temp_dir().join(format!("te-shader-play-atomic-{}.json", std::process::id()));
+        write_atomically(&path, b"old").unwrap();
+        write_atomically(&path, b"new").unwrap();
+
+        assert_eq!(std::fs::read(&path).unwrap(), b"new");
+        let mut temp_path = OsString::from(&path);
+        temp_path.push(".tmp");
+        assert!(!Path::new(&temp_path).exists());
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn encodings_pick_the_first_matching_format() {
+        let formats = [
+            TextureFormat::Bgra8Unorm,
+            TextureFormat::Rgba8Unorm,
+            TextureFormat::Bgra8UnormSrgb,
+        ];
+        assert_eq!(
+            OutputEncoding::Srgb.format(&formats),
+            Some(TextureFormat::Bgra8UnormSrgb)
+        );
+        assert_eq!(
+            OutputEncoding::Linear.format(&formats),
+            Some(TextureFormat::Bgra8Unorm)
+        );
+        assert_eq!(
+            OutputEncoding::Linear.format(&[TextureFormat::Rgba8UnormSrgb]),
+            None
+        );
+    }
+}
@@ -1,69 +1,737 @@
 use std::{
-    borrow::Cow, collections::HashMap, fs, path::Path, time::{Duration, Instant}
+    collections::HashMap, fmt::{self, Display}, fs::File, io::{BufWriter, Write}, path::{Path, PathBuf}, sync::{Arc, Mutex}, time::{Duration, Instant}
 };
 
-use cgmath::num_traits::ToBytes;
+use cgmath::{num_traits::ToBytes, InnerSpace, Vector3};
 use wgpu::{
     core::{
         binding_model::LateMinBufferBindingSizeMismatch, command::{DrawError, RenderPassErrorInner}, pipeline::{CreateRenderPipelineError, CreateShaderModuleError}, validation::{BindingError, StageError}
-    }, util::{BufferInitDescriptor, DeviceExt}, BlendState, Buffer, BufferUsages, Color, ColorTargetState, ColorWrites, CompareFunction, DepthBiasState, DepthStencilState, Device, Extent3d, FragmentState, FrontFace, MultisampleState, PipelineCompilationOptions, PipelineLayout, PipelineLayoutDescriptor, PolygonMode, PrimitiveState, PrimitiveTopology, Queue, RenderPipeline, RenderPipelineDescriptor, ShaderModule, ShaderModuleDescriptor, ShaderSource, StencilState, Surface, SurfaceConfiguration, Texture, TextureDescriptor, TextureFormat, TextureUsages, VertexAttribute, VertexBufferLayout, VertexFormat, VertexState, VertexStepMode
+    }, util::{BufferInitDescriptor, DeviceExt}, Adapter, AdapterInfo, Backends, BindGroupLayout, BlendState, BufferDescriptor, CompositeAlphaMode, DeviceDescriptor, DeviceLostReason, Dx12Compiler, Features, Gles3MinorVersion, Instance, InstanceDescriptor, InstanceFlags, Limits, PowerPreference, PresentMode, RequestAdapterOptions, Buffer, BufferUsages, Color, ColorTargetState, ColorWrites, CompareFunction, DepthBiasState, DepthStencilState, Device, Extent3d, FragmentState, FrontFace, MultisampleState, PipelineCache, PipelineCacheDescriptor, PipelineCompilationOptions, PipelineLayout, PipelineLayoutDescriptor, PolygonMode, PrimitiveState, PrimitiveTopology, Queue, RenderPipeline, RenderPipelineDescriptor, ShaderModule, ShaderModuleDescriptor, ShaderSource, ShaderStages, StencilState, Surface, SurfaceConfiguration, Texture, TextureDescriptor, TextureFormat, TextureUsages, VertexAttribute, VertexBufferLayout, VertexFormat, VertexState, VertexStepMode, CreateSurfaceError, RequestDeviceError
+};
+use rfd::FileDialog;
+use winit::{
+    error::{EventLoopError, OsError},
+    window::Window,
 };
-use winit::window::Window;
 
 use crate::{
-    imgui_state::{ImState, MeshConfig, Message, Uniforms, IMAGE_HEIGHT, IMAGE_WIDTH},
+    gpu_errors::{self, OrReport},
+    imgui_state::{
+        DebugView, ImState, MeshConfig, Message, NormalMode, Severity, UniformGroup, UniformRequests,
+        Uniforms, UniformsHandle, DEFAULT_INSTANCE_SPACING,
+        IMAGE_HEIGHT, IMAGE_WIDTH,
+    },
+    state::{
+        capture::ScreenshotMetadata,
+        clip_planes::ClipPlanes,
+        debug_texture::{DebugTexture, DebugTextureOptions},
+        mesh_generation::MeshGenerator,
+        post_process::PostProcess,
+    },
+    reflection,
     rendering::RenderMessage,
+    rust_export::RustExport,
+    settings::{write_atomically, OutputEncoding, Settings, WindowSettings, SETTINGS_FILE},
+    web_export::WebExport,
+};
+
+pub(crate) mod capture;
+pub(crate) mod clip_planes;
+pub(crate) mod debug_texture;
+pub(crate) mod mesh_generation;
+pub(crate) mod obj_export;
+pub(crate) mod png;
+pub(crate) mod post_process;
+#[cfg(feature = "shared-output")]
+pub(crate) mod shared_output;
+
+const GRID_SHADER: &str = include_str!("../shaders/grid.wgsl");
+const BOUNDING_BOX_SHADER: &str = include_str!("../shaders/bounding_box.wgsl");
+const WIREFRAME_SHADER: &str = include_str!("../shaders/wireframe.wgsl");
+const DEBUG_VIEWS_SHADER: &str = include_str!("../shaders/debug_views.wgsl");
+
+/// Vertices of the custom shader's mesh, and the offsets of its instances
+const MESH_BUFFERS: [VertexBufferLayout<'static>; 2] = [
+    VertexBufferLayout {
+        array_stride: Vertex::SIZE,
+        step_mode: VertexStepMode::Vertex,
+        attributes: &[
+            VertexAttribute {
+                format: VertexFormat::Float32x3,
+                offset: 0,
+                shader_location: 0,
+            },
+            VertexAttribute {
+                format: VertexFormat::Float32x3,
+                offset: std::mem::size_of::<f32>() as u64 * 3,
+                shader_location: 2,
+            },
+        ],
+    },
+    VertexBufferLayout {
+        array_stride: std::mem::size_of::<f32>() as u64 * 3,
+        step_mode: VertexStepMode::Instance,
+        attributes: &[VertexAttribute {
+            format: VertexFormat::Float32x3,
+            offset: 0,
+            shader_location: 1,
+        }],
+    },
+];
+/// Draws nothing, used while there isn't a valid shader
+const DUMMY_SHADER: &str = "
+struct VertexOutput {
+    @builtin(position) pos: vec4<f32>,
 };
 
+@vertex
+fn vs_main() -> VertexOutput {
+    var out: VertexOutput;
+    out.pos = vec4(0.0,0.0,0.0,0.0);
+    return out;
+}
+
+@fragment
+fn fs_main() -> @location(0) vec4<f32> {
+    return vec4(0.0,0.0,0.0,0.0);
+}
+";
+/// Consecutive times a lost/outdated surface is reconfigured before giving up
+const MAX_SURFACE_RECONFIGURES: u32 = 5;
+/// Times the parameters are fixed to match the shader before giving up on a pipeline
+const MAX_PIPELINE_FIXES: u32 = 256;
+
+#[derive(Debug, Clone)]
+pub struct Paths {
+    pub shaders_dir: PathBuf,
+    pub save_file: PathBuf,
+}
+
+impl Default for Paths {
+    fn default() -> Self {
+        Paths {
+            shaders_dir: PathBuf::from("shaders"),
+            save_file: PathBuf::from("save.json"),
+        }
+    }
+}
+
+impl Paths {
+    /// Shader names are relative to the shaders folder, unless they are absolute paths
+    pub fn shader_path(&self, shader_name: &str) -> PathBuf {
+        let path = Path::new(shader_name);
+        if path.is_absolute() {
+            path.to_path_buf()
+        } else {
+            self.shaders_dir.join(path)
+        }
+    }
+
+    /// Web exports are written next to the save file, named after the shader
+    pub(crate) fn web_export_path(&self, shader_name: &str) -> PathBuf {
+        self.export_path(shader_name, "html")
+    }
+
+    pub(crate) fn rust_export_path(&self, shader_name: &str) -> PathBuf {
+        self.export_path(shader_name, "rs")
+    }
+
+    /// Next to the save file, named after the shader
+    fn export_path(&self, shader_name: &str, extension: &str) -> PathBuf {
+        let stem = Path::new(shader_name)
+            .file_stem()
+            .map_or("shader".into(), |stem| stem.to_string_lossy());
+        self.save_file.with_file_name(format!("{stem}.{extension}"))
+    }
+}
+
+/// Options that can be changed from the command line
+#[derive(Debug, Clone)]
+pub struct StartupOptions {
+    pub shader: String,
+    pub paths: Paths,
+    /// None to keep what the window had last time
+    pub always_on_top: Option<bool>,
+    pub gpu: GpuOptions,
+    /// What settings.json had at startup
+    pub(crate) settings: Settings,
+}
+
+impl Default for StartupOptions {
+    fn default() -> Self {
+        StartupOptions {
+            shader: "shader.wgsl".to_string(),
+            paths: Paths::default(),
+            always_on_top: None,
+            gpu: GpuOptions::default(),
+            settings: Settings::default(),
+        }
+    }
+}
+
+impl StartupOptions {
+    /// The defaults, with the shaders directory, present mode and output encoding saved in settings.json
+    pub fn from_settings() -> StartupOptions {
+        let settings = Settings::load();
+        let mut options = StartupOptions::default();
+        options.paths.shaders_dir = settings.shaders_dir.clone();
+        options.gpu.present_mode = settings.present_mode;
+        options.gpu.output_encoding = settings.output_encoding;
+        options.settings = settings;
+        options
+    }
+
+    /// The command line choice, or the saved one if there was none
+    pub(crate) fn starts_on_top(&self) -> bool {
+        self.always_on_top.unwrap_or(self.settings.window.always_on_top)
+    }
+}
+
+/// How the adapter and device are chosen
+#[derive(Debug, Clone)]
+pub struct GpuOptions {
+    /// Request the conservative WebGL2 limits instead of everything the adapter supports
+    pub downlevel: bool,
+    /// Use the first adapter whose name contains this, ignoring case
+    pub adapter: Option<String>,
+    pub backends: Backends,
+    /// Used to pick the adapter when none is named
+    pub power_preference: PowerPreference,
+    /// Falls back to Fifo if the surface doesn't support it
+    pub present_mode: PresentMode,
+    /// Falls back to the surface's preferred format if it has none with this encoding
+    pub(crate) output_encoding: OutputEncoding,
+}
+
+impl Default for GpuOptions {
+    fn default() -> Self {
+        GpuOptions {
+            downlevel: false,
+            adapter: None,
+            backends: Backends::all(),
+            power_preference: PowerPreference::default(),
+            present_mode: PresentMode::Fifo,
+            output_encoding: OutputEncoding::Srgb,
+        }
+    }
+}
+
+impl GpuOptions {
+    pub fn parse_backend(backend: &str) -> Result<Backends, String> {
+        match backend.to_lowercase().as_str() {
+            "vulkan" => Ok(Backends::VULKAN),
+            "dx12" => Ok(Backends::DX12),
+            "metal" => Ok(Backends::METAL),
+            "gl" => Ok(Backends::GL),
+            _ => Err(format!(
+                "Invalid backend {backend}, expected one of vulkan, dx12, metal or gl"
+            )),
+        }
+    }
+
+    pub fn parse_power_preference(power: &str) -> Result<PowerPreference, String> {
+        match power.to_lowercase().as_str() {
+            "low" => Ok(PowerPreference::LowPower),
+            "high" => Ok(PowerPreference::HighPerformance),
+            _ => Err(format!("Invalid power {power}, expected low or high")),
+        }
+    }
+}
+
+/// Why the playground couldn't start
+#[derive(Debug)]
+pub enum Error {
+    EventLoop(EventLoopError),
+    Window(OsError),
+    Surface(CreateSurfaceError),
+    /// No adapter can draw to the window
+    NoAdapter,
+    Device(RequestDeviceError),
+    /// The startup shader couldn't be read, with its path
+    Shader(PathBuf, std::io::Error),
+    /// The errors of the GPU resources that couldn't be created went to the log
+    Resources,
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::EventLoop(err) => write!(f, "Event loop error: {err}"),
+            Error::Window(err) => write!(f, "Couldn't create the window: {err}"),
+            Error::Surface(err) => write!(f, "Couldn't create the window surface: {err}"),
+            Error::NoAdapter => write!(f, "No GPU adapter can draw to the window"),
+            Error::Device(err) => write!(f, "Couldn't create the GPU device: {err}"),
+            Error::Shader(path, err) => write!(f, "Couldn't read {}: {err}", path.display()),
+            Error::Resources => write!(f, "Couldn't create the GPU resources, see the log"),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+fn stage_name(stage: ShaderStages) -> &'static str {
+    if stage.contains(ShaderStages::VERTEX) {
+        "Vertex"
+    } else if stage.contains(ShaderStages::FRAGMENT) {
+        "Fragment"
+    } else {
+        "Compute"
+    }
+}
+
 pub struct TimeKeeper {
     last_render_time: Instant,
     starting_time: Instant,
+    paused_at: Option<Instant>,
+    /// Time the timeline is held at while it's dragged
+    scrubbed: Option<Duration>,
+    /// Time wraps around after this long
+    loop_length: Option<Duration>,
+    /// Deterministic mode: every rendered frame advances the time by this step, whatever the
+    /// clock says, so the same frame always gets the same time
+    fixed_step: Option<Duration>,
+    /// Time of the deterministic mode
+    stepped: Duration,
 }
 
 impl TimeKeeper {
-    fn new() -> TimeKeeper {
+    pub(crate) fn new() -> TimeKeeper {
         let now = Instant::now();
 
         TimeKeeper {
             last_render_time: now,
             starting_time: now,
+            paused_at: None,
+            scrubbed: None,
+            loop_length: None,
+            fixed_step: None,
+            stepped: Duration::ZERO,
+        }
+    }
+
+    fn elapsed(&self) -> Duration {
+        let elapsed = match (self.scrubbed, self.fixed_step, self.paused_at) {
+            (Some(scrubbed), ..) => scrubbed,
+            (None, Some(_), _) => self.stepped,
+            (None, None, Some(paused_at)) => paused_at - self.starting_time,
+            (None, None, None) => self.starting_time.elapsed(),
+        };
+        match self.loop_length {
+            Some(length) if !length.is_zero() => {
+                Duration::from_secs_f64(elapsed.as_secs_f64() % length.as_secs_f64())
+            }
+            _ => elapsed,
+        }
+    }
+
+    fn toggle_pause(&mut self) {
+        match self.paused_at.take() {
+            // Shift the start so the paused interval doesn't count
+            Some(paused_at) => self.starting_time += paused_at.elapsed(),
+            None => self.paused_at = Some(Instant::now()),
         }
     }
 
-    pub fn update_time(&mut self, queue: &Queue, uniforms: &mut Uniforms) -> Duration {
+    fn reset(&mut self) {
+        self.seek(Duration::ZERO)
+    }
+
+    fn scrub(&mut self, time: Duration) {
+        self.scrubbed = Some(time)
+    }
+
+    /// Time goes on from `time`, or stays there if it's paused
+    fn seek(&mut self, time: Duration) {
         let now = Instant::now();
-        let dt = now - self.last_render_time;
+        self.scrubbed = None;
+        self.stepped = time;
+        self.starting_time = now.checked_sub(time).unwrap_or(now);
+        if self.paused_at.is_some() {
+            self.paused_at = Some(now)
+        }
+    }
+
+    fn set_loop(&mut self, loop_length: Option<Duration>) {
+        // Keeps the current point, so the time doesn't jump when the loop is turned off
+        let elapsed = self.elapsed();
+        self.loop_length = loop_length;
+        if self.scrubbed.is_none() {
+            self.seek(elapsed)
+        }
+    }
+
+    /// Deterministic mode starts from 0, leaving it goes on from the current time
+    pub(crate) fn set_fixed_step(&mut self, fixed_step: Option<Duration>) {
+        let elapsed = self.elapsed();
+        self.fixed_step = fixed_step;
+        match fixed_step {
+            Some(_) => self.seek(Duration::ZERO),
+            None => self.seek(elapsed),
+        }
+    }
+
+    /// Time and delta time of the frame rendered at `now`
+    fn advance(&mut self, now: Instant) -> (Duration, Duration) {
+        let running = self.paused_at.is_none() && self.scrubbed.is_none();
+        let dt = match self.fixed_step {
+            Some(step) if running => step,
+            Some(_) => Duration::ZERO,
+            None => now - self.last_render_time,
+        };
         self.last_render_time = now;
 
-        let elapsed_time = self.starting_time.elapsed().as_millis() as u32;
-        uniforms.update_time(elapsed_time, queue);
+        let elapsed = self.elapsed();
+        if self.fixed_step.is_some() {
+            self.stepped += dt
+        }
+        (elapsed, dt)
+    }
+
+    pub fn update_time(&mut self, uniforms: &mut Uniforms) -> Duration {
+        let (elapsed, dt) = self.advance(Instant::now());
+        uniforms.update_time(elapsed);
+        uniforms.update_camera_path(elapsed);
+        uniforms.update_random();
+        uniforms.update_camera_transition(dt);
+        uniforms.run_script(elapsed, dt);
 
         dt
     }
 }
 
+/// Keeps the window title up to date without calling into the OS every frame
+struct TitleKeeper {
+    current: String,
+    frames: u32,
+    last_fps_update: Instant,
+    fps: f32,
+}
+
+impl TitleKeeper {
+    const FPS_INTERVAL: Duration = Duration::from_secs(1);
+
+    fn new() -> TitleKeeper {
+        TitleKeeper {
+            current: String::new(),
+            frames: 0,
+            last_fps_update: Instant::now(),
+            fps: 0.0,
+        }
+    }
+
+    /// Returns the new title only if it differs from the one the window already has
+    fn update(&mut self, shader_name: &str, has_errors: bool, dirty: bool) -> Option<String> {
+        self.frames += 1;
+        let elapsed = self.last_fps_update.elapsed();
+        if elapsed >= Self::FPS_INTERVAL {
+            self.fps = self.frames as f32 / elapsed.as_secs_f32();
+            self.frames = 0;
+            self.last_fps_update = Instant::now();
+        }
+
+        let error = if has_errors { " [ERROR]" } else { "" };
+        let dirty = if dirty { "*" } else { "" };
+        let title = format!(
+            "{shader_name}{dirty}{error} - {:.0} FPS - te-shader-play",
+            self.fps
+        );
+        if title == self.current {
+            None
+        } else {
+            self.current = title.clone();
+            Some(title)
+        }
+    }
+}
+
 pub struct Gpu<'surface> {
     pub surface: Surface<'surface>,
     pub device: Device,
     pub queue: Queue,
     pub config: SurfaceConfiguration,
+    surface_failures: u32,
+    /// Set by the device lost callback, with the message of the driver
+    device_lost: Arc<Mutex<Option<String>>>,
+    /// Limits granted to the device
+    pub(crate) limits: Limits,
+    pub(crate) adapter: AdapterInfo,
+    /// Every adapter that can draw to the window, shown so the user can pick another one
+    pub(crate) adapters: Vec<AdapterInfo>,
+    /// Present modes the surface supports with this adapter
+    pub(crate) present_modes: Vec<PresentMode>,
+    /// Formats the surface supports with this adapter, the preferred one first
+    pub(crate) formats: Vec<TextureFormat>,
+    /// Why the device didn't get the limits it asked for, if it didn't
+    pub(crate) limits_fallback: Option<String>,
+    /// Kept for when the device is recreated
+    options: GpuOptions,
+    /// Compiled pipelines from previous runs, if the adapter supports it
+    pub(crate) pipeline_cache: Option<PipelineCache>,
+}
+
+/// Features requested only when the adapter supports them, with what needs them
+pub(crate) const OPTIONAL_FEATURES: &[(Features, &str)] = &[
+    (Features::POLYGON_MODE_LINE, "Mesh wireframe"),
+    (Features::PIPELINE_CACHE, "Caching pipelines between runs"),
+];
+
+/// The desired features the adapter supports, requesting any other would fail
+fn available_features(desired: &[(Features, &str)], supported: Features) -> Features {
+    let desired: Features = desired.iter().map(|(feature, _)| *feature).collect();
+    desired & supported
+}
+
+/// The user's directory for files that can be rebuilt, None if the environment doesn't say
+fn cache_dir() -> Option<PathBuf> {
+    let var = |name: &str| {
+        std::env::var_os(name)
+            .filter(|value| !value.is_empty())
+            .map(PathBuf::from)
+    };
+    let base = if cfg!(windows) {
+        var("LOCALAPPDATA")?
+    } else if cfg!(target_os = "macos") {
+        var("HOME")?.join("Library/Caches")
+    } else {
+        var("XDG_CACHE_HOME").or_else(|| Some(var("HOME")?.join(".cache")))?
+    };
+    Some(base.join("te-shader-play"))
+}
+
+/// The cache only works with the adapter and driver that made it, so the file is named after them
+fn pipeline_cache_path(adapter: &AdapterInfo) -> Option<PathBuf> {
+    let key = wgpu::util::pipeline_cache_key(adapter)?;
+    Some(cache_dir()?.join(key))
 }
 
 impl<'surface> Gpu<'surface> {
+    /// Creates the surface of `window` and a device to draw on it.
+    /// Also used to start over when the device is lost
     pub fn new(
-        surface: Surface<'_>,
-        device: Device,
-        queue: Queue,
-        config: SurfaceConfiguration,
-    ) -> Gpu<'_> {
-        Gpu {
+        window: &'surface Window,
+        size: winit::dpi::PhysicalSize<u32>,
+        options: GpuOptions,
+    ) -> Result<Gpu<'surface>, Error> {
+        let instance = Instance::new(InstanceDescriptor {
+            backends: options.backends,
+            flags: InstanceFlags::default(),
+            dx12_shader_compiler: Dx12Compiler::Fxc,
+            gles_minor_version: Gles3MinorVersion::Automatic,
+        });
+
+        let surface = instance.create_surface(window).map_err(Error::Surface)?;
+
+        let mut adapters: Vec<Adapter> = instance
+            .enumerate_adapters(options.backends)
+            .into_iter()
+            .filter(|adapter| adapter.is_surface_supported(&surface))
+            .collect();
+        let adapter_infos = adapters.iter().map(Adapter::get_info).collect();
+        let chosen = options.adapter.as_ref().and_then(|name| {
+            let name = name.to_lowercase();
+            let index = adapters
+                .iter()
+                .position(|adapter| adapter.get_info().name.to_lowercase().contains(&name));
+            if index.is_none() {
+                log::warn!("No adapter matches \"{name}\", using the default one");
+            }
+            index.map(|index| adapters.swap_remove(index))
+        });
+        let adapter = match chosen {
+            Some(adapter) => adapter,
+            None => pollster::block_on(instance.request_adapter(&RequestAdapterOptions {
+                power_preference: options.power_preference,
+                force_fallback_adapter: false,
+                compatible_surface: Some(&surface),
+            }))
+            .ok_or(Error::NoAdapter)?,
+        };
+
+        // What needs a missing feature is disabled, e.g. "Show mesh" without POLYGON_MODE_LINE
+        let optional_features = available_features(OPTIONAL_FEATURES, adapter.features());
+        let mut limits_fallback = None;
+        let mut required_limits = if options.downlevel {
+            Limits::downlevel_webgl2_defaults()
+        } else {
+            adapter.limits()
+        };
+        let mut unmet_limits = Vec::new();
+        required_limits.check_limits_with_fail_fn(
+            &adapter.limits(),
+            false,
+            |name, requested, allowed| {
+                unmet_limits.push(format!("{name} ({requested}, it allows {allowed})"))
+            },
+        );
+        if !unmet_limits.is_empty() {
+            let fallback = format!(
+                "The adapter doesn't meet the WebGL2 limits, using its own. Unmet:\n{}",
+                unmet_limits.join("\n")
+            );
+            log::warn!("{fallback}");
+            limits_fallback = Some(fallback);
+            required_limits = adapter.limits();
+        }
+        let request_device = |required_limits| {
+            pollster::block_on(adapter.request_device(
+                &DeviceDescriptor {
+                    label: None,
+                    required_features: Features::default() | optional_features,
+                    required_limits,
+                    memory_hints: Default::default(),
+                },
+                None,
+            ))
+        };
+        let (device, queue) = match request_device(required_limits.clone()) {
+            Ok(device) => device,
+            Err(err) if required_limits != adapter.limits() => {
+                let fallback =
+                    format!("Couldn't create the device ({err}), using the adapter's limits");
+                log::warn!("{fallback}");
+                limits_fallback = Some(fallback);
+                request_device(adapter.limits()).map_err(Error::Device)?
+            }
+            Err(err) => return Err(Error::Device(err)),
+        };
+
+        device.on_uncaptured_error(Box::new(|error: wgpu::Error| gpu_errors::report(error)));
+
+        let device_lost = Arc::new(Mutex::new(None));
+        let lost = device_lost.clone();
+        device.set_device_lost_callback(move |reason, message| {
+            // Destroying the device on purpose (e.g. when it's replaced) isn't a loss
+            if let DeviceLostReason::Unknown = reason {
+                log::warn!("Device lost: {message}");
+                *lost.lock().unwrap() = Some(message)
+            }
+        });
+
+        let capabilities = surface.get_capabilities(&adapter);
+        // Fifo is always supported
+        let present_mode = if capabilities.present_modes.contains(&options.present_mode) {
+            options.present_mode
+        } else {
+            log::warn!(
+                "The surface doesn't support the {:?} present mode, using Fifo",
+                options.present_mode
+            );
+            PresentMode::Fifo
+        };
+        let format = options
+            .output_encoding
+            .format(&capabilities.formats)
+            .unwrap_or(capabilities.formats[0]);
+        let config = SurfaceConfiguration {
+            usage: TextureUsages::RENDER_ATTACHMENT,
+            format,
+            width: size.width,
+            height: size.height,
+            present_mode,
+            alpha_mode: CompositeAlphaMode::Auto,
+            view_formats: vec![format],
+            desired_maximum_frame_latency: 2,
+        };
+
+        surface.configure(&device, &config);
+
+        let adapter_info = adapter.get_info();
+        let pipeline_cache = if device.features().contains(Features::PIPELINE_CACHE) {
+            let data = pipeline_cache_path(&adapter_info).and_then(|path| std::fs::read(path).ok());
+            // SAFETY: the data was returned by `get_data` of a cache for this adapter and driver,
+            // and `fallback` discards it if it's invalid anyway
+            unsafe {
+                device.create_pipeline_cache(&PipelineCacheDescriptor {
+                    label: Some("Pipeline cache"),
+                    data: data.as_deref(),
+                    fallback: true,
+                })
+            }
+            .ok()
+        } else {
+            None
+        };
+
+        Ok(Gpu {
             surface,
             device,
             queue,
             config,
+            surface_failures: 0,
+            device_lost,
+            limits: device.limits(),
+            adapter: adapter_info,
+            adapters: adapter_infos,
+            present_modes: capabilities.present_modes,
+            formats: capabilities.formats,
+            limits_fallback,
+            options,
+            pipeline_cache,
+        })
+    }
+
+    /// Writes the pipeline cache to disk, so the next run starts with the compiled pipelines
+    pub(crate) fn save_pipeline_cache(&self) {
+        let Some(data) = self.pipeline_cache.as_ref().and_then(PipelineCache::get_data) else {
+            return;
+        };
+        let Some(path) = pipeline_cache_path(&self.adapter) else {
+            return;
+        };
+        let written = match path.parent() {
+            Some(dir) => std::fs::create_dir_all(dir),
+            None => Ok(()),
+        }
+        .and_then(|()| write_atomically(&path, &data));
+        if let Err(err) = written {
+            log::warn!("Couldn't save the pipeline cache to {}: {err}", path.display())
         }
     }
 
+    pub(crate) fn is_lost(&self) -> bool {
+        self.device_lost.lock().unwrap().is_some()
+    }
+
+    /// Why the device was lost, as the driver explained it
+    fn lost_message(&self) -> Option<String> {
+        self.device_lost.lock().unwrap().clone()
+    }
+
+    /// Reconfigures a lost or outdated surface with its current size.
+    /// Returns false once it has failed too many times in a row, so it isn't retried forever
+    pub(crate) fn reconfigure_surface(&mut self) -> bool {
+        self.surface_failures += 1;
+        if self.surface_failures > MAX_SURFACE_RECONFIGURES {
+            return false;
+        }
+
+        let size = winit::dpi::PhysicalSize::new(self.config.width, self.config.height);
+        self.resize(size);
+        true
+    }
+
+    pub(crate) fn surface_recovered(&mut self) {
+        self.surface_failures = 0
+    }
+
+    /// Only takes modes from `present_modes`
+    pub(crate) fn set_present_mode(&mut self, present_mode: PresentMode) {
+        self.options.present_mode = present_mode;
+        self.config.present_mode = present_mode;
+        self.surface.configure(&self.device, &self.config);
+    }
+
+    /// Does nothing if the surface has no format with that encoding
+    pub(crate) fn set_output_encoding(&mut self, output_encoding: OutputEncoding) {
+        let Some(format) = output_encoding.format(&self.formats) else {
+            return;
+        };
+        self.options.output_encoding = output_encoding;
+        self.config.format = format;
+        self.config.view_formats = vec![format];
+        self.surface.configure(&self.device, &self.config);
+    }
+
     fn resize(&mut self, size: winit::dpi::PhysicalSize<u32>) {
         self.config.width = size.width;
         self.config.height = size.height;
@@ -76,21 +744,57 @@ struct Shader {
     shader: ShaderModule,
 }
 
+impl Shader {
+    /// Falls back to a shader that draws nothing if `contents` doesn't compile.
+    /// None if not even that can be created
+    fn new(device: &Device, contents: String) -> Option<Shader> {
+        let shader = device
+            .create_shader_module(ShaderModuleDescriptor {
+                label: None,
+                source: ShaderSource::Wgsl(contents.clone().into()),
+            })
+            .or_else(|_| {
+                device.create_shader_module(ShaderModuleDescriptor {
+                    label: None,
+                    source: ShaderSource::Wgsl(DUMMY_SHADER.into()),
+                })
+            })
+            .or_report()?;
+
+        Some(Shader { contents, shader })
+    }
+
+    /// Creates the module again for a new device
+    fn recreate(&mut self, device: &Device) -> Result<(), Error> {
+        *self = Shader::new(device, self.contents.clone()).ok_or(Error::Resources)?;
+        Ok(())
+    }
+}
+
 #[derive(Debug, Clone, Copy)]
 pub struct Vertex {
     x: f32,
     y: f32,
     z: f32,
+    normal: [f32; 3],
 }
 impl Vertex {
+    /// Stride of vertex buffers, the position followed by the normal
+    pub(crate) const SIZE: u64 = std::mem::size_of::<f32>() as u64 * 6;
+
     fn to_le_bytes(&self) -> Vec<u8> {
         self.x
             .to_le_bytes()
             .into_iter()
             .chain(self.y.to_le_bytes())
             .chain(self.z.to_le_bytes())
+            .chain(self.normal.iter().flat_map(|v| v.to_le_bytes()))
             .collect()
     }
+
+    fn position(&self) -> Vector3<f32> {
+        Vector3::new(self.x, self.y, self.z)
+    }
 }
 
 pub struct VerticesSet {
@@ -103,9 +807,89 @@ pub struct VerticesSet {
 pub struct Vertices {
     pub custom_shader: VerticesSet,
     pub grid: VerticesSet,
+    /// Edges of the box around the custom shader's mesh, as a line list
+    pub(crate) bounding_box: VerticesSet,
+    pub(crate) instances: Instances,
+}
+
+/// Offsets of each instance of the custom shader's mesh, laid out in a grid on the XZ plane.
+/// The shader reads them as a `vec3<f32>` at `@location(1)`
+pub(crate) struct Instances {
+    pub(crate) buffer: Buffer,
+    pub(crate) count: u32,
+}
+
+impl Instances {
+    fn new(device: &Device, count: u32, spacing: f32) -> Option<Instances> {
+        let offsets: Vec<u8> = Self::grid_offsets(count, spacing)
+            .iter()
+            .flatten()
+            .flat_map(|v| v.to_le_bytes())
+            .collect();
+        let buffer = device
+            .create_buffer_init(&BufferInitDescriptor {
+                label: Some("Instance buffer"),
+                contents: &offsets,
+                usage: BufferUsages::VERTEX,
+            })
+            .or_report()?;
+
+        Some(Instances { buffer, count })
+    }
+
+    /// Rows of `spacing` apart, centered on the origin
+    fn grid_offsets(count: u32, spacing: f32) -> Vec<[f32; 3]> {
+        let columns = (count as f32).sqrt().ceil().max(1.0) as u32;
+        let rows = count.div_ceil(columns);
+        (0..count)
+            .map(|i| {
+                let column = (i % columns) as f32 - (columns - 1) as f32 / 2.0;
+                let row = (i / columns) as f32 - (rows - 1) as f32 / 2.0;
+                [column * spacing, 0.0, row * spacing]
+            })
+            .collect()
+    }
 }
 
 impl VerticesSet {
+    fn new(device: &Device, (vertices, indices): (Vec<Vertex>, Vec<u32>)) -> Option<VerticesSet> {
+        Some(VerticesSet {
+            vertex_buffer: device
+                .create_buffer_init(&BufferInitDescriptor {
+                    label: Some("Vertex buffer"),
+                    contents: &vertices
+                        .iter()
+                        .flat_map(|vert| vert.to_le_bytes())
+                        .collect::<Vec<_>>(),
+                    usage: BufferUsages::VERTEX | BufferUsages::COPY_DST,
+                })
+                .or_report()?,
+            vertices,
+            index_buffer: device
+                .create_buffer_init(&BufferInitDescriptor {
+                    label: Some("Index buffer"),
+                    contents: &indices
+                        .iter()
+                        .flat_map(|ind| (*ind).to_le_bytes())
+                        .collect::<Vec<_>>(),
+                    usage: BufferUsages::INDEX | BufferUsages::COPY_DST,
+                })
+                .or_report()?,
+            indices,
+        })
+    }
+
+    /// The same mesh in new buffers, for a new device
+    fn recreate(&self, device: &Device) -> Result<VerticesSet, Error> {
+        VerticesSet::new(device, (self.vertices.clone(), self.indices.clone()))
+            .ok_or(Error::Resources)
+    }
+
+    /// Quad that covers the whole target, regardless of the mesh configuration
+    pub(crate) fn screen_quad(device: &Device) -> Option<VerticesSet> {
+        Self::new(device, Self::screen_2d_vertices())
+    }
+
     fn default_vertices() -> (Vec<Vertex>, Vec<u32>) {
         Self::screen_2d_vertices()
     }
@@ -118,21 +902,25 @@ impl VerticesSet {
                     x: -1.0,
                     y: 1.0,
                     z: 0.0,
+                    normal: [0.0, 0.0, 1.0],
                 },
                 Vertex {
                     x: 1.0,
                     y: 1.0,
                     z: 0.0,
+                    normal: [0.0, 0.0, 1.0],
                 },
                 Vertex {
                     x: -1.0,
                     y: -1.0,
                     z: 0.0,
+                    normal: [0.0, 0.0, 1.0],
                 },
                 Vertex {
                     x: 1.0,
                     y: -1.0,
                     z: 0.0,
+                    normal: [0.0, 0.0, 1.0],
                 },
             ],
             vec![
@@ -142,57 +930,213 @@ impl VerticesSet {
         )
     }
 
-    fn switch(&mut self, mesh_config: &MeshConfig, device: &Device) {
-        let (vertices, indices) = match mesh_config {
+    /// The 8 corners of the box that contains every vertex, and its 12 edges
+    fn bounding_box(vertices: &[Vertex]) -> (Vec<Vertex>, Vec<u32>) {
+        let (min, max) = match vertices.first() {
+            Some(first) => vertices.iter().fold(
+                (first.position(), first.position()),
+                |(mut min, mut max), vertex| {
+                    let position = vertex.position();
+                    for axis in 0..3 {
+                        min[axis] = min[axis].min(position[axis]);
+                        max[axis] = max[axis].max(position[axis]);
+                    }
+                    (min, max)
+                },
+            ),
+            None => (Vector3::new(0.0, 0.0, 0.0), Vector3::new(0.0, 0.0, 0.0)),
+        };
+
+        // Bit 0 picks the x of the corner, bit 1 the y and bit 2 the z
+        let corners = (0..8)
+            .map(|corner| Vertex {
+                x: if corner & 1 == 0 { min.x } else { max.x },
+                y: if corner & 2 == 0 { min.y } else { max.y },
+                z: if corner & 4 == 0 { min.z } else { max.z },
+                normal: [0.0, 0.0, 0.0],
+            })
+            .collect();
+        // Each edge joins two corners that differ in a single bit
+        let edges = (0..8u32)
+            .flat_map(|corner| {
+                [1, 2, 4]
+                    .into_iter()
+                    .filter(move |bit| corner & bit == 0)
+                    .flat_map(move |bit| [corner, corner | bit])
+            })
+            .collect();
+
+        (corners, edges)
+    }
+
+    /// Runs on the mesh generation thread
+    fn generate(mesh_config: &MeshConfig, normal_mode: NormalMode) -> (Vec<Vertex>, Vec<u32>) {
+        let mesh = match mesh_config {
             MeshConfig::Screen2D => Self::screen_2d_vertices(),
             MeshConfig::Plane(size, resolution) => Self::plane_vertices(*size, *resolution),
-            MeshConfig::Sphere => todo!(),
-            MeshConfig::Cube => todo!(),
-            MeshConfig::Cylinder => todo!(),
-            MeshConfig::Cone => todo!(),
-            MeshConfig::Torus => todo!(),
+            MeshConfig::Sphere(radius, resolution) => mesh_generation::sphere(*radius, *resolution),
+            MeshConfig::Cube(side, subdivisions) => mesh_generation::cube(*side, *subdivisions),
+            MeshConfig::Cylinder(size, segments) => mesh_generation::cylinder(*size, *segments),
+            MeshConfig::Cone(size, segments) => mesh_generation::cone(*size, *segments),
+            MeshConfig::Torus(radii, resolution) => mesh_generation::torus(*radii, *resolution),
+        };
+        Self::with_normals(mesh, normal_mode)
+    }
+
+    /// Writes the mesh into the current buffers, only reallocating them when it doesn't fit.
+    /// The buffers may be bigger than the mesh, so draws must use `indices.len()`
+    fn replace(
+        &mut self,
+        (vertices, indices): (Vec<Vertex>, Vec<u32>),
+        device: &Device,
+        queue: &Queue,
+    ) {
+        let vertex_bytes: Vec<u8> = vertices
+            .iter()
+            .flat_map(|vert| vert.to_le_bytes())
+            .collect();
+        let index_bytes: Vec<u8> = indices.iter().flat_map(|ind| ind.to_le_bytes()).collect();
+
+        // Grow both buffers before writing to either, so they always match the mesh
+        let vertex_buffer = Self::grow(
+            &self.vertex_buffer,
+            vertex_bytes.len() as u64,
+            "vertex buffer",
+            BufferUsages::VERTEX,
+            device,
+        );
+        let index_buffer = Self::grow(
+            &self.index_buffer,
+            index_bytes.len() as u64,
+            "index buffer",
+            BufferUsages::INDEX,
+            device,
+        );
+        let (Some(vertex_buffer), Some(index_buffer)) = (vertex_buffer, index_buffer) else {
+            return;
         };
+        if let Some(vertex_buffer) = vertex_buffer {
+            self.vertex_buffer = vertex_buffer
+        }
+        if let Some(index_buffer) = index_buffer {
+            self.index_buffer = index_buffer
+        }
 
+        queue
+            .write_buffer(&self.vertex_buffer, 0, &vertex_bytes)
+            .or_report();
+        queue
+            .write_buffer(&self.index_buffer, 0, &index_bytes)
+            .or_report();
         self.vertices = vertices;
         self.indices = indices;
+    }
 
-        self.vertex_buffer = device
-            .create_buffer_init(&BufferInitDescriptor {
-                label: Some("vertex buffer"),
-                contents: &self
-                    .vertices
-                    .iter()
-                    .flat_map(|vert| vert.to_le_bytes())
-                    .collect::<Vec<_>>(),
-                usage: BufferUsages::VERTEX,
+    /// A new buffer if `size` bytes don't fit in `buffer`, Some(None) if they do.
+    /// None if the new buffer can't be created
+    fn grow(
+        buffer: &Buffer,
+        size: u64,
+        label: &str,
+        usage: BufferUsages,
+        device: &Device,
+    ) -> Option<Option<Buffer>> {
+        if size <= buffer.size() {
+            return Some(None);
+        }
+
+        // Leave room to grow, so dragging a slider doesn't reallocate on every step
+        let capacity = size
+            .next_power_of_two()
+            .min(device.limits().max_buffer_size)
+            .max(size);
+        device
+            .create_buffer(&BufferDescriptor {
+                label: Some(label),
+                size: capacity,
+                usage: usage | BufferUsages::COPY_DST,
+                mapped_at_creation: false,
             })
-            .unwrap();
+            .or_report()
+            .map(Some)
+    }
 
-        self.index_buffer = device
-            .create_buffer_init(&BufferInitDescriptor {
-                label: Some("index buffer"),
-                contents: &self
-                    .indices
+    /// Flat normals give each triangle its own copy of its vertices, with the face's normal.
+    /// Smooth normals keep the shared vertices, with the average normal of their faces
+    fn with_normals(
+        (vertices, indices): (Vec<Vertex>, Vec<u32>),
+        normal_mode: NormalMode,
+    ) -> (Vec<Vertex>, Vec<u32>) {
+        let face_normal = |triangle: &[u32]| {
+            let [p1, p2, p3] = [0, 1, 2].map(|i| vertices[triangle[i] as usize].position());
+            (p2 - p1).cross(p3 - p1)
+        };
+
+        match normal_mode {
+            NormalMode::Flat => {
+                let mut flat_vertices = Vec::with_capacity(indices.len());
+                for triangle in indices.chunks_exact(3) {
+                    let normal = face_normal(triangle);
+                    let normal = if normal.magnitude2() > 0.0 {
+                        normal.normalize()
+                    } else {
+                        normal
+                    };
+                    for index in triangle {
+                        flat_vertices.push(Vertex {
+                            normal: normal.into(),
+                            ..vertices[*index as usize]
+                        })
+                    }
+                }
+                let flat_indices = (0..flat_vertices.len() as u32).collect();
+                (flat_vertices, flat_indices)
+            }
+            NormalMode::Smooth => {
+                // Unnormalized face normals, so bigger faces weigh more
+                let mut normals = vec![Vector3::new(0.0, 0.0, 0.0); vertices.len()];
+                for triangle in indices.chunks_exact(3) {
+                    let normal = face_normal(triangle);
+                    for index in triangle {
+                        normals[*index as usize] += normal
+                    }
+                }
+                let smooth_vertices = vertices
                     .iter()
-                    .flat_map(|vert| vert.to_le_bytes())
-                    .collect::<Vec<_>>(),
-                usage: BufferUsages::INDEX,
-            })
-            .unwrap();
+                    .zip(normals)
+                    .map(|(vertex, normal)| Vertex {
+                        normal: if normal.magnitude2() > 0.0 {
+                            normal.normalize().into()
+                        } else {
+                            vertex.normal
+                        },
+                        ..*vertex
+                    })
+                    .collect();
+                (smooth_vertices, indices)
+            }
+        }
     }
 
     fn plane_vertices(size: (f32, f32), resolution: (u32, u32)) -> (Vec<Vertex>, Vec<u32>) {
-        let mut points = Vec::new();
+        let mut points =
+            Vec::with_capacity((resolution.0 as usize + 1) * (resolution.1 as usize + 1));
         for z in 0..=resolution.1 {
             for x in 0..=resolution.0 {
                 let x = (x as f32 / (resolution.0 as f32) - 1.0) * size.0;
                 let z = (z as f32 / (resolution.1 as f32) - 1.0) * size.1;
-                let vertex = Vertex { x, y: 0.0, z };
+                let vertex = Vertex {
+                    x,
+                    y: 0.0,
+                    z,
+                    normal: [0.0, 1.0, 0.0],
+                };
                 points.push(vertex)
             }
         }
 
-        let mut triangles: Vec<u32> = Vec::new();
+        let mut triangles: Vec<u32> =
+            Vec::with_capacity(resolution.0 as usize * resolution.1 as usize * 6);
         for i in 0..resolution.1 {
             for j in 0..resolution.0 {
                 // 2 triangles per square
@@ -230,6 +1174,11 @@ impl VerticesSet {
 pub struct Pipelines {
     pub custom_shader: RenderPipeline,
     pub grid: RenderPipeline,
+    pub(crate) bounding_box: RenderPipeline,
+    /// Wireframe of the mesh drawn over the filled one, when "Show mesh" is set to overlay
+    pub(crate) mesh_overlay: Option<RenderPipeline>,
+    /// Draw the mesh instead of the custom shader, created the first time each view is shown
+    pub(crate) debug_views: HashMap<DebugView, RenderPipeline>,
 }
 
 pub struct DepthTextures {
@@ -237,7 +1186,7 @@ pub struct DepthTextures {
     pub background: Texture
 }
 impl DepthTextures {
-    fn new(device: &Device, width: u32, height: u32) -> DepthTextures {
+    fn new(device: &Device, width: u32, height: u32) -> Option<DepthTextures> {
         let depth_texture = device
             .create_texture(&TextureDescriptor {
                 label: Some("Depth view"),
@@ -253,7 +1202,7 @@ impl DepthTextures {
                 usage: TextureUsages::RENDER_ATTACHMENT | TextureUsages::TEXTURE_BINDING,
                 view_formats: &[TextureFormat::Depth32Float],
             })
-            .unwrap();
+            .or_report()?;
 
         let imgui_depth_texture = device
             .create_texture(&TextureDescriptor {
@@ -270,12 +1219,12 @@ impl DepthTextures {
                 usage: TextureUsages::RENDER_ATTACHMENT | TextureUsages::TEXTURE_BINDING,
                 view_formats: &[TextureFormat::Depth32Float],
             })
-            .unwrap();
+            .or_report()?;
 
-        DepthTextures {
+        Some(DepthTextures {
             imgui: imgui_depth_texture,
             background: depth_texture,
-        }
+        })
     }
 }
 
@@ -284,76 +1233,60 @@ pub struct State<'surface> {
     pub pipelines: Pipelines,
     pub time: TimeKeeper,
     pub im_state: ImState,
-    current_shader_path: String,
+    pub(crate) current_shader_path: String,
+    paths: Paths,
+    title: TitleKeeper,
+    settings: Settings,
+    /// The window is bigger than the biggest surface, it's only warned about when it becomes so
+    oversized_window: bool,
+    /// The camera was enabled last frame. The debug view can only be built with the camera
+    camera_was_enabled: bool,
     current_shader: Shader,
     grid_shader: Shader,
+    bounding_box_shader: Shader,
+    wireframe_shader: Shader,
+    debug_views_shader: Shader,
     pub vertices: Vertices,
     pub depth_textures: DepthTextures,
+    /// Optional second pass that samples the output of the custom shader
+    pub(crate) post: Option<PostProcess>,
+    pub(crate) clip_planes: ClipPlanes,
+    /// Generated texture for checking UVs, bound in the group after the uniform groups
+    pub(crate) debug_texture: Option<DebugTexture>,
+    /// Layout of the enabled uniform groups, with the number of bindings of each group it was
+    /// created for. It only changes when bindings or groups are added or toggled
+    pipeline_layout: Option<(Vec<usize>, PipelineLayout)>,
+    mesh_generator: MeshGenerator,
+    uniform_requests: UniformRequests,
+    #[cfg(feature = "shared-output")]
+    shared_output: Option<shared_output::SharedOutput>,
 }
 
 impl<'surface> State<'surface> {
-    pub fn new(gpu: Gpu<'surface>, window: &Window) -> State<'surface> {
-        let current_shader =
-            std::fs::read_to_string(Path::new("shaders").join("shader.wgsl")).unwrap();
-        let dummy_shader_src: Cow<'static, str> = "
-struct VertexOutput {
-    @builtin(position) pos: vec4<f32>,
-};
-
-@vertex
-fn vs_main() -> VertexOutput {
-    var out: VertexOutput;
-    out.pos = vec4(0.0,0.0,0.0,0.0);
-    return out;
-}
-
-@fragment
-fn fs_main() -> @location(0) vec4<f32> {
-    return vec4(0.0,0.0,0.0,0.0);
-}
-        "
-        .into();
-
+    /// Loads the startup shader, fails if it can't be read
+    pub fn new(
+        gpu: Gpu<'surface>,
+        window: &Window,
+        options: &StartupOptions,
+    ) -> Result<State<'surface>, Error> {
+        let shader_path = options.paths.shader_path(&options.shader);
+        let current_shader = std::fs::read_to_string(&shader_path)
+            .map_err(|err| Error::Shader(shader_path, err))?;
         let dummy_shader = gpu
             .device
             .create_shader_module(ShaderModuleDescriptor {
                 label: None,
-                source: ShaderSource::Wgsl(dummy_shader_src.clone()),
+                source: ShaderSource::Wgsl(DUMMY_SHADER.into()),
             })
-            .unwrap();
-        let shader = gpu
-            .device
-            .create_shader_module(ShaderModuleDescriptor {
-                label: None,
-                source: ShaderSource::Wgsl(current_shader.clone().into()),
-            })
-            .unwrap_or(
-                gpu.device
-                    .create_shader_module(ShaderModuleDescriptor {
-                        label: None,
-                        source: ShaderSource::Wgsl(dummy_shader_src),
-                    })
-                    .unwrap(),
-            );
-        let grid_shader_src = fs::read_to_string("shaders/grid.wgsl").unwrap();
-        let grid_shader = gpu
-            .device
-            .create_shader_module(ShaderModuleDescriptor {
-                label: None,
-                source: ShaderSource::Wgsl(grid_shader_src.clone().into()),
-            })
-            .unwrap_or(
-                gpu.device
-                    .create_shader_module(ShaderModuleDescriptor {
-                        label: None,
-                        source: ShaderSource::Wgsl((&grid_shader_src).into()),
-                    }).map_err(|err| err.to_string())
-                    .unwrap(),
-            );
-        let grid_shader = Shader {
-            contents: grid_shader_src,
-            shader: grid_shader,
+            .or_report()
+            .ok_or(Error::Resources)?;
+        let builtin_shader = |contents: &str| {
+            Shader::new(&gpu.device, contents.to_string()).ok_or(Error::Resources)
         };
+        let grid_shader = builtin_shader(GRID_SHADER)?;
+        let bounding_box_shader = builtin_shader(BOUNDING_BOX_SHADER)?;
+        let wireframe_shader = builtin_shader(WIREFRAME_SHADER)?;
+        let debug_views_shader = builtin_shader(DEBUG_VIEWS_SHADER)?;
 
         let time = TimeKeeper::new();
         let layout = gpu
@@ -363,170 +1296,413 @@ fn fs_main() -> @location(0) vec4<f32> {
                 bind_group_layouts: &[],
                 push_constant_ranges: &[],
             })
-            .unwrap();
-        let pipeline = gpu
-            .device
-            .create_render_pipeline(&RenderPipelineDescriptor {
-                label: Some("dummy pipeline"),
-                layout: Some(&layout),
-                vertex: VertexState {
-                    module: &dummy_shader,
-                    entry_point: Some("vs_main"),
-                    buffers: &[],
-                    compilation_options: Default::default(),
+            .or_report()
+            .ok_or(Error::Resources)?;
+        let dummy_pipeline = || {
+            gpu.device
+                .create_render_pipeline(&RenderPipelineDescriptor {
+                    label: Some("dummy pipeline"),
+                    layout: Some(&layout),
+                    vertex: VertexState {
+                        module: &dummy_shader,
+                        entry_point: Some("vs_main"),
+                        buffers: &[],
+                        compilation_options: Default::default(),
+                    },
+                    primitive: PrimitiveState::default(),
+                    depth_stencil: None,
+                    multisample: MultisampleState::default(),
+                    fragment: Some(FragmentState {
+                        module: &dummy_shader,
+                        entry_point: None,
+                        compilation_options: Default::default(),
+                        targets: &[
+                            Some(ColorTargetState {
+                                format: TextureFormat::Rgba8Unorm,
+                                blend: None,
+                                write_mask: ColorWrites::all(),
+                            })
+                        ],
+                    }),
+                    multiview: None,
+                    cache: None,
+                })
+                .or_report()
+                .ok_or(Error::Resources)
+        };
+
+        let mut im_state = ImState::new(window, &gpu, options).ok_or(Error::Resources)?;
+        im_state.set_shader_source(&current_shader);
+        let current_shader = Shader::new(&gpu.device, current_shader).ok_or(Error::Resources)?;
+        let size = window.inner_size();
+        let depth_textures =
+            DepthTextures::new(&gpu.device, size.width, size.height).ok_or(Error::Resources)?;
+        let clip_planes = ClipPlanes::new(&gpu.device, gpu.config.format, &depth_textures)
+            .ok_or(Error::Resources)?;
+        let vertices_set = |mesh| VerticesSet::new(&gpu.device, mesh).ok_or(Error::Resources);
+        let vertices = Vertices {
+            custom_shader: vertices_set(VerticesSet::default_vertices())?,
+            grid: vertices_set(VerticesSet::default_vertices())?,
+            bounding_box: vertices_set(VerticesSet::bounding_box(
+                &VerticesSet::default_vertices().0,
+            ))?,
+            instances: Instances::new(&gpu.device, 1, DEFAULT_INSTANCE_SPACING)
+                .ok_or(Error::Resources)?,
+        };
+        let mut state = State {
+            time,
+            pipelines: Pipelines {
+                custom_shader: dummy_pipeline()?,
+                grid: dummy_pipeline()?,
+                bounding_box: dummy_pipeline()?,
+                mesh_overlay: None,
+                debug_views: HashMap::new(),
+            },
+            im_state,
+            current_shader_path: options.shader.clone(),
+            paths: options.paths.clone(),
+            title: TitleKeeper::new(),
+            oversized_window: false,
+            camera_was_enabled: false,
+            settings: Settings {
+                window: WindowSettings {
+                    size: Some(size),
+                    position: window.outer_position().ok(),
+                    always_on_top: options.starts_on_top(),
                 },
-                primitive: PrimitiveState::default(),
-                depth_stencil: None,
-                multisample: MultisampleState::default(),
-                fragment: Some(FragmentState {
-                    module: &dummy_shader,
-                    entry_point: None,
-                    compilation_options: Default::default(),
-                    targets: &[
-                        Some(ColorTargetState {
-                            format: TextureFormat::Rgba8Unorm,
-                            blend: None,
-                            write_mask: ColorWrites::all(),
-                        })
-                    ],
-                }),
-                multiview: None,
-                cache: None,
-            })
-            .unwrap();
-        let grid_pipeline = gpu
+                present_mode: gpu.options.present_mode,
+                output_encoding: gpu.options.output_encoding,
+                // --shaders-dir isn't saved, so it's kept from the settings
+                ..options.settings.clone()
+            },
+            current_shader,
+            grid_shader,
+            bounding_box_shader,
+            wireframe_shader,
+            debug_views_shader,
+            vertices,
+            depth_textures,
+            post: None,
+            debug_texture: None,
+            clip_planes,
+            pipeline_layout: None,
+            mesh_generator: MeshGenerator::default(),
+            uniform_requests: UniformRequests::default(),
+            #[cfg(feature = "shared-output")]
+            shared_output: None,
+            gpu,
+        };
+        state.refresh_pipelines();
+        state.update_window_size();
+
+        Ok(state)
+    }
+
+    /// Starts over with a new device, recreating every GPU resource from the data kept on the CPU.
+    /// Fails if there's no device to start over with
+    pub(crate) fn recover_device(&mut self, window: &'surface Window) -> Result<(), Error> {
+        let size = winit::dpi::PhysicalSize::new(self.gpu.config.width, self.gpu.config.height);
+        let lost_message = self.gpu.lost_message().unwrap_or_default();
+        self.gpu = Gpu::new(window, size, self.gpu.options.clone())?;
+
+        let device = &self.gpu.device;
+        self.current_shader.recreate(device)?;
+        self.grid_shader.recreate(device)?;
+        self.bounding_box_shader.recreate(device)?;
+        self.wireframe_shader.recreate(device)?;
+        self.debug_views_shader.recreate(device)?;
+        self.im_state.ui.inputs.recreate_buffers(device);
+        self.vertices.custom_shader = self.vertices.custom_shader.recreate(device)?;
+        self.vertices.grid = self.vertices.grid.recreate(device)?;
+        self.vertices.bounding_box = VerticesSet::new(
+            device,
+            VerticesSet::bounding_box(&self.vertices.custom_shader.vertices),
+        )
+        .ok_or(Error::Resources)?;
+        if let Some(instances) = Instances::new(
+            device,
+            self.im_state.ui.instances,
+            self.im_state.ui.instance_spacing,
+        ) {
+            self.vertices.instances = instances
+        }
+        self.depth_textures =
+            DepthTextures::new(device, size.width, size.height).ok_or(Error::Resources)?;
+        self.clip_planes = ClipPlanes::new(device, self.gpu.config.format, &self.depth_textures)
+            .ok_or(Error::Resources)?;
+        self.pipeline_layout = None;
+        self.debug_texture = self
+            .debug_texture
+            .as_ref()
+            .map(|texture| DebugTexture::new(device, &self.gpu.queue, texture.options))
+            .transpose()?;
+        self.im_state.recreate_renderer(&self.gpu);
+        self.update_window_size();
+
+        let post_shader = self.post.take().map(|post| post.shader_name);
+        self.refresh_pipelines();
+        if post_shader.is_some() {
+            self.load_post_shader(post_shader)
+        }
+
+        self.im_state.log(
+            Severity::Warning,
+            format!(
+                "The GPU device was lost ({lost_message}), all GPU resources have been recreated"
+            ),
+        );
+        Ok(())
+    }
+
+    /// Keeps the previous pipelines if the new ones can't be created
+    fn refresh_pipelines(&mut self) {
+        self.fix_and_recreate_pipelines();
+        // The layout may have changed. They don't use the custom shader, so they are there even
+        // when that doesn't build
+        self.pipelines.debug_views.clear();
+        self.refresh_debug_view_pipeline()
+    }
+
+    fn fix_and_recreate_pipelines(&mut self) {
+        for _ in 0..MAX_PIPELINE_FIXES {
+            // Fixes can add groups
+            self.fit_debug_texture();
+            if !self.refresh_pipeline_layout() {
+                return;
+            }
+            let started = Instant::now();
+            match self.recreate_pipelines() {
+                Ok(pipelines) => {
+                    self.pipelines = pipelines;
+                    self.im_state.ui.count_pipeline_rebuild(started.elapsed());
+                    self.refresh_post_pipeline();
+                    return;
+                }
+                Err(err) => {
+                    if !self.handle_pipeline_err(err) {
+                        return;
+                    }
+                }
+            }
+        }
+
+        self.im_state.push_error(format!(
+            "Gave up creating the pipeline after fixing {MAX_PIPELINE_FIXES} parameters"
+        ))
+    }
+
+    /// The post shader shares the uniform groups and the debug texture, and its input texture
+    /// goes in the next group. The previous pipeline is kept if the new one can't be created
+    fn refresh_post_pipeline(&mut self) {
+        let Some(post) = &self.post else {
+            return;
+        };
+
+        let group_count = self.shared_group_count();
+        if group_count >= self.gpu.limits.max_bind_groups {
+            self.im_state.push_error(format!(
+                "The post shader needs @group({group_count}) for its input, but only {} groups are allowed",
+                self.gpu.limits.max_bind_groups
+            ));
+            return;
+        }
+
+        let Some(layout) = self.get_pipeline_layout_with(&[&post.bg_layout]) else {
+            return;
+        };
+        let pipeline = self
+            .gpu
             .device
             .create_render_pipeline(&RenderPipelineDescriptor {
-                label: Some("dummy pipeline"),
+                label: Some("post-process pipeline"),
                 layout: Some(&layout),
                 vertex: VertexState {
-                    module: &dummy_shader,
+                    module: &post.shader,
                     entry_point: Some("vs_main"),
-                    buffers: &[],
+                    buffers: &[VertexBufferLayout {
+                        array_stride: Vertex::SIZE,
+                        step_mode: VertexStepMode::Vertex,
+                        attributes: &[VertexAttribute {
+                            format: VertexFormat::Float32x3,
+                            offset: 0,
+                            shader_location: 0,
+                        }],
+                    }],
                     compilation_options: Default::default(),
                 },
                 primitive: PrimitiveState::default(),
                 depth_stencil: None,
                 multisample: MultisampleState::default(),
                 fragment: Some(FragmentState {
-                    module: &dummy_shader,
-                    entry_point: None,
+                    module: &post.shader,
+                    entry_point: Some("fs_main"),
+                    targets: &[Some(ColorTargetState {
+                        format: self.gpu.config.format,
+                        blend: Some(BlendState::ALPHA_BLENDING),
+                        write_mask: ColorWrites::ALL,
+                    })],
                     compilation_options: Default::default(),
-                    targets: &[
-                        Some(ColorTargetState {
-                            format: TextureFormat::Rgba8Unorm,
-                            blend: None,
-                            write_mask: ColorWrites::all(),
-                        })
-                    ],
                 }),
                 multiview: None,
                 cache: None,
-            })
-            .unwrap();
+            });
 
-        let im_state = ImState::new(window, &gpu);
-        let current_shader = Shader {
-            contents: current_shader,
-            shader,
-        };
-        let (vertices, indices) = VerticesSet::default_vertices();
-        let size = window.inner_size();
-        let mut state = State {
-            time,
-            pipelines: Pipelines {
-                custom_shader: pipeline,
-                grid: grid_pipeline,
-            },
-            im_state,
-            current_shader_path: "shader.wgsl".into(),
-            current_shader,
-            grid_shader,
-            vertices: Vertices {
-                custom_shader: VerticesSet {
-                    vertex_buffer: gpu
-                        .device
-                        .create_buffer_init(&BufferInitDescriptor {
-                            label: Some("Vertex buffer"),
-                            contents: &vertices
-                                .iter()
-                                .flat_map(|vert| vert.to_le_bytes())
-                                .collect::<Vec<_>>(),
-                            usage: BufferUsages::VERTEX,
-                        })
-                        .unwrap(),
-                    vertices: vertices.clone(),
-                    index_buffer: gpu
-                        .device
-                        .create_buffer_init(&BufferInitDescriptor {
-                            label: Some("Index buffer"),
-                            contents: &indices
-                                .iter()
-                                .flat_map(|ind| (*ind).to_le_bytes())
-                                .collect::<Vec<_>>(),
-                            usage: BufferUsages::INDEX,
-                        })
-                        .unwrap(),
-                    indices: indices.clone(),
-                },
-                grid: VerticesSet {
-                    vertex_buffer: gpu
-                        .device
-                        .create_buffer_init(&BufferInitDescriptor {
-                            label: Some("Vertex buffer"),
-                            contents: &vertices
-                                .iter()
-                                .flat_map(|vert| vert.to_le_bytes())
-                                .collect::<Vec<_>>(),
-                            usage: BufferUsages::VERTEX,
-                        })
-                        .unwrap(),
-                    vertices,
-                    index_buffer: gpu
-                        .device
-                        .create_buffer_init(&BufferInitDescriptor {
-                            label: Some("Index buffer"),
-                            contents: &indices
-                                .iter()
-                                .flat_map(|ind| (*ind).to_le_bytes())
-                                .collect::<Vec<_>>(),
-                            usage: BufferUsages::INDEX,
-                        })
-                        .unwrap(),
-                    indices,
-                },
-            },
-            depth_textures: DepthTextures::new(&gpu.device, size.width, size.height),
-            gpu,
-        };
-        state.refresh_pipelines();
+        match pipeline {
+            Ok(pipeline) => {
+                if let Some(post) = &mut self.post {
+                    post.pipeline = Some(pipeline)
+                }
+            }
+            Err(err) => self
+                .im_state
+                .push_error(format!("Couldn't create the post-process pipeline: {err}")),
+        }
+    }
 
-        state
+    /// Groups bound for both the custom shader and the post shader
+    pub(crate) fn shared_group_count(&self) -> u32 {
+        let group_count = self.im_state.ui.inputs.enabled_groups().count() as u32;
+        group_count + self.debug_texture.is_some() as u32
     }
 
-    fn refresh_pipelines(&mut self) {
-        let pipelines = self.recreate_pipelines();
-        self.pipelines = pipelines;
+    /// None removes the debug texture. The pipelines are only created again when it's added or
+    /// removed, since that changes the groups
+    fn set_debug_texture(&mut self, options: Option<DebugTextureOptions>) {
+        if let (Some(debug_texture), Some(options)) = (&mut self.debug_texture, options) {
+            debug_texture.write(&self.gpu.queue, options);
+            return;
+        }
+
+        let debug_texture = options
+            .map(|options| DebugTexture::new(&self.gpu.device, &self.gpu.queue, options))
+            .transpose();
+        // Why it failed is in the log already
+        self.debug_texture = debug_texture.unwrap_or_else(|_| {
+            self.im_state.ui.debug_texture.enabled = false;
+            None
+        });
+        self.pipeline_layout = None;
+        self.refresh_pipelines()
+    }
+
+    /// Removes the debug texture if every group is taken by the uniform groups
+    fn fit_debug_texture(&mut self) {
+        let group_count = self.im_state.ui.inputs.enabled_groups().count() as u32;
+        if self.debug_texture.is_none() || group_count < self.gpu.limits.max_bind_groups {
+            return;
+        }
+
+        self.debug_texture = None;
+        self.pipeline_layout = None;
+        self.im_state.ui.debug_texture.enabled = false;
+        self.im_state.log(
+            Severity::Warning,
+            format!(
+                "The debug texture needs @group({group_count}), but only {} groups are allowed. It has been disabled",
+                self.gpu.limits.max_bind_groups
+            ),
+        )
+    }
+
+    fn load_post_shader(&mut self, shader_name: Option<String>) {
+        let Some(shader_name) = shader_name else {
+            self.post = None;
+            self.im_state.set_post_shader_source(None);
+            return;
+        };
+
+        let path = self.paths.shader_path(&shader_name);
+        let shader_contents = match std::fs::read_to_string(&path) {
+            Ok(shader_contents) => shader_contents,
+            Err(err) => {
+                self.im_state
+                    .push_error(format!("Couldn't read {}: {err}", path.display()));
+                return;
+            }
+        };
+
+        match self
+            .gpu
+            .device
+            .create_shader_module(ShaderModuleDescriptor {
+                label: Some("post-process shader"),
+                source: ShaderSource::Wgsl(shader_contents.clone().into()),
+            }) {
+            Ok(shader) => {
+                self.im_state.set_post_shader_source(Some(&shader_contents));
+                let size =
+                    winit::dpi::PhysicalSize::new(self.gpu.config.width, self.gpu.config.height);
+                self.post = PostProcess::new(
+                    &self.gpu.device,
+                    shader_name,
+                    shader,
+                    self.gpu.config.format,
+                    size,
+                );
+                self.refresh_post_pipeline()
+            }
+            Err(err) => {
+                self.post = None;
+                self.im_state.set_post_shader_source(None);
+                self.handle_shader_err(err)
+            }
+        }
     }
 
-    fn recreate_pipelines(&mut self) -> Pipelines {
-        let layout = self.get_pipeline_layout();
-        let poly_mode = if self.im_state.ui.show_mesh {
+    /// How the custom shader draws the mesh, following the wireframe and points settings
+    fn mesh_primitive(&self) -> PrimitiveState {
+        let show_mesh = self.im_state.ui.show_mesh && self.im_state.ui.wireframe_supported;
+        let polygon_mode = if show_mesh && !self.im_state.ui.mesh_overlay {
             PolygonMode::Line
         } else {
             PolygonMode::Fill
         };
+        // Every index is drawn as a point, which doesn't need POLYGON_MODE_POINT
+        let topology = if self.im_state.ui.show_points {
+            PrimitiveTopology::PointList
+        } else {
+            PrimitiveTopology::TriangleList
+        };
+        PrimitiveState {
+            topology,
+            strip_index_format: None,
+            front_face: FrontFace::Ccw,
+            cull_mode: None,
+            unclipped_depth: false,
+            polygon_mode,
+            conservative: false,
+        }
+    }
+
+    /// The pipeline layout must have been refreshed
+    fn recreate_pipelines(&mut self) -> Result<Pipelines, CreateRenderPipelineError> {
+        let Some((_, layout)) = &self.pipeline_layout else {
+            unreachable!()
+        };
+        let mesh_primitive = self.mesh_primitive();
+        let poly_mode = mesh_primitive.polygon_mode;
+        let depth_stencil = DepthStencilState {
+            format: TextureFormat::Depth32Float,
+            depth_write_enabled: self.im_state.ui.depth_write,
+            depth_compare: if self.im_state.ui.depth_test {
+                CompareFunction::Less
+            } else {
+                CompareFunction::Always
+            },
+            stencil: StencilState::default(),
+            bias: DepthBiasState::default(),
+        };
         let grid_pipeline = self
             .gpu
             .device
             .create_render_pipeline(&RenderPipelineDescriptor {
                 label: None,
-                layout: Some(&layout),
+                layout: Some(layout),
                 vertex: VertexState {
                     module: &self.grid_shader.shader,
                     entry_point: Some("vs_main"),
                     buffers: &[VertexBufferLayout {
-                        array_stride: std::mem::size_of::<f32>() as u64 * 3,
+                        array_stride: Vertex::SIZE,
                         step_mode: VertexStepMode::Vertex,
                         attributes: &[VertexAttribute {
                             format: VertexFormat::Float32x3,
@@ -545,13 +1721,7 @@ fn fs_main() -> @location(0) vec4<f32> {
                     polygon_mode: poly_mode,
                     conservative: false,
                 },
-                depth_stencil: Some(DepthStencilState {
-                    format: TextureFormat::Depth32Float,
-                    depth_write_enabled: true,
-                    depth_compare: CompareFunction::Less,
-                    stencil: StencilState::default(),
-                    bias: DepthBiasState::default(),
-                }),
+                depth_stencil: Some(depth_stencil.clone()),
                 multisample: MultisampleState {
                     count: 1,
                     mask: !0,
@@ -568,19 +1738,19 @@ fn fs_main() -> @location(0) vec4<f32> {
                     compilation_options: Default::default(),
                 }),
                 multiview: None,
-                cache: None,
+                cache: self.gpu.pipeline_cache.as_ref(),
             });
-        match self
+        let bounding_box_pipeline = self
             .gpu
             .device
             .create_render_pipeline(&RenderPipelineDescriptor {
                 label: None,
-                layout: Some(&layout),
+                layout: Some(layout),
                 vertex: VertexState {
-                    module: &self.current_shader.shader,
+                    module: &self.bounding_box_shader.shader,
                     entry_point: Some("vs_main"),
                     buffers: &[VertexBufferLayout {
-                        array_stride: std::mem::size_of::<f32>() as u64 * 3,
+                        array_stride: Vertex::SIZE,
                         step_mode: VertexStepMode::Vertex,
                         attributes: &[VertexAttribute {
                             format: VertexFormat::Float32x3,
@@ -591,21 +1761,47 @@ fn fs_main() -> @location(0) vec4<f32> {
                     compilation_options: Default::default(),
                 },
                 primitive: PrimitiveState {
-                    topology: PrimitiveTopology::TriangleList,
+                    topology: PrimitiveTopology::LineList,
                     strip_index_format: None,
                     front_face: FrontFace::Ccw,
                     cull_mode: None,
                     unclipped_depth: false,
-                    polygon_mode: poly_mode,
+                    polygon_mode: PolygonMode::Fill,
                     conservative: false,
                 },
-                depth_stencil: Some(DepthStencilState {
-                    format: TextureFormat::Depth32Float,
-                    depth_write_enabled: true,
-                    depth_compare: CompareFunction::Less,
-                    stencil: StencilState::default(),
-                    bias: DepthBiasState::default(),
+                depth_stencil: Some(depth_stencil.clone()),
+                multisample: MultisampleState {
+                    count: 1,
+                    mask: !0,
+                    alpha_to_coverage_enabled: false,
+                },
+                fragment: Some(FragmentState {
+                    module: &self.bounding_box_shader.shader,
+                    entry_point: Some("fs_main"),
+                    targets: &[Some(ColorTargetState {
+                        format: self.gpu.config.format,
+                        blend: None,
+                        write_mask: ColorWrites::ALL,
+                    })],
+                    compilation_options: Default::default(),
                 }),
+                multiview: None,
+                cache: self.gpu.pipeline_cache.as_ref(),
+            });
+        self
+            .gpu
+            .device
+            .create_render_pipeline(&RenderPipelineDescriptor {
+                label: None,
+                layout: Some(layout),
+                vertex: VertexState {
+                    module: &self.current_shader.shader,
+                    entry_point: Some("vs_main"),
+                    buffers: &MESH_BUFFERS,
+                    compilation_options: Default::default(),
+                },
+                primitive: mesh_primitive,
+                depth_stencil: Some(depth_stencil.clone()),
                 multisample: MultisampleState {
                     count: 1,
                     mask: !0,
@@ -616,81 +1812,279 @@ fn fs_main() -> @location(0) vec4<f32> {
                     entry_point: Some("fs_main"),
                     targets: &[Some(ColorTargetState {
                         format: self.gpu.config.format,
-                        blend: Some(BlendState::ALPHA_BLENDING),
+                        blend: Some(self.im_state.ui.blend_mode.blend_state()),
                         write_mask: ColorWrites::ALL,
                     })],
                     compilation_options: Default::default(),
                 }),
                 multiview: None,
-                cache: None,
-            }) {
-            Ok(pipeline) => Pipelines {
-                custom_shader: pipeline,
-                grid: grid_pipeline.unwrap(),
-            },
-            Err(err) => {std::mem::drop(grid_pipeline);self.handle_pipeline_err(err)},
+                cache: self.gpu.pipeline_cache.as_ref(),
+            })
+            .and_then(|pipeline| {
+                let mesh_overlay = if show_mesh && self.im_state.ui.mesh_overlay {
+                    Some(self.create_mesh_overlay_pipeline(layout, depth_stencil)?)
+                } else {
+                    None
+                };
+                Ok(Pipelines {
+                    custom_shader: pipeline,
+                    grid: grid_pipeline?,
+                    bounding_box: bounding_box_pipeline?,
+                    mesh_overlay,
+                    debug_views: HashMap::new(),
+                })
+            })
+    }
+
+    /// Creates the pipeline of the selected debug view, unless it's already cached
+    /// Builds the debug view picked while the camera was disabled, once it gets enabled by any means
+    pub(crate) fn follow_camera(&mut self) {
+        let camera_enabled = self.im_state.ui.inputs.camera_enabled();
+        if camera_enabled && !self.camera_was_enabled {
+            self.refresh_debug_view_pipeline()
         }
+        self.camera_was_enabled = camera_enabled
     }
 
-    fn handle_pipeline_err(&mut self, err: CreateRenderPipelineError) -> Pipelines {
-        match err {
-            CreateRenderPipelineError::Stage { stage: _, error } => {
-                match error {
-                    StageError::Binding(binding, error) => match error {
-                        BindingError::Missing => self.im_state.ui.inputs.define_binding(
-                            binding.group,
-                            binding.binding,
-                            &self.gpu.device,
-                        ),
-                        BindingError::Invisible => todo!(),
-                        BindingError::WrongType => todo!(),
-                        BindingError::WrongAddressSpace { .. } => todo!(),
-                        BindingError::WrongTextureViewDimension { .. } => todo!(),
-                        BindingError::WrongTextureClass { .. } => todo!(),
-                        BindingError::WrongSamplerComparison => todo!(),
-                        BindingError::InconsistentlyDerivedType => todo!(),
-                        BindingError::BadStorageFormat(_) => todo!(),
-                        BindingError::UnsupportedTextureStorageAccess(_) => todo!(),
-                        _ => todo!(),
-                    },
-                    StageError::InvalidWorkgroupSize { .. } => todo!(),
-                    StageError::TooManyVaryings { .. } => todo!(),
-                    StageError::MissingEntryPoint(_) => todo!(),
-                    StageError::Filtering { .. } => todo!(),
-                    StageError::Input { .. } => todo!(),
-                    _ => todo!(),
+    fn refresh_debug_view_pipeline(&mut self) {
+        let ui = &self.im_state.ui;
+        let debug_view = ui.debug_view;
+        let entry_point = match debug_view {
+            DebugView::Off => return,
+            DebugView::Normals => "fs_normals",
+            DebugView::UvChecker => "fs_uv_checker",
+            DebugView::Depth => "fs_depth",
+        };
+        if !ui.inputs.camera_enabled() || self.pipelines.debug_views.contains_key(&debug_view) {
+            return;
+        }
+
+        if !self.refresh_pipeline_layout() {
+            return;
+        }
+        let Some((_, layout)) = &self.pipeline_layout else {
+            unreachable!()
+        };
+        let depth_stencil = DepthStencilState {
+            format: TextureFormat::Depth32Float,
+            depth_write_enabled: true,
+            depth_compare: CompareFunction::Less,
+            stencil: StencilState::default(),
+            bias: DepthBiasState::default(),
+        };
+        let pipeline = self
+            .gpu
+            .device
+            .create_render_pipeline(&RenderPipelineDescriptor {
+                label: Some(debug_view.label()),
+                layout: Some(layout),
+                vertex: VertexState {
+                    module: &self.debug_views_shader.shader,
+                    entry_point: Some("vs_main"),
+                    buffers: &MESH_BUFFERS,
+                    compilation_options: Default::default(),
+                },
+                primitive: PrimitiveState {
+                    topology: PrimitiveTopology::TriangleList,
+                    strip_index_format: None,
+                    front_face: FrontFace::Ccw,
+                    cull_mode: None,
+                    unclipped_depth: false,
+                    polygon_mode: PolygonMode::Fill,
+                    conservative: false,
+                },
+                depth_stencil: Some(depth_stencil),
+                multisample: MultisampleState {
+                    count: 1,
+                    mask: !0,
+                    alpha_to_coverage_enabled: false,
+                },
+                fragment: Some(FragmentState {
+                    module: &self.debug_views_shader.shader,
+                    entry_point: Some(entry_point),
+                    targets: &[Some(ColorTargetState {
+                        format: self.gpu.config.format,
+                        blend: None,
+                        write_mask: ColorWrites::ALL,
+                    })],
+                    compilation_options: Default::default(),
+                }),
+                multiview: None,
+                cache: self.gpu.pipeline_cache.as_ref(),
+            });
+        match pipeline {
+            Ok(pipeline) => {
+                self.pipelines.debug_views.insert(debug_view, pipeline);
+            }
+            // The camera builtin was moved or its group disabled
+            Err(err) => self.im_state.push_error(format!(
+                "Couldn't show the {} debug view, it needs the camera at @group(1) @binding(0): {err}",
+                debug_view.label()
+            )),
+        }
+    }
+
+    /// The custom shader's vertex stage with a single color, drawn as lines. The depth bias
+    /// brings the lines in front of the filled triangles they are the edges of
+    fn create_mesh_overlay_pipeline(
+        &self,
+        layout: &PipelineLayout,
+        depth_stencil: DepthStencilState,
+    ) -> Result<RenderPipeline, CreateRenderPipelineError> {
+        let depth_stencil = DepthStencilState {
+            depth_write_enabled: false,
+            depth_compare: match depth_stencil.depth_compare {
+                CompareFunction::Less => CompareFunction::LessEqual,
+                compare => compare,
+            },
+            bias: DepthBiasState {
+                constant: -2,
+                slope_scale: -1.0,
+                clamp: 0.0,
+            },
+            ..depth_stencil
+        };
+        self.gpu
+            .device
+            .create_render_pipeline(&RenderPipelineDescriptor {
+                label: Some("Mesh overlay"),
+                layout: Some(layout),
+                vertex: VertexState {
+                    module: &self.current_shader.shader,
+                    entry_point: Some("vs_main"),
+                    buffers: &MESH_BUFFERS,
+                    compilation_options: Default::default(),
+                },
+                primitive: PrimitiveState {
+                    topology: PrimitiveTopology::TriangleList,
+                    strip_index_format: None,
+                    front_face: FrontFace::Ccw,
+                    cull_mode: None,
+                    unclipped_depth: false,
+                    polygon_mode: PolygonMode::Line,
+                    conservative: false,
+                },
+                depth_stencil: Some(depth_stencil),
+                multisample: MultisampleState {
+                    count: 1,
+                    mask: !0,
+                    alpha_to_coverage_enabled: false,
+                },
+                fragment: Some(FragmentState {
+                    module: &self.wireframe_shader.shader,
+                    entry_point: Some("fs_main"),
+                    targets: &[Some(ColorTargetState {
+                        format: self.gpu.config.format,
+                        blend: None,
+                        write_mask: ColorWrites::ALL,
+                    })],
+                    compilation_options: Default::default(),
+                }),
+                multiview: None,
+                cache: self.gpu.pipeline_cache.as_ref(),
+            })
+    }
+
+    /// Fixes the error if it's caused by the parameters not matching the shader.
+    /// Returns false if it can't be fixed, after reporting it
+    fn handle_pipeline_err(&mut self, err: CreateRenderPipelineError) -> bool {
+        let (binding, error) = match &err {
+            CreateRenderPipelineError::Stage {
+                stage: _,
+                error: StageError::Binding(binding, error),
+            } => (binding, error),
+            CreateRenderPipelineError::Stage {
+                stage: _,
+                error: StageError::MissingEntryPoint(name),
+            } => {
+                let available: Vec<String> =
+                    reflection::entry_points(&self.current_shader.contents)
+                        .into_iter()
+                        .map(|(stage, name)| format!("{name} ({stage})"))
+                        .collect();
+                let available = if available.is_empty() {
+                    "none".to_string()
+                } else {
+                    available.join(", ")
+                };
+                self.im_state.push_error(format!(
+                    "The shader has no entry point named '{name}'; available entry points: {available}"
+                ));
+                return false;
+            }
+            // e.g. InvalidWorkgroupSize, TooManyVaryings, Input, InputNotConsumed or Filtering
+            CreateRenderPipelineError::Stage { stage, error } => {
+                self.im_state
+                    .push_error(format!("{} stage: {error}", stage_name(*stage)));
+                return false;
+            }
+            err => {
+                self.im_state
+                    .push_error(format!("Couldn't create the pipeline: {err}"));
+                return false;
+            }
+        };
+
+        let (group, binding) = (binding.group, binding.binding);
+        let reason = match error {
+            BindingError::Missing => {
+                let limits = &self.gpu.limits;
+                if group >= limits.max_bind_groups {
+                    self.im_state.push_error(format!(
+                        "The shader uses @group({group}), but this device only supports {} groups",
+                        limits.max_bind_groups
+                    ));
+                    return false;
+                }
+                if binding >= limits.max_bindings_per_bind_group {
+                    self.im_state.push_error(format!(
+                        "The shader uses @binding({binding}), but this device only supports {} bindings per group",
+                        limits.max_bindings_per_bind_group
+                    ));
+                    return false;
+                }
+                if self.im_state.ui.inputs.is_group_disabled(group as usize) {
+                    self.im_state.ui.inputs.enable_group(group as usize);
+                    self.im_state.log(
+                        Severity::Warning,
+                        format!("The shader uses @group({group}), so it has been enabled again"),
+                    );
                 }
+                self.im_state
+                    .ui
+                    .inputs
+                    .define_binding(group, binding, &self.gpu.device);
+                return true;
             }
-            CreateRenderPipelineError::ColorAttachment(_) => todo!(),
-            CreateRenderPipelineError::Device(_) => todo!(),
-            CreateRenderPipelineError::Implicit(_) => todo!(),
-            CreateRenderPipelineError::ColorState(_, _) => todo!(),
-            CreateRenderPipelineError::DepthStencilState(_) => todo!(),
-            CreateRenderPipelineError::InvalidSampleCount(_) => todo!(),
-            CreateRenderPipelineError::TooManyVertexBuffers { .. } => todo!(),
-            CreateRenderPipelineError::TooManyVertexAttributes { .. } => todo!(),
-            CreateRenderPipelineError::VertexStrideTooLarge { .. } => todo!(),
-            CreateRenderPipelineError::UnalignedVertexStride { .. } => todo!(),
-            CreateRenderPipelineError::InvalidVertexAttributeOffset { .. } => todo!(),
-            CreateRenderPipelineError::ShaderLocationClash(_) => todo!(),
-            CreateRenderPipelineError::StripIndexFormatForNonStripTopology { .. } => todo!(),
-            CreateRenderPipelineError::ConservativeRasterizationNonFillPolygonMode => todo!(),
-            CreateRenderPipelineError::MissingFeatures(_) => todo!(),
-            CreateRenderPipelineError::MissingDownlevelFlags(_) => todo!(),
-            CreateRenderPipelineError::Internal { .. } => todo!(),
-            CreateRenderPipelineError::UnalignedShader { .. } => todo!(),
-            CreateRenderPipelineError::BlendFactorOnUnsupportedTarget { .. } => todo!(),
-            CreateRenderPipelineError::PipelineExpectsShaderToUseDualSourceBlending => todo!(),
-            CreateRenderPipelineError::ShaderExpectsPipelineToUseDualSourceBlending => todo!(),
-            _ => todo!(),
-        }
-
-        self.recreate_pipelines()
+            BindingError::Invisible => {
+                "it isn't visible to the shader stage that uses it".to_string()
+            }
+            BindingError::WrongType => {
+                "the shader expects another kind of resource, but parameters are uniform buffers"
+                    .to_string()
+            }
+            BindingError::WrongAddressSpace { binding, shader } => format!(
+                "the shader declares it in the {shader:?} address space, but parameters are in the {binding:?} address space. Declare it as var<uniform>"
+            ),
+            BindingError::WrongTextureViewDimension { .. }
+            | BindingError::WrongTextureClass { .. }
+            | BindingError::WrongSamplerComparison
+            | BindingError::BadStorageFormat(_)
+            | BindingError::UnsupportedTextureStorageAccess(_) => {
+                "the shader expects a texture or sampler, but parameters are uniform buffers"
+                    .to_string()
+            }
+            error => error.to_string(),
+        };
+
+        self.im_state
+            .push_error(format!("@group({group}) @binding({binding}): {reason}"));
+        false
     }
 
     pub fn refresh_shader(&mut self) {
         if let Ok(shader_contents) =
-            std::fs::read_to_string(Path::new("shaders").join(&self.current_shader_path))
+            std::fs::read_to_string(self.paths.shader_path(&self.current_shader_path))
         {
             match self
                 .gpu
@@ -700,7 +2094,8 @@ fn fs_main() -> @location(0) vec4<f32> {
                     source: ShaderSource::Wgsl(shader_contents.clone().into()),
                 }) {
                 Ok(shader) => {
-                    self.im_state.destroy_errors();
+                    self.im_state.resolve_errors(&self.current_shader_path);
+                    self.im_state.set_shader_source(&shader_contents);
                     self.current_shader.contents = shader_contents;
                     self.current_shader.shader = shader;
                     self.refresh_pipelines()
@@ -708,51 +2103,317 @@ fn fs_main() -> @location(0) vec4<f32> {
                 Err(err) => self.handle_shader_err(err),
             };
         };
+
+        let post_shader = self.post.as_ref().map(|post| post.shader_name.clone());
+        if post_shader.is_some() {
+            self.load_post_shader(post_shader)
+        }
     }
 
-    pub(crate) fn resize(&mut self, size: winit::dpi::PhysicalSize<u32>) {
+    /// `fullscreen` is the window's state, the UI follows it
+    pub(crate) fn resize(&mut self, size: winit::dpi::PhysicalSize<u32>, fullscreen: bool) {
+        self.im_state.ui.set_fullscreen(fullscreen);
         if size.height > 1 && size.width > 1 {
-            self.gpu.resize(size);
-            self.refresh_depth_texture(size)
+            // A fullscreen window on a big monitor can be larger than the textures the device
+            // supports, the surface is stretched over it then
+            let max_size = self.gpu.device.limits().max_texture_dimension_2d;
+            let surface_size = winit::dpi::PhysicalSize::new(
+                size.width.min(max_size),
+                size.height.min(max_size),
+            );
+            let oversized = surface_size != size;
+            if oversized && !self.oversized_window {
+                self.im_state.log(
+                    Severity::Warning,
+                    format!(
+                        "The window is bigger than this device's {max_size}x{max_size} texture limit, rendering at {}x{}",
+                        surface_size.width, surface_size.height
+                    ),
+                )
+            }
+            self.oversized_window = oversized;
+            self.gpu.resize(surface_size);
+            self.refresh_depth_texture(surface_size);
+            self.clip_planes.resize(&self.gpu.device, &self.depth_textures);
+            if let Some(post) = &mut self.post {
+                post.resize(&self.gpu.device, surface_size)
+            }
+            self.update_window_size();
+            // The size to restore is the one of the normal window
+            if !fullscreen {
+                self.settings.window.size = Some(size)
+            }
+        }
+    }
+
+    pub(crate) fn moved(&mut self, position: winit::dpi::PhysicalPosition<i32>, fullscreen: bool) {
+        if !fullscreen {
+            self.settings.window.position = Some(position)
+        }
+    }
+
+    pub(crate) fn save_settings(&mut self) {
+        self.settings.window.always_on_top = self.im_state.ui.always_on_top();
+        self.settings.hidden_windows = self.im_state.ui.hidden_windows.clone();
+        self.settings.defer_edits = self.im_state.ui.defer_edits;
+        self.settings.keybindings = self.im_state.ui.keybindings.bindings().to_vec();
+        self.settings.osc = self.im_state.ui.osc_settings();
+        if let Err(err) = self.settings.save() {
+            self.im_state
+                .push_error(format!("Couldn't write {SETTINGS_FILE}: {err}"))
+        }
+    }
+
+    fn update_window_size(&mut self) {
+        self.im_state
+            .ui
+            .inputs
+            .update_window_size(self.gpu.config.width, self.gpu.config.height)
+    }
+
+    /// Called every frame, but the title only changes when the shader, its errors, its unsaved
+    /// changes or the FPS do
+    pub(crate) fn update_title(&mut self) -> Option<RenderMessage> {
+        let has_errors = self.im_state.has_errors();
+        let dirty = self.im_state.ui.has_unsaved_changes();
+        self.title
+            .update(&self.current_shader_path, has_errors, dirty)
+            .map(RenderMessage::SetTitle)
+    }
+
+    /// Moves the errors of GPU operations into the log, called every frame
+    pub(crate) fn report_gpu_errors(&mut self) {
+        for error in gpu_errors::take() {
+            self.im_state.push_error(error)
+        }
+    }
+
+    /// Copies the last frame of the Render window to the clipboard
+    fn copy_image(&mut self) {
+        let Some(texture) = self.im_state.get_texture() else {
+            self.im_state
+                .log(Severity::Warning, "Couldn't copy the image: there's no image".into());
+            return;
+        };
+        let (width, height) = (texture.width(), texture.height());
+        let result = capture::read_rgba(&self.gpu.device, &self.gpu.queue, texture)
+            .and_then(|rgba| capture::copy_to_clipboard(width, height, rgba));
+        match result {
+            Ok(()) => self
+                .im_state
+                .log(Severity::Info, format!("Copied a {width}x{height} image to the clipboard")),
+            Err(err) => self
+                .im_state
+                .log(Severity::Warning, format!("Couldn't copy the image: {err}")),
+        }
+    }
+
+    /// Writes the rendered image to the shared output while it's enabled. Reading it back
+    /// waits for the GPU to finish the frame
+    #[cfg(feature = "shared-output")]
+    pub(crate) fn share_output(&mut self) {
+        let status = &self.im_state.ui.shared_output;
+        if !status.enabled || status.name.is_empty() {
+            self.shared_output = None;
+            self.im_state.ui.shared_output.path = None;
+            self.im_state.ui.shared_output.cost = None;
+            return;
+        }
+
+        let start = Instant::now();
+        let name = status.name.clone();
+        let Some(texture) = self.im_state.get_texture() else {
+            return;
+        };
+        let (width, height) = (texture.width(), texture.height());
+        let result =
+            capture::read_rgba(&self.gpu.device, &self.gpu.queue, texture).and_then(|rgba| {
+                let mut output = match self.shared_output.take() {
+                    Some(output) if output.matches(&name, width, height) => output,
+                    old => {
+                        // Removes the old file first, it may have the same name
+                        drop(old);
+                        shared_output::SharedOutput::create(&name, width, height)
+                            .map_err(|err| err.to_string())?
+                    }
+                };
+                output.write_frame(&rgba).map_err(|err| err.to_string())?;
+                Ok(output)
+            });
+
+        let status = &mut self.im_state.ui.shared_output;
+        match result {
+            Ok(output) => {
+                status.cost = Some(start.elapsed());
+                if status.path.as_deref() != Some(output.path()) {
+                    status.path = Some(output.path().to_path_buf())
+                }
+                self.shared_output = Some(output)
+            }
+            Err(err) => {
+                status.enabled = false;
+                status.path = None;
+                status.cost = None;
+                self.im_state.log(
+                    Severity::Warning,
+                    format!("Couldn't share the output, it was disabled: {err}"),
+                )
+            }
         }
     }
 
-    pub(crate) fn handle_message(&mut self, message: Message) -> Option<RenderMessage> {
+    /// Returns the changes the window needs, if any
+    pub fn handle_message(&mut self, message: Message) -> Option<RenderMessage> {
         let mut render_message = None;
         match message {
             Message::ReloadShader => self.refresh_shader(),
             Message::LoadShader(shader) => {
                 self.im_state.ui.load_uniforms(&shader, &self.gpu.device);
+                self.update_window_size();
                 self.current_shader_path = shader;
                 self.refresh_shader();
             }
             Message::ReloadPipeline => self.refresh_pipelines(),
+            Message::RefreshDebugView => self.refresh_debug_view_pipeline(),
             Message::ReloadMeshBuffers => {
                 self.auto_enable_camera();
                 self.reload_mesh_buffers()
             }
+            Message::ReloadInstances => self.reload_instances(),
+            Message::ReloadMeshNormals => self.reload_mesh_buffers(),
             Message::ChangeWindowLevel(window_level) => {
                 render_message = Some(RenderMessage::ChangeWindowLevel(window_level))
             }
             Message::SaveParameters => {
-                self.im_state.ui.inputs.save(&self.current_shader_path)
+                match self
+                    .im_state
+                    .ui
+                    .inputs
+                    .save(&self.current_shader_path, &self.paths.save_file)
+                {
+                    Ok(()) => self.im_state.log(
+                        Severity::Info,
+                        format!("Saved the parameters of {}", self.current_shader_path),
+                    ),
+                    Err(err) => self.im_state.push_error(err),
+                }
+                self.im_state.ui.refresh_saved_parameters(&self.current_shader_path)
             },
+            Message::ToggleGrid => self.im_state.ui.draw_grid = !self.im_state.ui.draw_grid,
+            Message::ToggleMesh => {
+                if !self.im_state.ui.wireframe_supported {
+                    return None;
+                }
+                self.im_state.ui.show_mesh = !self.im_state.ui.show_mesh;
+                self.refresh_pipelines()
+            }
+            Message::SetPresentMode(present_mode) => {
+                self.gpu.set_present_mode(present_mode);
+                self.im_state.ui.present_mode = present_mode;
+                self.settings.present_mode = present_mode;
+                self.save_settings()
+            }
+            Message::SetOutputEncoding(output_encoding) => {
+                self.gpu.set_output_encoding(output_encoding);
+                // Everything that draws to the window was made for the previous format
+                let clip_planes = ClipPlanes::new(
+                    &self.gpu.device,
+                    self.gpu.config.format,
+                    &self.depth_textures,
+                );
+                if let Some(clip_planes) = clip_planes {
+                    self.clip_planes = clip_planes
+                }
+                self.im_state.recreate_renderer(&self.gpu);
+                let post_shader = self.post.take().map(|post| post.shader_name);
+                self.refresh_pipelines();
+                if post_shader.is_some() {
+                    self.load_post_shader(post_shader)
+                }
+                self.settings.output_encoding = self.gpu.options.output_encoding;
+                self.save_settings()
+            }
+            Message::SaveSettings => self.save_settings(),
+            Message::SetAppearance(appearance) => {
+                self.im_state.set_appearance(&self.gpu, appearance.clone());
+                self.settings.appearance = appearance;
+                self.save_settings()
+            }
+            Message::SetShadersDir(shaders_dir) => {
+                self.settings.shaders_dir = shaders_dir.into();
+                self.save_settings();
+                self.im_state.log(
+                    Severity::Info,
+                    format!(
+                        "Shaders will be loaded from {} from the next start",
+                        self.settings.shaders_dir.display()
+                    ),
+                )
+            }
+            Message::ToggleAlwaysOnTop => {
+                let window_level = self.im_state.ui.toggle_always_on_top();
+                render_message = Some(RenderMessage::ChangeWindowLevel(window_level))
+            }
+            Message::SetFullscreen(fullscreen) => {
+                render_message = Some(RenderMessage::SetFullscreen(fullscreen))
+            }
+            Message::ToggleFullscreen => render_message = Some(RenderMessage::ToggleFullscreen),
+            Message::LoadPostShader(shader) => self.load_post_shader(shader),
+            Message::SetDebugTexture(options) => self.set_debug_texture(options),
+            Message::TogglePause => self.time.toggle_pause(),
+            Message::ResetTime => self.time.reset(),
+            Message::ScrubTime(time) => self.time.scrub(time),
+            Message::SeekTime(time) => self.time.seek(time),
+            Message::SetTimeLoop(loop_length) => self.time.set_loop(loop_length),
+            Message::SetFixedStep(fixed_step) => {
+                self.time.set_fixed_step(fixed_step);
+                if fixed_step.is_some() {
+                    self.im_state.ui.inputs.reseed_random()
+                }
+            }
+            Message::CopyImage => self.copy_image(),
+            Message::SaveScreenshot => self.save_screenshot(),
+            Message::ImportScreenshotParameters => self.import_screenshot_parameters(),
+            Message::ExportWeb => self.export_web(),
+            Message::ExportMesh => self.export_mesh(),
+            Message::ExportRust => self.export_rust(),
         };
 
         render_message
     }
 
-    fn get_pipeline_layout(&mut self) -> PipelineLayout {
-        let mut layouts = vec![];
-        for group in self.im_state.ui.inputs.groups.iter() {
-            let bgl = group.bg_layout(&self.gpu.device);
-            layouts.push(bgl)
+    /// Recreates the cached pipeline layout if the uniform groups changed since.
+    /// Returns false if there's no layout, because it couldn't be created
+    fn refresh_pipeline_layout(&mut self) -> bool {
+        let layout_lens: Vec<usize> = self
+            .im_state
+            .ui
+            .inputs
+            .enabled_groups()
+            .map(UniformGroup::layout_len)
+            .collect();
+        if let Some((cached_lens, _)) = &self.pipeline_layout {
+            if *cached_lens == layout_lens {
+                return true;
+            }
         }
 
-        let mut layout_refs = Vec::with_capacity(layouts.len());
-        for l in layouts.iter() {
-            layout_refs.push(l)
+        self.pipeline_layout = self
+            .get_pipeline_layout_with(&[])
+            .map(|layout| (layout_lens, layout));
+        self.pipeline_layout.is_some()
+    }
+
+    /// Layout of the uniform groups and the debug texture, followed by `extra` groups
+    fn get_pipeline_layout_with(&self, extra: &[&BindGroupLayout]) -> Option<PipelineLayout> {
+        let mut layout_refs = vec![];
+        for group in self.im_state.ui.inputs.enabled_groups() {
+            layout_refs.push(group.bg_layout())
+        }
+        if let Some(debug_texture) = &self.debug_texture {
+            layout_refs.push(&debug_texture.bg_layout)
         }
+        layout_refs.extend_from_slice(extra);
 
         self.gpu
             .device
@@ -761,7 +2422,7 @@ fn fs_main() -> @location(0) vec4<f32> {
                 bind_group_layouts: &layout_refs,
                 push_constant_ranges: &[],
             })
-            .unwrap()
+            .or_report()
     }
 
     fn handle_shader_err(&mut self, err: CreateShaderModuleError) {
@@ -788,26 +2449,283 @@ fn fs_main() -> @location(0) vec4<f32> {
                     );
                     Some(Message::ReloadPipeline)
                 }
-                _ => todo!(),
+                err => {
+                    self.im_state.push_error(format!("Couldn't draw: {err}"));
+                    None
+                }
             },
-            e => panic!("{:?}", e),
+            err => {
+                self.im_state.push_error(format!("Couldn't render: {err}"));
+                None
+            }
+        }
+    }
+
+    /// The previous instances are kept if the buffer can't be created
+    fn reload_instances(&mut self) {
+        if let Some(instances) = Instances::new(
+            &self.gpu.device,
+            self.im_state.ui.instances,
+            self.im_state.ui.instance_spacing,
+        ) {
+            self.vertices.instances = instances
         }
     }
 
+    /// The buffers are updated once the mesh is generated, see `poll_mesh_generation`
     fn reload_mesh_buffers(&mut self) {
-        self.vertices
-            .custom_shader
-            .switch(&self.im_state.ui.mesh_config, &self.gpu.device)
+        self.mesh_generator
+            .request(self.im_state.ui.mesh_config, self.im_state.ui.normal_mode);
+        self.im_state.ui.generating_mesh = true
+    }
+
+    /// A handle to change the uniforms from other threads
+    pub fn uniforms_handle(&self) -> UniformsHandle {
+        self.uniform_requests.handle()
+    }
+
+    /// Applies the changes requested by the uniforms handles and the parameter source file,
+    /// once per frame
+    pub fn apply_uniform_requests(&mut self) {
+        self.uniform_requests.apply(&mut self.im_state.ui.inputs);
+        self.im_state.ui.apply_parameter_source()
+    }
+
+    /// Applies the messages received by the OSC server, if it's enabled. Loading a shader
+    /// is returned as a message
+    pub fn poll_osc(&mut self) -> Option<Message> {
+        self.im_state.ui.poll_osc()
+    }
+
+    /// Replaces the mesh if the one last requested is ready
+    pub fn poll_mesh_generation(&mut self) {
+        let Some(result) = self.mesh_generator.poll() else {
+            return;
+        };
+        self.im_state.ui.generating_mesh = self.mesh_generator.is_busy();
+        match result {
+            Ok(generated) => {
+                self.vertices.custom_shader.replace(
+                    generated.mesh,
+                    &self.gpu.device,
+                    &self.gpu.queue,
+                );
+                self.vertices.bounding_box.replace(
+                    generated.bounding_box,
+                    &self.gpu.device,
+                    &self.gpu.queue,
+                )
+            }
+            Err(err) => self.im_state.push_error(err),
+        }
+    }
+
+    /// Writes a page that draws the current shader on the 2D whole screen mesh, with the current
+    /// parameters
+    fn export_web(&mut self) {
+        if !matches!(self.im_state.ui.mesh_config, MeshConfig::Screen2D) {
+            self.im_state.log(
+                Severity::Warning,
+                "The web export only supports the 2D whole screen mesh, the page will use it"
+                    .to_string(),
+            )
+        }
+
+        let (vertices, indices) = VerticesSet::screen_2d_vertices();
+        let export = WebExport {
+            title: &self.current_shader_path,
+            shader: &self.current_shader.contents,
+            groups: self.im_state.ui.inputs.web_groups(),
+            vertices: vertices.iter().flat_map(Vertex::to_le_bytes).collect(),
+            indices,
+            background: self.im_state.ui.background_color,
+            blend: self.im_state.ui.blend_mode.blend_state(),
+        };
+        let path = self.paths.web_export_path(&self.current_shader_path);
+        match std::fs::write(&path, export.to_html()) {
+            Ok(()) => self
+                .im_state
+                .log(Severity::Info, format!("Exported the shader to {}", path.display())),
+            Err(err) => self
+                .im_state
+                .push_error(format!("Couldn't write {}: {err}", path.display())),
+        }
+    }
+
+    fn export_rust(&mut self) {
+        let ui = &self.im_state.ui;
+        if ui.inputs.camera_enabled() && ui.debug_view != DebugView::Off {
+            let message = format!(
+                "The {} debug view replaces the shader, the export draws the shader instead",
+                ui.debug_view.label()
+            );
+            self.im_state.log(Severity::Warning, message)
+        }
+
+        let export = RustExport {
+            shader_path: &self.current_shader_path,
+            groups: self.im_state.ui.inputs.rust_groups(),
+            blend: self.im_state.ui.blend_mode.blend_state(),
+            primitive: self.mesh_primitive(),
+            depth_test: self.im_state.ui.depth_test,
+            depth_write: self.im_state.ui.depth_write,
+        };
+        let path = self.paths.rust_export_path(&self.current_shader_path);
+        match std::fs::write(&path, export.to_rust()) {
+            Ok(()) => self
+                .im_state
+                .log(Severity::Info, format!("Exported the setup to {}", path.display())),
+            Err(err) => self
+                .im_state
+                .push_error(format!("Couldn't write {}: {err}", path.display())),
+        }
+    }
+
+    /// Writes the last frame of the Render window to a PNG, with what's needed to render it again
+    fn save_screenshot(&mut self) {
+        let Some(path) = FileDialog::new()
+            .add_filter("PNG image", &["png"])
+            .set_file_name("screenshot.png")
+            .save_file()
+        else {
+            return;
+        };
+
+        match self.write_screenshot(&path) {
+            Ok(()) => self
+                .im_state
+                .log(Severity::Info, format!("Saved the screenshot to {}", path.display())),
+            Err(err) => self
+                .im_state
+                .log(Severity::Warning, format!("Couldn't save the screenshot: {err}")),
+        }
+    }
+
+    fn write_screenshot(&self, path: &Path) -> Result<(), String> {
+        let metadata = ScreenshotMetadata {
+            shader: self.current_shader_path.clone(),
+            time: self.im_state.ui.inputs.time,
+            parameters: self.im_state.ui.inputs.to_json(),
+        };
+        let texture = self
+            .im_state
+            .get_texture()
+            .ok_or_else(|| "There's no image".to_string())?;
+        let (width, height) = (texture.width(), texture.height());
+        capture::read_rgba(&self.gpu.device, &self.gpu.queue, texture)
+            .and_then(|rgba| capture::write_png(path, width, height, &rgba, &metadata))
+    }
+
+    /// Loads the shader a screenshot was taken with, then its parameters and time
+    fn import_screenshot_parameters(&mut self) {
+        let Some(path) = FileDialog::new()
+            .add_filter("PNG image", &["png"])
+            .pick_file()
+        else {
+            return;
+        };
+
+        self.import_parameters_from(&path)
+    }
+
+    fn import_parameters_from(&mut self, path: &Path) {
+        let metadata = match capture::read_metadata(path) {
+            Ok(metadata) => metadata,
+            Err(err) => {
+                self.im_state.log(Severity::Warning, err);
+                return;
+            }
+        };
+        if !self.paths.shader_path(&metadata.shader).exists() {
+            self.im_state.log(
+                Severity::Warning,
+                format!(
+                    "Can't import {}: its shader {} doesn't exist",
+                    path.display(),
+                    metadata.shader
+                ),
+            );
+            return;
+        }
+
+        let Some(inputs) = self
+            .im_state
+            .ui
+            .read_uniforms(&metadata.parameters, &self.gpu.device)
+        else {
+            self.im_state.log(
+                Severity::Warning,
+                format!("The parameters in {} are corrupted", path.display()),
+            );
+            return;
+        };
+
+        if metadata.shader != self.current_shader_path {
+            self.im_state.ui.set_shader_name(&metadata.shader);
+            self.handle_message(Message::LoadShader(metadata.shader.clone()));
+        }
+        self.im_state.ui.inputs = inputs;
+        self.time.seek(metadata.time);
+        self.update_window_size();
+        // The groups may have changed even if there's as many as before
+        self.pipeline_layout = None;
+        self.refresh_pipelines();
+        self.im_state.log(
+            Severity::Info,
+            format!("Imported the parameters of {}", path.display()),
+        )
+    }
+
+    fn export_mesh(&mut self) {
+        let Some(path) = FileDialog::new()
+            .add_filter("Wavefront OBJ", &["obj"])
+            .set_file_name("mesh.obj")
+            .save_file()
+        else {
+            return;
+        };
+
+        if matches!(self.im_state.ui.mesh_config, MeshConfig::Screen2D) {
+            self.im_state.log(
+                Severity::Warning,
+                "The 2D whole screen mesh is exported as a quad from -1 to 1".to_string(),
+            )
+        }
+        if self.im_state.ui.generating_mesh {
+            self.im_state.log(
+                Severity::Warning,
+                "A new mesh is being generated, the one currently drawn is exported".to_string(),
+            )
+        }
+
+        let mesh = &self.vertices.custom_shader;
+        let result = File::create(&path).and_then(|file| {
+            let mut file = BufWriter::new(file);
+            obj_export::write_obj(&mut file, &mesh.vertices, &mesh.indices)?;
+            file.flush()
+        });
+        match result {
+            Ok(()) => self.im_state.log(
+                Severity::Info,
+                format!(
+                    "Exported {} vertices and {} triangles to {}",
+                    mesh.vertices.len(),
+                    mesh.indices.len() / 3,
+                    path.display()
+                ),
+            ),
+            Err(err) => self
+                .im_state
+                .log(Severity::Warning, format!("Couldn't write {}: {err}", path.display())),
+        }
     }
 
     fn auto_enable_camera(&mut self) {
+        let screen_2d = matches!(self.im_state.ui.mesh_config, MeshConfig::Screen2D);
+        self.im_state.ui.inputs.enable_pan_zoom(screen_2d);
         match self.im_state.ui.mesh_config {
-            MeshConfig::Screen2D => self
-                .im_state
-                .ui
-                .inputs
-                .enable_camera(false, &self.gpu.queue),
-            _ => self.im_state.ui.inputs.enable_camera(true, &self.gpu.queue),
+            MeshConfig::Screen2D => self.im_state.ui.inputs.enable_camera(false),
+            _ => self.im_state.ui.inputs.enable_camera(true),
         };
     }
 
@@ -821,8 +2739,10 @@ fn fs_main() -> @location(0) vec4<f32> {
         }
     }
 
+    /// Keeps the previous texture if the new one can't be created
     fn refresh_depth_texture(&mut self, size: winit::dpi::PhysicalSize<u32>) {
-        self.depth_textures.background = self.gpu
+        let texture = self
+            .gpu
             .device
             .create_texture(&TextureDescriptor {
                 label: Some("Depth view"),
@@ -838,6 +2758,178 @@ fn fs_main() -> @location(0) vec4<f32> {
                 usage: TextureUsages::RENDER_ATTACHMENT | TextureUsages::TEXTURE_BINDING,
                 view_formats: &[TextureFormat::Depth32Float],
             })
-            .unwrap();
+            .or_report();
+        if let Some(texture) = texture {
+            self.depth_textures.background = texture
+        }
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use winit::event_loop::EventLoop;
+
+    use super::*;
+    use crate::imgui_state::{ScalarUniformValue, UniformValue};
+
+    /// A device without a surface. The tests that need one are ignored by default, run them with
+    /// `cargo test -- --ignored` on a machine with a GPU
+    pub(crate) fn headless_device() -> (Device, Queue) {
+        let instance = Instance::new(InstanceDescriptor::default());
+        let adapter =
+            pollster::block_on(instance.request_adapter(&RequestAdapterOptions::default()))
+                .expect("No GPU adapter available");
+        let (device, queue) =
+            pollster::block_on(adapter.request_device(&DeviceDescriptor::default(), None))
+                .expect("Couldn't create a device");
+        device.on_uncaptured_error(Box::new(|error: wgpu::Error| gpu_errors::report(error)));
+        (device, queue)
+    }
+
+    /// Waits for the device, then checks it reported no errors
+    pub(crate) fn assert_no_gpu_errors(device: &Device) {
+        device.poll(wgpu::Maintain::Wait);
+        assert_eq!(gpu_errors::take(), Vec::<String>::new());
+    }
+
+    /// A file in the temporary directory that other test runs don't share
+    pub(crate) fn temp_path(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("te-shader-play-{}-{name}", std::process::id()))
+    }
+
+    /// Runs `test` on the state of a hidden window showing the default shader. `name` keeps the
+    /// save file apart from the other tests. Tests don't run on the main thread, which only
+    /// Windows lets an event loop be created on
+    pub(crate) fn with_state(name: &str, test: impl FnOnce(&mut State, &Window)) {
+        let mut builder = EventLoop::builder();
+        #[cfg(windows)]
+        winit::platform::windows::EventLoopBuilderExtWindows::with_any_thread(&mut builder, true);
+        let event_loop = builder.build().expect("Couldn't create an event loop");
+        let window = event_loop
+            .create_window(Window::default_attributes().with_visible(false))
+            .expect("Couldn't create a window");
+        let gpu = Gpu::new(&window, window.inner_size(), GpuOptions::default())
+            .expect("Couldn't create a device for the window");
+        let save_file = temp_path(&format!("{name}.json"));
+        let options = StartupOptions {
+            paths: Paths {
+                save_file: save_file.clone(),
+                ..Paths::default()
+            },
+            ..StartupOptions::default()
+        };
+        let mut state = State::new(gpu, &window, &options).expect("Couldn't load the shader");
+        test(&mut state, &window);
+        let _ = std::fs::remove_file(save_file);
+    }
+
+    #[test]
+    #[ignore = "needs a GPU and a window"]
+    fn screenshots_restore_their_parameters() {
+        with_state("screenshot", |state, _window| {
+            let shader = temp_path("screenshot.wgsl");
+            std::fs::copy("shaders/shader.wgsl", &shader).unwrap();
+            let shader = shader.to_string_lossy().to_string();
+            state.handle_message(Message::LoadShader(shader.clone()));
+            let value = UniformValue::Scalar(ScalarUniformValue::F32(0.25));
+            let group = state.im_state.ui.inputs.groups.len();
+            state
+                .im_state
+                .ui
+                .inputs
+                .push_value(&state.gpu.device, group, value)
+                .unwrap();
+            state.handle_message(Message::ReloadPipeline);
+            state.time.toggle_pause();
+            state.time.seek(Duration::from_millis(1500));
+            state.time.update_time(&mut state.im_state.ui.inputs);
+            let parameters = state.im_state.ui.inputs.to_json();
+            let path = temp_path("screenshot.png");
+            let written = state.write_screenshot(&path);
+
+            // Another shader, without the added binding, at another time
+            state.handle_message(Message::LoadShader("shader.wgsl".to_string()));
+            state.time.seek(Duration::ZERO);
+            state.import_parameters_from(&path);
+            let _ = std::fs::remove_file(&path);
+            let _ = std::fs::remove_file(&shader);
+
+            written.unwrap();
+            assert_eq!(state.current_shader_path, shader);
+            assert_eq!(state.time.elapsed(), Duration::from_millis(1500));
+            assert_eq!(state.im_state.ui.inputs.to_json(), parameters);
+            assert_no_gpu_errors(&state.gpu.device);
+        })
+    }
+
+    #[test]
+    fn scrubbed_time_wraps_around_the_loop() {
+        let mut time = TimeKeeper::new();
+        time.toggle_pause();
+        time.seek(Duration::from_secs(5));
+        assert_eq!(time.elapsed(), Duration::from_secs(5));
+
+        time.scrub(Duration::from_millis(7500));
+        time.set_loop(Some(Duration::from_secs(2)));
+        assert_eq!(time.elapsed(), Duration::from_millis(1500));
+        // Releasing the timeline goes on from where it was dropped
+        time.seek(Duration::from_millis(1500));
+        assert_eq!(time.elapsed(), Duration::from_millis(1500));
+    }
+
+    #[test]
+    fn fixed_step_ignores_the_clock() {
+        let mut time = TimeKeeper::new();
+        let step = Duration::from_micros(16_667);
+        time.set_fixed_step(Some(step));
+        let start = Instant::now();
+        // Frames that take longer or shorter get the same times
+        for (frame, wait) in [0, 100, 3, 40].into_iter().enumerate() {
+            let now = start + Duration::from_millis(wait);
+            assert_eq!(time.advance(now), (step * frame as u32, step));
+        }
+
+        time.toggle_pause();
+        assert_eq!(time.advance(Instant::now()), (step * 4, Duration::ZERO));
+    }
+
+    #[test]
+    fn only_supported_features_are_requested() {
+        let supported = Features::POLYGON_MODE_LINE | Features::TEXTURE_BINDING_ARRAY;
+        assert_eq!(
+            available_features(OPTIONAL_FEATURES, supported),
+            Features::POLYGON_MODE_LINE
+        );
+        assert_eq!(
+            available_features(OPTIONAL_FEATURES, Features::empty()),
+            Features::empty()
+        );
+    }
+
+    #[test]
+    #[ignore = "needs a GPU"]
+    fn drag_plane_resolution() {
+        let (device, queue) = headless_device();
+
+        let mut mesh = VerticesSet::new(&device, VerticesSet::default_vertices()).unwrap();
+        for resolution in 1..=1000 {
+            let mesh_config = MeshConfig::Plane((1.0, 1.0), (resolution, 16));
+            let plane = VerticesSet::generate(&mesh_config, NormalMode::Smooth);
+            mesh.replace(plane, &device, &queue);
+            assert_eq!(mesh.indices.len(), resolution as usize * 16 * 6);
+            assert!(mesh.vertex_buffer.size() >= mesh.vertices.len() as u64 * Vertex::SIZE);
+            assert!(mesh.index_buffer.size() >= mesh.indices.len() as u64 * 4);
+        }
+
+        // Smaller meshes reuse the buffers
+        let capacity = (mesh.vertex_buffer.size(), mesh.index_buffer.size());
+        for resolution in (1..1000).rev() {
+            let mesh_config = MeshConfig::Plane((1.0, 1.0), (resolution, 16));
+            let plane = VerticesSet::generate(&mesh_config, NormalMode::Smooth);
+            mesh.replace(plane, &device, &queue);
+        }
+        assert_eq!(capacity, (mesh.vertex_buffer.size(), mesh.index_buffer.size()));
+
+        assert_no_gpu_errors(&device);
     }
 }
@@ -0,0 +1,170 @@
+use std::{borrow::Cow, path::Path, sync::mpsc, time::Duration};
+
+use serde_json::{Map, Value as JsonValue};
+use wgpu::{
+    BufferDescriptor, BufferUsages, CommandEncoderDescriptor, Device, Extent3d, ImageCopyBuffer,
+    ImageCopyTexture, ImageDataLayout, Maintain, MapMode, Origin3d, Queue, Texture, TextureAspect,
+    TextureFormat, COPY_BYTES_PER_ROW_ALIGNMENT,
+};
+
+use super::png;
+
+/// Keyword of the PNG text chunk that holds the `ScreenshotMetadata`
+const METADATA_KEYWORD: &str = "te-shader-play";
+
+/// What's needed to render a screenshot again
+#[derive(Debug, PartialEq)]
+pub(crate) struct ScreenshotMetadata {
+    pub(crate) shader: String,
+    pub(crate) time: Duration,
+    /// The shader's entry in the save file
+    pub(crate) parameters: JsonValue,
+}
+
+impl ScreenshotMetadata {
+    fn from_json(json_val: &JsonValue) -> Option<ScreenshotMetadata> {
+        let shader = json_val.get("shader")?.as_str()?.to_string();
+        let time = Duration::try_from_secs_f64(json_val.get("time")?.as_f64()?).ok()?;
+        let parameters = json_val.get("parameters")?.clone();
+        Some(ScreenshotMetadata {
+            shader,
+            time,
+            parameters,
+        })
+    }
+
+    fn to_json(&self) -> JsonValue {
+        let mut metadata = Map::new();
+        metadata.insert("shader".into(), self.shader.clone().into());
+        metadata.insert("time".into(), self.time.as_secs_f64().into());
+        metadata.insert("parameters".into(), self.parameters.clone());
+        JsonValue::Object(metadata)
+    }
+}
+
+/// Writes the image as a PNG with the metadata in a text chunk
+pub(crate) fn write_png(
+    path: &Path,
+    width: u32,
+    height: u32,
+    rgba: &[u8],
+    metadata: &ScreenshotMetadata,
+) -> Result<(), String> {
+    let text = metadata.to_json().to_string();
+    let png = png::encode(width, height, rgba, &[(METADATA_KEYWORD, &text)]);
+    std::fs::write(path, png).map_err(|err| format!("Couldn't write {}: {err}", path.display()))
+}
+
+pub(crate) fn read_metadata(path: &Path) -> Result<ScreenshotMetadata, String> {
+    let png =
+        std::fs::read(path).map_err(|err| format!("Couldn't read {}: {err}", path.display()))?;
+    let text = png::read_text(&png, METADATA_KEYWORD)
+        .map_err(|err| format!("Can't import {}: {err}", path.display()))?
+        .ok_or_else(|| {
+            format!(
+                "{} has no parameters, it wasn't saved with \"Save screenshot\"",
+                path.display()
+            )
+        })?;
+    serde_json::from_str(&text)
+        .ok()
+        .as_ref()
+        .and_then(ScreenshotMetadata::from_json)
+        .ok_or_else(|| format!("The parameters in {} are corrupted", path.display()))
+}
+
+/// Reads the texture back from the GPU as tightly packed RGBA8 rows
+pub(crate) fn read_rgba(
+    device: &Device,
+    queue: &Queue,
+    texture: &Texture,
+) -> Result<Vec<u8>, String> {
+    let width = texture.width();
+    let height = texture.height();
+    let swap_red_blue = match texture.format() {
+        TextureFormat::Rgba8Unorm | TextureFormat::Rgba8UnormSrgb => false,
+        TextureFormat::Bgra8Unorm | TextureFormat::Bgra8UnormSrgb => true,
+        format => return Err(format!("Can't read back a texture with format {format:?}")),
+    };
+
+    // Each row of a texture copy must start at a multiple of COPY_BYTES_PER_ROW_ALIGNMENT
+    let row_bytes = width * 4;
+    let padded_row_bytes =
+        row_bytes.div_ceil(COPY_BYTES_PER_ROW_ALIGNMENT) * COPY_BYTES_PER_ROW_ALIGNMENT;
+
+    let buffer = device
+        .create_buffer(&BufferDescriptor {
+            label: Some("Readback buffer"),
+            size: (padded_row_bytes * height) as u64,
+            usage: BufferUsages::COPY_DST | BufferUsages::MAP_READ,
+            mapped_at_creation: false,
+        })
+        .map_err(|err| err.to_string())?;
+
+    let mut encoder = device
+        .create_command_encoder(&CommandEncoderDescriptor {
+            label: Some("Readback encoder"),
+        })
+        .map_err(|err| err.to_string())?;
+    encoder.copy_texture_to_buffer(
+        ImageCopyTexture {
+            texture,
+            mip_level: 0,
+            origin: Origin3d::ZERO,
+            aspect: TextureAspect::All,
+        },
+        ImageCopyBuffer {
+            buffer: &buffer,
+            layout: ImageDataLayout {
+                offset: 0,
+                bytes_per_row: Some(padded_row_bytes),
+                rows_per_image: Some(height),
+            },
+        },
+        Extent3d {
+            width,
+            height,
+            depth_or_array_layers: 1,
+        },
+    );
+    queue.submit(Some(encoder.finish()));
+
+    let slice = buffer.slice(..);
+    let (sender, receiver) = mpsc::channel();
+    slice.map_async(MapMode::Read, move |result| {
+        let _ = sender.send(result);
+    });
+    device.poll(Maintain::Wait);
+    receiver
+        .recv()
+        .map_err(|err| err.to_string())?
+        .map_err(|err| err.to_string())?;
+
+    let mut rgba = Vec::with_capacity((row_bytes * height) as usize);
+    {
+        let mapped = slice.get_mapped_range();
+        for row in mapped.chunks(padded_row_bytes as usize) {
+            rgba.extend_from_slice(&row[..row_bytes as usize]);
+        }
+    }
+    buffer.unmap();
+
+    if swap_red_blue {
+        for pixel in rgba.chunks_exact_mut(4) {
+            pixel.swap(0, 2)
+        }
+    }
+
+    Ok(rgba)
+}
+
+pub(crate) fn copy_to_clipboard(width: u32, height: u32, rgba: Vec<u8>) -> Result<(), String> {
+    let mut clipboard = arboard::Clipboard::new().map_err(|err| err.to_string())?;
+    clipboard
+        .set_image(arboard::ImageData {
+            width: width as usize,
+            height: height as usize,
+            bytes: Cow::Owned(rgba),
+        })
+        .map_err(|err| err.to_string())
+}
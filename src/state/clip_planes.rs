@@ -0,0 +1,187 @@
+use wgpu::{
+    util::{BufferInitDescriptor, DeviceExt},
+    BindGroup, BindGroupDescriptor, BindGroupEntry, BindGroupLayout, BindGroupLayoutDescriptor,
+    BindGroupLayoutEntry, BindingResource, BindingType, BlendState, Buffer, BufferBindingType,
+    BufferUsages, ColorTargetState, ColorWrites, Device, FragmentState, MultisampleState,
+    PipelineLayoutDescriptor, PrimitiveState, RenderPipeline, RenderPipelineDescriptor,
+    ShaderModuleDescriptor, ShaderSource, ShaderStages, Texture, TextureFormat, TextureSampleType,
+    TextureViewDescriptor, TextureViewDimension, VertexAttribute, VertexBufferLayout, VertexFormat,
+    VertexState, VertexStepMode,
+};
+
+use crate::{gpu_errors::OrReport, imgui_state::camera_projection};
+
+use super::{post_process::PostTarget, DepthTextures, Vertex, VerticesSet};
+
+const CLIP_PLANES_SHADER: &str = include_str!("../../shaders/clip_planes.wgsl");
+
+/// Overlay that tints the geometry close to the camera's near and far planes,
+/// reading the depth the custom shader left behind
+pub(crate) struct ClipPlanes {
+    pub(crate) pipeline: RenderPipeline,
+    bg_layout: BindGroupLayout,
+    params: Buffer,
+    background: BindGroup,
+    imgui: BindGroup,
+    pub(crate) quad: VerticesSet,
+}
+
+impl ClipPlanes {
+    pub(crate) fn new(
+        device: &Device,
+        format: TextureFormat,
+        depth_textures: &DepthTextures,
+    ) -> Option<ClipPlanes> {
+        let shader = device
+            .create_shader_module(ShaderModuleDescriptor {
+                label: Some("Clip planes shader"),
+                source: ShaderSource::Wgsl(CLIP_PLANES_SHADER.into()),
+            })
+            .or_report()?;
+        let bg_layout = device
+            .create_bind_group_layout(&BindGroupLayoutDescriptor {
+                label: Some("Clip planes layout"),
+                entries: &[
+                    BindGroupLayoutEntry {
+                        binding: 0,
+                        visibility: ShaderStages::FRAGMENT,
+                        ty: BindingType::Texture {
+                            sample_type: TextureSampleType::Depth,
+                            view_dimension: TextureViewDimension::D2,
+                            multisampled: false,
+                        },
+                        count: None,
+                    },
+                    BindGroupLayoutEntry {
+                        binding: 1,
+                        visibility: ShaderStages::FRAGMENT,
+                        ty: BindingType::Buffer {
+                            ty: BufferBindingType::Uniform,
+                            has_dynamic_offset: false,
+                            min_binding_size: None,
+                        },
+                        count: None,
+                    },
+                ],
+            })
+            .or_report()?;
+
+        // The depth buffer holds `-a + b / distance`, so `distance = b / (depth + a)`
+        let projection = camera_projection();
+        let (a, b) = (projection.z.z, projection.w.z);
+        let params: Vec<u8> = [a, b, b / a, b / (1.0 + a)]
+            .iter()
+            .flat_map(|v| v.to_le_bytes())
+            .collect();
+        let params = device
+            .create_buffer_init(&BufferInitDescriptor {
+                label: Some("Clip planes parameters"),
+                contents: &params,
+                usage: BufferUsages::UNIFORM,
+            })
+            .or_report()?;
+
+        let layout = device
+            .create_pipeline_layout(&PipelineLayoutDescriptor {
+                label: Some("Clip planes pipeline layout"),
+                bind_group_layouts: &[&bg_layout],
+                push_constant_ranges: &[],
+            })
+            .or_report()?;
+        let pipeline = device
+            .create_render_pipeline(&RenderPipelineDescriptor {
+                label: Some("Clip planes pipeline"),
+                layout: Some(&layout),
+                vertex: VertexState {
+                    module: &shader,
+                    entry_point: Some("vs_main"),
+                    buffers: &[VertexBufferLayout {
+                        array_stride: Vertex::SIZE,
+                        step_mode: VertexStepMode::Vertex,
+                        attributes: &[VertexAttribute {
+                            format: VertexFormat::Float32x3,
+                            offset: 0,
+                            shader_location: 0,
+                        }],
+                    }],
+                    compilation_options: Default::default(),
+                },
+                primitive: PrimitiveState::default(),
+                depth_stencil: None,
+                multisample: MultisampleState::default(),
+                fragment: Some(FragmentState {
+                    module: &shader,
+                    entry_point: Some("fs_main"),
+                    targets: &[Some(ColorTargetState {
+                        format,
+                        blend: Some(BlendState::ALPHA_BLENDING),
+                        write_mask: ColorWrites::ALL,
+                    })],
+                    compilation_options: Default::default(),
+                }),
+                multiview: None,
+                cache: None,
+            })
+            .or_report()?;
+
+        let background =
+            Self::create_bind_group(device, &bg_layout, &params, &depth_textures.background)?;
+        let imgui = Self::create_bind_group(device, &bg_layout, &params, &depth_textures.imgui)?;
+
+        Some(ClipPlanes {
+            pipeline,
+            bg_layout,
+            params,
+            background,
+            imgui,
+            quad: VerticesSet::screen_quad(device)?,
+        })
+    }
+
+    fn create_bind_group(
+        device: &Device,
+        layout: &BindGroupLayout,
+        params: &Buffer,
+        depth_texture: &Texture,
+    ) -> Option<BindGroup> {
+        let view = depth_texture
+            .create_view(&TextureViewDescriptor::default())
+            .or_report()?;
+        device
+            .create_bind_group(&BindGroupDescriptor {
+                label: Some("Clip planes bind group"),
+                layout,
+                entries: &[
+                    BindGroupEntry {
+                        binding: 0,
+                        resource: BindingResource::TextureView(&view),
+                    },
+                    BindGroupEntry {
+                        binding: 1,
+                        resource: params.as_entire_binding(),
+                    },
+                ],
+            })
+            .or_report()
+    }
+
+    pub(crate) fn bind_group(&self, target: PostTarget) -> &BindGroup {
+        match target {
+            PostTarget::Background => &self.background,
+            PostTarget::Imgui => &self.imgui,
+        }
+    }
+
+    /// The background depth texture is recreated when the window is resized
+    pub(crate) fn resize(&mut self, device: &Device, depth_textures: &DepthTextures) {
+        let background = Self::create_bind_group(
+            device,
+            &self.bg_layout,
+            &self.params,
+            &depth_textures.background,
+        );
+        if let Some(background) = background {
+            self.background = background
+        }
+    }
+}
@@ -0,0 +1,235 @@
+use wgpu::{
+    AddressMode, BindGroup, BindGroupDescriptor, BindGroupEntry, BindGroupLayout,
+    BindGroupLayoutDescriptor, BindGroupLayoutEntry, BindingResource, BindingType, Device,
+    Extent3d, FilterMode, ImageCopyTexture, ImageDataLayout, Origin3d, Queue, SamplerBindingType,
+    SamplerDescriptor, ShaderStages, Texture, TextureAspect, TextureDescriptor, TextureFormat,
+    TextureSampleType, TextureUsages, TextureViewDescriptor, TextureViewDimension,
+};
+
+use crate::{gpu_errors::OrReport, state::Error};
+
+/// Width and height of the generated texture
+const TEXTURE_SIZE: u32 = 256;
+pub(crate) const MAX_CHECKER_CELLS: u32 = 64;
+
+/// Image generated for checking the UVs of a mesh
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum DebugPattern {
+    /// Light and dark squares, to spot stretching and seams
+    Checkerboard,
+    /// U in red and V in green, to spot flips
+    UvGradient,
+}
+
+impl DebugPattern {
+    pub(crate) const ALL: &'static [DebugPattern] =
+        &[DebugPattern::Checkerboard, DebugPattern::UvGradient];
+
+    pub(crate) fn label(&self) -> &'static str {
+        match self {
+            DebugPattern::Checkerboard => "Checkerboard",
+            DebugPattern::UvGradient => "UV gradient",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct DebugTextureOptions {
+    pub(crate) pattern: DebugPattern,
+    /// Squares of the checkerboard along each side
+    pub(crate) cells: u32,
+}
+
+impl Default for DebugTextureOptions {
+    fn default() -> Self {
+        DebugTextureOptions {
+            pattern: DebugPattern::Checkerboard,
+            cells: 8,
+        }
+    }
+}
+
+/// RGBA8 rows of the pattern, V grows downwards like the texture coordinates
+fn pixels(options: DebugTextureOptions, size: u32) -> Vec<u8> {
+    let cells = options.cells.clamp(1, MAX_CHECKER_CELLS);
+    let mut pixels = Vec::with_capacity((size * size * 4) as usize);
+    for y in 0..size {
+        for x in 0..size {
+            let pixel = match options.pattern {
+                DebugPattern::Checkerboard => {
+                    let light = (x * cells / size + y * cells / size) % 2 == 0;
+                    if light {
+                        [230, 230, 230, 255]
+                    } else {
+                        [40, 40, 40, 255]
+                    }
+                }
+                DebugPattern::UvGradient => {
+                    let u = (x * 255 / (size - 1)) as u8;
+                    let v = (y * 255 / (size - 1)) as u8;
+                    [u, v, 0, 255]
+                }
+            };
+            pixels.extend(pixel)
+        }
+    }
+
+    pixels
+}
+
+/// Generated texture and its sampler, bound in the group after the uniform groups
+pub(crate) struct DebugTexture {
+    texture: Texture,
+    pub(crate) bg_layout: BindGroupLayout,
+    pub(crate) bind_group: BindGroup,
+    pub(crate) options: DebugTextureOptions,
+}
+
+impl DebugTexture {
+    pub(crate) fn new(
+        device: &Device,
+        queue: &Queue,
+        options: DebugTextureOptions,
+    ) -> Result<DebugTexture, Error> {
+        let size = Extent3d {
+            width: TEXTURE_SIZE,
+            height: TEXTURE_SIZE,
+            depth_or_array_layers: 1,
+        };
+        let texture = device
+            .create_texture(&TextureDescriptor {
+                label: Some("Debug texture"),
+                size,
+                mip_level_count: 1,
+                sample_count: 1,
+                dimension: wgpu::TextureDimension::D2,
+                format: TextureFormat::Rgba8Unorm,
+                usage: TextureUsages::TEXTURE_BINDING | TextureUsages::COPY_DST,
+                view_formats: &[TextureFormat::Rgba8Unorm],
+            })
+            .or_report()
+            .ok_or(Error::Resources)?;
+        let view = texture
+            .create_view(&TextureViewDescriptor::default())
+            .or_report()
+            .ok_or(Error::Resources)?;
+        // Nearest keeps the edges of the squares sharp up close
+        let sampler = device
+            .create_sampler(&SamplerDescriptor {
+                label: Some("Debug texture sampler"),
+                address_mode_u: AddressMode::Repeat,
+                address_mode_v: AddressMode::Repeat,
+                address_mode_w: AddressMode::Repeat,
+                mag_filter: FilterMode::Nearest,
+                min_filter: FilterMode::Linear,
+                ..Default::default()
+            })
+            .or_report()
+            .ok_or(Error::Resources)?;
+
+        let bg_layout = device
+            .create_bind_group_layout(&BindGroupLayoutDescriptor {
+                label: Some("Debug texture layout"),
+                entries: &[
+                    BindGroupLayoutEntry {
+                        binding: 0,
+                        visibility: ShaderStages::VERTEX_FRAGMENT,
+                        ty: BindingType::Texture {
+                            sample_type: TextureSampleType::Float { filterable: true },
+                            view_dimension: TextureViewDimension::D2,
+                            multisampled: false,
+                        },
+                        count: None,
+                    },
+                    BindGroupLayoutEntry {
+                        binding: 1,
+                        visibility: ShaderStages::VERTEX_FRAGMENT,
+                        ty: BindingType::Sampler(SamplerBindingType::Filtering),
+                        count: None,
+                    },
+                ],
+            })
+            .or_report()
+            .ok_or(Error::Resources)?;
+        let bind_group = device
+            .create_bind_group(&BindGroupDescriptor {
+                label: Some("Debug texture bind group"),
+                layout: &bg_layout,
+                entries: &[
+                    BindGroupEntry {
+                        binding: 0,
+                        resource: BindingResource::TextureView(&view),
+                    },
+                    BindGroupEntry {
+                        binding: 1,
+                        resource: BindingResource::Sampler(&sampler),
+                    },
+                ],
+            })
+            .or_report()
+            .ok_or(Error::Resources)?;
+
+        let mut debug_texture = DebugTexture {
+            texture,
+            bg_layout,
+            bind_group,
+            options,
+        };
+        debug_texture.write(queue, options);
+        Ok(debug_texture)
+    }
+
+    /// Draws another pattern in the same texture, the pipelines don't change
+    pub(crate) fn write(&mut self, queue: &Queue, options: DebugTextureOptions) {
+        queue
+            .write_texture(
+                ImageCopyTexture {
+                    texture: &self.texture,
+                    mip_level: 0,
+                    origin: Origin3d::ZERO,
+                    aspect: TextureAspect::All,
+                },
+                &pixels(options, TEXTURE_SIZE),
+                ImageDataLayout {
+                    offset: 0,
+                    bytes_per_row: Some(TEXTURE_SIZE * 4),
+                    rows_per_image: Some(TEXTURE_SIZE),
+                },
+                self.texture.size(),
+            )
+            .or_report();
+        self.options = options
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn checkerboard_alternates() {
+        let options = DebugTextureOptions {
+            pattern: DebugPattern::Checkerboard,
+            cells: 2,
+        };
+        let pixels = pixels(options, 4);
+        let pixel = |x: usize, y: usize| pixels[(y * 4 + x) * 4];
+        assert_eq!(pixel(0, 0), pixel(1, 1));
+        assert_ne!(pixel(1, 0), pixel(2, 0));
+        assert_ne!(pixel(0, 1), pixel(0, 2));
+        assert_eq!(pixel(0, 0), pixel(2, 2));
+    }
+
+    #[test]
+    fn gradient_follows_the_uvs() {
+        let options = DebugTextureOptions {
+            pattern: DebugPattern::UvGradient,
+            cells: 8,
+        };
+        let pixels = pixels(options, 4);
+        assert_eq!(pixels[..4], [0, 0, 0, 255]);
+        // Last pixel of the first row, and first pixel of the last row
+        assert_eq!(pixels[12..16], [255, 0, 0, 255]);
+        assert_eq!(pixels[48..52], [0, 255, 0, 255]);
+    }
+}
@@ -0,0 +1,295 @@
+use std::{
+    f32::consts::TAU,
+    sync::mpsc::{self, Receiver, TryRecvError},
+    thread,
+};
+
+use cgmath::Vector3;
+
+use crate::imgui_state::{MeshConfig, NormalMode};
+
+use super::{Vertex, VerticesSet};
+
+/// Highest number of segments or subdivisions the Mesh configuration window allows
+pub(crate) const MAX_SEGMENTS: u32 = 256;
+
+pub(crate) struct GeneratedMesh {
+    pub(crate) mesh: (Vec<Vertex>, Vec<u32>),
+    pub(crate) bounding_box: (Vec<Vertex>, Vec<u32>),
+}
+
+/// Generates meshes on a worker thread, so big meshes don't freeze the UI.
+/// Only one job runs at a time, configs requested meanwhile replace each other
+#[derive(Default)]
+pub(crate) struct MeshGenerator {
+    running: Option<Receiver<GeneratedMesh>>,
+    /// Newest config, to generate once the running job finishes
+    pending: Option<(MeshConfig, NormalMode)>,
+}
+
+impl MeshGenerator {
+    pub(crate) fn request(&mut self, mesh_config: MeshConfig, normal_mode: NormalMode) {
+        if self.running.is_some() {
+            self.pending = Some((mesh_config, normal_mode))
+        } else {
+            self.start(mesh_config, normal_mode)
+        }
+    }
+
+    fn start(&mut self, mesh_config: MeshConfig, normal_mode: NormalMode) {
+        let (sender, receiver) = mpsc::channel();
+        thread::spawn(move || {
+            let mesh = VerticesSet::generate(&mesh_config, normal_mode);
+            let bounding_box = VerticesSet::bounding_box(&mesh.0);
+            // The receiver is gone if the app is closing
+            let _ = sender.send(GeneratedMesh { mesh, bounding_box });
+        });
+        self.running = Some(receiver)
+    }
+
+    /// The mesh of the newest config once it's ready, results of superseded configs are dropped.
+    /// Err if the job failed
+    pub(crate) fn poll(&mut self) -> Option<Result<GeneratedMesh, String>> {
+        let result = match self.running.as_ref()?.try_recv() {
+            Ok(mesh) => Ok(mesh),
+            Err(TryRecvError::Empty) => return None,
+            Err(TryRecvError::Disconnected) => Err("The mesh generation thread panicked".into()),
+        };
+        self.running = None;
+
+        match self.pending.take() {
+            Some((mesh_config, normal_mode)) => {
+                self.start(mesh_config, normal_mode);
+                None
+            }
+            None => Some(result),
+        }
+    }
+
+    pub(crate) fn is_busy(&self) -> bool {
+        self.running.is_some()
+    }
+}
+
+/// A grid of `columns` x `rows` quads over `point(u, v)`, with u and v going from 0 to 1.
+/// The normal of the surface must be `d point/du x d point/dv`. A wrapped direction shares the
+/// vertices of its first and last edge, so smooth normals have no seam there
+fn parametric(
+    (columns, rows): (u32, u32),
+    (wrap_u, wrap_v): (bool, bool),
+    point: impl Fn(f32, f32) -> Vector3<f32>,
+) -> (Vec<Vertex>, Vec<u32>) {
+    let row_len = if wrap_u { columns } else { columns + 1 };
+    let row_count = if wrap_v { rows } else { rows + 1 };
+
+    let mut vertices = Vec::with_capacity(row_len as usize * row_count as usize);
+    for row in 0..row_count {
+        for column in 0..row_len {
+            let point = point(column as f32 / columns as f32, row as f32 / rows as f32);
+            vertices.push(Vertex {
+                x: point.x,
+                y: point.y,
+                z: point.z,
+                normal: [0.0, 0.0, 0.0],
+            })
+        }
+    }
+
+    let index = |column: u32, row: u32| (row % row_count) * row_len + column % row_len;
+    let mut indices = Vec::with_capacity(columns as usize * rows as usize * 6);
+    for row in 0..rows {
+        for column in 0..columns {
+            let corners = [
+                index(column, row),
+                index(column + 1, row),
+                index(column + 1, row + 1),
+                index(column, row + 1),
+            ];
+            indices.extend([corners[0], corners[1], corners[2]]);
+            indices.extend([corners[0], corners[2], corners[3]]);
+        }
+    }
+
+    (vertices, indices)
+}
+
+/// A disc facing up or down, its own vertices so its edge is sharp
+fn disc(radius: f32, y: f32, segments: u32, up: bool) -> (Vec<Vertex>, Vec<u32>) {
+    let vertex = |x: f32, z: f32| Vertex {
+        x,
+        y,
+        z,
+        normal: [0.0, 0.0, 0.0],
+    };
+    let mut vertices = vec![vertex(0.0, 0.0)];
+    for segment in 0..segments {
+        let angle = segment as f32 / segments as f32 * TAU;
+        vertices.push(vertex(radius * angle.cos(), radius * angle.sin()))
+    }
+
+    let mut indices = Vec::with_capacity(segments as usize * 3);
+    for segment in 0..segments {
+        let current = segment + 1;
+        let next = (segment + 1) % segments + 1;
+        if up {
+            indices.extend([0, next, current])
+        } else {
+            indices.extend([0, current, next])
+        }
+    }
+
+    (vertices, indices)
+}
+
+fn merge(meshes: impl IntoIterator<Item = (Vec<Vertex>, Vec<u32>)>) -> (Vec<Vertex>, Vec<u32>) {
+    let (mut merged_vertices, mut merged_indices) = (Vec::new(), Vec::new());
+    for (vertices, indices) in meshes {
+        let offset = merged_vertices.len() as u32;
+        merged_vertices.extend(vertices);
+        merged_indices.extend(indices.into_iter().map(|index| index + offset));
+    }
+    (merged_vertices, merged_indices)
+}
+
+/// `segments` around the y axis and `rings` from the top pole to the bottom one
+pub(super) fn sphere(radius: f32, (segments, rings): (u32, u32)) -> (Vec<Vertex>, Vec<u32>) {
+    parametric((segments, rings), (true, false), |u, v| {
+        let (around, down) = (u * TAU, v * TAU / 2.0);
+        Vector3::new(
+            radius * down.sin() * around.cos(),
+            radius * down.cos(),
+            radius * down.sin() * around.sin(),
+        )
+    })
+}
+
+/// Every side is split in `subdivisions` x `subdivisions` squares
+pub(super) fn cube(side: f32, subdivisions: u32) -> (Vec<Vertex>, Vec<u32>) {
+    // The normal of each face, and its u and v directions
+    let faces = [
+        ([1.0, 0.0, 0.0], [0.0, 0.0, -1.0], [0.0, 1.0, 0.0]),
+        ([-1.0, 0.0, 0.0], [0.0, 0.0, 1.0], [0.0, 1.0, 0.0]),
+        ([0.0, 1.0, 0.0], [1.0, 0.0, 0.0], [0.0, 0.0, -1.0]),
+        ([0.0, -1.0, 0.0], [1.0, 0.0, 0.0], [0.0, 0.0, 1.0]),
+        ([0.0, 0.0, 1.0], [1.0, 0.0, 0.0], [0.0, 1.0, 0.0]),
+        ([0.0, 0.0, -1.0], [-1.0, 0.0, 0.0], [0.0, 1.0, 0.0]),
+    ];
+    merge(faces.map(|(normal, u_axis, v_axis)| {
+        let [normal, u_axis, v_axis] = [normal, u_axis, v_axis].map(Vector3::<f32>::from);
+        parametric((subdivisions, subdivisions), (false, false), |u, v| {
+            (normal / 2.0 + u_axis * (u - 0.5) + v_axis * (v - 0.5)) * side
+        })
+    }))
+}
+
+/// Centered on the origin, with `segments` around the y axis
+pub(super) fn cylinder((radius, height): (f32, f32), segments: u32) -> (Vec<Vertex>, Vec<u32>) {
+    let side = parametric((segments, 1), (true, false), |u, v| {
+        let around = u * TAU;
+        Vector3::new(
+            radius * around.cos(),
+            height * (0.5 - v),
+            radius * around.sin(),
+        )
+    });
+    merge([
+        side,
+        disc(radius, height / 2.0, segments, true),
+        disc(radius, -height / 2.0, segments, false),
+    ])
+}
+
+/// Centered on the origin, with the tip up and `segments` around the y axis
+pub(super) fn cone((radius, height): (f32, f32), segments: u32) -> (Vec<Vertex>, Vec<u32>) {
+    let side = parametric((segments, 1), (true, false), |u, v| {
+        let around = u * TAU;
+        Vector3::new(
+            radius * v * around.cos(),
+            height * (0.5 - v),
+            radius * v * around.sin(),
+        )
+    });
+    merge([side, disc(radius, -height / 2.0, segments, false)])
+}
+
+/// A ring around the y axis. `segments` go around the ring and `sides` around its tube
+pub(super) fn torus(
+    (ring_radius, tube_radius): (f32, f32),
+    (segments, sides): (u32, u32),
+) -> (Vec<Vertex>, Vec<u32>) {
+    parametric((segments, sides), (true, true), |u, v| {
+        let (around, tube) = (u * TAU, v * TAU);
+        let distance = ring_radius + tube_radius * tube.cos();
+        Vector3::new(
+            distance * around.cos(),
+            -tube_radius * tube.sin(),
+            distance * around.sin(),
+        )
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use cgmath::InnerSpace;
+
+    use super::*;
+
+    fn wait_for(generator: &mut MeshGenerator) -> Result<GeneratedMesh, String> {
+        loop {
+            if let Some(result) = generator.poll() {
+                return result;
+            }
+            thread::sleep(Duration::from_millis(1))
+        }
+    }
+
+    #[test]
+    fn superseded_meshes_are_dropped() {
+        let mut generator = MeshGenerator::default();
+        generator.request(
+            MeshConfig::Plane((1.0, 1.0), (500, 500)),
+            NormalMode::Smooth,
+        );
+        generator.request(MeshConfig::Plane((1.0, 1.0), (3, 2)), NormalMode::Smooth);
+        generator.request(MeshConfig::Plane((1.0, 1.0), (2, 2)), NormalMode::Smooth);
+
+        let generated = wait_for(&mut generator).unwrap();
+        assert_eq!(generated.mesh.1.len(), 2 * 2 * 6);
+        assert!(!generator.is_busy());
+        assert!(generator.poll().is_none());
+    }
+
+    #[test]
+    fn closed_meshes_face_outwards() {
+        let meshes = [
+            (MeshConfig::Sphere(1.0, (16, 8)), 16 * 8 * 2),
+            (MeshConfig::Cube(2.0, 3), 6 * 3 * 3 * 2),
+            (MeshConfig::Cylinder((1.0, 2.0), 12), 12 * 2 + 12 * 2),
+            (MeshConfig::Cone((1.0, 2.0), 12), 12 * 2 + 12),
+            (MeshConfig::Torus((1.0, 0.25), (12, 8)), 12 * 8 * 2),
+        ];
+        for (mesh_config, triangles) in meshes {
+            let (vertices, indices) = VerticesSet::generate(&mesh_config, NormalMode::Flat);
+            assert_eq!(indices.len(), triangles * 3, "{mesh_config:?}");
+            // The center of every face is on the side its normal points to
+            for triangle in vertices.chunks_exact(3) {
+                let center = triangle
+                    .iter()
+                    .map(Vertex::position)
+                    .fold(Vector3::new(0.0, 0.0, 0.0), |sum, position| sum + position)
+                    / 3.0;
+                let normal = Vector3::from(triangle[0].normal);
+                let outside = match mesh_config {
+                    // The tube of the torus is the inside
+                    MeshConfig::Torus((ring_radius, _), _) => {
+                        center - Vector3::new(center.x, 0.0, center.z).normalize() * ring_radius
+                    }
+                    _ => center,
+                };
+                assert!(normal.dot(outside) >= -1e-5, "{mesh_config:?}");
+            }
+        }
+    }
+}
@@ -0,0 +1,49 @@
+use std::io::{self, Write};
+
+use super::Vertex;
+
+/// Writes the mesh as a Wavefront OBJ, with a normal per vertex. The meshes have no UVs.
+/// Indices that don't make a whole triangle are left out
+pub(crate) fn write_obj(
+    out: &mut impl Write,
+    vertices: &[Vertex],
+    indices: &[u32],
+) -> io::Result<()> {
+    writeln!(out, "# Exported by te-shader-play")?;
+    for vertex in vertices {
+        writeln!(out, "v {} {} {}", vertex.x, vertex.y, vertex.z)?;
+    }
+    for vertex in vertices {
+        let [x, y, z] = vertex.normal;
+        writeln!(out, "vn {x} {y} {z}")?;
+    }
+    // OBJ indices start at 1
+    for triangle in indices.chunks_exact(3) {
+        let [a, b, c] = [triangle[0] + 1, triangle[1] + 1, triangle[2] + 1];
+        writeln!(out, "f {a}//{a} {b}//{b} {c}//{c}")?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state::VerticesSet;
+
+    #[test]
+    fn screen_quad_is_two_faces() {
+        let (vertices, indices) = VerticesSet::screen_2d_vertices();
+        let mut obj = Vec::new();
+        write_obj(&mut obj, &vertices, &indices).unwrap();
+        let obj = String::from_utf8(obj).unwrap();
+
+        assert_eq!(obj.lines().filter(|line| line.starts_with("v ")).count(), 4);
+        assert_eq!(
+            obj.lines().filter(|line| line.starts_with("vn ")).count(),
+            4
+        );
+        let faces: Vec<&str> = obj.lines().filter(|line| line.starts_with("f ")).collect();
+        assert_eq!(faces, ["f 1//1 3//3 4//4", "f 1//1 4//4 2//2"]);
+    }
+}
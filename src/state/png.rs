@@ -0,0 +1,217 @@
+//! Just enough of PNG to write RGBA8 images with text chunks, and to read those chunks back.
+//! The pixels are stored without compression, which keeps the encoder small
+
+const SIGNATURE: &[u8; 8] = b"\x89PNG\r\n\x1a\n";
+/// The biggest block a stored deflate block can hold
+const MAX_STORED_BLOCK: usize = u16::MAX as usize;
+
+fn crc32(bytes: &[u8]) -> u32 {
+    let mut crc = !0u32;
+    for byte in bytes {
+        crc ^= *byte as u32;
+        for _ in 0..8 {
+            crc = if crc & 1 == 1 {
+                (crc >> 1) ^ 0xEDB8_8320
+            } else {
+                crc >> 1
+            };
+        }
+    }
+    !crc
+}
+
+fn adler32(bytes: &[u8]) -> u32 {
+    let (mut a, mut b) = (1u32, 0u32);
+    for byte in bytes {
+        a = (a + *byte as u32) % 65521;
+        b = (b + a) % 65521;
+    }
+    (b << 16) | a
+}
+
+fn write_chunk(png: &mut Vec<u8>, kind: &[u8; 4], data: &[u8]) {
+    png.extend_from_slice(&(data.len() as u32).to_be_bytes());
+    let start = png.len();
+    png.extend_from_slice(kind);
+    png.extend_from_slice(data);
+    let crc = crc32(&png[start..]);
+    png.extend_from_slice(&crc.to_be_bytes());
+}
+
+/// A zlib stream of stored, uncompressed, deflate blocks
+fn zlib_stored(data: &[u8]) -> Vec<u8> {
+    let blocks = data.len().div_ceil(MAX_STORED_BLOCK).max(1);
+    let mut stream = Vec::with_capacity(data.len() + blocks * 5 + 6);
+    // Deflate with a 32K window and no preset dictionary
+    stream.extend_from_slice(&[0x78, 0x01]);
+    let mut chunks = data.chunks(MAX_STORED_BLOCK).peekable();
+    if chunks.peek().is_none() {
+        stream.extend_from_slice(&[1, 0, 0, 0xFF, 0xFF]);
+    }
+    while let Some(chunk) = chunks.next() {
+        let last = chunks.peek().is_none();
+        stream.push(last as u8);
+        let len = chunk.len() as u16;
+        stream.extend_from_slice(&len.to_le_bytes());
+        stream.extend_from_slice(&(!len).to_le_bytes());
+        stream.extend_from_slice(chunk);
+    }
+    stream.extend_from_slice(&adler32(data).to_be_bytes());
+    stream
+}
+
+/// Encodes tightly packed RGBA8 rows, top first. Every `(keyword, text)` is stored as an
+/// uncompressed iTXt chunk, so the text can be any UTF-8
+pub(crate) fn encode(width: u32, height: u32, rgba: &[u8], texts: &[(&str, &str)]) -> Vec<u8> {
+    let mut png = SIGNATURE.to_vec();
+
+    let mut header = Vec::with_capacity(13);
+    header.extend_from_slice(&width.to_be_bytes());
+    header.extend_from_slice(&height.to_be_bytes());
+    // 8 bits per channel, RGBA, deflate, adaptive filtering, not interlaced
+    header.extend_from_slice(&[8, 6, 0, 0, 0]);
+    write_chunk(&mut png, b"IHDR", &header);
+
+    for (keyword, text) in texts {
+        // Keyword, no compression, and empty language and translated keyword
+        let mut data = Vec::with_capacity(keyword.len() + text.len() + 5);
+        data.extend_from_slice(keyword.as_bytes());
+        data.extend_from_slice(&[0, 0, 0, 0, 0]);
+        data.extend_from_slice(text.as_bytes());
+        write_chunk(&mut png, b"iTXt", &data);
+    }
+
+    // Every row starts with its filter, 0 leaves it as it is
+    let row_bytes = width as usize * 4;
+    let mut filtered = Vec::with_capacity((row_bytes + 1) * height as usize);
+    for row in rgba.chunks(row_bytes.max(1)) {
+        filtered.push(0);
+        filtered.extend_from_slice(row);
+    }
+    write_chunk(&mut png, b"IDAT", &zlib_stored(&filtered));
+    write_chunk(&mut png, b"IEND", &[]);
+
+    png
+}
+
+/// The text of the iTXt chunk with `keyword`, None if there's no such chunk
+pub(crate) fn read_text(png: &[u8], keyword: &str) -> Result<Option<String>, String> {
+    let Some(mut chunks) = png.strip_prefix(SIGNATURE) else {
+        return Err("It isn't a PNG".to_string());
+    };
+
+    while !chunks.is_empty() {
+        let Some((len, rest)) = chunks.split_first_chunk::<4>() else {
+            return Err("It's truncated".to_string());
+        };
+        let len = u32::from_be_bytes(*len) as usize;
+        // The type, the data and the CRC
+        if rest.len() < len + 8 {
+            return Err("It's truncated".to_string());
+        }
+        let (typed_data, rest) = rest.split_at(len + 4);
+        let (crc, rest) = rest.split_at(4);
+        chunks = rest;
+
+        let (kind, data) = typed_data.split_at(4);
+        if crc32(typed_data).to_be_bytes() != crc {
+            return Err(format!(
+                "Its {} chunk is corrupted",
+                String::from_utf8_lossy(kind)
+            ));
+        }
+        if kind == b"IEND" {
+            break;
+        }
+        if kind != b"iTXt" {
+            continue;
+        }
+
+        let mut fields = data.splitn(2, |byte| *byte == 0);
+        if fields.next() != Some(keyword.as_bytes()) {
+            continue;
+        }
+        let Some([compressed, _method, rest @ ..]) = fields.next() else {
+            return Err(format!("Its {keyword} text is corrupted"));
+        };
+        if *compressed != 0 {
+            return Err(format!(
+                "Its {keyword} text is compressed, which isn't supported"
+            ));
+        }
+        // Skips the language and the translated keyword
+        let mut fields = rest.splitn(3, |byte| *byte == 0);
+        let Some(text) = fields.nth(2) else {
+            return Err(format!("Its {keyword} text is corrupted"));
+        };
+        return String::from_utf8(text.to_vec())
+            .map(Some)
+            .map_err(|_| format!("Its {keyword} text isn't UTF-8"));
+    }
+
+    Ok(None)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Undoes `zlib_stored`, which only has stored blocks
+    fn inflate_stored(stream: &[u8]) -> Vec<u8> {
+        let mut data = Vec::new();
+        let mut i = 2;
+        loop {
+            let last = stream[i] & 1 == 1;
+            let len = u16::from_le_bytes([stream[i + 1], stream[i + 2]]) as usize;
+            data.extend_from_slice(&stream[i + 5..i + 5 + len]);
+            i += 5 + len;
+            if last {
+                break;
+            }
+        }
+        assert_eq!(stream[i..], adler32(&data).to_be_bytes());
+        data
+    }
+
+    #[test]
+    fn text_and_pixels_round_trip() {
+        let rgba = [255, 0, 0, 255, 0, 255, 0, 128];
+        let text = "{\"name\": \"ünïcode\"}";
+        let png = encode(2, 1, &rgba, &[("other", "ignored"), ("params", text)]);
+        assert_eq!(read_text(&png, "params"), Ok(Some(text.to_string())));
+        assert_eq!(read_text(&png, "missing"), Ok(None));
+
+        let idat = png.windows(4).position(|kind| kind == b"IDAT").unwrap();
+        let len = u32::from_be_bytes(png[idat - 4..idat].try_into().unwrap()) as usize;
+        let rows = inflate_stored(&png[idat + 4..idat + 4 + len]);
+        assert_eq!(rows[0], 0);
+        assert_eq!(rows[1..], rgba);
+    }
+
+    #[test]
+    fn big_images_are_split_in_blocks() {
+        let data = vec![7; MAX_STORED_BLOCK * 2 + 10];
+        assert_eq!(inflate_stored(&zlib_stored(&data)), data);
+        assert_eq!(inflate_stored(&zlib_stored(&[])), Vec::<u8>::new());
+    }
+
+    #[test]
+    fn corruption_is_reported() {
+        let mut png = encode(1, 1, &[0, 0, 0, 255], &[("params", "{}")]);
+        assert_eq!(
+            read_text(b"GIF89a", "params"),
+            Err("It isn't a PNG".to_string())
+        );
+        assert_eq!(
+            read_text(&png[..40], "params"),
+            Err("It's truncated".to_string())
+        );
+
+        let text = png.windows(2).position(|bytes| bytes == b"{}").unwrap();
+        png[text] = b'[';
+        assert_eq!(
+            read_text(&png, "params"),
+            Err("Its iTXt chunk is corrupted".to_string())
+        );
+    }
+}
@@ -0,0 +1,189 @@
+use wgpu::{
+    AddressMode, BindGroup, BindGroupDescriptor, BindGroupEntry, BindGroupLayout,
+    BindGroupLayoutDescriptor, BindGroupLayoutEntry, BindingResource, BindingType, Device,
+    Extent3d, FilterMode, RenderPipeline, Sampler, SamplerBindingType, SamplerDescriptor,
+    ShaderModule, ShaderStages, Texture, TextureDescriptor, TextureFormat, TextureSampleType,
+    TextureUsages, TextureView, TextureViewDescriptor, TextureViewDimension,
+};
+
+use crate::{
+    gpu_errors::OrReport,
+    imgui_state::{IMAGE_HEIGHT, IMAGE_WIDTH},
+};
+
+use super::VerticesSet;
+
+/// Which image a pass is drawing, each one has its own intermediate texture
+#[derive(Debug, Clone, Copy)]
+pub(crate) enum PostTarget {
+    Background,
+    Imgui,
+}
+
+/// Texture the custom shader renders to, so the post shader can sample it
+pub(crate) struct PostTexture {
+    _texture: Texture,
+    pub(crate) view: TextureView,
+    pub(crate) bind_group: BindGroup,
+}
+
+impl PostTexture {
+    fn new(
+        device: &Device,
+        layout: &BindGroupLayout,
+        sampler: &Sampler,
+        format: TextureFormat,
+        width: u32,
+        height: u32,
+    ) -> Option<PostTexture> {
+        let texture = device
+            .create_texture(&TextureDescriptor {
+                label: Some("Post-process input texture"),
+                size: Extent3d {
+                    width,
+                    height,
+                    depth_or_array_layers: 1,
+                },
+                mip_level_count: 1,
+                sample_count: 1,
+                dimension: wgpu::TextureDimension::D2,
+                format,
+                usage: TextureUsages::RENDER_ATTACHMENT | TextureUsages::TEXTURE_BINDING,
+                view_formats: &[format],
+            })
+            .or_report()?;
+        let view = texture
+            .create_view(&TextureViewDescriptor::default())
+            .or_report()?;
+        let bind_group = device
+            .create_bind_group(&BindGroupDescriptor {
+                label: Some("Post-process input bind group"),
+                layout,
+                entries: &[
+                    BindGroupEntry {
+                        binding: 0,
+                        resource: BindingResource::TextureView(&view),
+                    },
+                    BindGroupEntry {
+                        binding: 1,
+                        resource: BindingResource::Sampler(sampler),
+                    },
+                ],
+            })
+            .or_report()?;
+
+        Some(PostTexture {
+            _texture: texture,
+            view,
+            bind_group,
+        })
+    }
+}
+
+pub(crate) struct PostProcess {
+    pub(crate) shader_name: String,
+    pub(crate) shader: ShaderModule,
+    pub(crate) pipeline: Option<RenderPipeline>,
+    pub(crate) bg_layout: BindGroupLayout,
+    sampler: Sampler,
+    format: TextureFormat,
+    pub(crate) background: PostTexture,
+    pub(crate) imgui: PostTexture,
+    pub(crate) quad: VerticesSet,
+}
+
+impl PostProcess {
+    pub(crate) fn new(
+        device: &Device,
+        shader_name: String,
+        shader: ShaderModule,
+        format: TextureFormat,
+        size: winit::dpi::PhysicalSize<u32>,
+    ) -> Option<PostProcess> {
+        let bg_layout = device
+            .create_bind_group_layout(&BindGroupLayoutDescriptor {
+                label: Some("Post-process input layout"),
+                entries: &[
+                    BindGroupLayoutEntry {
+                        binding: 0,
+                        visibility: ShaderStages::FRAGMENT,
+                        ty: BindingType::Texture {
+                            sample_type: TextureSampleType::Float { filterable: true },
+                            view_dimension: TextureViewDimension::D2,
+                            multisampled: false,
+                        },
+                        count: None,
+                    },
+                    BindGroupLayoutEntry {
+                        binding: 1,
+                        visibility: ShaderStages::FRAGMENT,
+                        ty: BindingType::Sampler(SamplerBindingType::Filtering),
+                        count: None,
+                    },
+                ],
+            })
+            .or_report()?;
+        let sampler = device
+            .create_sampler(&SamplerDescriptor {
+                label: Some("Post-process sampler"),
+                address_mode_u: AddressMode::ClampToEdge,
+                address_mode_v: AddressMode::ClampToEdge,
+                address_mode_w: AddressMode::ClampToEdge,
+                mag_filter: FilterMode::Linear,
+                min_filter: FilterMode::Linear,
+                ..Default::default()
+            })
+            .or_report()?;
+
+        let background = PostTexture::new(
+            device,
+            &bg_layout,
+            &sampler,
+            format,
+            size.width,
+            size.height,
+        )?;
+        let imgui = PostTexture::new(
+            device,
+            &bg_layout,
+            &sampler,
+            format,
+            IMAGE_WIDTH as u32,
+            IMAGE_HEIGHT as u32,
+        )?;
+
+        Some(PostProcess {
+            shader_name,
+            shader,
+            pipeline: None,
+            bg_layout,
+            sampler,
+            format,
+            background,
+            imgui,
+            quad: VerticesSet::screen_quad(device)?,
+        })
+    }
+
+    pub(crate) fn target(&self, target: PostTarget) -> &PostTexture {
+        match target {
+            PostTarget::Background => &self.background,
+            PostTarget::Imgui => &self.imgui,
+        }
+    }
+
+    /// Keeps the previous texture if the new one can't be created
+    pub(crate) fn resize(&mut self, device: &Device, size: winit::dpi::PhysicalSize<u32>) {
+        let background = PostTexture::new(
+            device,
+            &self.bg_layout,
+            &self.sampler,
+            self.format,
+            size.width,
+            size.height,
+        );
+        if let Some(background) = background {
+            self.background = background
+        }
+    }
+}
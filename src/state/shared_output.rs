@@ -0,0 +1,142 @@
+use std::{
+    fs::{File, OpenOptions},
+    io::{self, Seek, SeekFrom, Write},
+    path::{Path, PathBuf},
+};
+
+/// Identifies the layout of the frame file, bump the number when it changes
+const MAGIC: &[u8; 8] = b"TESPOUT1";
+const HEADER_SIZE: u64 = 32;
+/// Where the frame counter is, it's odd while a frame is being written
+const COUNTER_OFFSET: u64 = 16;
+
+/// The directory of the frame files, which is shared memory on Linux
+fn frames_dir() -> PathBuf {
+    let shm = Path::new("/dev/shm");
+    if shm.is_dir() {
+        shm.to_path_buf()
+    } else {
+        std::env::temp_dir()
+    }
+}
+
+/// Names can only have ASCII letters, digits, `-` and `_`, the rest become `_`
+pub(crate) fn frame_path(name: &str) -> PathBuf {
+    let name: String = name
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '-' || c == '_' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    frames_dir().join(format!("{name}.tesp-frame"))
+}
+
+/// Writes every frame to a file other applications can map and read.
+///
+/// The file starts with a 32 byte header of little endian values: the magic `TESPOUT1`,
+/// the width and height as u32, a u64 frame counter, the bytes per row as u32, and the
+/// format as u32 (0 is RGBA8). The rows follow, top first. The counter is odd while a frame is
+/// being written, readers copy the rows when it's even and check it didn't change meanwhile
+pub(crate) struct SharedOutput {
+    name: String,
+    path: PathBuf,
+    width: u32,
+    height: u32,
+    file: File,
+    frames: u64,
+}
+
+impl SharedOutput {
+    pub(crate) fn create(name: &str, width: u32, height: u32) -> io::Result<SharedOutput> {
+        Self::create_at(name, frame_path(name), width, height)
+    }
+
+    fn create_at(name: &str, path: PathBuf, width: u32, height: u32) -> io::Result<SharedOutput> {
+        let mut file = OpenOptions::new()
+            .write(true)
+            .create(true)
+            .truncate(true)
+            .open(&path)?;
+        file.set_len(HEADER_SIZE + width as u64 * height as u64 * 4)?;
+
+        let mut header = Vec::with_capacity(HEADER_SIZE as usize);
+        header.extend(MAGIC);
+        header.extend(width.to_le_bytes());
+        header.extend(height.to_le_bytes());
+        header.extend(0u64.to_le_bytes());
+        header.extend((width * 4).to_le_bytes());
+        header.extend(0u32.to_le_bytes());
+        file.write_all(&header)?;
+
+        Ok(SharedOutput {
+            name: name.to_string(),
+            path,
+            width,
+            height,
+            file,
+            frames: 0,
+        })
+    }
+
+    /// The file is created again when the name or the size of the image changes
+    pub(crate) fn matches(&self, name: &str, width: u32, height: u32) -> bool {
+        self.name == name && self.width == width && self.height == height
+    }
+
+    pub(crate) fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// `rgba` has to be tightly packed rows of the size the output was created with
+    pub(crate) fn write_frame(&mut self, rgba: &[u8]) -> io::Result<()> {
+        self.frames += 1;
+        self.write_counter(self.frames * 2 - 1)?;
+        self.file.seek(SeekFrom::Start(HEADER_SIZE))?;
+        self.file.write_all(rgba)?;
+        self.write_counter(self.frames * 2)
+    }
+
+    fn write_counter(&mut self, counter: u64) -> io::Result<()> {
+        self.file.seek(SeekFrom::Start(COUNTER_OFFSET))?;
+        self.file.write_all(&counter.to_le_bytes())
+    }
+}
+
+impl Drop for SharedOutput {
+    /// Readers stop seeing frames instead of the last one forever
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.path);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn frames_follow_the_header() {
+        let path = std::env::temp_dir().join("te-shader-play-test.tesp-frame");
+        let mut output = SharedOutput::create_at("test", path.clone(), 2, 1).unwrap();
+        output.write_frame(&[1, 2, 3, 4, 5, 6, 7, 8]).unwrap();
+
+        let contents = std::fs::read(&path).unwrap();
+        assert_eq!(&contents[..8], MAGIC);
+        assert_eq!(contents[8..12], 2u32.to_le_bytes());
+        assert_eq!(contents[12..16], 1u32.to_le_bytes());
+        assert_eq!(contents[16..24], 2u64.to_le_bytes());
+        assert_eq!(contents[24..28], 8u32.to_le_bytes());
+        assert_eq!(contents[32..], [1, 2, 3, 4, 5, 6, 7, 8]);
+
+        drop(output);
+        assert!(!path.exists());
+    }
+
+    #[test]
+    fn names_are_file_names() {
+        assert!(frame_path("Main out/1").ends_with("Main_out_1.tesp-frame"));
+    }
+}
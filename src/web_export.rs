@@ -0,0 +1,338 @@
+use serde_json::{Map, Value as JsonValue};
+use wgpu::{BlendComponent, BlendFactor, BlendOperation, BlendState};
+
+/// Builtins the page updates every frame, like the app does
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum WebAnimation {
+    Milliseconds,
+    Seconds,
+    WindowSize,
+    RandomF32,
+    RandomU32,
+}
+
+impl WebAnimation {
+    fn name(self) -> &'static str {
+        match self {
+            WebAnimation::Milliseconds => "milliseconds",
+            WebAnimation::Seconds => "seconds",
+            WebAnimation::WindowSize => "window_size",
+            WebAnimation::RandomF32 => "random_f32",
+            WebAnimation::RandomU32 => "random_u32",
+        }
+    }
+}
+
+pub(crate) struct WebBinding {
+    /// Contents of the whole buffer
+    pub(crate) bytes: Vec<u8>,
+    pub(crate) animation: Option<WebAnimation>,
+}
+
+/// A standalone HTML page that runs a shader with WebGPU
+pub(crate) struct WebExport<'a> {
+    pub(crate) title: &'a str,
+    pub(crate) shader: &'a str,
+    /// Bindings of every enabled group, in order
+    pub(crate) groups: Vec<Vec<WebBinding>>,
+    /// Vertex buffer, laid out like `Vertex`
+    pub(crate) vertices: Vec<u8>,
+    pub(crate) indices: Vec<u32>,
+    pub(crate) background: [f32; 4],
+    pub(crate) blend: BlendState,
+}
+
+impl WebExport<'_> {
+    pub(crate) fn to_html(&self) -> String {
+        let groups: Vec<JsonValue> = self
+            .groups
+            .iter()
+            .map(|bindings| {
+                bindings
+                    .iter()
+                    .map(|binding| {
+                        let mut json_obj = Map::new();
+                        json_obj.insert("bytes".into(), binding.bytes.clone().into());
+                        json_obj.insert(
+                            "animation".into(),
+                            binding.animation.map(WebAnimation::name).into(),
+                        );
+                        JsonValue::Object(json_obj)
+                    })
+                    .collect::<Vec<_>>()
+                    .into()
+            })
+            .collect();
+        let [r, g, b, a] = self.background;
+        let mut background = Map::new();
+        background.insert("r".into(), r.into());
+        background.insert("g".into(), g.into());
+        background.insert("b".into(), b.into());
+        background.insert("a".into(), a.into());
+
+        fill_template(
+            PAGE,
+            &[
+                ("title", escape_html(self.title)),
+                ("shader", script_json(&self.shader.into())),
+                ("groups", script_json(&groups.into())),
+                ("vertices", script_json(&self.vertices.clone().into())),
+                ("indices", script_json(&self.indices.clone().into())),
+                ("background", script_json(&background.into())),
+                ("blend", script_json(&blend_json(self.blend))),
+            ],
+        )
+    }
+}
+
+/// Replaces every `{name}` in a single pass, so the values can't be mistaken for placeholders
+fn fill_template(template: &str, values: &[(&str, String)]) -> String {
+    let mut page = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        page.push_str(&rest[..start]);
+        rest = &rest[start..];
+        let value = values.iter().find_map(|(name, value)| {
+            let after = rest[1..].strip_prefix(*name)?.strip_prefix('}')?;
+            Some((value, after))
+        });
+        match value {
+            Some((value, after)) => {
+                page.push_str(value);
+                rest = after
+            }
+            None => {
+                page.push('{');
+                rest = &rest[1..]
+            }
+        }
+    }
+    page.push_str(rest);
+
+    page
+}
+
+/// JSON is valid JavaScript, as long as it can't close the script tag
+fn script_json(value: &JsonValue) -> String {
+    value.to_string().replace("</", "<\\/")
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+fn blend_json(blend: BlendState) -> JsonValue {
+    let component = |component: BlendComponent| {
+        let mut json_obj = Map::new();
+        json_obj.insert("srcFactor".into(), factor_name(component.src_factor).into());
+        json_obj.insert("dstFactor".into(), factor_name(component.dst_factor).into());
+        json_obj.insert(
+            "operation".into(),
+            operation_name(component.operation).into(),
+        );
+        JsonValue::Object(json_obj)
+    };
+    let mut json_obj = Map::new();
+    json_obj.insert("color".into(), component(blend.color));
+    json_obj.insert("alpha".into(), component(blend.alpha));
+    JsonValue::Object(json_obj)
+}
+
+fn factor_name(factor: BlendFactor) -> &'static str {
+    match factor {
+        BlendFactor::Zero => "zero",
+        BlendFactor::One => "one",
+        BlendFactor::Src => "src",
+        BlendFactor::OneMinusSrc => "one-minus-src",
+        BlendFactor::SrcAlpha => "src-alpha",
+        BlendFactor::OneMinusSrcAlpha => "one-minus-src-alpha",
+        BlendFactor::Dst => "dst",
+        BlendFactor::OneMinusDst => "one-minus-dst",
+        BlendFactor::DstAlpha => "dst-alpha",
+        BlendFactor::OneMinusDstAlpha => "one-minus-dst-alpha",
+        BlendFactor::SrcAlphaSaturated => "src-alpha-saturated",
+        BlendFactor::Constant => "constant",
+        BlendFactor::OneMinusConstant => "one-minus-constant",
+        BlendFactor::Src1 => "src1",
+        BlendFactor::OneMinusSrc1 => "one-minus-src1",
+        BlendFactor::Src1Alpha => "src1-alpha",
+        BlendFactor::OneMinusSrc1Alpha => "one-minus-src1-alpha",
+    }
+}
+
+fn operation_name(operation: BlendOperation) -> &'static str {
+    match operation {
+        BlendOperation::Add => "add",
+        BlendOperation::Subtract => "subtract",
+        BlendOperation::ReverseSubtract => "reverse-subtract",
+        BlendOperation::Min => "min",
+        BlendOperation::Max => "max",
+    }
+}
+
+/// Mirrors the app's custom shader pipeline: one uniform buffer per binding visible to both
+/// stages, the vertex buffer at 0 (position at location 0, normal at 2) and a single instance
+/// at 1 (offset at location 1)
+const PAGE: &str = r#"<!DOCTYPE html>
+<html>
+<head>
+<meta charset="utf-8">
+<title>{title}</title>
+<style>
+html, body { margin: 0; height: 100%; background: #000; overflow: hidden; }
+canvas { display: block; width: 100%; height: 100%; }
+#error { position: absolute; top: 0; left: 0; margin: 1em; color: #f55; font-family: monospace; white-space: pre-wrap; }
+</style>
+</head>
+<body>
+<canvas id="canvas"></canvas>
+<div id="error"></div>
+<script type="module">
+const SHADER = {shader};
+const GROUPS = {groups};
+const VERTICES = {vertices};
+const INDICES = {indices};
+const BACKGROUND = {background};
+const BLEND = {blend};
+
+function fail(message) {
+    document.getElementById("error").textContent = message;
+}
+
+function bufferWith(device, usage, bytes) {
+    const buffer = device.createBuffer({ size: Math.max(bytes.byteLength, 4), usage: usage | GPUBufferUsage.COPY_DST });
+    device.queue.writeBuffer(buffer, 0, bytes);
+    return buffer;
+}
+
+function animatedValue(animation, elapsed, canvas) {
+    switch (animation) {
+        case "milliseconds": return new Uint32Array([Math.floor(elapsed) >>> 0]);
+        case "seconds": return new Float32Array([elapsed / 1000]);
+        case "window_size": return new Float32Array([canvas.width, canvas.height]);
+        case "random_f32": return new Float32Array([Math.floor(Math.random() * 2 ** 24) / 2 ** 24]);
+        case "random_u32": return new Uint32Array([Math.floor(Math.random() * 2 ** 32)]);
+    }
+}
+
+async function main() {
+    if (!navigator.gpu) {
+        fail("This browser doesn't support WebGPU");
+        return;
+    }
+    const adapter = await navigator.gpu.requestAdapter();
+    if (!adapter) {
+        fail("Couldn't find a WebGPU adapter");
+        return;
+    }
+    const device = await adapter.requestDevice();
+    device.lost.then((info) => fail(`The device was lost: ${info.message}`));
+    device.onuncapturederror = (event) => fail(event.error.message);
+
+    const canvas = document.getElementById("canvas");
+    const context = canvas.getContext("webgpu");
+    const format = navigator.gpu.getPreferredCanvasFormat();
+    context.configure({ device, format, alphaMode: "premultiplied" });
+
+    const module = device.createShaderModule({ code: SHADER });
+    const errors = (await module.getCompilationInfo()).messages.filter((message) => message.type === "error");
+    if (errors.length > 0) {
+        fail(errors.map((error) => `${error.lineNum}:${error.linePos} ${error.message}`).join("\n"));
+        return;
+    }
+
+    const groups = GROUPS.map((bindings) => {
+        const layout = device.createBindGroupLayout({
+            entries: bindings.map((_, binding) => ({
+                binding,
+                visibility: GPUShaderStage.VERTEX | GPUShaderStage.FRAGMENT,
+                buffer: { type: "uniform" },
+            })),
+        });
+        const buffers = bindings.map(({ bytes }) => bufferWith(device, GPUBufferUsage.UNIFORM, new Uint8Array(bytes)));
+        const bindGroup = device.createBindGroup({
+            layout,
+            entries: buffers.map((buffer, binding) => ({ binding, resource: { buffer } })),
+        });
+        return { layout, bindGroup, buffers, bindings };
+    });
+
+    const pipeline = device.createRenderPipeline({
+        layout: device.createPipelineLayout({ bindGroupLayouts: groups.map((group) => group.layout) }),
+        vertex: {
+            module,
+            entryPoint: "vs_main",
+            buffers: [
+                {
+                    arrayStride: 24,
+                    stepMode: "vertex",
+                    attributes: [
+                        { format: "float32x3", offset: 0, shaderLocation: 0 },
+                        { format: "float32x3", offset: 12, shaderLocation: 2 },
+                    ],
+                },
+                {
+                    arrayStride: 12,
+                    stepMode: "instance",
+                    attributes: [{ format: "float32x3", offset: 0, shaderLocation: 1 }],
+                },
+            ],
+        },
+        fragment: {
+            module,
+            entryPoint: "fs_main",
+            targets: [{ format, blend: BLEND }],
+        },
+        primitive: { topology: "triangle-list" },
+    });
+
+    const vertexBuffer = bufferWith(device, GPUBufferUsage.VERTEX, new Uint8Array(VERTICES));
+    const instanceBuffer = bufferWith(device, GPUBufferUsage.VERTEX, new Float32Array([0, 0, 0]));
+    const indexBuffer = bufferWith(device, GPUBufferUsage.INDEX, new Uint32Array(INDICES));
+
+    const start = performance.now();
+    function frame(now) {
+        const width = Math.max(1, Math.floor(canvas.clientWidth * devicePixelRatio));
+        const height = Math.max(1, Math.floor(canvas.clientHeight * devicePixelRatio));
+        if (canvas.width !== width || canvas.height !== height) {
+            canvas.width = width;
+            canvas.height = height;
+        }
+
+        for (const group of groups) {
+            group.bindings.forEach(({ animation }, binding) => {
+                if (animation) {
+                    device.queue.writeBuffer(group.buffers[binding], 0, animatedValue(animation, now - start, canvas));
+                }
+            });
+        }
+
+        const encoder = device.createCommandEncoder();
+        const pass = encoder.beginRenderPass({
+            colorAttachments: [{
+                view: context.getCurrentTexture().createView(),
+                clearValue: BACKGROUND,
+                loadOp: "clear",
+                storeOp: "store",
+            }],
+        });
+        pass.setPipeline(pipeline);
+        groups.forEach((group, index) => pass.setBindGroup(index, group.bindGroup));
+        pass.setVertexBuffer(0, vertexBuffer);
+        pass.setVertexBuffer(1, instanceBuffer);
+        pass.setIndexBuffer(indexBuffer, "uint32");
+        pass.drawIndexed(INDICES.length);
+        pass.end();
+        device.queue.submit([encoder.finish()]);
+        requestAnimationFrame(frame);
+    }
+    requestAnimationFrame(frame);
+}
+
+main();
+</script>
+</body>
+</html>
+"#;
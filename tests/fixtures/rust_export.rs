@@ -0,0 +1,174 @@
+// Generated by te-shader-play.
+// Every binding has its own uniform buffer, visible to the vertex and fragment stages.
+
+pub const SHADER_PATH: &str = "shaders/shader.wgsl";
+
+/// @group(0)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Group0 {
+    /// @binding(0), the playground updates it with the milliseconds since it started
+    pub unnamed: u32,
+    /// @binding(1), the playground updates it with the window size
+    pub unnamed_1: [f32; 2],
+}
+
+impl Group0 {
+    pub const LAYOUT: wgpu::BindGroupLayoutDescriptor<'static> = wgpu::BindGroupLayoutDescriptor {
+        label: Some("Group 0"),
+        entries: &[
+            uniform_entry(0),
+            uniform_entry(1),
+        ],
+    };
+
+    /// Contents of the buffer of each binding, in binding order
+    pub fn to_bytes(&self) -> Vec<Vec<u8>> {
+        vec![
+            self.unnamed.to_le_bytes().to_vec(),
+            self.unnamed_1.iter().flat_map(|v| v.to_le_bytes()).collect(),
+        ]
+    }
+}
+
+impl Default for Group0 {
+    /// The values the playground had
+    fn default() -> Self {
+        Group0 {
+            unnamed: 1234,
+            unnamed_1: [768.0, 512.0],
+        }
+    }
+}
+
+/// @group(1)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Group1 {
+    /// @binding(0)
+    pub speed: f32,
+    /// @binding(1)
+    pub tint_color: [f32; 6],
+    /// @binding(2)
+    pub type_: [i32; 2],
+    /// @binding(3)
+    pub _2nd_pad: [u8; 8],
+}
+
+impl Group1 {
+    pub const LAYOUT: wgpu::BindGroupLayoutDescriptor<'static> = wgpu::BindGroupLayoutDescriptor {
+        label: Some("Group 1"),
+        entries: &[
+            uniform_entry(0),
+            uniform_entry(1),
+            uniform_entry(2),
+            uniform_entry(3),
+        ],
+    };
+
+    /// Contents of the buffer of each binding, in binding order
+    pub fn to_bytes(&self) -> Vec<Vec<u8>> {
+        vec![
+            self.speed.to_le_bytes().to_vec(),
+            self.tint_color.iter().flat_map(|v| v.to_le_bytes()).collect(),
+            self.type_.iter().flat_map(|v| v.to_le_bytes()).collect(),
+            self._2nd_pad.to_vec(),
+        ]
+    }
+}
+
+impl Default for Group1 {
+    /// The values the playground had
+    fn default() -> Self {
+        Group1 {
+            speed: 0.5,
+            tint_color: [
+                1.0, 0.25, 0.0, 1.0,
+                -2.5, 3.0,
+            ],
+            type_: [-3, 4],
+            _2nd_pad: [0; 8],
+        }
+    }
+}
+
+const fn uniform_entry(binding: u32) -> wgpu::BindGroupLayoutEntry {
+    wgpu::BindGroupLayoutEntry {
+        binding,
+        visibility: wgpu::ShaderStages::VERTEX_FRAGMENT,
+        ty: wgpu::BindingType::Buffer {
+            ty: wgpu::BufferBindingType::Uniform,
+            has_dynamic_offset: false,
+            min_binding_size: None,
+        },
+        count: None,
+    }
+}
+
+/// The position (location 0) and normal (location 2) of each vertex, then the offset
+/// (location 1) of each instance
+pub const VERTEX_BUFFERS: [wgpu::VertexBufferLayout<'static>; 2] = [
+    wgpu::VertexBufferLayout {
+        array_stride: 24,
+        step_mode: wgpu::VertexStepMode::Vertex,
+        attributes: &[
+            wgpu::VertexAttribute {
+                format: wgpu::VertexFormat::Float32x3,
+                offset: 0,
+                shader_location: 0,
+            },
+            wgpu::VertexAttribute {
+                format: wgpu::VertexFormat::Float32x3,
+                offset: 12,
+                shader_location: 2,
+            },
+        ],
+    },
+    wgpu::VertexBufferLayout {
+        array_stride: 12,
+        step_mode: wgpu::VertexStepMode::Instance,
+        attributes: &[wgpu::VertexAttribute {
+            format: wgpu::VertexFormat::Float32x3,
+            offset: 0,
+            shader_location: 1,
+        }],
+    },
+];
+
+pub const PRIMITIVE: wgpu::PrimitiveState = wgpu::PrimitiveState {
+    topology: wgpu::PrimitiveTopology::TriangleList,
+    strip_index_format: None,
+    front_face: wgpu::FrontFace::Ccw,
+    cull_mode: None,
+    unclipped_depth: false,
+    polygon_mode: wgpu::PolygonMode::Fill,
+    conservative: false,
+};
+
+pub const DEPTH_STENCIL: wgpu::DepthStencilState = wgpu::DepthStencilState {
+    format: wgpu::TextureFormat::Depth32Float,
+    depth_write_enabled: false,
+    depth_compare: wgpu::CompareFunction::Less,
+    stencil: wgpu::StencilState {
+        front: wgpu::StencilFaceState::IGNORE,
+        back: wgpu::StencilFaceState::IGNORE,
+        read_mask: 0,
+        write_mask: 0,
+    },
+    bias: wgpu::DepthBiasState {
+        constant: 0,
+        slope_scale: 0.0,
+        clamp: 0.0,
+    },
+};
+
+pub const BLEND: wgpu::BlendState = wgpu::BlendState {
+    color: wgpu::BlendComponent {
+        src_factor: wgpu::BlendFactor::SrcAlpha,
+        dst_factor: wgpu::BlendFactor::OneMinusSrcAlpha,
+        operation: wgpu::BlendOperation::Add,
+    },
+    alpha: wgpu::BlendComponent {
+        src_factor: wgpu::BlendFactor::One,
+        dst_factor: wgpu::BlendFactor::OneMinusSrcAlpha,
+        operation: wgpu::BlendOperation::Add,
+    },
+};
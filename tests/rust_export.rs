@@ -0,0 +1,29 @@
+//! The Rust export has to compile against plain wgpu. The example is checked against the
+//! generator by the unit tests of `rust_export`
+
+#[allow(dead_code)]
+mod example {
+    include!("fixtures/rust_export.rs");
+}
+
+use example::{Group0, Group1};
+
+#[test]
+fn every_binding_has_a_buffer() {
+    assert_eq!(
+        Group0::default().to_bytes().len(),
+        Group0::LAYOUT.entries.len()
+    );
+    assert_eq!(
+        Group1::default().to_bytes().len(),
+        Group1::LAYOUT.entries.len()
+    );
+}
+
+#[test]
+fn buffers_hold_the_values() {
+    let bytes = Group1::default().to_bytes();
+    assert_eq!(bytes[0], 0.5f32.to_le_bytes());
+    assert_eq!(bytes[1].len(), 6 * 4);
+    assert_eq!(bytes[3], [0; 8]);
+}